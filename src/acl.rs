@@ -0,0 +1,353 @@
+// One line of an access control list, as shown in Properties
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub who: String,
+    pub rights: String,
+    // What `revoke` takes to remove it; None for entries that can't be,
+    // such as the owner's or inherited ones
+    pub key: Option<String>,
+}
+
+// Whether lists can be read and changed here
+pub const SUPPORTED: bool = cfg!(any(target_os = "linux", windows));
+
+// How a grant names who it is for, shown next to the editor
+pub const WHO_HINT: &str = if cfg!(windows) { "User or group, e.g. DOMAIN\\name" } else { "user:NAME or group:NAME" };
+
+// How a grant's rights are written: any of r, w and x
+pub fn parse_rights(rights: &str) -> Option<(bool, bool, bool)> {
+    let rights = rights.trim().to_lowercase();
+    if rights.is_empty() || rights.chars().any(|c| !matches!(c, 'r' | 'w' | 'x' | '-')) {
+        return None;
+    }
+    Some((rights.contains('r'), rights.contains('w'), rights.contains('x')))
+}
+
+#[cfg(target_os = "linux")]
+pub use posix::{grant, read, revoke};
+
+#[cfg(windows)]
+pub use windows::{grant, read, revoke};
+
+#[cfg(not(any(target_os = "linux", windows)))]
+use std::{io, path::Path};
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn unsupported<T>() -> io::Result<T> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "access control lists are not supported here"))
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn read(_path: &Path) -> io::Result<Vec<Entry>> {
+    unsupported()
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn grant(_path: &Path, _who: &str, _rights: &str) -> io::Result<()> {
+    unsupported()
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn revoke(_path: &Path, _key: &str) -> io::Result<()> {
+    unsupported()
+}
+
+// POSIX ACLs as the kernel keeps them, in the system.posix_acl_access
+// attribute: a version, then tag, permission and id for each entry, little
+// endian. A file without one has only what its mode says.
+#[cfg(target_os = "linux")]
+mod posix {
+    use super::{parse_rights, Entry};
+    use crate::permissions;
+    use crate::xattr;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::io;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    const ATTRIBUTE: &str = "system.posix_acl_access";
+    const VERSION: u32 = 2;
+    const NO_ID: u32 = u32::MAX;
+
+    // Tags, in the order the kernel wants the entries
+    const USER_OBJ: u16 = 0x01;
+    const USER: u16 = 0x02;
+    const GROUP_OBJ: u16 = 0x04;
+    const GROUP: u16 = 0x08;
+    const MASK: u16 = 0x10;
+    const OTHER: u16 = 0x20;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    struct Rule {
+        tag: u16,
+        id: u32,
+        perm: u16,
+    }
+
+    fn invalid(message: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+    }
+
+    fn load(path: &Path) -> io::Result<Vec<Rule>> {
+        let Some(bytes) = xattr::get(path, ATTRIBUTE)? else {
+            let mode = fs::metadata(path)?.permissions().mode();
+            return Ok(vec![
+                Rule { tag: USER_OBJ, id: NO_ID, perm: ((mode >> 6) & 7) as u16 },
+                Rule { tag: GROUP_OBJ, id: NO_ID, perm: ((mode >> 3) & 7) as u16 },
+                Rule { tag: OTHER, id: NO_ID, perm: (mode & 7) as u16 },
+            ]);
+        };
+        decode(&bytes)
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Vec<Rule>> {
+        let (header, body) = bytes.split_at_checked(4).ok_or_else(|| invalid("the access list is cut short"))?;
+        if u32::from_le_bytes(header.try_into().unwrap_or_default()) != VERSION || body.len() % 8 != 0 {
+            return Err(invalid("the access list has an unknown format"));
+        }
+        Ok(body
+            .chunks_exact(8)
+            .map(|entry| Rule {
+                tag: u16::from_le_bytes([entry[0], entry[1]]),
+                perm: u16::from_le_bytes([entry[2], entry[3]]),
+                id: u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]),
+            })
+            .collect())
+    }
+
+    fn store(path: &Path, rules: Vec<Rule>) -> io::Result<()> {
+        xattr::set(path, ATTRIBUTE, &encode(rules))
+    }
+
+    // Named entries need a mask, which is recomputed as setfacl does: all
+    // the rights the group class has. Without named entries it goes, and
+    // the group's rights are the mode's again.
+    fn encode(mut rules: Vec<Rule>) -> Vec<u8> {
+        rules.retain(|rule| rule.tag != MASK);
+        if rules.iter().any(|rule| rule.tag == USER || rule.tag == GROUP) {
+            let perm = rules.iter().filter(|rule| matches!(rule.tag, USER | GROUP_OBJ | GROUP)).fold(0, |perm, rule| perm | rule.perm);
+            rules.push(Rule { tag: MASK, id: NO_ID, perm });
+        }
+        rules.sort_by_key(|rule| (rule.tag, rule.id));
+
+        let mut bytes = VERSION.to_le_bytes().to_vec();
+        for rule in rules {
+            bytes.extend(rule.tag.to_le_bytes());
+            bytes.extend(rule.perm.to_le_bytes());
+            bytes.extend(rule.id.to_le_bytes());
+        }
+        bytes
+    }
+
+    // Gives `tag` and `id` the rights `perm`, or takes their entry away
+    fn set_rule(rules: &mut Vec<Rule>, tag: u16, id: u32, perm: Option<u16>) {
+        rules.retain(|rule| !(rule.tag == tag && rule.id == id));
+        rules.extend(perm.map(|perm| Rule { tag, id, perm }));
+    }
+
+    fn rights(perm: u16) -> String {
+        let bit = |mask: u16, c: char| if perm & mask != 0 { c } else { '-' };
+        [bit(4, 'r'), bit(2, 'w'), bit(1, 'x')].iter().collect()
+    }
+
+    fn name(table: &HashMap<u32, String>, id: u32) -> String {
+        table.get(&id).cloned().unwrap_or_else(|| id.to_string())
+    }
+
+    pub fn read(path: &Path) -> io::Result<Vec<Entry>> {
+        let (users, groups) = (permissions::users(), permissions::groups());
+        let rules = load(path)?;
+        let mask = rules.iter().find(|rule| rule.tag == MASK).map(|rule| rule.perm);
+        Ok(rules
+            .into_iter()
+            .map(|rule| {
+                let (who, key) = match rule.tag {
+                    USER_OBJ => ("owner".to_string(), None),
+                    USER => {
+                        let who = format!("user:{}", name(&users, rule.id));
+                        (who.clone(), Some(who))
+                    }
+                    GROUP_OBJ => ("group".to_string(), None),
+                    GROUP => {
+                        let who = format!("group:{}", name(&groups, rule.id));
+                        (who.clone(), Some(who))
+                    }
+                    MASK => ("mask".to_string(), None),
+                    _ => ("other".to_string(), None),
+                };
+                // The mask caps what named entries and the group get
+                let capped = match mask {
+                    Some(mask) if matches!(rule.tag, USER | GROUP_OBJ | GROUP) && rule.perm & !mask != 0 => {
+                        format!("{} (effective {})", rights(rule.perm), rights(rule.perm & mask))
+                    }
+                    _ => rights(rule.perm),
+                };
+                Entry { who, rights: capped, key }
+            })
+            .collect())
+    }
+
+    // `who` is user:NAME, group:NAME or a bare user name; names may also be
+    // numeric ids
+    fn parse_who(who: &str) -> io::Result<(u16, u32)> {
+        let who = who.trim();
+        let (tag, name, table) = match who.split_once(':') {
+            Some(("group" | "g", name)) => (GROUP, name, permissions::groups()),
+            Some(("user" | "u", name)) => (USER, name, permissions::users()),
+            Some(_) => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("expected user:NAME or group:NAME, not '{}'", who))),
+            None => (USER, who, permissions::users()),
+        };
+        let id = permissions::resolve(name.trim(), &table).map_err(|error| io::Error::new(io::ErrorKind::NotFound, error))?;
+        Ok((tag, id))
+    }
+
+    pub fn grant(path: &Path, who: &str, rights: &str) -> io::Result<()> {
+        let (read, write, execute) =
+            parse_rights(rights).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "rights are any of r, w and x"))?;
+        let (tag, id) = parse_who(who)?;
+        let perm = u16::from(read) << 2 | u16::from(write) << 1 | u16::from(execute);
+        let mut rules = load(path)?;
+        set_rule(&mut rules, tag, id, Some(perm));
+        store(path, rules)
+    }
+
+    pub fn revoke(path: &Path, key: &str) -> io::Result<()> {
+        let (tag, id) = parse_who(key)?;
+        let mut rules = load(path)?;
+        set_rule(&mut rules, tag, id, None);
+        store(path, rules)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn rule(tag: u16, id: u32, perm: u16) -> Rule {
+            Rule { tag, id, perm }
+        }
+
+        // A file with mode 640
+        fn plain() -> Vec<Rule> {
+            vec![rule(USER_OBJ, NO_ID, 6), rule(GROUP_OBJ, NO_ID, 4), rule(OTHER, NO_ID, 0)]
+        }
+
+        #[test]
+        fn lists_round_trip_through_the_attribute() {
+            let rules = vec![rule(USER_OBJ, NO_ID, 6), rule(USER, 1000, 7), rule(GROUP_OBJ, NO_ID, 4), rule(MASK, NO_ID, 7), rule(OTHER, NO_ID, 0)];
+            let bytes = encode(rules.clone());
+            assert_eq!(bytes.len(), 4 + 8 * rules.len());
+            assert_eq!(&bytes[..4], &[2, 0, 0, 0]);
+            assert_eq!(&bytes[12..20], &[0x02, 0, 7, 0, 0xe8, 0x03, 0, 0]);
+            assert_eq!(decode(&bytes).unwrap(), rules);
+        }
+
+        #[test]
+        fn malformed_lists_are_refused() {
+            let bytes = encode(plain());
+            assert!(decode(&bytes[..2]).is_err());
+            assert!(decode(&bytes[..bytes.len() - 3]).is_err());
+            let mut version = bytes.clone();
+            version[0] = 1;
+            assert!(decode(&version).is_err());
+            assert_eq!(decode(&bytes[..4]).unwrap(), vec![]);
+        }
+
+        #[test]
+        fn the_mask_follows_grants_and_revokes() {
+            let mut rules = plain();
+            set_rule(&mut rules, USER, 1000, Some(5));
+            set_rule(&mut rules, GROUP, 50, Some(2));
+            let stored = decode(&encode(rules.clone())).unwrap();
+            assert_eq!(stored.iter().find(|rule| rule.tag == MASK), Some(&rule(MASK, NO_ID, 7)));
+            // Kept in the order the kernel wants
+            let tags: Vec<u16> = stored.iter().map(|rule| rule.tag).collect();
+            assert_eq!(tags, [USER_OBJ, USER, GROUP_OBJ, GROUP, MASK, OTHER]);
+
+            // Granting again replaces the entry
+            set_rule(&mut rules, GROUP, 50, None);
+            set_rule(&mut rules, USER, 1000, Some(4));
+            let stored = decode(&encode(rules.clone())).unwrap();
+            assert_eq!(stored.iter().filter(|rule| rule.tag == USER).count(), 1);
+            assert_eq!(stored.iter().find(|rule| rule.tag == MASK), Some(&rule(MASK, NO_ID, 4)));
+
+            // With no named entries left the mask goes
+            set_rule(&mut rules, USER, 1000, None);
+            assert_eq!(decode(&encode(rules)).unwrap(), plain());
+        }
+
+        #[test]
+        fn names_are_looked_up_or_refused() {
+            assert_eq!(parse_who("user:0").unwrap(), (USER, 0));
+            assert_eq!(parse_who(" g:0 ").unwrap(), (GROUP, 0));
+            assert_eq!(parse_who("1234").unwrap(), (USER, 1234));
+            assert_eq!(parse_who("user:no-such-user-here").unwrap_err().kind(), io::ErrorKind::NotFound);
+            assert_eq!(parse_who("group:no-such-group-here").unwrap_err().kind(), io::ErrorKind::NotFound);
+            assert_eq!(parse_who("other:x").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
+
+// Windows security descriptors, read and changed with icacls, which prints
+// one access entry per line after the path
+#[cfg(windows)]
+mod windows {
+    use super::{parse_rights, Entry};
+    use std::io;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn icacls(path: &Path, args: &[&str]) -> io::Result<String> {
+        let output = Command::new("icacls").arg(path).args(args).output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            let said = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            Err(io::Error::other(if said.is_empty() { output.status.to_string() } else { said }))
+        }
+    }
+
+    pub fn read(path: &Path) -> io::Result<Vec<Entry>> {
+        let listing = icacls(path, &[])?;
+        let shown = path.display().to_string();
+        Ok(listing
+            .lines()
+            .map(|line| line.strip_prefix(shown.as_str()).unwrap_or(line).trim())
+            .filter_map(|line| line.split_once(":("))
+            .map(|(who, rights)| {
+                let rights = format!("({}", rights);
+                // Inherited entries are changed where they come from
+                let key = (!rights.contains("(I)")).then(|| who.to_string());
+                Entry { who: who.to_string(), rights, key }
+            })
+            .collect())
+    }
+
+    pub fn grant(path: &Path, who: &str, rights: &str) -> io::Result<()> {
+        let (read, write, execute) =
+            parse_rights(rights).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "rights are any of r, w and x"))?;
+        let generic: Vec<&str> = [(read, "GR"), (write, "GW"), (execute, "GE")].into_iter().filter(|(on, _)| *on).map(|(_, right)| right).collect();
+        if generic.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "give at least one of r, w and x"));
+        }
+        icacls(path, &["/grant:r", &format!("{}:({})", who.trim(), generic.join(","))]).map(|_| ())
+    }
+
+    pub fn revoke(path: &Path, key: &str) -> io::Result<()> {
+        icacls(path, &["/remove", key]).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rights_are_read_from_r_w_and_x() {
+        assert_eq!(parse_rights("rw-"), Some((true, true, false)));
+        assert_eq!(parse_rights(" X "), Some((false, false, true)));
+        assert_eq!(parse_rights("---"), Some((false, false, false)));
+        assert_eq!(parse_rights(""), None);
+        assert_eq!(parse_rights("rwz"), None);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::filetype;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// The extension rules are keyed by: lowercase, without the dot, and the
+// one the contents call for when they tell, so a renamed photo still opens
+// in the image viewer
+pub fn extension_of(path: &Path) -> Option<String> {
+    filetype::extension(path)
+}
+
+// Opens `path` with the app the user chose for its extension, or with the
+// system default when there is no rule for it
+pub fn open(path: &Path, rules: &BTreeMap<String, String>) -> io::Result<()> {
+    match extension_of(path).and_then(|ext| rules.get(&ext)) {
+        Some(command) => launch(command, path),
+        None => open::that(path),
+    }
+}
+
+// Runs `command` on `path`. The file goes where the command says `{}`, or
+// at the end; arguments are split on whitespace, so a terminal program can
+// be started as e.g. `xterm -e lnav`.
+pub fn launch(command: &str, path: &Path) -> io::Result<()> {
+    let mut words = command.split_whitespace();
+    let Some(program) = words.next() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "No command given"));
+    };
+
+    let mut process = Command::new(program);
+    let mut placed = false;
+    for word in words {
+        if word == "{}" {
+            process.arg(path);
+            placed = true;
+        } else {
+            process.arg(word);
+        }
+    }
+    if !placed {
+        process.arg(path);
+    }
+
+    let mut child = process.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+    // Reap the app once it exits so it does not linger as a zombie
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use crate::search::Matcher;
+use crate::translate::{fill, tr};
+
+// A query combining several terms, e.g. `report (pdf OR docx) -draft`.
+// Terms next to each other must all match; `OR` and `NOT` are only
+// operators when written in capitals, so names containing "and" or "or"
+// can still be searched for. Quoted text is a single term.
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Term(Box<Matcher>),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    // Quoted text, never an operator
+    Phrase(String),
+    Open,
+    Close,
+}
+
+impl Expr {
+    // True if `query` uses any boolean syntax; a single plain term is left to
+    // the ordinary matchers
+    pub fn is_boolean(query: &str) -> bool {
+        let tokens = tokenize(query);
+        tokens.len() > 1
+            || tokens.iter().any(|token| match token {
+                Token::Word(word) => word.starts_with('-') || word == "NOT",
+                Token::Phrase(_) => false,
+                Token::Open | Token::Close => true,
+            })
+    }
+
+    // Parses `query`, building each term with `term`
+    pub fn parse(query: &str, term: impl Fn(&str) -> Result<Matcher, String>) -> Result<Expr, String> {
+        let mut parser = Parser {
+            tokens: tokenize(query),
+            position: 0,
+            term: &term,
+        };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(expr),
+            Some(Token::Close) => Err(tr("Unmatched ')' in query").to_string()),
+            Some(_) => Err(tr("Could not read the whole query").to_string()),
+        }
+    }
+
+    // Evaluates the expression, asking `test` about each term
+    pub fn eval(&self, test: &impl Fn(&Matcher) -> bool) -> bool {
+        match self {
+            Expr::Term(matcher) => test(matcher),
+            Expr::Not(inner) => !inner.eval(test),
+            Expr::And(parts) => parts.iter().all(|part| part.eval(test)),
+            Expr::Or(parts) => parts.iter().any(|part| part.eval(test)),
+        }
+    }
+
+    // Every term, whether it counts for or against a match
+    pub fn terms(&self) -> Vec<&Matcher> {
+        match self {
+            Expr::Term(matcher) => vec![matcher],
+            Expr::Not(inner) => inner.terms(),
+            Expr::And(parts) | Expr::Or(parts) => parts.iter().flat_map(Expr::terms).collect(),
+        }
+    }
+
+    // Terms that count in favour of a match, i.e. those not under a NOT
+    pub fn positive_terms(&self) -> Vec<&Matcher> {
+        let mut terms = Vec::new();
+        self.collect_positive(&mut terms);
+        terms
+    }
+
+    fn collect_positive<'a>(&'a self, terms: &mut Vec<&'a Matcher>) {
+        match self {
+            Expr::Term(matcher) => terms.push(matcher),
+            Expr::Not(_) => {}
+            Expr::And(parts) | Expr::Or(parts) => parts.iter().for_each(|part| part.collect_positive(terms)),
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    term: &'a dyn Fn(&str) -> Result<Matcher, String>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w == word)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut parts = vec![self.and()?];
+        while self.peek_word("OR") {
+            self.position += 1;
+            parts.push(self.and()?);
+        }
+        Ok(if parts.len() == 1 { parts.remove(0) } else { Expr::Or(parts) })
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut parts = vec![self.unary()?];
+        loop {
+            if self.peek_word("AND") {
+                self.position += 1;
+            } else if self.peek().is_none() || self.peek() == Some(&Token::Close) || self.peek_word("OR") {
+                break;
+            }
+            parts.push(self.unary()?);
+        }
+        Ok(if parts.len() == 1 { parts.remove(0) } else { Expr::And(parts) })
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.tokens.get(self.position).cloned() else {
+            return Err(tr("Query ends where a term was expected").to_string());
+        };
+        self.position += 1;
+
+        match token {
+            Token::Open => {
+                let inner = self.or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(tr("Missing ')' in query").to_string());
+                }
+                self.position += 1;
+                Ok(inner)
+            }
+            Token::Close => Err(tr("Unexpected ')' in query").to_string()),
+            Token::Word(word) if word == "NOT" => Ok(Expr::Not(Box::new(self.unary()?))),
+            Token::Word(word) if matches!(word.as_str(), "AND" | "OR") => {
+                Err(fill(tr("'{}' needs a term on both sides"), &[&word]))
+            }
+            Token::Word(word) => match word.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => Ok(Expr::Not(Box::new(Expr::Term(Box::new((self.term)(rest)?))))),
+                _ => Ok(Expr::Term(Box::new((self.term)(&word)?))),
+            },
+            Token::Phrase(phrase) => Ok(Expr::Term(Box::new((self.term)(&phrase)?))),
+        }
+    }
+}
+
+fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(if c == '(' { Token::Open } else { Token::Close });
+        } else if c == '"' {
+            chars.next();
+            let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+            if !phrase.is_empty() {
+                tokens.push(Token::Phrase(phrase));
+            }
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::Syntax;
+    use std::path::Path;
+
+    fn parse(query: &str) -> Result<Expr, String> {
+        Expr::parse(query, |text| Matcher::new(text, Syntax::Plain))
+    }
+
+    fn matches(query: &str, name: &str) -> bool {
+        parse(query).unwrap().eval(&|matcher| matcher.is_match(Path::new(name)))
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        // a OR (b c)
+        assert!(matches("a OR b c", "a.txt"));
+        assert!(!matches("a OR b c", "b.txt"));
+        assert!(matches("a OR b c", "bc.txt"));
+        // (a OR b) c
+        assert!(!matches("(a OR b) c", "a.txt"));
+        assert!(matches("(a OR b) c", "ac.txt"));
+        // NOT takes only the term after it
+        assert!(matches("NOT a b", "b.txt"));
+        assert!(!matches("NOT a b", "ab.txt"));
+    }
+
+    #[test]
+    fn minus_leaves_out_a_term() {
+        assert!(matches("report -draft", "report.pdf"));
+        assert!(!matches("report -draft", "report-draft.pdf"));
+        assert!(matches("report (-draft OR final)", "report-draft-final.pdf"));
+        // Operators only count in capitals, and quotes make a single term
+        assert!(matches("and", "Sand.txt"));
+        assert!(matches("\"a OR b\"", "a or b.txt"));
+    }
+
+    #[test]
+    fn unbalanced_parentheses_are_refused() {
+        assert_eq!(parse("(a b").unwrap_err(), "Missing ')' in query");
+        assert_eq!(parse("a b)").unwrap_err(), "Unmatched ')' in query");
+        assert_eq!(parse(")").unwrap_err(), "Unexpected ')' in query");
+        assert_eq!(parse("a OR").unwrap_err(), "Query ends where a term was expected");
+        assert_eq!(parse("OR a").unwrap_err(), "'OR' needs a term on both sides");
+        assert!(parse("((a))").is_ok());
+    }
+}
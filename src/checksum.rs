@@ -0,0 +1,59 @@
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// Files are hashed in chunks of this size
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Md5,
+    Sha256,
+}
+
+// Reads a hex digest, telling the algorithm apart by its length
+pub fn parse_hex(text: &str) -> Option<(Algorithm, Vec<u8>)> {
+    let text = text.trim();
+    let algorithm = match text.len() {
+        32 => Algorithm::Md5,
+        64 => Algorithm::Sha256,
+        _ => return None,
+    };
+
+    let bytes = (0..text.len())
+        .step_by(2)
+        .map(|start| u8::from_str_radix(text.get(start..start + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some((algorithm, bytes))
+}
+
+// Hashes the file at `path`; gives up and returns None if it cannot be read
+// or `cancelled` reports true between chunks
+pub fn hash_file(path: &Path, algorithm: Algorithm, cancelled: impl Fn() -> bool) -> Option<Vec<u8>> {
+    match algorithm {
+        Algorithm::Md5 => digest::<Md5>(path, cancelled),
+        Algorithm::Sha256 => digest::<Sha256>(path, cancelled),
+    }
+}
+
+fn digest<D: Digest>(path: &Path, cancelled: impl Fn() -> bool) -> Option<Vec<u8>> {
+    let mut file = File::open(path).ok()?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0; CHUNK_SIZE];
+
+    loop {
+        if cancelled() {
+            return None;
+        }
+        match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => hasher.update(&buffer[..read]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => return None,
+        }
+    }
+
+    Some(hasher.finalize().to_vec())
+}
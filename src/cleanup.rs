@@ -0,0 +1,37 @@
+use crate::excludes::Excludes;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct EmptyEntry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+// Zero-byte files and folders with nothing in them below `root`; hidden and
+// excluded entries are skipped. Blocking, so call it off the UI thread.
+pub fn find_empty(root: &Path, excludes: &Excludes) -> Vec<EmptyEntry> {
+    let mut found = Vec::new();
+
+    for entry in excludes.walk(root, false).build().flatten() {
+        let Some(kind) = entry.file_type() else {
+            continue;
+        };
+
+        let is_empty = if kind.is_dir() {
+            // Hidden children still count, so such a folder is not empty
+            read_dir(entry.path()).is_ok_and(|mut children| children.next().is_none())
+        } else {
+            kind.is_file() && entry.metadata().is_ok_and(|meta| meta.len() == 0)
+        };
+
+        if is_empty {
+            found.push(EmptyEntry {
+                path: entry.into_path(),
+                is_dir: kind.is_dir(),
+            });
+        }
+    }
+
+    found
+}
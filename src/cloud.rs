@@ -0,0 +1,35 @@
+use std::fs::{File, Metadata};
+use std::io;
+use std::path::Path;
+
+// Whether a sync client (OneDrive, Dropbox, iCloud Drive and the like) has
+// left only a placeholder here, with the contents still in the cloud.
+// Reading such a file makes the client download it, so the list and
+// searches leave it unread.
+#[cfg(windows)]
+pub fn is_placeholder(meta: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+    const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x4_0000;
+    const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x40_0000;
+    meta.file_attributes() & (FILE_ATTRIBUTE_OFFLINE | FILE_ATTRIBUTE_RECALL_ON_OPEN | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS) != 0
+}
+
+// File Provider clients mark evicted files as dataless
+#[cfg(target_os = "macos")]
+pub fn is_placeholder(meta: &Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    const SF_DATALESS: u32 = 0x4000_0000;
+    meta.st_flags() & SF_DATALESS != 0
+}
+
+// Sync clients elsewhere keep no mark on their placeholders
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn is_placeholder(_meta: &Metadata) -> bool {
+    false
+}
+
+// Has the sync client fetch `path` by reading it through once
+pub fn download(path: &Path) -> io::Result<()> {
+    io::copy(&mut File::open(path)?, &mut io::sink()).map(|_| ())
+}
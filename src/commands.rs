@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// A shell command the user named, run on the selected file. The template
+// may use {path}, {name}, {stem}, {ext} and {dir}, e.g.
+// `convert {path} {stem}.png`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomCommand {
+    pub name: String,
+    pub template: String,
+}
+
+impl fmt::Display for CustomCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+// What a command printed and how it ended
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub command_line: String,
+    pub success: bool,
+    pub status: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+// The placeholders a template may use, in the order the shell receives
+// their values as positional parameters
+const PLACEHOLDERS: [&str; 5] = ["path", "name", "stem", "ext", "dir"];
+
+impl CustomCommand {
+    // The command line for `path`, with each placeholder quoted for the shell.
+    // Shown to the user; `run` hands the values over separately.
+    pub fn expand(&self, path: &Path) -> String {
+        let values = values(path);
+        fill(&self.template, |index| quote(&values[index]))
+    }
+
+    // Runs the command on `path` from the folder holding it and waits for
+    // it; blocking, so call it off the UI thread
+    pub fn run(&self, path: &Path) -> Result<Output, String> {
+        let command_line = self.expand(path);
+        let mut shell = self.shell(path);
+        if let Some(dir) = path.parent() {
+            shell.current_dir(dir);
+        }
+
+        let output = shell.stdin(Stdio::null()).output().map_err(|e| e.to_string())?;
+        Ok(Output {
+            command_line,
+            success: output.status.success(),
+            status: output.status.to_string(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+
+    // The script only names `"$1"` to `"$5"`; the file's values follow it as
+    // arguments, so nothing in a file name is ever read as shell syntax
+    #[cfg(unix)]
+    fn shell(&self, path: &Path) -> Command {
+        let script = fill(&self.template, |index| format!("\"${}\"", index + 1));
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(script).arg("sh").args(values(path));
+        shell
+    }
+
+    // The values go in environment variables that the script names as
+    // `"!FILE_PATH!"` and so on. With delayed expansion cmd fills those in
+    // only after it has parsed the line, so a `&` or `%` in a name is never
+    // read as syntax; the line is passed as written, as cmd doesn't follow
+    // the quoting other programs do.
+    #[cfg(not(unix))]
+    fn shell(&self, path: &Path) -> Command {
+        use std::os::windows::process::CommandExt;
+        let script = fill(&self.template, |index| format!("\"!{}!\"", variable(index)));
+        let mut shell = Command::new("cmd");
+        shell.args(["/D", "/V:ON", "/C"]).raw_arg(script);
+        for (index, value) in values(path).into_iter().enumerate() {
+            shell.env(variable(index), value);
+        }
+        shell
+    }
+}
+
+// The environment variable holding a placeholder's value for cmd
+#[cfg(not(unix))]
+fn variable(index: usize) -> String {
+    format!("FILE_{}", PLACEHOLDERS[index].to_uppercase())
+}
+
+fn values(path: &Path) -> [String; 5] {
+    let part = |value: Option<&std::ffi::OsStr>| value.map(|value| value.to_string_lossy().into_owned()).unwrap_or_default();
+    [
+        path.to_string_lossy().into_owned(),
+        part(path.file_name()),
+        part(path.file_stem()),
+        part(path.extension()),
+        path.parent().map(|dir| dir.to_string_lossy().into_owned()).unwrap_or_default(),
+    ]
+}
+
+// Replaces each placeholder in one pass from left to right, so text that
+// was put in is never searched for placeholders again. Anything in braces
+// that isn't a placeholder stays as written.
+fn fill(template: &str, value: impl Fn(usize) -> String) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let placeholder = after
+            .find('}')
+            .and_then(|close| PLACEHOLDERS.iter().position(|name| *name == &after[..close]).map(|index| (index, close)));
+        match placeholder {
+            Some((index, close)) => {
+                filled.push_str(&value(index));
+                rest = &after[close + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+#[cfg(unix)]
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(not(unix))]
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(template: &str) -> CustomCommand {
+        CustomCommand { name: "test".to_string(), template: template.to_string() }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fills_each_placeholder_once() {
+        let line = command("cp {path} {dir}/{stem}.bak {unknown}").expand(Path::new("/tmp/a b.txt"));
+        assert_eq!(line, "cp '/tmp/a b.txt' '/tmp'/'a b'.bak {unknown}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn inserted_names_are_not_filled_again() {
+        let line = command("echo {path}").expand(Path::new("/tmp/x{name};touch pwned;#"));
+        assert_eq!(line, "echo '/tmp/x{name};touch pwned;#'");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hostile_name_is_not_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("x{name};touch pwned;#");
+        std::fs::write(&file, "").unwrap();
+
+        let output = command("echo {name}").run(&file).unwrap();
+        assert!(output.success);
+        assert_eq!(output.stdout, "x{name};touch pwned;#\n");
+        assert!(!dir.path().join("pwned").exists());
+    }
+}
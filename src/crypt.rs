@@ -0,0 +1,209 @@
+// Encrypts and decrypts single files in the age format through the age
+// crate: to a passphrase, or to age and SSH public keys and back with the
+// matching private key
+use crate::translate::{fill, tr};
+use age::secrecy::SecretString;
+use std::fmt;
+use std::fs::{read_to_string, File};
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+// Files age writes end in this
+const EXTENSION: &str = "age";
+
+// Kept out of debug output, as operations are logged
+#[derive(Clone, PartialEq, Eq)]
+pub struct Passphrase(String);
+
+impl Passphrase {
+    pub fn new(passphrase: String) -> Passphrase {
+        Passphrase(passphrase)
+    }
+
+    fn secret(&self) -> SecretString {
+        SecretString::from(self.0.clone())
+    }
+}
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Passphrase(…)")
+    }
+}
+
+// What a file is encrypted to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lock {
+    Passphrase(Passphrase),
+    // Public keys (age1…, ssh-ed25519 …, ssh-rsa …) or files listing them
+    Recipients(Vec<String>),
+}
+
+// What a file is decrypted with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Unlock {
+    Passphrase(Passphrase),
+    // A file holding age private keys, or an SSH private key
+    Identity(PathBuf),
+}
+
+pub fn is_encrypted(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(EXTENSION))
+}
+
+// Whether `path` was encrypted to a passphrase rather than to keys; false
+// as well when it isn't an age file at all
+pub fn needs_passphrase(path: &Path) -> bool {
+    File::open(path)
+        .ok()
+        .and_then(|file| age::Decryptor::new_buffered(BufReader::new(file)).ok())
+        .is_some_and(|decryptor| decryptor.is_scrypt())
+}
+
+// Where `source` is encrypted to: next to it with .age added, numbered
+// when that is taken
+pub fn encrypted_path(source: &Path) -> PathBuf {
+    let name = source.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    unused(source, &name, EXTENSION)
+}
+
+// Where `source` is decrypted to: its name without .age, or with
+// "decrypted" added when it had no .age to take off; numbered before the
+// extension when that is taken
+pub fn decrypted_path(source: &Path) -> PathBuf {
+    let (stem, ext) = if is_encrypted(source) {
+        let plain = PathBuf::from(source.file_stem().unwrap_or_default());
+        let stem = plain.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        (stem, plain.extension().map(|ext| ext.to_string_lossy().into_owned()).unwrap_or_default())
+    } else {
+        let stem = source.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let ext = source.extension().map(|ext| ext.to_string_lossy().into_owned()).unwrap_or_default();
+        (format!("{} (decrypted)", stem), ext)
+    };
+    unused(source, &stem, &ext)
+}
+
+// `stem.ext` beside `source`, or `stem (2).ext` and so on; nothing is
+// ever written over
+fn unused(source: &Path, stem: &str, ext: &str) -> PathBuf {
+    let dir = source.parent().unwrap_or(source);
+    let named = |stem: String| if ext.is_empty() { dir.join(stem) } else { dir.join(format!("{}.{}", stem, ext)) };
+    let mut path = named(stem.to_string());
+    let mut copy = 2;
+    while path.symlink_metadata().is_ok() {
+        path = named(format!("{} ({})", stem, copy));
+        copy += 1;
+    }
+    path
+}
+
+// A writer encrypting what is written to it into `output`; the file is
+// only complete once the writer is finished
+pub fn encrypt<W: Write>(lock: &Lock, output: W) -> io::Result<age::stream::StreamWriter<W>> {
+    let encryptor = match lock {
+        Lock::Passphrase(passphrase) => age::Encryptor::with_user_passphrase(passphrase.secret()),
+        Lock::Recipients(recipients) => {
+            let mut keys = Vec::new();
+            for recipient in recipients {
+                keys.extend(read_recipients(recipient)?);
+            }
+            age::Encryptor::with_recipients(keys.iter().map(|key| key.as_ref() as &dyn age::Recipient)).map_err(io::Error::other)?
+        }
+    };
+    encryptor.wrap_output(output)
+}
+
+// A reader giving the plain contents of the age file read from `input`.
+// A wrong key or passphrase fails here, before anything is read.
+pub fn decrypt<R: Read>(unlock: &Unlock, input: R) -> io::Result<impl Read> {
+    let decryptor = age::Decryptor::new_buffered(BufReader::new(input)).map_err(invalid)?;
+    let identities: Vec<Box<dyn age::Identity>> = match unlock {
+        Unlock::Passphrase(passphrase) => vec![Box::new(age::scrypt::Identity::new(passphrase.secret()))],
+        Unlock::Identity(path) => read_identities(path)?,
+    };
+    decryptor.decrypt(identities.iter().map(|identity| identity.as_ref() as &dyn age::Identity)).map_err(invalid)
+}
+
+// The keys a recipient stands for: itself, or every key listed in the file
+// it names, one per line with # starting a comment
+fn read_recipients(recipient: &str) -> io::Result<Vec<Box<dyn age::Recipient + Send>>> {
+    if !Path::new(recipient).is_file() {
+        return Ok(vec![parse_recipient(recipient)?]);
+    }
+    read_to_string(recipient)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_recipient)
+        .collect()
+}
+
+fn parse_recipient(key: &str) -> io::Result<Box<dyn age::Recipient + Send>> {
+    if let Ok(key) = key.parse::<age::x25519::Recipient>() {
+        return Ok(Box::new(key));
+    }
+    match key.parse::<age::ssh::Recipient>() {
+        Ok(key) => Ok(Box::new(key)),
+        Err(_) => Err(invalid(fill(tr("{} is not an age or SSH public key"), &[&key]))),
+    }
+}
+
+// The private keys in `path`: an SSH key, or an age identity file
+fn read_identities(path: &Path) -> io::Result<Vec<Box<dyn age::Identity>>> {
+    let contents = read_to_string(path)?;
+    if contents.trim_start().starts_with("-----BEGIN") {
+        let filename = Some(path.display().to_string());
+        let key = age::ssh::Identity::from_buffer(contents.as_bytes(), filename)?;
+        if !matches!(key, age::ssh::Identity::Unencrypted(_)) {
+            return Err(invalid(tr("SSH keys protected by a passphrase can't be used here")));
+        }
+        return Ok(vec![Box::new(key)]);
+    }
+    age::IdentityFile::from_buffer(contents.as_bytes())?.into_identities().map_err(invalid)
+}
+
+fn invalid(error: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+// Recipients as typed, separated by commas; SSH keys hold spaces, so
+// those can't separate them
+pub fn parse_recipients(text: &str) -> Vec<String> {
+    text.split(',').map(str::trim).filter(|recipient| !recipient.is_empty()).map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(lock: &Lock, unlock: &Unlock) -> io::Result<Vec<u8>> {
+        let mut writer = encrypt(lock, Vec::new())?;
+        writer.write_all(b"secret contents")?;
+        let encrypted = writer.finish()?;
+        let mut plain = Vec::new();
+        decrypt(unlock, encrypted.as_slice())?.read_to_end(&mut plain)?;
+        Ok(plain)
+    }
+
+    #[test]
+    fn passphrase_opens_what_it_locked() {
+        let lock = Lock::Passphrase(Passphrase::new("correct horse".to_string()));
+        let right = Unlock::Passphrase(Passphrase::new("correct horse".to_string()));
+        let wrong = Unlock::Passphrase(Passphrase::new("battery staple".to_string()));
+        assert_eq!(round_trip(&lock, &right).unwrap(), b"secret contents");
+        assert!(round_trip(&lock, &wrong).is_err());
+    }
+
+    #[test]
+    fn private_key_opens_what_its_public_key_locked() {
+        let identity = age::x25519::Identity::generate();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        {
+            use age::secrecy::ExposeSecret;
+            std::fs::write(file.path(), identity.to_string().expose_secret()).unwrap();
+        }
+        let lock = Lock::Recipients(vec![identity.to_public().to_string()]);
+        let plain = round_trip(&lock, &Unlock::Identity(file.path().to_path_buf()));
+        assert_eq!(plain.unwrap(), b"secret contents");
+    }
+}
@@ -0,0 +1,101 @@
+use crate::translate::{fill, tr};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// A failed operation with what is known about why: the one-line message
+// shown in toasts, plus the path, OS error and a likely cause for the
+// details view and bug reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub path: Option<PathBuf>,
+    pub code: Option<i32>,
+    pub kind: Option<io::ErrorKind>,
+}
+
+impl Diagnostic {
+    pub fn new(message: String) -> Diagnostic {
+        Diagnostic { message, path: None, code: None, kind: None }
+    }
+
+    // An OS error on `path`, labelled with the path
+    pub fn io(path: &Path, error: &io::Error) -> Diagnostic {
+        Diagnostic::new(format!("{}: {}", path.display(), error)).with_io(error).at(path)
+    }
+
+    pub fn at(mut self, path: &Path) -> Diagnostic {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+
+    pub fn with_io(mut self, error: &io::Error) -> Diagnostic {
+        self.code = error.raw_os_error();
+        self.kind = Some(error.kind());
+        self
+    }
+
+    // The same failure told differently, keeping what caused it
+    pub fn reworded(self, message: String) -> Diagnostic {
+        Diagnostic { message, ..self }
+    }
+
+    // Whether there's anything to show beyond the message
+    pub fn has_details(&self) -> bool {
+        self.path.is_some() || self.code.is_some() || self.kind.is_some()
+    }
+
+    pub fn is_denied(&self) -> bool {
+        self.kind == Some(io::ErrorKind::PermissionDenied)
+    }
+
+    // What usually leads to this kind of error
+    pub fn cause(&self) -> Option<&'static str> {
+        let cause = match self.kind? {
+            io::ErrorKind::NotFound => "It was moved, renamed or deleted, possibly by another program.",
+            io::ErrorKind::PermissionDenied => "Your account isn't allowed to do this; check the owner and permissions.",
+            io::ErrorKind::AlreadyExists => "Something with this name is already there.",
+            io::ErrorKind::StorageFull => "The drive is full.",
+            io::ErrorKind::ReadOnlyFilesystem => "The drive is mounted read-only.",
+            io::ErrorKind::DirectoryNotEmpty => "The folder still has something in it.",
+            io::ErrorKind::ResourceBusy => "Another program is using it.",
+            io::ErrorKind::CrossesDevices => "Source and destination are on different drives.",
+            io::ErrorKind::InvalidFilename => "The name is too long or uses characters this drive doesn't allow.",
+            io::ErrorKind::TimedOut => "A network drive stopped answering.",
+            io::ErrorKind::Unsupported => "The drive doesn't support this.",
+            _ => return None,
+        };
+        Some(tr(cause))
+    }
+
+    // Everything known, as plain text to paste into a bug report
+    pub fn report(&self) -> String {
+        let mut lines = vec![self.message.clone()];
+        if let Some(path) = &self.path {
+            lines.push(fill(tr("Path: {}"), &[&path.display()]));
+        }
+        if let Some(code) = self.code {
+            lines.push(fill(tr("OS error code: {}"), &[&code]));
+        }
+        if let Some(kind) = self.kind {
+            lines.push(fill(tr("Kind: {}"), &[&format!("{:?}", kind)]));
+        }
+        if let Some(cause) = self.cause() {
+            lines.push(fill(tr("Likely cause: {}"), &[&cause]));
+        }
+        lines.push(format!("{} {} ({} {})", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), std::env::consts::OS, std::env::consts::ARCH));
+        lines.join("\n")
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<String> for Diagnostic {
+    fn from(message: String) -> Diagnostic {
+        Diagnostic::new(message)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::entry::is_hidden;
+use crate::excludes::Excludes;
+use crate::outlet::Outlet;
+use crate::visited::Visited;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+// How many files are summed between checks for cancellation
+const CANCEL_CHECK_INTERVAL: usize = 256;
+
+// What to compute for each folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Measure {
+    // Total size of every file below the folder
+    Size,
+    // Number of visible entries directly inside the folder
+    Count,
+}
+
+// Measures each folder in `folders`, sending each one to `outlet` as soon as
+// it is known; stops once nobody is listening
+pub fn measure(measure: Measure, folders: Vec<PathBuf>, excludes: &Excludes, outlet: &impl Outlet<(PathBuf, u64)>) {
+    for folder in folders {
+        let value = match measure {
+            Measure::Size => folder_size(&folder, outlet),
+            Measure::Count => Some(item_count(&folder, excludes)),
+        };
+        let Some(value) = value else {
+            return;
+        };
+        if !outlet.send((folder, value)) {
+            return;
+        }
+    }
+}
+
+// Sums the sizes of all files below `folder`, or returns None if cancelled
+fn folder_size(folder: &Path, outlet: &impl Outlet<(PathBuf, u64)>) -> Option<u64> {
+    let mut total = 0;
+
+    for (index, entry) in WalkDir::new(folder).into_iter().filter_entry(Visited::filter()).flatten().enumerate() {
+        if index % CANCEL_CHECK_INTERVAL == 0 && outlet.is_closed() {
+            return None;
+        }
+
+        if entry.file_type().is_file()
+            && let Ok(meta) = entry.metadata()
+        {
+            total += meta.len();
+        }
+    }
+
+    Some(total)
+}
+
+// Counts the entries the file list would show for `folder`
+fn item_count(folder: &Path, excludes: &Excludes) -> u64 {
+    read_dir(folder)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| !is_hidden(&entry.path()) && !excludes.hides(&entry.path()))
+                .count() as u64
+        })
+        .unwrap_or(0)
+}
@@ -0,0 +1,76 @@
+// Free and total space of the filesystem holding a path, through the C
+// library's statvfs or the Windows call, which std doesn't offer
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskSpace {
+    // What the user may still write, which leaves out space kept for root
+    pub free: u64,
+    pub total: u64,
+}
+
+pub fn of(path: &Path) -> io::Result<DiskSpace> {
+    sys::of(path)
+}
+
+#[cfg(unix)]
+mod sys {
+    use super::DiskSpace;
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    pub fn of(path: &Path) -> io::Result<DiskSpace> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        // SAFETY: statvfs is plain data, for which all zeroes is a valid value
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        // SAFETY: the path ends in NUL and libc's struct matches this system's
+        if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Blocks are counted in fragments; some systems leave that size 0
+        let unit = if stat.f_frsize == 0 { stat.f_bsize } else { stat.f_frsize };
+        Ok(DiskSpace { free: bytes(stat.f_bavail, unit), total: bytes(stat.f_blocks, unit) })
+    }
+
+    // Counts and sizes are 32 or 64 bits wide depending on the system
+    fn bytes(blocks: impl Into<u64>, unit: impl Into<u64>) -> u64 {
+        blocks.into().saturating_mul(unit.into())
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::DiskSpace;
+    use std::io;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetDiskFreeSpaceExW(directory: *const u16, available: *mut u64, total: *mut u64, free: *mut u64) -> i32;
+    }
+
+    pub fn of(path: &Path) -> io::Result<DiskSpace> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+        let (mut available, mut total, mut free) = (0u64, 0u64, 0u64);
+        // SAFETY: the path ends in NUL and the three counts are ours to write
+        if unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, &mut total, &mut free) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(DiskSpace { free: available, total })
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use super::DiskSpace;
+    use std::io;
+    use std::path::Path;
+
+    pub fn of(_path: &Path) -> io::Result<DiskSpace> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "free space can't be read here"))
+    }
+}
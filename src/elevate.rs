@@ -0,0 +1,90 @@
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// A file change that can be redone with administrator rights
+#[derive(Debug, Clone, Copy)]
+pub enum Step<'a> {
+    CreateDir(&'a Path),
+    RemoveFile(&'a Path),
+    RemoveDir(&'a Path),
+    CopyFile(&'a Path, &'a Path),
+    Rename(&'a Path, &'a Path),
+}
+
+// Whether this system has a way to ask for administrator rights
+pub fn available() -> bool {
+    if cfg!(any(target_os = "macos", windows)) {
+        return true;
+    }
+    // Elsewhere it takes polkit's pkexec
+    std::env::var_os("PATH").is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join("pkexec").is_file()))
+}
+
+// Carries out `step` as administrator once the system has asked for a
+// password: pkexec on Linux, the system dialog on macOS, a UAC prompt on
+// Windows. Blocking, so call it off the UI thread.
+pub fn run(step: Step) -> io::Result<()> {
+    let status = elevated(step).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("administrator command failed ({})", status)))
+    }
+}
+
+// The shell words doing `step`
+#[cfg(unix)]
+fn words(step: Step<'_>) -> Vec<&std::ffi::OsStr> {
+    let word = |text: &'static str| std::ffi::OsStr::new(text);
+    match step {
+        Step::CreateDir(path) => vec![word("mkdir"), word("-p"), word("--"), path.as_os_str()],
+        Step::RemoveFile(path) => vec![word("rm"), word("-f"), word("--"), path.as_os_str()],
+        Step::RemoveDir(path) => vec![word("rmdir"), word("--"), path.as_os_str()],
+        Step::CopyFile(from, to) => vec![word("cp"), word("-p"), word("--"), from.as_os_str(), to.as_os_str()],
+        Step::Rename(from, to) => vec![word("mv"), word("--"), from.as_os_str(), to.as_os_str()],
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn elevated(step: Step) -> Command {
+    let mut command = Command::new("pkexec");
+    command.args(words(step));
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn elevated(step: Step) -> Command {
+    let line = words(step)
+        .iter()
+        .map(|word| format!("'{}'", word.to_string_lossy().replace('\'', "'\\''")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let script = format!(
+        "do shell script \"{}\" with administrator privileges",
+        line.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(script);
+    command
+}
+
+#[cfg(windows)]
+fn elevated(step: Step) -> Command {
+    let quote = |path: &Path| format!("\"{}\"", path.display());
+    let line = match step {
+        Step::CreateDir(path) => format!("mkdir {}", quote(path)),
+        Step::RemoveFile(path) => format!("del /F /Q {}", quote(path)),
+        Step::RemoveDir(path) => format!("rmdir {}", quote(path)),
+        Step::CopyFile(from, to) => format!("copy /Y {} {}", quote(from), quote(to)),
+        Step::Rename(from, to) => format!("move {} {}", quote(from), quote(to)),
+    };
+    // Start-Process raises the UAC prompt; its exit code is passed on
+    let script = format!(
+        "$p = Start-Process -FilePath cmd.exe -ArgumentList '/C', '{}' -Verb RunAs -Wait -PassThru; exit $p.ExitCode",
+        line.replace('\'', "''")
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command"]).arg(script);
+    command
+}
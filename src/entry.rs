@@ -0,0 +1,256 @@
+// What a listing holds about each file and folder, and the orders it can
+// be shown in
+use crate::icons::IconKind;
+use crate::translate::tr;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    // The name as stored on disk, which need not be valid UTF-8
+    pub name: OsString,
+    // Read once at load time; `invalidate` re-reads them
+    pub is_dir: bool,
+    // Only a placeholder, with the contents still with a sync client
+    pub cloud: bool,
+    pub icon: IconKind,
+    pub size: u64,
+    pub modified: SystemTime,
+    pub file_type: String,
+    // Tags kept in the file's own attributes
+    pub tags: Vec<String>,
+    // Lines that matched a content search
+    pub line_matches: Vec<LineMatch>,
+    // Extra facts a search reported, such as mode and owner
+    pub detail: Option<String>,
+}
+
+impl FileEntry {
+    // The name for display, with invalid bytes replaced
+    pub fn display_name(&self) -> Cow<'_, str> {
+        self.name.to_string_lossy()
+    }
+
+    // Whether the name had to be altered for display
+    pub fn has_lossy_name(&self) -> bool {
+        self.name.to_str().is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LineMatch {
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortMode {
+    NameAsc,
+    NameDesc,
+    SizeAsc,
+    SizeDesc,
+    DateAsc,
+    DateDesc,
+    // Best fuzzy matches first
+    Relevance,
+}
+
+impl SortMode {
+    // The orders a column header can pick
+    pub const COLUMNS: [SortMode; 6] = [
+        SortMode::NameAsc,
+        SortMode::NameDesc,
+        SortMode::SizeAsc,
+        SortMode::SizeDesc,
+        SortMode::DateAsc,
+        SortMode::DateDesc,
+    ];
+}
+
+impl std::fmt::Display for SortMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            SortMode::NameAsc => "Name (A–Z)",
+            SortMode::NameDesc => "Name (Z–A)",
+            SortMode::SizeAsc => "Size (smallest first)",
+            SortMode::SizeDesc => "Size (largest first)",
+            SortMode::DateAsc => "Modified (oldest first)",
+            SortMode::DateDesc => "Modified (newest first)",
+            SortMode::Relevance => "Relevance",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+// Orders `entries` by one of the column orders, with folders first when
+// `dirs_first`. Relevance depends on a search query, so the caller ranks
+// those itself.
+pub fn sort(entries: &mut [FileEntry], mode: &SortMode, dirs_first: bool) {
+    match mode {
+        SortMode::NameAsc => {
+            // Sort directories first, then files alphabetically
+            entries.sort_by(|a, b| {
+                let a_is_dir = a.is_dir;
+                let b_is_dir = b.is_dir;
+
+                match (a_is_dir && dirs_first, b_is_dir && dirs_first) {
+                    (true, false) => std::cmp::Ordering::Less,
+                    (false, true) => std::cmp::Ordering::Greater,
+                    _ => a.path.file_name().cmp(&b.path.file_name()),
+                }
+            });
+        },
+        SortMode::NameDesc => {
+            // Sort directories first, then files reverse alphabetically
+            entries.sort_by(|a, b| {
+                let a_is_dir = a.is_dir;
+                let b_is_dir = b.is_dir;
+
+                match (a_is_dir && dirs_first, b_is_dir && dirs_first) {
+                    (true, false) => std::cmp::Ordering::Less,
+                    (false, true) => std::cmp::Ordering::Greater,
+                    _ => b.path.file_name().cmp(&a.path.file_name()),
+                }
+            });
+        },
+        SortMode::SizeAsc => {
+            // Sort by size (ascending)
+            entries.sort_by(|a, b| {
+                let a_is_dir = a.is_dir;
+                let b_is_dir = b.is_dir;
+
+                match (a_is_dir && dirs_first, b_is_dir && dirs_first) {
+                    (true, true) => a.path.file_name().cmp(&b.path.file_name()),
+                    (true, false) => std::cmp::Ordering::Less,
+                    (false, true) => std::cmp::Ordering::Greater,
+                    (false, false) => a.size.cmp(&b.size),
+                }
+            });
+        },
+        SortMode::SizeDesc => {
+            // Sort by size (descending)
+            entries.sort_by(|a, b| {
+                let a_is_dir = a.is_dir;
+                let b_is_dir = b.is_dir;
+
+                match (a_is_dir && dirs_first, b_is_dir && dirs_first) {
+                    (true, true) => a.path.file_name().cmp(&b.path.file_name()),
+                    (true, false) => std::cmp::Ordering::Less,
+                    (false, true) => std::cmp::Ordering::Greater,
+                    (false, false) => b.size.cmp(&a.size),
+                }
+            });
+        },
+        SortMode::DateAsc => {
+            // Sort by modification date (ascending)
+            entries.sort_by(|a, b| {
+                let a_is_dir = a.is_dir;
+                let b_is_dir = b.is_dir;
+
+                match (a_is_dir && dirs_first, b_is_dir && dirs_first) {
+                    (true, false) => std::cmp::Ordering::Less,
+                    (false, true) => std::cmp::Ordering::Greater,
+                    _ => a.modified.cmp(&b.modified),
+                }
+            });
+        },
+        SortMode::DateDesc => {
+            // Sort by modification date (descending)
+            entries.sort_by(|a, b| {
+                let a_is_dir = a.is_dir;
+                let b_is_dir = b.is_dir;
+
+                match (a_is_dir && dirs_first, b_is_dir && dirs_first) {
+                    (true, false) => std::cmp::Ordering::Less,
+                    (false, true) => std::cmp::Ordering::Greater,
+                    _ => b.modified.cmp(&a.modified),
+                }
+            });
+        },
+        SortMode::Relevance => {}
+    }
+}
+
+pub fn is_hidden(path: &Path) -> bool {
+    // Compared as bytes so names that aren't UTF-8 are judged too
+    path.file_name()
+        .is_some_and(|name| name.as_encoded_bytes().starts_with(b"."))
+}
+
+// Why `name` can't be given to a new file or folder, if it can't: it has to
+// name one entry of the folder it is made in, not a path leading elsewhere
+pub fn name_problem(name: &str) -> Option<&'static str> {
+    if name == "." || name == ".." {
+        Some(tr("'.' and '..' can't be used as names"))
+    } else if name.contains(std::path::is_separator) {
+        Some(tr("Names can't contain a path separator"))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_leading_out_of_the_folder_are_refused() {
+        for name in [".", "..", "a/b", "../escape", "/etc"] {
+            assert!(name_problem(name).is_some(), "{} was accepted", name);
+        }
+        for name in ["notes.txt", ".hidden", "..dots", "a b"] {
+            assert_eq!(name_problem(name), None);
+        }
+    }
+
+    fn entry(name: &str, is_dir: bool, size: u64, minute: u64) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from("/listed").join(name),
+            name: OsString::from(name),
+            is_dir,
+            cloud: false,
+            icon: if is_dir { IconKind::Folder } else { IconKind::Text },
+            size,
+            modified: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(minute * 60),
+            file_type: String::new(),
+            tags: Vec::new(),
+            line_matches: Vec::new(),
+            detail: None,
+        }
+    }
+
+    fn sorted(mode: SortMode, dirs_first: bool) -> Vec<String> {
+        let mut entries = vec![
+            entry("b", true, 0, 0),
+            entry("c.txt", false, 5, 1),
+            entry("a", true, 0, 4),
+            entry("a.txt", false, 10, 3),
+            entry("b.txt", false, 1, 2),
+        ];
+        sort(&mut entries, &mode, dirs_first);
+        entries.iter().map(|entry| entry.display_name().into_owned()).collect()
+    }
+
+    #[test]
+    fn sorts_each_column_both_ways_with_folders_first() {
+        assert_eq!(sorted(SortMode::NameAsc, true), ["a", "b", "a.txt", "b.txt", "c.txt"]);
+        assert_eq!(sorted(SortMode::NameDesc, true), ["b", "a", "c.txt", "b.txt", "a.txt"]);
+        // Folders have no size of their own, so they go by name
+        assert_eq!(sorted(SortMode::SizeAsc, true), ["a", "b", "b.txt", "c.txt", "a.txt"]);
+        assert_eq!(sorted(SortMode::SizeDesc, true), ["a", "b", "a.txt", "c.txt", "b.txt"]);
+        assert_eq!(sorted(SortMode::DateAsc, true), ["b", "a", "c.txt", "b.txt", "a.txt"]);
+        assert_eq!(sorted(SortMode::DateDesc, true), ["a", "b", "a.txt", "b.txt", "c.txt"]);
+    }
+
+    #[test]
+    fn folders_mix_in_unless_they_go_first() {
+        assert_eq!(sorted(SortMode::NameAsc, false), ["a", "a.txt", "b", "b.txt", "c.txt"]);
+        assert_eq!(sorted(SortMode::DateDesc, false), ["a", "a.txt", "b.txt", "c.txt", "b"]);
+        // Relevance is ranked by the caller
+        assert_eq!(sorted(SortMode::Relevance, true), ["b", "c.txt", "a", "a.txt", "b.txt"]);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::entry::is_hidden;
+use crate::visited::Visited;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::path::Path;
+use std::sync::Mutex;
+
+// Paths that recursive searches and usage scans leave out: anything matched
+// by .gitignore/.ignore files, plus entries whose name matches a pattern.
+// Entries matching a hide pattern are left out of every listing, the
+// folder view included, whether or not the rest is enabled.
+#[derive(Debug, Clone)]
+pub struct Excludes {
+    enabled: bool,
+    patterns: Vec<String>,
+    names: GlobSet,
+    hide_patterns: Vec<String>,
+    hidden: GlobSet,
+}
+
+impl Excludes {
+    // When disabled only the hide patterns apply; invalid patterns are skipped
+    pub fn new(enabled: bool, patterns: &[String], hide_patterns: &[String]) -> Excludes {
+        Excludes {
+            enabled,
+            patterns: patterns.to_vec(),
+            names: glob_set(patterns),
+            hide_patterns: hide_patterns.to_vec(),
+            hidden: glob_set(hide_patterns),
+        }
+    }
+
+    // Whether two sets of rules would skip the same paths
+    pub fn same_rules(&self, enabled: bool, patterns: &[String], hide_patterns: &[String]) -> bool {
+        self.enabled == enabled && (!enabled || self.patterns == patterns) && self.hide_patterns == hide_patterns
+    }
+
+    // Whether `path` is one the hide patterns keep out of listings
+    pub fn hides(&self, path: &Path) -> bool {
+        path.file_name().is_some_and(|name| self.hidden.is_match(name))
+    }
+
+    pub fn hide_patterns(&self) -> &[String] {
+        &self.hide_patterns
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    // Walks everything below `root` (not `root` itself) that the rules keep.
+    // Symlinks are not followed, and no folder is walked twice.
+    pub fn walk(&self, root: &Path, include_hidden: bool) -> WalkBuilder {
+        let mut builder = WalkBuilder::new(root);
+        builder
+            .standard_filters(false)
+            .git_ignore(self.enabled)
+            .git_exclude(self.enabled)
+            .ignore(self.enabled)
+            .parents(self.enabled)
+            // Honour .gitignore files outside of repositories too
+            .require_git(false)
+            .min_depth(Some(1));
+
+        let names = self.names.clone();
+        let hidden = self.hidden.clone();
+        let enabled = self.enabled;
+        let visited = Mutex::new(Visited::default());
+        if let Ok(mut visited) = visited.lock() {
+            visited.enter(root);
+        }
+        builder.filter_entry(move |entry| {
+            let skipped = (!include_hidden && is_hidden(entry.path()))
+                || (enabled && names.is_match(entry.file_name()))
+                || hidden.is_match(entry.file_name());
+            if skipped {
+                return false;
+            }
+            let is_dir = entry.file_type().is_some_and(|file_type| file_type.is_dir());
+            !is_dir || visited.lock().map_or(true, |mut visited| visited.enter(entry.path()))
+        });
+        builder
+    }
+}
+
+fn glob_set(patterns: &[String]) -> GlobSet {
+    let mut set = GlobSetBuilder::new();
+    for pattern in patterns {
+        if let Ok(glob) = Glob::new(pattern) {
+            set.add(glob);
+        }
+    }
+    set.build().unwrap_or_else(|_| GlobSet::empty())
+}
@@ -0,0 +1,245 @@
+use crate::entry::LineMatch;
+use crate::excludes::Excludes;
+use crate::lan;
+use crate::loader::{self, Sink};
+use crate::search::{Matcher, Query, Scope, MAX_CONTENT_SIZE, MAX_SNIPPETS, MAX_SNIPPET_LEN};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+// How often a running tool is checked for the search being stopped
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+// Runs `query` through ripgrep (contents) or fd (names). Returns false
+// without pushing anything if the tool is missing or cannot express the
+// query, so the caller can fall back to its own walk.
+pub fn search(root: &Path, query: &Query, excludes: &Excludes, sink: &mut Sink) -> bool {
+    match query.scope {
+        Scope::Contents => ripgrep(root, query, excludes, sink),
+        Scope::Names => fd(root, query, excludes, sink),
+        Scope::Checksum | Scope::Permissions => false,
+    }
+}
+
+fn ripgrep(root: &Path, query: &Query, excludes: &Excludes, sink: &mut Sink) -> bool {
+    let matcher = &query.matcher;
+    let mut command = Command::new("rg");
+    command
+        .arg("--json")
+        .arg("--ignore-case")
+        .arg(format!("--max-count={}", MAX_SNIPPETS))
+        .arg(format!("--max-filesize={}", MAX_CONTENT_SIZE));
+
+    match matcher {
+        Matcher::Substring { text, .. } => command.arg("--fixed-strings").arg("-e").arg(text),
+        Matcher::Regex(regex) => command.arg("-e").arg(regex.as_str()),
+        _ => return false,
+    };
+    apply_excludes(&mut command, excludes, "--glob", |pattern| format!("!{}", pattern));
+
+    let Some(child) = spawn(command.arg(root)) else {
+        return false;
+    };
+
+    supervise(child, sink, |stdout, sink| {
+        // rg reports each file as begin, its matches, then end
+        let mut current: Option<(PathBuf, Vec<LineMatch>)> = None;
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+
+            let Ok(message) = serde_json::from_str::<serde_json::Value>(&line) else {
+                continue;
+            };
+            let data = &message["data"];
+            match message["type"].as_str() {
+                Some("begin") => current = path_of(&data["path"]).map(|path| (path, Vec::new())),
+                Some("match") => {
+                    if let Some((_, lines)) = &mut current
+                        && let Some(text) = data["lines"]["text"].as_str()
+                    {
+                        lines.push(LineMatch {
+                            line: data["line_number"].as_u64().unwrap_or(0) as usize,
+                            text: text.trim().chars().take(MAX_SNIPPET_LEN).collect(),
+                        });
+                    }
+                }
+                Some("end") => {
+                    if let Some((path, lines)) = current.take() {
+                        let mut found = loader::read_entry(path);
+                        found.line_matches = lines;
+                        if query.accepts_modified(found.modified) && !sink.push(found) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    })
+}
+
+fn fd(root: &Path, query: &Query, excludes: &Excludes, sink: &mut Sink) -> bool {
+    let matcher = &query.matcher;
+    let mut command = Command::new("fd");
+    command.arg("--print0").arg("--absolute-path").arg("--ignore-case");
+
+    let pattern = match matcher {
+        Matcher::Substring { text, .. } => {
+            command.arg("--fixed-strings");
+            text.as_str()
+        }
+        Matcher::Regex(regex) => {
+            command.arg("--regex");
+            regex.as_str()
+        }
+        // fd would match path globs against absolute paths, unlike our walk
+        Matcher::Glob { text, match_path: false, .. } => {
+            command.arg("--glob");
+            text.as_str()
+        }
+        _ => return false,
+    };
+    if matcher.wants_hidden() {
+        command.arg("--hidden");
+    }
+    apply_excludes(&mut command, excludes, "--exclude", str::to_string);
+
+    let Some(child) = spawn(command.arg("--").arg(pattern).arg(root)) else {
+        return false;
+    };
+
+    supervise(child, sink, |stdout, sink| {
+        for path in BufReader::new(stdout).split(0) {
+            let Ok(path) = path else {
+                break;
+            };
+            let Some(path) = path_from_bytes(path) else {
+                continue;
+            };
+            let found = loader::read_entry(path);
+            if query.accepts_modified(found.modified) && !sink.push(found) {
+                break;
+            }
+        }
+    })
+}
+
+// Hands the tool's output to `read`, and stops the tool once `read` is done
+// or the search is cancelled, also while the tool prints nothing
+fn supervise(mut child: Child, sink: &mut Sink, read: impl FnOnce(ChildStdout, &mut Sink)) -> bool {
+    let Some(stdout) = child.stdout.take() else {
+        let _ = child.kill();
+        let _ = child.wait();
+        return false;
+    };
+    let child = Mutex::new(child);
+    let done = AtomicBool::new(false);
+    let watcher = sink.fork();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                if watcher.is_cancelled() {
+                    let _ = child.lock().map(|mut child| child.kill());
+                    break;
+                }
+                thread::sleep(CANCEL_POLL);
+            }
+        });
+        read(stdout, sink);
+        done.store(true, Ordering::Relaxed);
+    });
+
+    // `read` may have stopped before the tool did
+    let mut child = child.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let _ = child.kill();
+    let _ = child.wait();
+    true
+}
+
+// rg gives a path as text, or as base64 bytes when it isn't UTF-8
+fn path_of(value: &serde_json::Value) -> Option<PathBuf> {
+    match value["text"].as_str() {
+        Some(text) => Some(PathBuf::from(text)),
+        None => value["bytes"].as_str().and_then(lan::base64_decode).and_then(path_from_bytes),
+    }
+}
+
+// Paths are bytes on Unix, and kept exactly; elsewhere the tools print UTF-8
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> Option<PathBuf> {
+    use std::os::unix::ffi::OsStrExt;
+    Some(PathBuf::from(std::ffi::OsStr::from_bytes(&bytes)))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> Option<PathBuf> {
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+// Both tools take the same ignore-file flags
+fn apply_excludes(command: &mut Command, excludes: &Excludes, flag: &str, pattern: impl Fn(&str) -> String) {
+    for hidden in excludes.hide_patterns() {
+        command.arg(flag).arg(pattern(hidden));
+    }
+    if !excludes.is_enabled() {
+        command.arg("--no-ignore");
+        return;
+    }
+    // Like our walk, honour .gitignore files outside of repositories too
+    command.arg("--no-require-git");
+    for excluded in excludes.patterns() {
+        command.arg(flag).arg(pattern(excluded));
+    }
+}
+
+fn spawn(command: &mut Command) -> Option<Child> {
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null()).spawn().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_paths_given_as_text_or_bytes() {
+        assert_eq!(path_of(&serde_json::json!({"text": "/tmp/a b.txt"})), Some(PathBuf::from("/tmp/a b.txt")));
+        assert_eq!(path_of(&serde_json::json!({})), None);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            // "/tmp/caf\xe9" as rg encodes it
+            let path = path_of(&serde_json::json!({"bytes": "L3RtcC9jYWbp"})).unwrap();
+            assert_eq!(path.as_os_str().as_bytes(), b"/tmp/caf\xe9");
+        }
+    }
+
+    // Nobody listens to it
+    struct Closed;
+
+    impl crate::outlet::Outlet<loader::Batch> for Closed {
+        fn send(&self, _: loader::Batch) -> bool {
+            false
+        }
+
+        fn is_closed(&self) -> bool {
+            true
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_silent_tool_is_stopped_when_the_search_is() {
+        let child = spawn(Command::new("sleep").arg("30")).unwrap();
+        let started = std::time::Instant::now();
+        let mut sink = Sink::new(Closed);
+        assert!(supervise(child, &mut sink, |stdout, _| BufReader::new(stdout).lines().for_each(drop)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
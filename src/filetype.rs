@@ -0,0 +1,132 @@
+use infer::MatcherType;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// Number of leading bytes inspected for magic numbers and binary content
+const SNIFF_LEN: u64 = 8192;
+
+// Describes a file for the Type column, e.g. "PNG image" or "Rust source"
+pub fn describe(path: &Path) -> String {
+    detect(path).0
+}
+
+// The description, and the type the contents tell where they do, from one
+// read of the file
+pub fn detect(path: &Path) -> (String, Option<infer::Type>) {
+    if path.is_dir() {
+        return ("Folder".to_string(), None);
+    }
+
+    let sample = read_sample(path).unwrap_or_default();
+
+    if let Some(kind) = infer::get(&sample) {
+        return (describe_magic(&kind), Some(kind).filter(telling));
+    }
+    (describe_sample(path, &sample), None)
+}
+
+// The type of a file by its contents, so a renamed photo is still a photo.
+// None when they don't say, or say no more than the name would.
+pub fn sniff(path: &Path) -> Option<infer::Type> {
+    read_sample(path).ok().and_then(|sample| infer::get(&sample)).filter(telling)
+}
+
+// The extension a file's contents call for, or else the one in its name;
+// lowercase, without the dot
+pub fn extension(path: &Path) -> Option<String> {
+    sniff(path).map(|kind| kind.extension().to_string()).or_else(|| name_extension(path))
+}
+
+// Scripts are recognized as text and many formats are zip files
+// underneath; for those, the name says more than the contents
+fn telling(kind: &infer::Type) -> bool {
+    kind.matcher_type() != MatcherType::Text && kind.extension() != "zip"
+}
+
+fn describe_sample(path: &Path, sample: &[u8]) -> String {
+    if let Some(description) = name_extension(path).and_then(|ext| describe_extension(&ext)) {
+        return description.to_string();
+    }
+
+    if sample.is_empty() {
+        "Empty file".to_string()
+    } else if sample.contains(&0) {
+        "Unknown binary".to_string()
+    } else {
+        "Plain text".to_string()
+    }
+}
+
+// Describes a file from its name alone, for files whose contents
+// shouldn't be read, such as cloud placeholders
+pub fn describe_by_name(path: &Path) -> String {
+    name_extension(path)
+        .and_then(|ext| describe_extension(&ext))
+        .unwrap_or("Online-only file")
+        .to_string()
+}
+
+fn read_sample(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut sample = Vec::new();
+    File::open(path)?.take(SNIFF_LEN).read_to_end(&mut sample)?;
+    Ok(sample)
+}
+
+fn name_extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+}
+
+fn describe_magic(kind: &infer::Type) -> String {
+    let name = kind.extension().to_uppercase();
+    match kind.matcher_type() {
+        MatcherType::Image => format!("{} image", name),
+        MatcherType::Audio => format!("{} audio", name),
+        MatcherType::Video => format!("{} video", name),
+        MatcherType::Archive => format!("{} archive", name),
+        MatcherType::Doc => format!("{} document", name),
+        MatcherType::Book => format!("{} e-book", name),
+        MatcherType::Font => format!("{} font", name),
+        MatcherType::App => match kind.extension() {
+            "exe" | "elf" | "dll" | "so" => "Executable".to_string(),
+            _ => format!("{} application", name),
+        },
+        MatcherType::Text => format!("{} text", name),
+        MatcherType::Custom => kind.mime_type().to_string(),
+    }
+}
+
+fn describe_extension(ext: &str) -> Option<&'static str> {
+    let description = match ext {
+        "rs" => "Rust source",
+        "py" => "Python source",
+        "js" | "mjs" | "cjs" => "JavaScript source",
+        "ts" | "tsx" => "TypeScript source",
+        "c" => "C source",
+        "h" | "hpp" => "C header",
+        "cpp" | "cc" | "cxx" => "C++ source",
+        "go" => "Go source",
+        "java" => "Java source",
+        "kt" => "Kotlin source",
+        "swift" => "Swift source",
+        "rb" => "Ruby source",
+        "sh" | "bash" | "zsh" => "Shell script",
+        "md" | "markdown" => "Markdown document",
+        "txt" => "Plain text",
+        "log" => "Log file",
+        "toml" => "TOML config",
+        "json" => "JSON data",
+        "yaml" | "yml" => "YAML config",
+        "xml" => "XML document",
+        "html" | "htm" => "HTML document",
+        "css" => "Stylesheet",
+        "csv" => "CSV data",
+        "ini" | "cfg" | "conf" => "Config file",
+        "lock" => "Lock file",
+        "svg" => "SVG image",
+        _ => return None,
+    };
+    Some(description)
+}
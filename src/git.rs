@@ -0,0 +1,209 @@
+use git2::build::CheckoutBuilder;
+use git2::{BranchType, DiffFormat, DiffOptions, Repository, Status, StatusOptions};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// Git state of an entry, ordered from least to most important so that a
+// folder can show the most significant status of anything inside it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GitBadge {
+    Ignored,
+    Untracked,
+    Added,
+    Modified,
+    Conflicted,
+}
+
+impl GitBadge {
+    fn from_status(status: Status) -> Option<GitBadge> {
+        if status.is_conflicted() {
+            Some(GitBadge::Conflicted)
+        } else if status.intersects(
+            Status::WT_MODIFIED
+                | Status::WT_DELETED
+                | Status::WT_RENAMED
+                | Status::WT_TYPECHANGE
+                | Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE,
+        ) {
+            Some(GitBadge::Modified)
+        } else if status.is_index_new() {
+            Some(GitBadge::Added)
+        } else if status.is_wt_new() {
+            Some(GitBadge::Untracked)
+        } else if status.is_ignored() {
+            Some(GitBadge::Ignored)
+        } else {
+            None
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GitBadge::Ignored => "ignored",
+            GitBadge::Untracked => "untracked",
+            GitBadge::Added => "added",
+            GitBadge::Modified => "modified",
+            GitBadge::Conflicted => "conflict",
+        }
+    }
+
+    // The short form `git status --short` uses
+    pub fn letter(self) -> &'static str {
+        match self {
+            GitBadge::Ignored => "!",
+            GitBadge::Untracked => "??",
+            GitBadge::Added => "A",
+            GitBadge::Modified => "M",
+            GitBadge::Conflicted => "U",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RepoStatus {
+    pub branch: String,
+    // Commits the branch is ahead of and behind its upstream, if it has one
+    pub ahead_behind: Option<(usize, usize)>,
+    // Badges for the direct children of the listed directory
+    pub badges: HashMap<PathBuf, GitBadge>,
+}
+
+// Collects the git status of the entries in `dir`, or None outside a repository
+pub fn status(dir: &Path) -> Option<RepoStatus> {
+    let repo = Repository::discover(dir).ok()?;
+    let workdir = repo.workdir()?.to_path_buf();
+
+    let branch = match repo.head() {
+        Ok(head) if head.is_branch() => head.shorthand().unwrap_or("HEAD").to_string(),
+        Ok(head) => match head.target() {
+            Some(oid) => format!("detached at {:.7}", oid),
+            None => "HEAD".to_string(),
+        },
+        // A fresh repository has no commits and therefore no resolvable HEAD
+        Err(_) => "no commits yet".to_string(),
+    };
+
+    let ahead_behind = ahead_behind(&repo);
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .include_ignored(true)
+        .recurse_untracked_dirs(false)
+        .recurse_ignored_dirs(false);
+
+    let mut badges: HashMap<PathBuf, GitBadge> = HashMap::new();
+
+    if let Ok(statuses) = repo.statuses(Some(&mut options)) {
+        for entry in statuses.iter() {
+            let (Ok(relative), Some(badge)) = (entry.path(), GitBadge::from_status(entry.status())) else {
+                continue;
+            };
+
+            let full = workdir.join(relative.trim_end_matches('/'));
+            let Some(child) = direct_child(dir, &full) else {
+                continue;
+            };
+
+            let current = badges.entry(child).or_insert(badge);
+            if badge > *current {
+                *current = badge;
+            }
+        }
+    }
+
+    Some(RepoStatus { branch, ahead_behind, badges })
+}
+
+fn ahead_behind(repo: &Repository) -> Option<(usize, usize)> {
+    let head = repo.head().ok()?;
+    let name = head.shorthand().ok().filter(|_| head.is_branch())?;
+    let upstream = repo.find_branch(name, BranchType::Local).ok()?.upstream().ok()?;
+    repo.graph_ahead_behind(head.target()?, upstream.get().target()?).ok()
+}
+
+// Maps a path anywhere below `dir` to the entry of `dir` that contains it
+fn direct_child(dir: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(dir).ok()?;
+    let first = relative.components().next()?;
+    Some(dir.join(first))
+}
+
+// What can be done to a single file from the explorer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileAction {
+    Stage,
+    Unstage,
+    // Throws away changes not yet committed, back to the last commit
+    Discard,
+}
+
+// The repository holding `path` and the path relative to its work tree
+fn open(path: &Path) -> Result<(Repository, PathBuf), git2::Error> {
+    let repo = Repository::discover(path.parent().unwrap_or(path))?;
+    let workdir = repo.workdir().ok_or_else(|| git2::Error::from_str("the repository has no work tree"))?;
+    let relative = path
+        .strip_prefix(workdir)
+        .map(Path::to_path_buf)
+        .map_err(|_| git2::Error::from_str("the file is outside the work tree"))?;
+    Ok((repo, relative))
+}
+
+pub fn apply(path: &Path, action: FileAction) -> Result<(), git2::Error> {
+    let (repo, relative) = open(path)?;
+    match action {
+        FileAction::Stage => {
+            let mut index = repo.index()?;
+            // A deleted file is staged by dropping it from the index
+            if path.symlink_metadata().is_ok() {
+                index.add_path(&relative)?;
+            } else {
+                index.remove_path(&relative)?;
+            }
+            index.write()
+        }
+        FileAction::Unstage => match repo.head().and_then(|head| head.peel_to_commit()) {
+            Ok(commit) => repo.reset_default(Some(commit.as_object()), [&relative]),
+            // Before the first commit there's nothing to go back to
+            Err(_) => {
+                let mut index = repo.index()?;
+                index.remove_path(&relative)?;
+                index.write()
+            }
+        },
+        FileAction::Discard => {
+            // Both the index and the file go back to the last commit, as
+            // with `git restore --staged --worktree`
+            let mut checkout = CheckoutBuilder::new();
+            checkout.force().path(&relative);
+            repo.checkout_head(Some(&mut checkout))
+        }
+    }
+}
+
+// The changes to `path` since the last commit, staged or not, as a
+// unified diff
+pub fn diff(path: &Path) -> Result<String, git2::Error> {
+    let (repo, relative) = open(path)?;
+    let tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut options = DiffOptions::new();
+    options
+        .pathspec(&relative)
+        .include_untracked(true)
+        .show_untracked_content(true)
+        .recurse_untracked_dirs(true);
+    let diff = repo.diff_tree_to_workdir_with_index(tree.as_ref(), Some(&mut options))?;
+
+    let mut patch = String::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+    Ok(patch)
+}
@@ -0,0 +1,42 @@
+use std::path::{Component, Path, PathBuf};
+
+// The deepest setting offered for how far below the root folders are
+// protected
+pub const MAX_PROTECTED_DEPTH: usize = 4;
+
+// Folders the system or the user's session can't do without, wherever
+// they sit
+#[cfg(unix)]
+const SYSTEM_FOLDERS: &[&str] = &[
+    "/bin", "/boot", "/dev", "/etc", "/lib", "/lib64", "/opt", "/proc", "/sbin", "/sys", "/usr", "/var",
+    "/Applications", "/Library", "/System", "/Users",
+];
+#[cfg(not(unix))]
+const SYSTEM_FOLDERS: &[&str] = &["C:\\Windows", "C:\\Program Files", "C:\\Program Files (x86)", "C:\\ProgramData", "C:\\Users"];
+
+// Folders below home holding the user's settings and keys
+const HOME_FOLDERS: &[&str] = &[".config", ".local", ".ssh", ".gnupg"];
+
+// Whether deleting or moving `path` needs the extra confirmation: a system
+// folder, home or a settings folder in it, anything holding home, or
+// anything within `depth` levels of the root
+pub fn is_protected(path: &Path, home: &Path, depth: usize) -> bool {
+    // Not canonicalized: a link to /etc is only a link, and removing it
+    // leaves /etc alone
+    let levels = path.components().filter(|component| matches!(component, Component::Normal(_))).count();
+
+    levels <= depth
+        || home.starts_with(path)
+        || HOME_FOLDERS.iter().any(|folder| path == home.join(folder))
+        || SYSTEM_FOLDERS.iter().any(|folder| path == Path::new(folder))
+}
+
+// What has to be typed to go ahead with touching `paths`: their names, in
+// the order given
+pub fn confirmation_text(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| path.display().to_string()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
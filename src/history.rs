@@ -0,0 +1,153 @@
+use crate::paths;
+use crate::translate::{fill, tr};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{create_dir_all, read_to_string, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// The history dialog lists this many of the latest records; the file keeps
+// them all
+pub const SHOWN_RECORDS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Create,
+    Save,
+    Copy,
+    Move,
+    Delete,
+    Trash,
+    Restore,
+    Compress,
+    Encrypt,
+    Decrypt,
+    Extract,
+    Convert,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Action::Create => "Created",
+            Action::Save => "Saved",
+            Action::Copy => "Copied",
+            Action::Move => "Moved",
+            Action::Delete => "Deleted",
+            Action::Trash => "Moved to the trash",
+            Action::Restore => "Restored",
+            Action::Compress => "Compressed",
+            Action::Encrypt => "Encrypted",
+            Action::Decrypt => "Decrypted",
+            Action::Extract => "Extracted",
+            Action::Convert => "Converted",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Done,
+    // Finished, leaving out this many items that failed
+    Partial(usize),
+    Cancelled,
+    Failed(String),
+}
+
+impl Outcome {
+    pub fn of<T>(result: &io::Result<T>) -> Outcome {
+        match result {
+            Ok(_) => Outcome::Done,
+            Err(error) => Outcome::Failed(error.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Done => write!(f, "{}", tr("Done")),
+            Outcome::Partial(skipped) => write!(f, "{}", fill(tr("Done, {} skipped"), &[skipped])),
+            Outcome::Cancelled => write!(f, "{}", tr("Cancelled")),
+            Outcome::Failed(error) => write!(f, "{}", fill(tr("Failed: {}"), &[error])),
+        }
+    }
+}
+
+// One change the app made to the file system
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    // Seconds since the Unix epoch
+    pub time: u64,
+    pub action: Action,
+    pub path: PathBuf,
+    // Where a copy, move or archive went
+    pub target: Option<PathBuf>,
+    // Bytes written or removed, when known
+    pub size: Option<u64>,
+    pub outcome: Outcome,
+}
+
+impl Record {
+    pub fn new(action: Action, path: PathBuf, target: Option<PathBuf>, size: Option<u64>, outcome: Outcome) -> Record {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+        Record { time, action, path, target, size, outcome }
+    }
+
+    pub fn system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.time)
+    }
+}
+
+fn store_path() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join("history.jsonl"))
+}
+
+// Adds `record` to the end of the history file, one JSON object per line
+// so a record is never lost by rewriting the file
+pub fn append(record: &Record) -> io::Result<()> {
+    let Some(path) = store_path() else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let line = serde_json::to_string(record).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+// Every record, oldest first; lines that can't be read are passed over
+pub fn load() -> Vec<Record> {
+    store_path()
+        .and_then(|path| read_to_string(path).ok())
+        .map(|contents| contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default()
+}
+
+// Writes every record to `path` as CSV, for spreadsheets and audits
+pub fn export(records: &[Record], path: &Path) -> io::Result<()> {
+    let mut csv = String::from("time,action,path,target,size,outcome\n");
+    for record in records {
+        let fields = [
+            chrono::DateTime::<chrono::Utc>::from(record.system_time()).to_rfc3339(),
+            format!("{:?}", record.action),
+            record.path.display().to_string(),
+            record.target.as_ref().map(|target| target.display().to_string()).unwrap_or_default(),
+            record.size.map(|size| size.to_string()).unwrap_or_default(),
+            match &record.outcome {
+                Outcome::Done => "done".to_string(),
+                Outcome::Partial(skipped) => format!("partial ({} skipped)", skipped),
+                Outcome::Cancelled => "cancelled".to_string(),
+                Outcome::Failed(error) => format!("failed: {}", error),
+            },
+        ];
+        let quoted: Vec<String> = fields.iter().map(|field| format!("\"{}\"", field.replace('"', "\"\""))).collect();
+        csv.push_str(&quoted.join(","));
+        csv.push('\n');
+    }
+    std::fs::write(path, csv)
+}
@@ -0,0 +1,653 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+pub use file_explorer::translate::fill;
+
+// Languages the interface can be shown in. Strings are looked up by their
+// English text, so anything missing from a table simply stays English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    // The language of the user's locale, if there is a translation for it
+    pub fn from_env() -> Language {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default();
+        if locale.starts_with("de") {
+            Language::German
+        } else {
+            Language::English
+        }
+    }
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Language::from_env()
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Each language is listed under its own name
+        let label = match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// The language `tr` translates into; kept globally so labels can be
+// translated wherever they are built, Display impls included
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+fn current() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Language::German,
+        _ => Language::English,
+    }
+}
+
+// Translates an English interface string into the current language
+pub fn tr(text: &'static str) -> &'static str {
+    match current() {
+        Language::English => text,
+        Language::German => german(text).unwrap_or(text),
+    }
+}
+
+fn german(text: &str) -> Option<&'static str> {
+    let translated = match text {
+        // Toolbar
+        "⬆️ Up" => "⬆️ Hoch",
+        "🏠 Home" => "🏠 Start",
+        "🔄 Refresh" => "🔄 Neu laden",
+        "📊 Usage" => "📊 Belegung",
+        "🧹 Empty" => "🧹 Leere",
+        "🐘 Largest" => "🐘 Größte",
+        "🕘 Recent" => "🕘 Zuletzt",
+        "➕ New" => "➕ Neu",
+        "🗑️ Delete" => "🗑️ Löschen",
+        "✏️ Edit" => "✏️ Bearbeiten",
+        "📂 Open With" => "📂 Öffnen mit",
+        "🧩 Plugins" => "🧩 Erweiterungen",
+        "🛠 Tools" => "🛠 Werkzeuge",
+        "ℹ️ Properties" => "ℹ️ Eigenschaften",
+        "⚙ Settings" => "⚙ Einstellungen",
+        "Current Directory: {}" => "Aktueller Ordner: {}",
+        "Recently Modified: {}" => "Zuletzt geändert: {}",
+
+        // Search
+        "Filter this folder, or press Enter to search subfolders (try *.rs, a OR b, -skip)..." => {
+            "Diesen Ordner filtern oder mit Enter Unterordner durchsuchen (z. B. *.rs, a OR b, -ohne)..."
+        }
+        "🔍 Search" => "🔍 Suchen",
+        "Regex" => "Regex",
+        "Fuzzy" => "Unscharf",
+        "Skip ignored" => "Ignorierte auslassen",
+        "☆ Save" => "☆ Merken",
+        "Saved searches" => "Gespeicherte Suchen",
+        "Modified:" => "Geändert:",
+        "From YYYY-MM-DD" => "Von JJJJ-MM-TT",
+        "To YYYY-MM-DD" => "Bis JJJJ-MM-TT",
+        "Refine results (use -term to exclude)..." => "Ergebnisse eingrenzen (-wort schließt aus)...",
+        "Names" => "Namen",
+        "Contents" => "Inhalte",
+        "Checksum" => "Prüfsumme",
+        "Permissions" => "Berechtigungen",
+        "Any time" => "Beliebig",
+        "Today" => "Heute",
+        "Last 24 hours" => "Letzte 24 Stunden",
+        "Last 7 days" => "Letzte 7 Tage",
+        "Last 30 days" => "Letzte 30 Tage",
+        "Last year" => "Letztes Jahr",
+        "Between dates…" => "Zwischen Daten…",
+
+        // File list
+        "Name" => "Name",
+        "Size" => "Größe",
+        "Modified" => "Geändert",
+        "Type" => "Typ",
+        "Folder" => "Ordner",
+        "Unknown" => "Unbekannt",
+        "This name isn't valid UTF-8; some characters are shown as �" => "Dieser Name ist kein gültiges UTF-8; manche Zeichen werden als � angezeigt",
+        "Directory" => "Ordner",
+        "1 item" => "1 Element",
+        "{} items" => "{} Elemente",
+        "{} items listed before loading was stopped" => "{} Elemente aufgelistet, bevor das Laden abgebrochen wurde",
+        "Show more ({} not shown)" => "Mehr anzeigen ({} ausgeblendet)",
+        "📂 Show in folder" => "📂 Im Ordner zeigen",
+        "Name (A–Z)" => "Name (A–Z)",
+        "Name (Z–A)" => "Name (Z–A)",
+        "Size (smallest first)" => "Größe (kleinste zuerst)",
+        "Size (largest first)" => "Größe (größte zuerst)",
+        "Modified (oldest first)" => "Geändert (älteste zuerst)",
+        "Modified (newest first)" => "Geändert (neueste zuerst)",
+        "Relevance" => "Relevanz",
+        "Compact" => "Kompakt",
+        "Comfortable" => "Normal",
+        "Spacious" => "Großzügig",
+        "Emoji icons" => "Emoji-Symbole",
+        "Symbol icons" => "Schrift-Symbole",
+        "Plain icons" => "Einfache Symbole",
+
+        // Status bar
+        "{} Looking for recent changes… {} files so far" => "{} Suche nach Änderungen… bisher {} Dateien",
+        "{} recently modified files" => "{} kürzlich geänderte Dateien",
+        "{} Searching for '{}'… {} matches so far" => "{} Suche nach „{}“… bisher {} Treffer",
+        "{} matches for '{}'" => "{} Treffer für „{}“",
+        "{} Loading… {} entries so far" => "{} Lade… bisher {} Einträge",
+        "{} of {} items match '{}'" => "{} von {} Elementen passen zu „{}“",
+        "{} · {} shown after refining" => "{} · {} nach Eingrenzung",
+        "{} · Indexing…" => "{} · Indiziere…",
+        "{} · Zoom {}%" => "{} · Zoom {} %",
+        "⏹ Stop (Esc)" => "⏹ Abbrechen (Esc)",
+        "Hidden files" => "Versteckte Dateien",
+        "Folder sizes" => "Ordnergrößen",
+        "Index" => "Index",
+        "Use rg/fd" => "rg/fd verwenden",
+
+        // Dialogs
+        "Cancel" => "Abbrechen",
+        "Close" => "Schließen",
+        "Clear" => "Leeren",
+        "Error log" => "Fehlerprotokoll",
+        "Nothing has gone wrong so far." => "Bisher ist nichts schiefgegangen.",
+        "⚠ {} error(s)" => "⚠ {} Fehler",
+        "Could not open {}" => "{} konnte nicht geöffnet werden",
+        "Could not delete {}" => "{} konnte nicht gelöscht werden",
+        "Could not create {}" => "{} konnte nicht erstellt werden",
+        "Could not read {}" => "{} konnte nicht gelesen werden",
+        "Could not read this folder: {}" => "Dieser Ordner konnte nicht gelesen werden: {}",
+        "📋 Copy to…" => "📋 Kopieren nach…",
+        "🚚 Move to…" => "🚚 Verschieben nach…",
+        "🗜 Compress" => "🗜 Komprimieren",
+        "Copy {} to {}" => "{} nach {} kopieren",
+        "Move {} to {}" => "{} nach {} verschieben",
+        "Delete {}" => "{} löschen",
+        "Compress {} into {}" => "{} in {} komprimieren",
+        "Waiting" => "Wartet",
+        "{} of {} items" => "{} von {} Elementen",
+        "{} of {}" => "{} von {}",
+        "Cancelled" => "Abgebrochen",
+        "Paused at {} of {}" => "Angehalten bei {} von {}",
+        "Done, {} skipped" => "Fertig, {} übersprungen",
+        "{} was only partly copied, so it was left in place" => "{} wurde nur teilweise kopiert und daher nicht entfernt",
+        "{} was copied to {}, but removing the original stopped: {}" => "{} wurde nach {} kopiert, aber das Entfernen des Originals brach ab: {}",
+        "↩ Undo {} finished move(s)" => "↩ {} abgeschlossene Verschiebung(en) rückgängig machen",
+        "{}: {} item(s) skipped" => "{}: {} Element(e) übersprungen",
+        "Retry" => "Wiederholen",
+        "Skip" => "Überspringen",
+        "Skip all" => "Alle überspringen",
+        "Retry as administrator" => "Als Administrator wiederholen",
+        "As administrator: {}" => "Als Administrator: {}",
+        "Permission denied" => "Zugriff verweigert",
+        "🔒 Permission denied" => "🔒 Zugriff verweigert",
+        "Your account is not allowed to see what is in this folder. Copying, moving or deleting inside it can be retried as administrator from the Operations panel." => "Ihr Konto darf den Inhalt dieses Ordners nicht sehen. Kopieren, Verschieben oder Löschen darin kann im Bereich Vorgänge als Administrator wiederholt werden.",
+        "⚠ Could not read this folder: {}" => "⚠ Dieser Ordner konnte nicht gelesen werden: {}",
+        "Abort" => "Beenden",
+        "Pause" => "Anhalten",
+        "Resume" => "Fortsetzen",
+        "Failed: {}" => "Fehlgeschlagen: {}",
+        "The operation stopped unexpectedly" => "Der Vorgang wurde unerwartet beendet",
+        "{} has no name to copy under" => "{} hat keinen Namen zum Kopieren",
+        "{} cannot go inside itself" => "{} kann nicht in sich selbst abgelegt werden",
+        "{} already exists" => "{} existiert bereits",
+        "{} Operations ({} pending)" => "{} Vorgänge ({} ausstehend)",
+        "Clear finished" => "Erledigte entfernen",
+        "Copy '{}' to" => "„{}“ kopieren nach",
+        "Move '{}' to" => "„{}“ verschieben nach",
+        "Copy" => "Kopieren",
+        "Move" => "Verschieben",
+        "Folder..." => "Ordner...",
+        "Could not save settings" => "Einstellungen konnten nicht gespeichert werden",
+        "Could not save the folder view" => "Ordneransicht konnte nicht gespeichert werden",
+        "✕ Close" => "✕ Schließen",
+        "Delete" => "Löschen",
+        "Save" => "Speichern",
+        "Remove" => "Entfernen",
+        "Add" => "Hinzufügen",
+        "Done" => "Fertig",
+        "Create New" => "Neu erstellen",
+        "Create" => "Erstellen",
+        "Enter name..." => "Name eingeben...",
+        "Is Directory" => "Ist ein Ordner",
+        "{} Listing what would change…" => "{} Betroffene Elemente werden aufgelistet…",
+        "{} items, {} in total" => "{} Elemente, insgesamt {}",
+        "Untick anything that should stay where it is." => "Entfernen Sie das Häkchen bei allem, was bleiben soll, wo es ist.",
+        "Only the first {} of {} items are listed." => "Nur die ersten {} von {} Elementen werden aufgelistet.",
+        "Could not list what the operation would change" => "Die betroffenen Elemente konnten nicht aufgelistet werden",
+        "Nothing to confirm" => "Nichts zu bestätigen",
+        "This action cannot be undone." => "Dies kann nicht rückgängig gemacht werden.",
+        "Replace '{}'?" => "„{}“ ersetzen?",
+        "A file with this name already exists. Replacing it empties it." => "Eine Datei mit diesem Namen existiert bereits. Beim Ersetzen wird sie geleert.",
+        "Something with this name is already there. Replacing it deletes it first, a folder with everything in it." => "Etwas mit diesem Namen ist bereits dort. Beim Ersetzen wird es zuerst gelöscht, ein Ordner mit allem, was darin ist.",
+        "⚠ {} changed on disk after the list was loaded." => "⚠ {} wurde nach dem Laden der Liste auf dem Datenträger geändert.",
+        "The file changed on disk after it was opened. Save again to replace those changes." => "Die Datei wurde nach dem Öffnen auf dem Datenträger geändert. Speichern Sie erneut, um diese Änderungen zu ersetzen.",
+        "Don't ask again" => "Nicht mehr fragen",
+        "Replace" => "Ersetzen",
+        "Open" => "Öffnen",
+        "Open '{}' with" => "„{}“ öffnen mit",
+        "Command, e.g. gimp or xterm -e lnav" => "Befehl, z. B. gimp oder xterm -e lnav",
+        "Always use this app for .{} files" => "Immer für .{}-Dateien verwenden",
+        "Could not start '{}': {}" => "„{}“ konnte nicht gestartet werden: {}",
+        "Open with" => "Öffnen mit",
+        "Tools" => "Werkzeuge",
+        "Storage" => "Speicherorte",
+        "Profile" => "Profil",
+        "Default" => "Standard",
+        "New profile, e.g. work" => "Neues Profil, z. B. Arbeit",
+        "Profile names may only use letters, digits, spaces, - and _" => "Profilnamen dürfen nur Buchstaben, Ziffern, Leerzeichen, - und _ enthalten",
+        "There already is a profile called {}" => "Es gibt bereits ein Profil namens {}",
+        "Settings: {}" => "Einstellungen: {}",
+        "Data: {}" => "Daten: {}",
+        "Caches: {}" => "Zwischenspeicher: {}",
+        "Log: {}" => "Protokoll: {}",
+        "No home folder was found, so {} is used instead." => "Es wurde kein persönlicher Ordner gefunden, stattdessen wird {} verwendet.",
+        "{} is no longer available, so {} is shown instead" => "{} ist nicht mehr verfügbar, daher wird stattdessen {} angezeigt",
+        "View log" => "Protokoll anzeigen",
+        "Log" => "Protokoll",
+        "The log is empty." => "Das Protokoll ist leer.",
+        "Could not read the log: {}" => "Das Protokoll konnte nicht gelesen werden: {}",
+        "Unmatched ')' in query" => "Nicht zugeordnete ')' in der Abfrage",
+        "Could not read the whole query" => "Die Abfrage konnte nicht vollständig gelesen werden",
+        "Query ends where a term was expected" => "Die Abfrage endet, wo ein Suchbegriff erwartet wurde",
+        "Missing ')' in query" => "Fehlende ')' in der Abfrage",
+        "Unexpected ')' in query" => "Unerwartete ')' in der Abfrage",
+        "'{}' needs a term on both sides" => "'{}' braucht auf beiden Seiten einen Suchbegriff",
+        "Permission search is only available on Unix" => "Die Suche nach Berechtigungen gibt es nur unter Unix",
+        "Unknown term '{}'; try {}" => "Versuchen Sie {1}; '{0}' ist kein bekannter Begriff",
+        "'{}' is not an octal mode" => "'{}' ist kein oktaler Modus",
+        "No user or group named '{}'" => "Kein Benutzer und keine Gruppe namens '{}'",
+        "'{}' is not a date like 2024-01-31" => "'{}' ist kein Datum wie 2024-01-31",
+        "The range ends on {} before it starts on {}" => "Der Zeitraum endet am {} vor seinem Beginn am {}",
+        "Expected a tag name after tag:" => "Nach tag: wurde ein Tag-Name erwartet",
+        "Invalid regular expression: {}" => "Ungültiger regulärer Ausdruck: {}",
+        "Invalid glob pattern: {}" => "Ungültiges Glob-Muster: {}",
+        "Expected a 32-digit MD5 or 64-digit SHA-256 hex value" => "Erwartet wurde ein 32-stelliger MD5- oder 64-stelliger SHA-256-Hexwert",
+        "JPEG quality" => "JPEG-Qualität",
+        "Enter a passphrase" => "Geben Sie eine Passphrase ein",
+        "The passphrases don't match" => "Die Passphrasen stimmen nicht überein",
+        "Anyone who knows the passphrase can open it." => "Jeder, der die Passphrase kennt, kann sie öffnen.",
+        "The passphrase the file was encrypted with." => "Die Passphrase, mit der die Datei verschlüsselt wurde.",
+        "Passphrase" => "Passphrase",
+        "Use a passphrase" => "Eine Passphrase verwenden",
+        "Repeat the passphrase" => "Passphrase wiederholen",
+        "{} is not an age or SSH public key" => "{} ist kein öffentlicher age- oder SSH-Schlüssel",
+        "SSH keys protected by a passphrase can't be used here" => "Mit einer Passphrase geschützte SSH-Schlüssel können hier nicht verwendet werden",
+        "Text in images is not searched, as tesseract isn't installed" => "Text in Bildern wird nicht durchsucht, da tesseract nicht installiert ist",
+        "'.' and '..' can't be used as names" => "'.' und '..' können nicht als Namen verwendet werden",
+        "Names can't contain a path separator" => "Namen dürfen kein Pfadtrennzeichen enthalten",
+        "The name did not match; nothing was deleted" => "Der Name stimmte nicht überein; nichts wurde gelöscht",
+        "Error:" => "Fehler:",
+        "Press any key to close" => "Zum Schließen eine beliebige Taste drücken",
+        "New file name:" => "Name der neuen Datei:",
+        "New folder name:" => "Name des neuen Ordners:",
+        "'{}' is a protected location; type its name to delete it:" => "'{}' ist ein geschützter Ort; zum Löschen den Namen eingeben:",
+        "Delete '{}' for good? (y/n)" => "'{}' endgültig löschen? (y/n)",
+        "Move '{}' to the trash? (y/n)" => "'{}' in den Papierkorb verschieben? (y/n)",
+        "↑↓ move · ⏎ open · ⌫ up · n new file · N new folder · d trash · D delete · i properties · . hidden · q quit" => "↑↓ bewegen · ⏎ öffnen · ⌫ hoch · n neue Datei · N neuer Ordner · d Papierkorb · D löschen · i Eigenschaften · . versteckte · q beenden",
+        "Copy full path" => "Vollständigen Pfad kopieren",
+        "Copy name" => "Namen kopieren",
+        "📎 Copy full path" => "📎 Vollständigen Pfad kopieren",
+        "🔤 Copy name" => "🔤 Namen kopieren",
+        "🔗 Copy as file:// URI" => "🔗 Als file://-URI kopieren",
+        "Copied {}" => "{} kopiert",
+        "Same format" => "Gleiches Format",
+        "Convert {} into {}" => "{} nach {} umwandeln",
+        "Convert {}" => "{} umwandeln",
+        "Could not convert {}: {}" => "{} konnte nicht umgewandelt werden: {}",
+        "Converted" => "Umgewandelt",
+        "🖼 Convert images…" => "🖼 Bilder umwandeln…",
+        "{} holds no images to convert" => "{} enthält keine umwandelbaren Bilder",
+        "The largest size must be a number of pixels" => "Die größte Seite muss eine Anzahl Pixel sein",
+        "Quality must be between 1 and 100" => "Die Qualität muss zwischen 1 und 100 liegen",
+        "Convert '{}'" => "'{}' umwandeln",
+        "Convert {} images" => "{} Bilder umwandeln",
+        "Converted copies are saved with a new name; the originals stay as they are." => "Umgewandelte Kopien werden unter neuem Namen gespeichert; die Originale bleiben unverändert.",
+        "Format" => "Format",
+        "Largest side" => "Größte Seite",
+        "Pixels; empty keeps the size" => "Pixel; leer behält die Größe",
+        "Save in" => "Speichern in",
+        "Beside the originals" => "Neben den Originalen",
+        "Convert" => "Umwandeln",
+        "✅ Verify checksums" => "✅ Prüfsummen prüfen",
+        "Passed" => "Bestanden",
+        "Failed" => "Fehlgeschlagen",
+        "Missing" => "Fehlt",
+        "Could not be read" => "Nicht lesbar",
+        "{} of {} checked · {} passed · {} failed" => "{} von {} geprüft · {} bestanden · {} fehlgeschlagen",
+        "Checksums in '{}'" => "Prüfsummen in '{}'",
+        "Lines without a file and an MD5 or SHA-256 digest: {}" => "Zeilen ohne Datei und MD5- oder SHA-256-Prüfsumme: {}",
+        "Stop" => "Anhalten",
+        "Extract {} from {} to {}" => "{} aus {} nach {} entpacken",
+        "Extracted" => "Entpackt",
+        "Could not extract the file from the image" => "Die Datei konnte nicht aus dem Abbild entpackt werden",
+        "⬆ Up" => "⬆ Nach oben",
+        "Could not read the image: {}" => "Das Abbild konnte nicht gelesen werden: {}",
+        "{} Reading the image…" => "{} Abbild wird gelesen…",
+        "Could not read the folder: {}" => "Der Ordner konnte nicht gelesen werden: {}",
+        "This folder is empty." => "Dieser Ordner ist leer.",
+        "⬇ Extract" => "⬇ Entpacken",
+        "Read-only. Files open from a copy; Extract puts them beside the image." => "Nur lesbar. Dateien werden aus einer Kopie geöffnet; Entpacken legt sie neben das Abbild.",
+        "{} · 💾 {} free of {}" => "{} · 💾 {} von {} frei",
+        "General" => "Allgemein",
+        "Grant" => "Erlauben",
+        "Access control list:" => "Zugriffsliste:",
+        "user:NAME or group:NAME" => "user:NAME oder group:NAME",
+        "User or group, e.g. DOMAIN\\name" => "Benutzer oder Gruppe, z. B. DOMÄNE\\name",
+        "Could not give {} access to {}" => "{} konnte kein Zugriff auf {} gegeben werden",
+        "Could not take {}'s access to {} away" => "Der Zugriff von {} auf {} konnte nicht entzogen werden",
+        "Could not set {} on {}" => "{} konnte nicht auf {} gesetzt werden",
+        "Could not remove {} from {}" => "{} konnte nicht von {} entfernt werden",
+        "Extended attributes:" => "Erweiterte Attribute:",
+        "Value" => "Wert",
+        "None" => "Keine",
+        "Could not save the star on {}" => "Der Stern auf {} konnte nicht gespeichert werden",
+        "⭐ Starred" => "⭐ Markiert",
+        "{} starred items" => "{} markierte Einträge",
+        "☆ Star / unstar" => "☆ Markieren / Markierung entfernen",
+        "Could not save the note on {}" => "Die Notiz zu {} konnte nicht gespeichert werden",
+        "Save note" => "Notiz speichern",
+        "Note:" => "Notiz:",
+        "Why is this here?" => "Warum liegt das hier?",
+        "Red" => "Rot",
+        "Orange" => "Orange",
+        "Yellow" => "Gelb",
+        "Green" => "Grün",
+        "Blue" => "Blau",
+        "Purple" => "Lila",
+        "Gray" => "Grau",
+        "🏷 All tags" => "🏷 Alle Tags",
+        "Could not save the tags of {}" => "Die Tags von {} konnten nicht gespeichert werden",
+        "Could not save the tags" => "Die Tags konnten nicht gespeichert werden",
+        "🏷 Tags…" => "🏷 Tags…",
+        "Remove tag" => "Tag entfernen",
+        "Add tag" => "Tag hinzufügen",
+        "New tag..." => "Neuer Tag...",
+        "Tags of '{}'" => "Tags von '{}'",
+        "✅ Good signature" => "✅ Gültige Signatur",
+        "❌ Bad signature: the file does not match it" => "❌ Ungültige Signatur: die Datei passt nicht dazu",
+        "⚠ Good signature from an expired key" => "⚠ Gültige Signatur eines abgelaufenen Schlüssels",
+        "⚠ Good signature from a revoked key" => "⚠ Gültige Signatur eines widerrufenen Schlüssels",
+        "❔ Can't check: the signer's key is not in your keyring" => "❔ Nicht prüfbar: der Schlüssel des Unterzeichners ist nicht in Ihrem Schlüsselbund",
+        "❔ Can't check the signature; gpg's report says why" => "❔ Signatur nicht prüfbar; der Bericht von gpg nennt den Grund",
+        "Ultimate (your own key)" => "Uneingeschränkt (Ihr eigener Schlüssel)",
+        "Full" => "Vollständig",
+        "Marginal" => "Teilweise",
+        "Not certified; the signer may not be who the key claims" => "Nicht beglaubigt; der Unterzeichner ist womöglich nicht, wer der Schlüssel angibt",
+        "Never" => "Nie",
+        "gpg found no signature" => "gpg hat keine Signatur gefunden",
+        "🔏 Verify signature" => "🔏 Signatur prüfen",
+        "Signature of '{}'" => "Signatur von '{}'",
+        "Checked against {}" => "Geprüft mit {}",
+        "Checking…" => "Wird geprüft…",
+        "Could not check the signature: {}" => "Die Signatur konnte nicht geprüft werden: {}",
+        "Signed by" => "Signiert von",
+        "Key" => "Schlüssel",
+        "Signed on" => "Signiert am",
+        "Trust" => "Vertrauen",
+        "Encrypt {} into {}" => "{} in {} verschlüsseln",
+        "Decrypt {} into {}" => "{} in {} entschlüsseln",
+        "Encrypted" => "Verschlüsselt",
+        "Decrypted" => "Entschlüsselt",
+        "🔒 Encrypt…" => "🔒 Verschlüsseln…",
+        "🔓 Decrypt…" => "🔓 Entschlüsseln…",
+        "{} is a folder; compress it first to encrypt it" => "{} ist ein Ordner; zum Verschlüsseln zuerst komprimieren",
+        "Give at least one recipient" => "Mindestens einen Empfänger angeben",
+        "{} is not a key file" => "{} ist keine Schlüsseldatei",
+        "Encrypt '{}'" => "'{}' verschlüsseln",
+        "Decrypt '{}'" => "'{}' entschlüsseln",
+        "Only the holders of these keys can open it. Give age or SSH public keys, or files listing them, separated by commas." => "Nur die Inhaber dieser Schlüssel können sie öffnen. Öffentliche age- oder SSH-Schlüssel oder Dateien mit solchen, durch Kommas getrennt.",
+        "The file holding your age or SSH private key." => "Die Datei mit Ihrem privaten age- oder SSH-Schlüssel.",
+        "Key file..." => "Schlüsseldatei...",
+        "Encrypt" => "Verschlüsseln",
+        "Decrypt" => "Entschlüsseln",
+        "Reading text in images needs tesseract, which isn't installed" => "Texterkennung in Bildern braucht tesseract, das nicht installiert ist",
+        "OCR" => "OCR",
+        "Search text in images and scanned PDFs (tesseract)" => "Text in Bildern und gescannten PDFs durchsuchen (tesseract)",
+        "Print" => "Drucken",
+        "🖨 Print" => "🖨 Drucken",
+        "{} can't be printed from here; open it and print from its app" => "{} kann hier nicht gedruckt werden; bitte in der zugehörigen App öffnen und drucken",
+        "🖨 Sending {} to the printer…" => "🖨 {} wird an den Drucker gesendet…",
+        "Could not print {}" => "{} konnte nicht gedruckt werden",
+        "🖨 Sent {} to the printer" => "🖨 {} wurde an den Drucker gesendet",
+        "📡 Share over LAN" => "📡 Im Netzwerk teilen",
+        "Could not share {} over the network" => "{} konnte nicht im Netzwerk geteilt werden",
+        "{} · 📡 Sharing at {}" => "{} · 📡 Geteilt unter {}",
+        "Sharing {}" => "{} wird geteilt",
+        "Open this address on a phone or computer on the same network:" => "Diese Adresse auf einem Telefon oder Rechner im selben Netzwerk öffnen:",
+        "Stop sharing" => "Teilen beenden",
+        "Share {} over the local network?" => "{} im lokalen Netzwerk teilen?",
+        "Anyone on the network who has the address can download it until sharing is stopped." => "Bis das Teilen beendet wird, kann jeder im Netzwerk mit der Adresse es herunterladen.",
+        "Password (optional)" => "Passwort (optional)",
+        "Start sharing" => "Teilen starten",
+        "📤 Share…" => "📤 Teilen…",
+        "Could not share {}" => "{} konnte nicht geteilt werden",
+        "Share with (empty for the mail client)" => "Teilen mit (leer für das E-Mail-Programm)",
+        "Command; the files go where it says {}" => "Befehl; die Dateien kommen an die Stelle von {}",
+        "Could not download {}" => "{} konnte nicht heruntergeladen werden",
+        "{} · ☁ Downloading {} file(s)…" => "{} · ☁ {} Datei(en) werden heruntergeladen…",
+        "Online-only; it is downloaded when opened" => "Nur online; wird beim Öffnen heruntergeladen",
+        "Moved to the trash" => "In den Papierkorb verschoben",
+        "Restored" => "Wiederhergestellt",
+        "Delete selection permanently" => "Auswahl endgültig löschen",
+        "♻️ Trash" => "♻️ Papierkorb",
+        "⛔ Delete permanently" => "⛔ Endgültig löschen",
+        "Ask before moving to the trash" => "Vor dem Verschieben in den Papierkorb fragen",
+        "Move '{}' to the trash?" => "'{}' in den Papierkorb verschieben?",
+        "It can be restored from the Trash view until the trash is emptied." => "Bis der Papierkorb geleert wird, lässt es sich in der Papierkorb-Ansicht wiederherstellen.",
+        "Move to Trash" => "In den Papierkorb",
+        "Empty for good?" => "Endgültig leeren?",
+        "🧹 Empty Trash" => "🧹 Papierkorb leeren",
+        "Trash: {} items, {}" => "Papierkorb: {} Elemente, {}",
+        "Trash" => "Papierkorb",
+        "{} Reading the trash…" => "{} Papierkorb wird gelesen…",
+        "Could not read the trash: {}" => "Der Papierkorb konnte nicht gelesen werden: {}",
+        "The trash is empty." => "Der Papierkorb ist leer.",
+        "↩ Restore" => "↩ Wiederherstellen",
+        "Original location" => "Ursprünglicher Ort",
+        "Could not restore {}" => "{} konnte nicht wiederhergestellt werden",
+        "Could not empty the trash" => "Der Papierkorb konnte nicht geleert werden",
+        "Could not move {} to the trash" => "{} konnte nicht in den Papierkorb verschoben werden",
+        "Nothing to show" => "Nichts anzuzeigen",
+        "Loading…" => "Wird geladen…",
+        "⎇ Git" => "⎇ Git",
+        "Stage" => "Vormerken",
+        "Unstage" => "Nicht mehr vormerken",
+        "Discard changes" => "Änderungen verwerfen",
+        "Really discard?" => "Wirklich verwerfen?",
+        "No changes since the last commit." => "Keine Änderungen seit dem letzten Commit.",
+        "Could not read the changes: {}" => "Die Änderungen konnten nicht gelesen werden: {}",
+        "Git could not update {}" => "Git konnte {} nicht aktualisieren",
+        "Git" => "Git",
+        "{} (up to date)" => "{} (aktuell)",
+        "Previews kept in memory" => "Vorschauen im Arbeitsspeicher",
+        "Previews kept on disk" => "Vorschauen auf der Festplatte",
+        "{} is offline" => "{} ist nicht erreichbar",
+        "📴 {} is offline" => "📴 {} ist nicht erreichbar",
+        "{} · 📴 {} offline" => "{} · 📴 {} nicht erreichbar",
+        "The network drive isn't answering. Refresh once it is reachable again." => "Das Netzlaufwerk antwortet nicht. Laden Sie neu, sobald es wieder erreichbar ist.",
+        "Details" => "Details",
+        "Details ▸" => "Details ▸",
+        "Details ▾" => "Details ▾",
+        "Copy details" => "Details kopieren",
+        "Path: {}" => "Pfad: {}",
+        "OS error code: {}" => "Fehlercode des Systems: {}",
+        "Kind: {}" => "Art: {}",
+        "Likely cause: {}" => "Wahrscheinliche Ursache: {}",
+        "It was moved, renamed or deleted, possibly by another program." => "Es wurde verschoben, umbenannt oder gelöscht, vielleicht von einem anderen Programm.",
+        "Your account isn't allowed to do this; check the owner and permissions." => "Ihr Konto darf das nicht; prüfen Sie Besitzer und Berechtigungen.",
+        "Something with this name is already there." => "Dort gibt es bereits etwas mit diesem Namen.",
+        "The drive is full." => "Das Laufwerk ist voll.",
+        "The drive is mounted read-only." => "Das Laufwerk ist schreibgeschützt eingebunden.",
+        "The folder still has something in it." => "Der Ordner ist noch nicht leer.",
+        "Another program is using it." => "Ein anderes Programm verwendet es.",
+        "Source and destination are on different drives." => "Quelle und Ziel liegen auf verschiedenen Laufwerken.",
+        "The name is too long or uses characters this drive doesn't allow." => "Der Name ist zu lang oder enthält Zeichen, die dieses Laufwerk nicht erlaubt.",
+        "A network drive stopped answering." => "Ein Netzlaufwerk antwortet nicht mehr.",
+        "The drive doesn't support this." => "Das Laufwerk unterstützt das nicht.",
+        "⚠ {} is a system, home or top-level folder." => "⚠ {} ist ein System-, persönlicher oder oberster Ordner.",
+        "Type {} to go ahead" => "{} eingeben, um fortzufahren",
+        "Ask to type the name of folders this close to the root" => "Namen von Ordnern so nah am Stammverzeichnis eingeben lassen",
+        "📜 History" => "📜 Verlauf",
+        "History" => "Verlauf",
+        "No changes have been made yet." => "Es wurden noch keine Änderungen vorgenommen.",
+        "Export CSV" => "Als CSV exportieren",
+        "Exported to {}" => "Exportiert nach {}",
+        "Could not export: {}" => "Export fehlgeschlagen: {}",
+        "Could not write the history" => "Der Verlauf konnte nicht geschrieben werden",
+        "Created" => "Erstellt",
+        "Saved" => "Gespeichert",
+        "Copied" => "Kopiert",
+        "Moved" => "Verschoben",
+        "Deleted" => "Gelöscht",
+        "Compressed" => "Komprimiert",
+        "Clear caches" => "Zwischenspeicher leeren",
+        "Cleared {} of cached data" => "{} aus dem Zwischenspeicher gelöscht",
+        "Could not clear the caches: {}" => "Zwischenspeicher konnte nicht geleert werden: {}",
+        "Commands run on the selected file; use {path}, {name}, {stem}, {ext} or {dir}" => {
+            "Befehle für die ausgewählte Datei; verwendbar sind {path}, {name}, {stem}, {ext} und {dir}"
+        }
+        "Command, e.g. convert {path} {stem}.png" => "Befehl, z. B. convert {path} {stem}.png",
+        "Output of '{}'" => "Ausgabe von „{}“",
+        "Running…" => "Läuft…",
+        "Could not run the command: {}" => "Der Befehl konnte nicht ausgeführt werden: {}",
+        "The command printed nothing." => "Der Befehl hat nichts ausgegeben.",
+        "The command was interrupted" => "Der Befehl wurde unterbrochen",
+        "Tick \"Always use this app\" in Open With to add a rule" => "Zum Hinzufügen in „Öffnen mit“ „Immer verwenden“ ankreuzen",
+        "Save Search" => "Suche merken",
+        "Name..." => "Name...",
+        "'{}' in {} ({}{})" => "„{}“ in {} ({}{})",
+        ", regex" => ", Regex",
+        ", fuzzy" => ", unscharf",
+        "Settings" => "Einstellungen",
+        "Language" => "Sprache",
+        "Theme" => "Farbschema",
+        "Interface scale {}%" => "Oberflächengröße {} %",
+        "Show dates as" => "Datumsformat",
+        "ISO (2024-01-31 14:05)" => "ISO (2024-01-31 14:05)",
+        "System locale" => "Wie im System eingestellt",
+        "Relative (3 hours ago)" => "Relativ (vor 3 Std.)",
+        "just now" => "gerade eben",
+        "{} min ago" => "vor {} Min.",
+        "1 hour ago" => "vor 1 Std.",
+        "{} hours ago" => "vor {} Std.",
+        "yesterday" => "gestern",
+        "{} days ago" => "vor {} Tagen",
+        "Show sizes in" => "Größen anzeigen in",
+        "Binary (KiB, MiB)" => "Binär (KiB, MiB)",
+        "Decimal (KB, MB)" => "Dezimal (KB, MB)",
+        "1 byte" => "1 Byte",
+        "{} bytes" => "{} Bytes",
+        "Follow system" => "Wie das System",
+        "Light" => "Hell",
+        "Dark" => "Dunkel",
+        "Sort new folders by" => "Neue Ordner sortieren nach",
+        "List folders before files" => "Ordner vor Dateien anzeigen",
+        "Start in (empty for home)" => "Starten in (leer für Persönlicher Ordner)",
+        "Use current folder" => "Aktuellen Ordner verwenden",
+        "Indexed folders" => "Indizierte Ordner",
+        "Add current folder" => "Aktuellen Ordner hinzufügen",
+        "Never search or index" => "Nie durchsuchen oder indizieren",
+        "Always hide" => "Immer ausblenden",
+        "Name or glob, e.g. *.pyc" => "Name oder Muster, z. B. *.pyc",
+        "Name or glob, e.g. target or *.log" => "Name oder Muster, z. B. target oder *.log",
+        "Show hidden files" => "Versteckte Dateien anzeigen",
+        "Show folder sizes" => "Ordnergrößen anzeigen",
+        "Skip ignored files" => "Ignorierte Dateien auslassen",
+        "Keep a search index" => "Suchindex führen",
+        "Search with ripgrep/fd when installed" => "Mit ripgrep/fd suchen, falls installiert",
+        "Ask before replacing an existing file" => "Vor dem Ersetzen einer Datei fragen",
+        "Toolbar" => "Werkzeugleiste",
+        "Tools on neither row are listed under ⋯ More" => "Werkzeuge in keiner Zeile stehen unter ⋯ Mehr",
+        "Navigation row" => "Navigationszeile",
+        "Action row" => "Aktionszeile",
+        "More menu" => "Menü „Mehr“",
+        "⋯ More" => "⋯ Mehr",
+        "🕘 Recent: {}" => "🕘 Zuletzt: {}",
+        "Keyboard shortcuts" => "Tastenkürzel",
+        "Separate several shortcuts with commas, e.g. F5, Ctrl+R" => "Mehrere Kürzel durch Kommas trennen, z. B. F5, Ctrl+R",
+        "'{}' is not a key this app knows" => "„{}“ ist keine bekannte Taste",
+        "{} is also bound to {}" => "{} ist auch „{}“ zugewiesen",
+        "Stop search" => "Suche abbrechen",
+        "Refresh" => "Neu laden",
+        "Go up" => "Nach oben",
+        "Go home" => "Zum persönlichen Ordner",
+        "New file or folder" => "Neue Datei oder neuer Ordner",
+        "Delete selection" => "Auswahl löschen",
+        "Properties" => "Eigenschaften",
+        "Zoom in" => "Vergrößern",
+        "Zoom out" => "Verkleinern",
+        "Reset zoom" => "Zoom zurücksetzen",
+        "Command palette" => "Befehlspalette",
+        "Type a command" => "Befehl eingeben",
+        "No command matches" => "Kein Befehl passt",
+        "{} is not a folder" => "{} ist kein Ordner",
+
+        // Properties
+        "File Properties" => "Dateieigenschaften",
+        "No properties available" => "Keine Eigenschaften verfügbar",
+        "Path:" => "Pfad:",
+        "Type:" => "Typ:",
+        "Size:" => "Größe:",
+        "Created:" => "Erstellt:",
+        "Permissions:" => "Rechte:",
+        "Read-only" => "Schreibgeschützt",
+        "Read-write" => "Lesen und schreiben",
+        "Camera:" => "Kamera:",
+        "Dimensions:" => "Abmessungen:",
+        "Taken:" => "Aufgenommen:",
+        "Location:" => "Ort:",
+        "Media:" => "Medien:",
+        "Reading…" => "Lese…",
+        "Duration:" => "Dauer:",
+        "Format:" => "Format:",
+        "Bitrate:" => "Bitrate:",
+        "Video:" => "Video:",
+        "Audio:" => "Audio:",
+        "Probe was interrupted" => "Analyse wurde unterbrochen",
+        "Preview:" => "Vorschau:",
+        "Preview was interrupted" => "Vorschau wurde unterbrochen",
+        "The plugin was interrupted" => "Die Erweiterung wurde unterbrochen",
+        "{} × {} px" => "{} × {} px",
+
+        // Usage, cleanup and largest files
+        "Analyzing {}…" => "Analysiere {}…",
+        "Click a folder to open it · Right-click to delete" => "Ordner anklicken zum Öffnen · Rechtsklick zum Löschen",
+        "Looking for empty files and folders in {}…" => "Suche leere Dateien und Ordner in {}…",
+        "🗑️ Delete {} selected" => "🗑️ {} ausgewählte löschen",
+        "{} empty items in {}" => "{} leere Elemente in {}",
+        "Select all" => "Alle auswählen",
+        "Delete {} empty items?" => "{} leere Elemente löschen?",
+        "{} item(s) could not be deleted" => "{} Element(e) konnten nicht gelöscht werden",
+        "{} Finding the {} largest files in {}…" => "{} Suche die {} größten Dateien in {}…",
+        "Largest files in {}" => "Größte Dateien in {}",
+
+        // Editor
+        "Nothing to edit" => "Nichts zu bearbeiten",
+        "Editing '{}'" => "Bearbeite „{}“",
+        "Editing '{}' (modified)" => "Bearbeite „{}“ (geändert)",
+        "Add line" => "Zeile hinzufügen",
+        "Could not open: {}" => "Konnte nicht geöffnet werden: {}",
+        "Could not save: {}" => "Konnte nicht gespeichert werden: {}",
+        "File is too large to edit here ({}, limit {})" => "Datei ist zu groß zum Bearbeiten ({}, höchstens {})",
+        "This looks like a binary file and can't be edited" => "Dies scheint eine Binärdatei zu sein und kann nicht bearbeitet werden",
+        "File is not valid UTF-8 text" => "Datei ist kein gültiger UTF-8-Text",
+        _ => return None,
+    };
+    Some(translated)
+}
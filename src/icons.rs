@@ -0,0 +1,152 @@
+use crate::filetype;
+use crate::translate::tr;
+use infer::MatcherType;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::Metadata;
+use std::path::Path;
+
+// Broad categories of entries that get their own icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconKind {
+    Folder,
+    Code,
+    Text,
+    Document,
+    Image,
+    Audio,
+    Video,
+    Archive,
+    Executable,
+    Generic,
+}
+
+impl IconKind {
+    // The kind of the file at `path` by its contents where they tell, and
+    // by its name where they don't
+    pub fn for_file(path: &Path, meta: Option<&Metadata>) -> IconKind {
+        let sniffed = if meta.is_some_and(Metadata::is_dir) { None } else { filetype::sniff(path) };
+        IconKind::for_sniffed(path, meta, sniffed)
+    }
+
+    // As for_file, given what was sniffed already
+    pub fn for_sniffed(path: &Path, meta: Option<&Metadata>, sniffed: Option<infer::Type>) -> IconKind {
+        let by_contents = sniffed.and_then(|kind| match kind.matcher_type() {
+            MatcherType::Image => Some(IconKind::Image),
+            MatcherType::Audio => Some(IconKind::Audio),
+            MatcherType::Video => Some(IconKind::Video),
+            MatcherType::Archive => Some(IconKind::Archive),
+            MatcherType::Doc | MatcherType::Book => Some(IconKind::Document),
+            MatcherType::App => Some(IconKind::Executable),
+            MatcherType::Font | MatcherType::Text | MatcherType::Custom => None,
+        });
+        by_contents.unwrap_or_else(|| IconKind::for_metadata(path, meta))
+    }
+
+    // The kind of the entry at `path`, given its metadata if it could be read
+    pub fn for_metadata(path: &Path, meta: Option<&Metadata>) -> IconKind {
+        if meta.is_some_and(Metadata::is_dir) {
+            return IconKind::Folder;
+        }
+
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_default();
+
+        match ext.as_str() {
+            "rs" | "py" | "js" | "mjs" | "ts" | "tsx" | "jsx" | "c" | "h" | "cpp" | "cc" | "hpp" | "go" | "java"
+            | "kt" | "swift" | "rb" | "php" | "cs" | "lua" | "sh" | "bash" | "zsh" | "html" | "css" | "scss"
+            | "json" | "toml" | "yaml" | "yml" | "xml" | "sql" => IconKind::Code,
+            "txt" | "md" | "markdown" | "log" | "csv" | "ini" | "cfg" | "conf" | "rst" => IconKind::Text,
+            "pdf" | "doc" | "docx" | "odt" | "rtf" | "xls" | "xlsx" | "ods" | "ppt" | "pptx" | "odp" | "epub" => {
+                IconKind::Document
+            }
+            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "ico" | "tif" | "tiff" | "heic" => {
+                IconKind::Image
+            }
+            "mp3" | "wav" | "flac" | "ogg" | "m4a" | "aac" | "opus" => IconKind::Audio,
+            "mp4" | "mkv" | "avi" | "mov" | "webm" | "wmv" | "flv" => IconKind::Video,
+            "zip" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "7z" | "rar" | "zst" | "iso" | "dmg" => IconKind::Archive,
+            "exe" | "msi" | "bin" | "appimage" | "deb" | "rpm" | "app" => IconKind::Executable,
+            _ if meta.is_some_and(is_executable) => IconKind::Executable,
+            _ => IconKind::Generic,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_meta: &Metadata) -> bool {
+    false
+}
+
+// Selectable icon sets; each maps every IconKind to a glyph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IconSet {
+    Emoji,
+    Symbols,
+    Plain,
+}
+
+impl IconSet {
+    pub const ALL: [IconSet; 3] = [IconSet::Emoji, IconSet::Symbols, IconSet::Plain];
+
+    pub fn glyph(self, kind: IconKind) -> &'static str {
+        match self {
+            IconSet::Emoji => match kind {
+                IconKind::Folder => "📁",
+                IconKind::Code => "📜",
+                IconKind::Text => "📝",
+                IconKind::Document => "📑",
+                IconKind::Image => "🖼️",
+                IconKind::Audio => "🎵",
+                IconKind::Video => "🎬",
+                IconKind::Archive => "📦",
+                IconKind::Executable => "⚙️",
+                IconKind::Generic => "📄",
+            },
+            IconSet::Symbols => match kind {
+                IconKind::Folder => "▸",
+                IconKind::Code => "‹›",
+                IconKind::Text => "≡",
+                IconKind::Document => "¶",
+                IconKind::Image => "◩",
+                IconKind::Audio => "♪",
+                IconKind::Video => "▶",
+                IconKind::Archive => "▤",
+                IconKind::Executable => "⚙",
+                IconKind::Generic => "·",
+            },
+            IconSet::Plain => match kind {
+                IconKind::Folder => "[D]",
+                IconKind::Code => "[C]",
+                IconKind::Text => "[T]",
+                IconKind::Document => "[P]",
+                IconKind::Image => "[I]",
+                IconKind::Audio => "[A]",
+                IconKind::Video => "[V]",
+                IconKind::Archive => "[Z]",
+                IconKind::Executable => "[X]",
+                IconKind::Generic => "[F]",
+            },
+        }
+    }
+}
+
+impl fmt::Display for IconSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            IconSet::Emoji => "Emoji icons",
+            IconSet::Symbols => "Symbol icons",
+            IconSet::Plain => "Plain icons",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
@@ -0,0 +1,131 @@
+// Converts and shrinks images with the image crate
+use crate::filetype;
+use crate::translate::tr;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, ImageResult};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+// What the image crate is built to read
+const IMAGES: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "tif", "tiff", "pnm", "pbm", "pgm", "ppm"];
+
+pub const DEFAULT_QUALITY: u8 = 85;
+
+// What the converted images are saved as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    // Whatever each image already is
+    #[default]
+    Keep,
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [Format::Keep, Format::Jpeg, Format::Png, Format::Webp];
+
+    fn extension(self) -> Option<&'static str> {
+        match self {
+            Format::Keep => None,
+            Format::Jpeg => Some("jpg"),
+            Format::Png => Some("png"),
+            Format::Webp => Some("webp"),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Keep => write!(f, "{}", tr("Same format")),
+            Format::Jpeg => write!(f, "JPEG"),
+            Format::Png => write!(f, "PNG"),
+            Format::Webp => write!(f, "WebP"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conversion {
+    pub format: Format,
+    // Neither side ends up longer than this; smaller images are left as big
+    // as they are
+    pub max_dimension: Option<u32>,
+    // Only JPEG loses detail; WebP is written losslessly
+    pub quality: u8,
+}
+
+pub fn can_convert(path: &Path) -> bool {
+    filetype::extension(path).is_some_and(|ext| IMAGES.contains(&ext.as_str()))
+}
+
+// The images directly inside folder `dir`
+pub fn images_in(dir: &Path) -> Vec<PathBuf> {
+    let mut images: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_file() && can_convert(path)).collect())
+        .unwrap_or_default();
+    images.sort();
+    images
+}
+
+// Where `source` is converted to: in `target`, or beside it when there is
+// none, with the new format's extension. The caller numbers it when taken,
+// so an original is never written over.
+pub fn output_path(source: &Path, target: Option<&Path>, format: Format) -> PathBuf {
+    let dir = target.or_else(|| source.parent()).unwrap_or(Path::new(""));
+    let stem = source.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    match format.extension().map(str::to_string).or_else(|| source.extension().map(|ext| ext.to_string_lossy().into_owned())) {
+        Some(ext) => dir.join(format!("{}.{}", stem, ext)),
+        None => dir.join(stem),
+    }
+}
+
+// Writes the first frame of `source` to the new file `output`, turned
+// upright as the camera recorded and shrunk to fit the conversion. The
+// format follows `output`'s extension.
+pub fn convert(source: &Path, output: &Path, conversion: &Conversion) -> ImageResult<()> {
+    let mut decoder = ImageReader::open(source)?.with_guessed_format()?.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+    if let Some(max) = conversion.max_dimension
+        && (image.width() > max || image.height() > max)
+    {
+        image = image.resize(max, max, FilterType::Lanczos3);
+    }
+
+    let format = ImageFormat::from_path(output)?;
+    let mut writer = BufWriter::new(File::create_new(output)?);
+    match format {
+        // Neither holds more than 8 bits a channel, and JPEG has no alpha
+        ImageFormat::Jpeg => image.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut writer, conversion.quality))?,
+        ImageFormat::WebP => image.to_rgba8().write_to(&mut writer, format)?,
+        _ => image.write_to(&mut writer, format)?,
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrinks_to_fit_and_changes_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("wide.png");
+        DynamicImage::new_rgba8(400, 100).save(&source).unwrap();
+
+        let conversion = Conversion { format: Format::Jpeg, max_dimension: Some(200), quality: DEFAULT_QUALITY };
+        let output = output_path(&source, None, conversion.format);
+        convert(&source, &output, &conversion).unwrap();
+
+        let converted = image::open(&output).unwrap();
+        assert_eq!(output.extension().unwrap(), "jpg");
+        assert_eq!((converted.width(), converted.height()), (200, 50));
+    }
+}
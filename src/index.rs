@@ -0,0 +1,134 @@
+use crate::excludes::Excludes;
+use crate::loader::Sink;
+use crate::paths;
+use crate::search::Query;
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, read, write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// An index older than this is rebuilt in the background
+pub const MAX_AGE: Duration = Duration::from_secs(6 * 60 * 60);
+
+// How many entries are checked between checks for cancellation
+const CANCEL_CHECK_INTERVAL: usize = 4096;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    // Seconds since the Unix epoch
+    pub modified: u64,
+    pub is_dir: bool,
+}
+
+// A snapshot of every visible entry below the indexed roots
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Index {
+    pub roots: Vec<PathBuf>,
+    // Seconds since the Unix epoch
+    pub built: u64,
+    // The exclude rules in force when the index was built
+    #[serde(default)]
+    pub skip_ignored: bool,
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    #[serde(default)]
+    pub hide_patterns: Vec<String>,
+    pub entries: Vec<IndexEntry>,
+}
+
+impl Index {
+    // Whether searches below `dir` can be answered from the index
+    pub fn covers(&self, dir: &Path) -> bool {
+        self.roots.iter().any(|root| dir.starts_with(root))
+    }
+
+    // Whether the index was built for other roots or rules, or has gone stale
+    pub fn is_outdated(&self, roots: &[PathBuf], excludes: &Excludes) -> bool {
+        self.roots != roots
+            || !excludes.same_rules(self.skip_ignored, &self.exclude_patterns, &self.hide_patterns)
+            || unix_seconds(SystemTime::now()).saturating_sub(self.built) > MAX_AGE.as_secs()
+    }
+}
+
+pub fn index_path() -> Option<PathBuf> {
+    paths::cache_dir().map(|dir| dir.join("index.json"))
+}
+
+// Reads the index from disk; None if it is missing or unreadable
+pub fn load() -> Option<Index> {
+    let bytes = read(index_path()?).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+pub fn save(index: &Index) -> std::io::Result<()> {
+    let Some(path) = index_path() else {
+        return Ok(());
+    };
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let contents = serde_json::to_vec(index).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    write(path, contents)
+}
+
+// Crawls `roots`, skipping hidden and excluded entries; blocking, so call it
+// off the UI thread
+pub fn build(roots: Vec<PathBuf>, excludes: &Excludes) -> Index {
+    let mut entries = Vec::new();
+
+    for root in &roots {
+        for entry in excludes.walk(root, false).build().flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            entries.push(IndexEntry {
+                path: entry.into_path(),
+                size: meta.len(),
+                modified: meta.modified().map(unix_seconds).unwrap_or(0),
+                is_dir: meta.is_dir(),
+            });
+        }
+    }
+
+    Index {
+        roots,
+        built: unix_seconds(SystemTime::now()),
+        skip_ignored: excludes.is_enabled(),
+        exclude_patterns: excludes.patterns().to_vec(),
+        hide_patterns: excludes.hide_patterns().to_vec(),
+        entries,
+    }
+}
+
+// Finds the indexed entries below `root` whose name matches. Entries that
+// have disappeared since the index was built are left out.
+pub fn lookup(index: &Index, root: &Path, query: &Query, sink: &mut Sink) {
+    for (position, entry) in index.entries.iter().enumerate() {
+        if position % CANCEL_CHECK_INTERVAL == 0 && sink.is_cancelled() {
+            return;
+        }
+
+        let found = entry
+            .path
+            .strip_prefix(root)
+            .ok()
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .filter(|_| query.accepts_modified(UNIX_EPOCH + Duration::from_secs(entry.modified)))
+            .and_then(|relative| query.read_if_named(entry.path.clone(), relative));
+
+        if let Some(found) = found
+            && entry.path.exists()
+            && !sink.push(found)
+        {
+            return;
+        }
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
@@ -0,0 +1,323 @@
+// Reads ISO 9660 disk images without mounting them, so installer media can
+// be looked through without root. Names come from the Joliet tree when the
+// image has one, or else from Rock Ridge entries, or else the plain 8.3
+// names, lowercased as Linux shows them.
+use chrono::NaiveDate;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const SECTOR: u64 = 2048;
+// Volume descriptors start after the system area
+const FIRST_DESCRIPTOR: u64 = 16;
+const MAX_DESCRIPTORS: u64 = 64;
+// A directory larger than this is taken as damage rather than read
+const MAX_DIRECTORY: u64 = 16 * 1024 * 1024;
+
+const EXTENSIONS: [&str; 2] = ["iso", "img"];
+
+// A file or folder inside an image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    // Where its data lies in the image: offset and length of each part.
+    // Files over 4 GiB come in several.
+    extents: Vec<(u64, u64)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub path: PathBuf,
+    pub root: Entry,
+    joliet: bool,
+}
+
+// Whether `path` is named like a disk image and holds an ISO 9660 volume;
+// .img files are often something else, such as a raw disk
+pub fn is_image(path: &Path) -> bool {
+    let named = path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+    named && File::open(path).and_then(|mut file| read_at(&mut file, FIRST_DESCRIPTOR * SECTOR, 6)).is_ok_and(|start| &start[1..6] == b"CD001")
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_at(file: &mut File, offset: u64, length: usize) -> io::Result<Vec<u8>> {
+    let mut buffer = vec![0; length];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+pub fn open(path: &Path) -> io::Result<Image> {
+    let mut file = File::open(path)?;
+    let (mut primary, mut joliet) = (None, None);
+    for index in FIRST_DESCRIPTOR..FIRST_DESCRIPTOR + MAX_DESCRIPTORS {
+        let Ok(descriptor) = read_at(&mut file, index * SECTOR, SECTOR as usize) else {
+            break;
+        };
+        if &descriptor[1..6] != b"CD001" {
+            break;
+        }
+        match descriptor[0] {
+            1 => primary = Some(descriptor),
+            // Joliet is a supplementary descriptor naming a UCS-2 level
+            2 if matches!(&descriptor[88..91], b"%/@" | b"%/C" | b"%/E") => joliet = Some(descriptor),
+            255 => break,
+            _ => {}
+        }
+    }
+    let (descriptor, is_joliet) = match (joliet, primary) {
+        (Some(descriptor), _) => (descriptor, true),
+        (None, Some(descriptor)) => (descriptor, false),
+        (None, None) => return Err(invalid("not an ISO 9660 image")),
+    };
+    // The root's record sits inside the descriptor
+    let (root, _) = parse_record(&descriptor[156..190], is_joliet).ok_or_else(|| invalid("the image's root folder is damaged"))?;
+    Ok(Image { path: path.to_path_buf(), root, joliet: is_joliet })
+}
+
+impl Image {
+    // The entries of folder `dir`, folders first, then by name
+    pub fn list(&self, dir: &Entry) -> io::Result<Vec<Entry>> {
+        if dir.size > MAX_DIRECTORY {
+            return Err(invalid("a folder in the image is too large to be real"));
+        }
+        let mut file = File::open(&self.path)?;
+        let mut records = Vec::new();
+        for &(offset, length) in &dir.extents {
+            records.extend(read_at(&mut file, offset, length as usize)?);
+        }
+
+        let mut entries: Vec<Entry> = Vec::new();
+        let mut continued = false;
+        let mut position = 0;
+        while position < records.len() {
+            let length = records[position] as usize;
+            // Records don't cross sectors; the rest of one is padding
+            if length == 0 {
+                position = (position / SECTOR as usize + 1) * SECTOR as usize;
+                continue;
+            }
+            let Some((entry, more)) = records.get(position..position + length).and_then(|record| parse_record(record, self.joliet)) else {
+                break;
+            };
+            position += length;
+            match entries.last_mut() {
+                // The next part of a file in several
+                Some(last) if continued && last.name == entry.name => {
+                    last.size += entry.size;
+                    last.extents.extend(entry.extents);
+                }
+                _ if entry.name == "." || entry.name == ".." => {}
+                _ => entries.push(entry),
+            }
+            continued = more;
+        }
+        entries.sort_by_key(|entry| (!entry.is_dir, entry.name.to_lowercase()));
+        Ok(entries)
+    }
+
+    // Everything at and below `entry`, each with its path relative to it.
+    // A folder that shows up twice, as in a damaged or hostile image, is
+    // only walked once.
+    pub fn walk(&self, entry: &Entry) -> io::Result<Vec<(PathBuf, Entry)>> {
+        let mut found = vec![(PathBuf::new(), entry.clone())];
+        let mut seen = HashSet::new();
+        let mut index = 0;
+        while index < found.len() {
+            let (relative, dir) = found[index].clone();
+            index += 1;
+            if !dir.is_dir || !seen.insert(dir.extents.clone()) {
+                continue;
+            }
+            for child in self.list(&dir)? {
+                found.push((relative.join(&child.name), child));
+            }
+        }
+        Ok(found)
+    }
+
+    // The contents of file `entry`
+    pub fn read(&self, entry: &Entry) -> io::Result<Box<dyn Read + Send>> {
+        let mut reader: Box<dyn Read + Send> = Box::new(io::empty());
+        for &(offset, length) in &entry.extents {
+            let mut file = File::open(&self.path)?;
+            file.seek(SeekFrom::Start(offset))?;
+            reader = Box::new(reader.chain(file.take(length)));
+        }
+        Ok(reader)
+    }
+}
+
+// One directory record, and whether the file goes on in the next one
+fn parse_record(record: &[u8], joliet: bool) -> Option<(Entry, bool)> {
+    let length = *record.first()? as usize;
+    if length < 34 || record.len() < length {
+        return None;
+    }
+    let number = |at: usize| u32::from_le_bytes([record[at], record[at + 1], record[at + 2], record[at + 3]]) as u64;
+    let (sector, size, flags) = (number(2), number(10), record[25]);
+    let name_length = record[32] as usize;
+    let raw = record.get(33..33 + name_length)?;
+
+    let name = match raw {
+        [0] => ".".to_string(),
+        [1] => "..".to_string(),
+        _ if joliet => {
+            let units: Vec<u16> = raw.chunks_exact(2).map(|unit| u16::from_be_bytes([unit[0], unit[1]])).collect();
+            strip_version(&String::from_utf16_lossy(&units))
+        }
+        _ => {
+            // The system use area follows the name, padded to an even offset
+            let system_use = record.get(33 + name_length + (1 - name_length % 2)..length).unwrap_or_default();
+            rock_ridge_name(system_use).unwrap_or_else(|| strip_version(&String::from_utf8_lossy(raw)).to_lowercase())
+        }
+    };
+    // Names must not reach outside the folder they are extracted to
+    let name = if name == "." || name == ".." { name } else { name.replace(['/', '\\'], "_") };
+
+    let entry = Entry {
+        name,
+        is_dir: flags & 0x02 != 0,
+        size,
+        modified: recorded(&record[18..25]),
+        extents: vec![(sector * SECTOR, size)],
+    };
+    Some((entry, flags & 0x80 != 0))
+}
+
+// Plain names end in a version, as in README.TXT;1, and a bare dot when
+// they have no extension
+fn strip_version(name: &str) -> String {
+    let name = name.split_once(';').map_or(name, |(name, _)| name);
+    name.strip_suffix('.').unwrap_or(name).to_string()
+}
+
+// The long name in a record's Rock Ridge NM entries, which may split it
+fn rock_ridge_name(mut area: &[u8]) -> Option<String> {
+    let mut name: Option<Vec<u8>> = None;
+    while area.len() >= 4 {
+        let length = area[2] as usize;
+        if length < 4 || length > area.len() {
+            break;
+        }
+        if &area[..2] == b"NM" && length >= 5 {
+            name.get_or_insert_with(Vec::new).extend_from_slice(&area[5..length]);
+        }
+        area = &area[length..];
+    }
+    name.map(|name| String::from_utf8_lossy(&name).into_owned())
+}
+
+// The recording time: years since 1900, month, day, hour, minute, second
+// and the zone in quarter hours from UTC
+fn recorded(stamp: &[u8]) -> Option<SystemTime> {
+    let time = NaiveDate::from_ymd_opt(1900 + stamp[0] as i32, stamp[1] as u32, stamp[2] as u32)?
+        .and_hms_opt(stamp[3] as u32, stamp[4] as u32, stamp[5] as u32)?;
+    let seconds = time.and_utc().timestamp() - (stamp[6] as i8) as i64 * 15 * 60;
+    u64::try_from(seconds).ok().map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A directory record for `name` whose data is `size` bytes at `sector`
+    fn record(sector: u32, size: u32, is_dir: bool, name: &[u8]) -> Vec<u8> {
+        let length = 33 + name.len() + (1 - name.len() % 2);
+        let mut record = vec![0; length];
+        record[0] = length as u8;
+        record[2..6].copy_from_slice(&sector.to_le_bytes());
+        record[6..10].copy_from_slice(&sector.to_be_bytes());
+        record[10..14].copy_from_slice(&size.to_le_bytes());
+        record[14..18].copy_from_slice(&size.to_be_bytes());
+        record[18..21].copy_from_slice(&[124, 1, 31]);
+        record[25] = if is_dir { 0x02 } else { 0 };
+        record[32] = name.len() as u8;
+        record[33..33 + name.len()].copy_from_slice(name);
+        record
+    }
+
+    // An image whose root folder, `root_size` bytes at sector 18, holds
+    // `records`, with `contents` at sector 19
+    fn image(root_size: u32, records: &[Vec<u8>], contents: &[u8]) -> tempfile::NamedTempFile {
+        let sector = SECTOR as usize;
+        let mut data = vec![0; 20 * sector];
+        for (index, kind) in [(16, 1), (17, 255)] {
+            data[index * sector] = kind;
+            data[index * sector + 1..index * sector + 6].copy_from_slice(b"CD001");
+        }
+        let root = record(18, root_size, true, &[0]);
+        data[16 * sector + 156..16 * sector + 156 + root.len()].copy_from_slice(&root);
+        let mut at = 18 * sector;
+        for record in records {
+            data[at..at + record.len()].copy_from_slice(record);
+            at += record.len();
+        }
+        data[19 * sector..19 * sector + contents.len()].copy_from_slice(contents);
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), data).unwrap();
+        file
+    }
+
+    #[test]
+    fn records_are_read_within_their_bounds() {
+        let whole = record(19, 5, false, b"A.TXT;1");
+        let (entry, more) = parse_record(&whole, false).unwrap();
+        assert_eq!((entry.name.as_str(), entry.size, more), ("a.txt", 5, false));
+
+        // Cut short, claiming less than the fixed part, or a name running
+        // past the record
+        assert!(parse_record(&whole[..whole.len() - 1], false).is_none());
+        let mut short = whole.clone();
+        short[0] = 33;
+        assert!(parse_record(&short, false).is_none());
+        let mut long_name = whole.clone();
+        long_name[32] = 200;
+        assert!(parse_record(&long_name, false).is_none());
+
+        // Names can't lead out of the folder they are extracted to
+        let (entry, _) = parse_record(&record(19, 5, false, b"../X"), false).unwrap();
+        assert_eq!(entry.name, ".._x");
+    }
+
+    #[test]
+    fn a_folder_holding_itself_is_walked_once() {
+        let records = [
+            record(18, SECTOR as u32, true, &[0]),
+            record(18, SECTOR as u32, true, &[1]),
+            record(18, SECTOR as u32, true, b"LOOP"),
+            record(19, 5, false, b"A.TXT;1"),
+        ];
+        let file = image(SECTOR as u32, &records, b"hello");
+        let image = open(file.path()).unwrap();
+        let walked = image.walk(&image.root).unwrap();
+        let mut contents = String::new();
+        image.read(&walked.iter().find(|(_, entry)| entry.name == "a.txt").unwrap().1).unwrap().read_to_string(&mut contents).unwrap();
+
+        let paths: Vec<PathBuf> = walked.into_iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, [PathBuf::new(), PathBuf::from("loop"), PathBuf::from("a.txt")]);
+        assert_eq!(contents, "hello");
+    }
+
+    #[test]
+    fn a_record_past_the_folder_end_stops_the_listing() {
+        // The folder is 120 bytes; the last record starts at 74 and claims 255
+        let mut overrunning = record(19, 5, false, b"B.TXT;1");
+        overrunning[0] = 255;
+        let records = [record(18, 120, true, &[0]), record(19, 5, false, b"A.TXT;1"), overrunning];
+        let file = image(120, &records, b"hello");
+        let image = open(file.path()).unwrap();
+
+        let names: Vec<String> = image.list(&image.root).unwrap().into_iter().map(|entry| entry.name).collect();
+        assert_eq!(names, ["a.txt"]);
+    }
+}
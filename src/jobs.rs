@@ -0,0 +1,1409 @@
+use crate::crypt;
+use crate::diagnostics::Diagnostic;
+use crate::elevate;
+use crate::imaging;
+use crate::iso;
+use crate::outlet::Outlet;
+use crate::sizes::SizeUnits;
+use crate::translate::{fill, tr};
+use crate::visited::Visited;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+// Files are copied in chunks so progress and cancelling stay responsive
+const CHUNK_SIZE: usize = 1024 * 1024;
+// Progress goes to the UI at most this often
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+// How often a paused or stuck job checks whether it may go on
+const PAUSE_POLL: Duration = Duration::from_millis(200);
+// Paths listed for confirmation before a delete or move; the rest are
+// only counted
+const MAX_PREVIEW_ITEMS: usize = 2000;
+
+// Deletes and moves carry the paths below their sources that the user
+// left out; those stay, and so do the folders holding them. Copies and
+// moves with `replace` delete whatever already has a source's name in the
+// target first, instead of stopping at it.
+#[derive(Debug, Clone)]
+pub enum Operation {
+    Copy { sources: Vec<PathBuf>, target: PathBuf, replace: bool },
+    Move { sources: Vec<PathBuf>, target: PathBuf, excluded: Vec<PathBuf>, replace: bool },
+    Delete { paths: Vec<PathBuf>, excluded: Vec<PathBuf> },
+    // Packs the sources into a new zip file
+    Archive { sources: Vec<PathBuf>, archive: PathBuf },
+    // Runs one file through age into a new file beside it
+    Encrypt { source: PathBuf, output: PathBuf, lock: crypt::Lock },
+    Decrypt { source: PathBuf, output: PathBuf, unlock: crypt::Unlock },
+    // Copies entries out of a disk image into `target`
+    Extract { image: iso::Image, entries: Vec<iso::Entry>, target: PathBuf },
+    // Converts images into `target`, or beside each when there is none
+    ConvertImages { sources: Vec<PathBuf>, target: Option<PathBuf>, conversion: imaging::Conversion },
+}
+
+impl Operation {
+    pub fn is_delete(&self) -> bool {
+        matches!(self, Operation::Delete { .. })
+    }
+
+    // Copies, moves, archives and encryption write data and can be paused
+    // part way
+    pub fn can_pause(&self) -> bool {
+        !self.is_delete()
+    }
+
+    pub fn sources(&self) -> &[PathBuf] {
+        match self {
+            Operation::Copy { sources, .. }
+            | Operation::Move { sources, .. }
+            | Operation::Archive { sources, .. }
+            | Operation::ConvertImages { sources, .. } => sources,
+            Operation::Delete { paths, .. } => paths,
+            Operation::Encrypt { source, .. } | Operation::Decrypt { source, .. } => std::slice::from_ref(source),
+            Operation::Extract { image, .. } => std::slice::from_ref(&image.path),
+        }
+    }
+
+    // Leaves `excluded` out of a delete or move: sources among them are
+    // dropped, anything below a source is kept in place. None when nothing
+    // is left to do.
+    pub fn without(self, excluded: Vec<PathBuf>) -> Option<Operation> {
+        let keep = |sources: Vec<PathBuf>| -> Vec<PathBuf> {
+            sources.into_iter().filter(|source| !excluded.contains(source)).collect()
+        };
+        let operation = match self {
+            Operation::Move { sources, target, replace, .. } => Operation::Move { sources: keep(sources), target, excluded, replace },
+            Operation::Delete { paths, .. } => Operation::Delete { paths: keep(paths), excluded },
+            operation => operation,
+        };
+        (!operation.sources().is_empty()).then_some(operation)
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Operation::Copy { sources, target, .. } => fill(tr("Copy {} to {}"), &[&describe(sources), &target.display()]),
+            Operation::Move { sources, target, .. } => fill(tr("Move {} to {}"), &[&describe(sources), &target.display()]),
+            Operation::Delete { paths, .. } => fill(tr("Delete {}"), &[&describe(paths)]),
+            Operation::Archive { sources, archive } => {
+                fill(tr("Compress {} into {}"), &[&describe(sources), &describe(std::slice::from_ref(archive))])
+            }
+            Operation::Encrypt { source, output, .. } => {
+                fill(tr("Encrypt {} into {}"), &[&describe(std::slice::from_ref(source)), &describe(std::slice::from_ref(output))])
+            }
+            Operation::Decrypt { source, output, .. } => {
+                fill(tr("Decrypt {} into {}"), &[&describe(std::slice::from_ref(source)), &describe(std::slice::from_ref(output))])
+            }
+            Operation::Extract { image, entries, target } => {
+                let what = match entries.as_slice() {
+                    [entry] => entry.name.clone(),
+                    _ => fill(tr("{} items"), &[&entries.len()]),
+                };
+                fill(tr("Extract {} from {} to {}"), &[&what, &describe(std::slice::from_ref(&image.path)), &target.display()])
+            }
+            Operation::ConvertImages { sources, target: Some(target), .. } => {
+                fill(tr("Convert {} into {}"), &[&describe(sources), &target.display()])
+            }
+            Operation::ConvertImages { sources, target: None, .. } => fill(tr("Convert {}"), &[&describe(sources)]),
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// The file name for a single path, a count otherwise
+fn describe(paths: &[PathBuf]) -> String {
+    match paths {
+        [path] => path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned()),
+        _ => fill(tr("{} items"), &[&paths.len()]),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Cancelled,
+    Failed(Diagnostic),
+}
+
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: u64,
+    pub operation: Operation,
+    pub state: JobState,
+    // Bytes, or items for a delete
+    pub done: u64,
+    pub total: u64,
+    // The failure the job waits on
+    pub problem: Option<Problem>,
+    // Failures the user chose to skip
+    pub skipped: Vec<Diagnostic>,
+    // Sources a move has finished with, and where each went, so a move that
+    // stops part way can be undone
+    pub moved: Vec<(PathBuf, PathBuf)>,
+    // Bytes in the files a delete is removing
+    pub freed: Option<u64>,
+    control: Control,
+}
+
+// A failed step and the ways it can be tried again
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub error: Diagnostic,
+    pub can_retry: bool,
+    // Permission was denied and the step can be redone as administrator
+    pub can_elevate: bool,
+}
+
+// What the user chose after a step failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Retry,
+    RetryAsAdmin,
+    Skip,
+    // Skip this and every later failure of the job
+    SkipAll,
+    Abort,
+}
+
+// What the UI sets and the worker checks between chunks
+#[derive(Debug, Clone, Default)]
+struct Control {
+    cancel: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    decision: Arc<Mutex<Option<Decision>>>,
+}
+
+impl Job {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self.state, JobState::Queued | JobState::Running)
+    }
+
+    // A move that failed or was cancelled after finishing some sources
+    pub fn can_roll_back(&self) -> bool {
+        matches!(self.state, JobState::Failed(_) | JobState::Cancelled) && !self.moved.is_empty()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.control.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+
+    pub fn status(&self, units: SizeUnits) -> String {
+        match &self.state {
+            JobState::Queued => tr("Waiting").to_string(),
+            JobState::Running if self.operation.is_delete() => fill(tr("{} of {} items"), &[&self.done, &self.total]),
+            JobState::Running if self.is_paused() => {
+                fill(tr("Paused at {} of {}"), &[&units.format(self.done), &units.format(self.total)])
+            }
+            JobState::Running => fill(tr("{} of {}"), &[&units.format(self.done), &units.format(self.total)]),
+            JobState::Done if !self.skipped.is_empty() => fill(tr("Done, {} skipped"), &[&self.skipped.len()]),
+            JobState::Done => tr("Done").to_string(),
+            JobState::Cancelled => tr("Cancelled").to_string(),
+            JobState::Failed(error) => fill(tr("Failed: {}"), &[error]),
+        }
+    }
+}
+
+// What a running job tells the UI
+#[derive(Debug, Clone)]
+pub enum Event {
+    // Everything has been counted; this is how much work there is
+    Total(u64),
+    Progress(u64),
+    // A step failed and the job waits for a decision
+    Problem(Problem),
+    // A failed step was left out
+    Skipped(Diagnostic),
+    // A source of a move is entirely at its destination
+    Moved(PathBuf, PathBuf),
+    // The bytes a delete will free
+    Freed(u64),
+    Finished(JobState),
+}
+
+// File operations in the order they were started. They run one at a time,
+// each on its own worker thread, and stay listed once finished until
+// cleared.
+#[derive(Debug, Default)]
+pub struct Jobs {
+    jobs: Vec<Job>,
+    next_id: u64,
+}
+
+impl Jobs {
+    pub fn push(&mut self, operation: Operation) {
+        tracing::info!(id = self.next_id, "queued: {}", operation);
+        self.jobs.push(Job {
+            id: self.next_id,
+            operation,
+            state: JobState::Queued,
+            done: 0,
+            total: 0,
+            problem: None,
+            skipped: Vec::new(),
+            moved: Vec::new(),
+            freed: None,
+            control: Control::default(),
+        });
+        self.next_id += 1;
+    }
+
+    pub fn all(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    // The job that runs now
+    pub fn current(&self) -> Option<&Job> {
+        self.jobs.iter().find(|job| !job.is_finished())
+    }
+
+    pub fn pending(&self) -> usize {
+        self.jobs.iter().filter(|job| !job.is_finished()).count()
+    }
+
+    // A queued job is dropped at once; a running one stops at its next chunk
+    pub fn cancel(&mut self, id: u64) {
+        if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+            job.control.cancel.store(true, Ordering::Relaxed);
+            job.problem = None;
+            if job.state == JobState::Queued {
+                job.state = JobState::Cancelled;
+            }
+        }
+    }
+
+    // A paused job holds its place in the current file and waits there, so
+    // resuming picks up from the same offset
+    pub fn set_paused(&mut self, id: u64, paused: bool) {
+        if let Some(job) = self.jobs.iter().find(|job| job.id == id && job.state == JobState::Running) {
+            job.control.paused.store(paused, Ordering::Relaxed);
+        }
+    }
+
+    // Answers the problem a job is waiting on
+    pub fn decide(&mut self, id: u64, decision: Decision) {
+        if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id && job.problem.is_some()) {
+            job.problem = None;
+            if let Ok(mut pending) = job.control.decision.lock() {
+                *pending = Some(decision);
+            }
+        }
+    }
+
+    // Queues moves putting back what a stopped move had finished with; the
+    // sources that went to one folder come back in one job per folder they
+    // came from
+    pub fn roll_back(&mut self, id: u64) {
+        let Some(job) = self.jobs.iter_mut().find(|job| job.id == id && job.can_roll_back()) else {
+            return;
+        };
+        let moved = std::mem::take(&mut job.moved);
+        tracing::info!(id, count = moved.len(), "roll back");
+
+        let mut by_folder: Vec<(PathBuf, Vec<PathBuf>)> = Vec::new();
+        for (from, to) in moved {
+            let Some(folder) = from.parent() else {
+                continue;
+            };
+            match by_folder.iter_mut().find(|(target, _)| target == folder) {
+                Some((_, sources)) => sources.push(to),
+                None => by_folder.push((folder.to_path_buf(), vec![to])),
+            }
+        }
+        for (target, sources) in by_folder {
+            self.push(Operation::Move { sources, target, excluded: Vec::new(), replace: false });
+        }
+    }
+
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(|job| !job.is_finished());
+    }
+
+    // Records what a job reported; hands back the job once it has finished
+    pub fn apply(&mut self, id: u64, event: Event) -> Option<&Job> {
+        let job = self.jobs.iter_mut().find(|job| job.id == id)?;
+        match event {
+            Event::Total(total) => {
+                job.total = total;
+                job.state = JobState::Running;
+            }
+            Event::Progress(done) => job.done = done,
+            Event::Problem(problem) => job.problem = Some(problem),
+            Event::Skipped(error) => job.skipped.push(error),
+            Event::Freed(bytes) => job.freed = Some(bytes),
+            Event::Moved(from, to) => {
+                tracing::info!(id, from = %from.display(), to = %to.display(), "moved");
+                job.moved.push((from, to));
+            }
+            Event::Finished(state) => {
+                tracing::info!(id, skipped = job.skipped.len(), "finished: {:?}", state);
+                job.problem = None;
+                if state == JobState::Done {
+                    job.done = job.total;
+                }
+                job.state = state;
+                return Some(job);
+            }
+        }
+        None
+    }
+}
+
+// Where an archive of `source` goes: beside it, named after it, without
+// replacing an existing file
+pub fn archive_path(source: &Path) -> PathBuf {
+    let dir = source.parent().unwrap_or(source);
+    let name = source.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut archive = dir.join(format!("{}.zip", name));
+    let mut copy = 2;
+    while archive.symlink_metadata().is_ok() {
+        archive = dir.join(format!("{} ({}).zip", name, copy));
+        copy += 1;
+    }
+    archive
+}
+
+// One path a delete or move would touch, listed for confirmation
+#[derive(Debug, Clone)]
+pub struct PreviewItem {
+    pub path: PathBuf,
+    // Levels below the source it belongs to
+    pub depth: usize,
+    pub is_dir: bool,
+    // For a folder, the files below it
+    pub size: u64,
+    pub included: bool,
+}
+
+// Everything a delete or move would touch. Only the first
+// MAX_PREVIEW_ITEMS paths are listed; the rest are counted in the totals.
+#[derive(Debug, Clone, Default)]
+pub struct Preview {
+    pub items: Vec<PreviewItem>,
+    pub total_items: usize,
+    pub total_size: u64,
+}
+
+impl Preview {
+    pub fn is_truncated(&self) -> bool {
+        self.items.len() < self.total_items
+    }
+
+    // For each item, whether every folder holding it is ticked. The list is
+    // in walk order, so everything below an unticked folder follows it.
+    pub fn holders_included(&self) -> Vec<bool> {
+        let mut unticked_at: Option<usize> = None;
+        self.items
+            .iter()
+            .map(|item| {
+                if unticked_at.is_some_and(|depth| item.depth > depth) {
+                    return false;
+                }
+                unticked_at = (!item.included).then_some(item.depth);
+                true
+            })
+            .collect()
+    }
+
+    // The items and bytes that would be affected; unlisted paths all count
+    pub fn affected(&self) -> (usize, u64) {
+        let listed_size: u64 = self.items.iter().filter(|item| !item.is_dir).map(|item| item.size).sum();
+        let (mut count, mut size) = (self.total_items - self.items.len(), self.total_size - listed_size);
+        for (item, holders_included) in self.items.iter().zip(self.holders_included()) {
+            if item.included && holders_included {
+                count += 1;
+                if !item.is_dir {
+                    size += item.size;
+                }
+            }
+        }
+        (count, size)
+    }
+
+    // The unticked paths not already inside an unticked folder
+    pub fn excluded(&self) -> Vec<PathBuf> {
+        self.items
+            .iter()
+            .zip(self.holders_included())
+            .filter(|(item, holders_included)| !item.included && *holders_included)
+            .map(|(item, _)| item.path.clone())
+            .collect()
+    }
+}
+
+// Walks `roots` to list what a delete or move of them would touch
+pub fn preview(roots: &[PathBuf]) -> Preview {
+    let mut preview = Preview::default();
+    for root in roots {
+        for entry in WalkDir::new(root).into_iter().filter_entry(Visited::filter()).flatten() {
+            let is_dir = entry.file_type().is_dir();
+            let size = if is_dir { 0 } else { entry.metadata().map(|meta| meta.len()).unwrap_or(0) };
+            preview.total_items += 1;
+            preview.total_size += size;
+            if preview.items.len() < MAX_PREVIEW_ITEMS {
+                preview.items.push(PreviewItem {
+                    path: entry.path().to_path_buf(),
+                    depth: entry.depth(),
+                    is_dir,
+                    size,
+                    included: true,
+                });
+            }
+        }
+    }
+
+    // Folders show the size of the listed files below them
+    for index in 0..preview.items.len() {
+        if preview.items[index].is_dir {
+            let dir = &preview.items[index].path;
+            preview.items[index].size = preview.items[index + 1..]
+                .iter()
+                .take_while(|item| item.path.starts_with(dir))
+                .filter(|item| !item.is_dir)
+                .map(|item| item.size)
+                .sum();
+        }
+    }
+    preview
+}
+
+// Performs `job`, sending its progress to `outlet` and how it ended last.
+// The job's pause and cancel take effect here, and nobody listening any more
+// cancels it too. Blocking, so call it off the UI thread.
+pub fn run(job: &Job, outlet: impl Outlet<Event> + 'static) {
+    let mut reporter = Reporter {
+        outlet: Box::new(outlet),
+        control: job.control.clone(),
+        done: 0,
+        last_sent: Instant::now(),
+        skip_all: false,
+        skipped: 0,
+    };
+    let state = match perform(&job.operation, &mut reporter) {
+        Ok(()) => JobState::Done,
+        Err(Stop::Cancelled) => JobState::Cancelled,
+        Err(Stop::Failed(error) | Stop::Denied(error)) => JobState::Failed(error),
+    };
+    reporter.outlet.send(Event::Finished(state));
+}
+
+// Why a job stopped early
+enum Stop {
+    Cancelled,
+    Failed(Diagnostic),
+    // Failed for lack of permission, so it might work as administrator
+    Denied(Diagnostic),
+}
+
+// How a step that may fail went
+enum Attempt<T> {
+    Done(T),
+    // Redone as administrator after permission was denied
+    Elevated,
+    Skipped,
+}
+
+// Labels an error with the path it happened on
+fn failed<E: Into<io::Error>>(path: &Path) -> impl FnOnce(E) -> Stop + '_ {
+    move |error| {
+        let error = Diagnostic::io(path, &error.into());
+        if error.is_denied() {
+            Stop::Denied(error)
+        } else {
+            Stop::Failed(error)
+        }
+    }
+}
+
+struct Reporter {
+    outlet: Box<dyn Outlet<Event>>,
+    control: Control,
+    done: u64,
+    last_sent: Instant,
+    skip_all: bool,
+    skipped: usize,
+}
+
+impl Reporter {
+    fn total(&self, total: u64) {
+        self.outlet.send(Event::Total(total));
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.control.cancel.load(Ordering::Relaxed) || self.outlet.is_closed()
+    }
+
+    // Counts finished work, waiting here while the job is paused; fails
+    // once the job was cancelled
+    fn advance(&mut self, amount: u64) -> Result<(), Stop> {
+        if self.is_cancelled() {
+            return Err(Stop::Cancelled);
+        }
+        if self.control.paused.load(Ordering::Relaxed) {
+            // Show exactly where the job stopped
+            self.outlet.send(Event::Progress(self.done + amount));
+            while self.control.paused.load(Ordering::Relaxed) {
+                if self.is_cancelled() {
+                    return Err(Stop::Cancelled);
+                }
+                thread::sleep(PAUSE_POLL);
+            }
+        }
+        self.done += amount;
+        if self.last_sent.elapsed() >= REPORT_INTERVAL {
+            self.last_sent = Instant::now();
+            self.outlet.send(Event::Progress(self.done));
+        }
+        Ok(())
+    }
+
+    // Runs one step of the job. When it fails the user decides whether to
+    // retry it, skip it or give up on the job; Ok(None) means skipped.
+    fn attempt<T>(&mut self, step: impl FnMut(&mut Reporter) -> Result<T, Stop>) -> Result<Option<T>, Stop> {
+        match self.attempt_as(None, step)? {
+            Attempt::Done(value) => Ok(Some(value)),
+            Attempt::Elevated | Attempt::Skipped => Ok(None),
+        }
+    }
+
+    // Like `attempt`, except that when permission is denied `change` can be
+    // redone as administrator, which counts `size` as done; false means
+    // skipped
+    fn attempt_change(
+        &mut self,
+        change: elevate::Step,
+        size: u64,
+        step: impl FnMut(&mut Reporter) -> Result<(), Stop>,
+    ) -> Result<bool, Stop> {
+        Ok(!matches!(self.attempt_as(Some((change, size)), step)?, Attempt::Skipped))
+    }
+
+    fn attempt_as<T>(
+        &mut self,
+        change: Option<(elevate::Step, u64)>,
+        mut step: impl FnMut(&mut Reporter) -> Result<T, Stop>,
+    ) -> Result<Attempt<T>, Stop> {
+        let mut as_admin = false;
+        loop {
+            let before = self.done;
+            let result = match change {
+                Some((change, size)) if as_admin => match elevate::run(change) {
+                    Ok(()) => self.advance(size).map(|()| Attempt::Elevated),
+                    Err(error) => Err(Stop::Failed(fill(tr("As administrator: {}"), &[&error]).into())),
+                },
+                _ => step(self).map(Attempt::Done),
+            };
+            let (error, denied) = match result {
+                Ok(attempt) => return Ok(attempt),
+                Err(Stop::Failed(error)) => (error, false),
+                Err(Stop::Denied(error)) => (error, true),
+                Err(Stop::Cancelled) => return Err(Stop::Cancelled),
+            };
+
+            // Work counted by the failed try is done again or not at all
+            self.done = before;
+            let can_elevate = (denied || as_admin) && change.is_some() && elevate::available();
+            as_admin = match self.decide(error, true, can_elevate)? {
+                Decision::Retry => false,
+                Decision::RetryAsAdmin => true,
+                _ => return Ok(Attempt::Skipped),
+            };
+        }
+    }
+
+    // Waits for the user to decide what happens after `error`; answers
+    // one of the retries or Skip, or fails the job on Abort
+    fn decide(&mut self, error: Diagnostic, can_retry: bool, can_elevate: bool) -> Result<Decision, Stop> {
+        let decision = if self.skip_all {
+            Decision::Skip
+        } else {
+            let problem = Problem { error: error.clone(), can_retry, can_elevate };
+            self.outlet.send(Event::Problem(problem));
+            loop {
+                if self.is_cancelled() {
+                    return Err(Stop::Cancelled);
+                }
+                if let Ok(mut pending) = self.control.decision.lock()
+                    && let Some(decision) = pending.take()
+                {
+                    break decision;
+                }
+                thread::sleep(PAUSE_POLL);
+            }
+        };
+
+        match decision {
+            Decision::Abort => Err(Stop::Failed(error)),
+            Decision::Retry if can_retry => Ok(Decision::Retry),
+            Decision::RetryAsAdmin if can_elevate => Ok(Decision::RetryAsAdmin),
+            Decision::SkipAll => {
+                self.skip_all = true;
+                self.skip(error);
+                Ok(Decision::Skip)
+            }
+            Decision::Retry | Decision::RetryAsAdmin | Decision::Skip => {
+                self.skip(error);
+                Ok(Decision::Skip)
+            }
+        }
+    }
+
+    fn moved(&self, from: &Path, to: &Path) {
+        self.outlet.send(Event::Moved(from.to_path_buf(), to.to_path_buf()));
+    }
+
+    fn skip(&mut self, error: Diagnostic) {
+        self.skipped += 1;
+        self.outlet.send(Event::Skipped(error));
+    }
+
+    // A folder that could not be read; it is skipped or ends the job, as
+    // the walk cannot go back to retry it
+    fn walk_failed(&mut self, root: &Path, error: walkdir::Error) -> Result<PathBuf, Stop> {
+        let path = error.path().unwrap_or(root).to_path_buf();
+        let diagnostic = Diagnostic::new(format!("{}: {}", path.display(), error)).at(&path);
+        let diagnostic = match error.io_error() {
+            Some(io_error) => diagnostic.with_io(io_error),
+            None => diagnostic,
+        };
+        self.decide(diagnostic, false, false)?;
+        Ok(path)
+    }
+}
+
+fn perform(operation: &Operation, reporter: &mut Reporter) -> Result<(), Stop> {
+    match operation {
+        Operation::Copy { sources, target, replace } => {
+            reporter.total(sources.iter().map(|source| tree_size(source, &[])).sum());
+            for source in sources {
+                if let Some(destination) = reporter.attempt(|_| destination(source, target, *replace))? {
+                    clear(&destination, reporter)?;
+                    copy_tree(source, &destination, &[], reporter)?;
+                }
+            }
+        }
+        Operation::Move { sources, target, excluded, replace } => {
+            reporter.total(sources.iter().map(|source| tree_size(source, excluded)).sum());
+            for source in sources {
+                let Some(destination) = reporter.attempt(|_| destination(source, target, *replace))? else {
+                    continue;
+                };
+                clear(&destination, reporter)?;
+                // Within one filesystem a rename is instant; across them, or
+                // when part of the source stays, the files are copied and
+                // the originals deleted
+                let partial = excluded.iter().any(|path| path.starts_with(source));
+                let renamed = if partial {
+                    Attempt::Done(false)
+                } else {
+                    let rename = elevate::Step::Rename(source, &destination);
+                    reporter.attempt_as(Some((rename, tree_size(source, &[]))), |reporter| {
+                        match fs::rename(source, &destination) {
+                            Ok(()) => reporter.advance(tree_size(&destination, &[])).map(|()| true),
+                            Err(error) if error.kind() == io::ErrorKind::CrossesDevices => Ok(false),
+                            Err(error) => Err(failed(source)(error)),
+                        }
+                    })?
+                };
+                match renamed {
+                    Attempt::Done(true) | Attempt::Elevated => reporter.moved(source, &destination),
+                    Attempt::Done(false) => {
+                        let skipped = reporter.skipped;
+                        // A copy that stops part way is taken back, so the
+                        // source is either moved entirely or left alone
+                        if let Err(stop) = copy_tree(source, &destination, excluded, reporter) {
+                            remove_partial(&destination);
+                            return Err(stop);
+                        }
+                        // The originals of skipped files must not be lost
+                        if reporter.skipped == skipped {
+                            // Everything is at the destination by now, so say
+                            // exactly where a failure left the original
+                            remove_tree(source, excluded, reporter, false).map_err(|stop| match stop {
+                                Stop::Failed(error) | Stop::Denied(error) => Stop::Failed(error.clone().reworded(fill(
+                                    tr("{} was copied to {}, but removing the original stopped: {}"),
+                                    &[&source.display(), &destination.display(), &error],
+                                ))),
+                                Stop::Cancelled => Stop::Cancelled,
+                            })?;
+                            if !partial {
+                                reporter.moved(source, &destination);
+                            }
+                        } else {
+                            reporter.skip(fill(tr("{} was only partly copied, so it was left in place"), &[&source.display()]).into());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Operation::Delete { paths, excluded } => {
+            let count = |path| {
+                let mut unvisited = Visited::filter();
+                WalkDir::new(path)
+                    .into_iter()
+                    .filter_entry(|entry| !is_excluded(entry.path(), excluded) && unvisited(entry))
+                    .count() as u64
+            };
+            reporter.total(paths.iter().map(count).sum());
+            reporter.outlet.send(Event::Freed(paths.iter().map(|path| tree_size(path, excluded)).sum()));
+            for path in paths {
+                remove_tree(path, excluded, reporter, true)?;
+            }
+        }
+        Operation::Archive { sources, archive } => {
+            reporter.total(sources.iter().map(|source| tree_size(source, &[])).sum());
+            let written = write_archive(sources, archive, reporter);
+            // Don't leave half an archive behind
+            if written.is_err() {
+                let _ = fs::remove_file(archive);
+            }
+            written?;
+        }
+        Operation::Encrypt { source, output, lock } => encrypt(source, output, lock, reporter)?,
+        Operation::Decrypt { source, output, unlock } => decrypt(source, output, unlock, reporter)?,
+        Operation::Extract { image, entries, target } => {
+            let walked = entries.iter().map(|entry| image.walk(entry)).collect::<io::Result<Vec<_>>>().map_err(failed(&image.path))?;
+            reporter.total(walked.iter().flatten().filter(|(_, entry)| !entry.is_dir).map(|(_, entry)| entry.size).sum());
+            for (entry, walked) in entries.iter().zip(&walked) {
+                let destination = unused(&target.join(&entry.name));
+                let extracted = extract(image, walked, &destination, reporter);
+                // An entry is only kept once it is all there
+                if extracted.is_err() {
+                    remove_partial(&destination);
+                }
+                extracted?;
+            }
+        }
+        Operation::ConvertImages { sources, target, conversion } => {
+            let size = |source: &PathBuf| source.metadata().map(|meta| meta.len()).unwrap_or(0);
+            reporter.total(sources.iter().map(size).sum());
+            for source in sources {
+                let output = unused(&imaging::output_path(source, target.as_deref(), conversion.format));
+                // An image that can't be converted doesn't hold up the rest
+                if let Err(error) = imaging::convert(source, &output, conversion) {
+                    let _ = fs::remove_file(&output);
+                    reporter.skip(Diagnostic::new(fill(tr("Could not convert {}: {}"), &[&source.display(), &error])).at(source));
+                }
+                reporter.advance(size(source))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Whether `path` is one of `excluded` or below one
+fn is_excluded(path: &Path, excluded: &[PathBuf]) -> bool {
+    excluded.iter().any(|excluded| path.starts_with(excluded))
+}
+
+// Takes back the copy of a source whose move stopped part way. It was
+// created by the move, as anything in its place was deleted first, so all
+// of it goes.
+fn remove_partial(destination: &Path) {
+    let removed = match destination.symlink_metadata() {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(destination),
+        Ok(_) => fs::remove_file(destination),
+        Err(_) => Ok(()),
+    };
+    if let Err(error) = removed {
+        tracing::warn!(path = %destination.display(), "could not take back a partial copy: {}", error);
+    }
+}
+
+// Bytes in the files at and below `path`, leaving out `excluded`
+fn tree_size(path: &Path, excluded: &[PathBuf]) -> u64 {
+    let mut unvisited = Visited::filter();
+    WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|entry| !is_excluded(entry.path(), excluded) && unvisited(entry))
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+// What copying or moving `sources` into `target` would replace, the names
+// already taken there
+pub fn replaced(sources: &[PathBuf], target: &Path) -> Vec<PathBuf> {
+    sources
+        .iter()
+        .filter_map(|source| {
+            let destination = target.join(source.file_name()?);
+            (destination.symlink_metadata().is_ok() && !source.starts_with(&destination)).then_some(destination)
+        })
+        .collect()
+}
+
+// Where `source` ends up inside `target`. Something already there is only
+// replaced with `replace`, and never when it is the source or holds it.
+fn destination(source: &Path, target: &Path, replace: bool) -> Result<PathBuf, Stop> {
+    let Some(name) = source.file_name() else {
+        return Err(Stop::Failed(fill(tr("{} has no name to copy under"), &[&source.display()]).into()));
+    };
+    if target.starts_with(source) {
+        return Err(Stop::Failed(fill(tr("{} cannot go inside itself"), &[&source.display()]).into()));
+    }
+    let destination = target.join(name);
+    if destination.symlink_metadata().is_ok() && (!replace || source.starts_with(&destination)) {
+        return Err(Stop::Failed(fill(tr("{} already exists"), &[&destination.display()]).into()));
+    }
+    Ok(destination)
+}
+
+// Deletes what is at `destination` before something replaces it
+fn clear(destination: &Path, reporter: &mut Reporter) -> Result<(), Stop> {
+    match destination.symlink_metadata() {
+        Ok(_) => remove_tree(destination, &[], reporter, false),
+        Err(_) => Ok(()),
+    }
+}
+
+fn copy_tree(source: &Path, destination: &Path, excluded: &[PathBuf], reporter: &mut Reporter) -> Result<(), Stop> {
+    let mut unvisited = Visited::filter();
+    let mut walk = WalkDir::new(source)
+        .into_iter()
+        .filter_entry(|entry| !is_excluded(entry.path(), excluded) && unvisited(entry));
+    while let Some(entry) = walk.next() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                reporter.walk_failed(source, error)?;
+                continue;
+            }
+        };
+        let relative = entry.path().strip_prefix(source).unwrap_or(Path::new(""));
+        let to = if relative.as_os_str().is_empty() { destination.to_path_buf() } else { destination.join(relative) };
+
+        let file_type = entry.file_type();
+        let copied = if file_type.is_dir() {
+            reporter.attempt_change(elevate::Step::CreateDir(&to), 0, |_| fs::create_dir_all(&to).map_err(failed(&to)))?
+        } else if file_type.is_symlink() {
+            reporter.attempt(|_| copy_link(entry.path(), &to))?.is_some()
+        } else {
+            let size = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+            reporter.attempt_change(elevate::Step::CopyFile(entry.path(), &to), size, |reporter| {
+                let copied = copy_file(entry.path(), &to, reporter);
+                // Don't leave half a file behind
+                if copied.is_err() {
+                    let _ = fs::remove_file(&to);
+                }
+                copied
+            })?
+        };
+        // Nothing inside a skipped folder can be copied either
+        if !copied && file_type.is_dir() {
+            walk.skip_current_dir();
+        }
+    }
+    Ok(())
+}
+
+fn copy_file(from: &Path, to: &Path, reporter: &mut Reporter) -> Result<(), Stop> {
+    let mut reader = File::open(from).map_err(failed(from))?;
+    let mut writer = File::create(to).map_err(failed(to))?;
+    copy_chunks(&mut reader, &mut writer, from, to, reporter)?;
+    if let Ok(meta) = fs::metadata(from) {
+        let _ = fs::set_permissions(to, meta.permissions());
+    }
+    Ok(())
+}
+
+fn copy_chunks(reader: &mut impl Read, writer: &mut impl Write, from: &Path, to: &Path, reporter: &mut Reporter) -> Result<(), Stop> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let read = reader.read(&mut buffer).map_err(failed(from))?;
+        if read == 0 {
+            return Ok(());
+        }
+        writer.write_all(&buffer[..read]).map_err(failed(to))?;
+        reporter.advance(read as u64)?;
+    }
+}
+
+// Writes the walked entries of an image below `destination`, keeping their
+// times
+fn extract(image: &iso::Image, walked: &[(PathBuf, iso::Entry)], destination: &Path, reporter: &mut Reporter) -> Result<(), Stop> {
+    for (relative, entry) in walked {
+        let path = if relative.as_os_str().is_empty() { destination.to_path_buf() } else { destination.join(relative) };
+        if entry.is_dir {
+            fs::create_dir_all(&path).map_err(failed(&path))?;
+            continue;
+        }
+        let mut reader = image.read(entry).map_err(failed(&image.path))?;
+        let mut file = File::create_new(&path).map_err(failed(&path))?;
+        copy_chunks(&mut reader, &mut file, &image.path, &path, reporter)?;
+        if let Some(modified) = entry.modified {
+            let _ = file.set_modified(modified);
+        }
+    }
+    Ok(())
+}
+
+// `path`, or `name (2).ext` and so on beside it when that is taken
+fn unused(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    let mut candidate = path.to_path_buf();
+    let mut copy = 2;
+    while candidate.symlink_metadata().is_ok() {
+        candidate = path.with_file_name(format!("{} ({}){}", stem, copy, ext));
+        copy += 1;
+    }
+    candidate
+}
+
+// Encrypts `source` into the new file `output` chunk by chunk, so it shows
+// progress and can be paused or cancelled. An output left part way is
+// removed.
+fn encrypt(source: &Path, output: &Path, lock: &crypt::Lock, reporter: &mut Reporter) -> Result<(), Stop> {
+    let mut reader = File::open(source).map_err(failed(source))?;
+    reporter.total(reader.metadata().map(|meta| meta.len()).unwrap_or(0));
+    let file = File::create_new(output).map_err(failed(output))?;
+    let written = crypt::encrypt(lock, file).map_err(failed(output)).and_then(|mut writer| {
+        copy_chunks(&mut reader, &mut writer, source, output, reporter)?;
+        writer.finish().map(drop).map_err(failed(output))
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(output);
+    }
+    written
+}
+
+// Decrypts `source` into the new file `output` the same way. The key is
+// tried before `output` is made, so a wrong one leaves nothing behind.
+fn decrypt(source: &Path, output: &Path, unlock: &crypt::Unlock, reporter: &mut Reporter) -> Result<(), Stop> {
+    let file = File::open(source).map_err(failed(source))?;
+    reporter.total(file.metadata().map(|meta| meta.len()).unwrap_or(0));
+    let mut reader = crypt::decrypt(unlock, file).map_err(failed(source))?;
+    let mut file = File::create_new(output).map_err(failed(output))?;
+    let written = copy_chunks(&mut reader, &mut file, source, output, reporter);
+    if written.is_err() {
+        let _ = fs::remove_file(output);
+    }
+    written
+}
+
+// Links are copied as links, not as what they point to
+#[cfg(unix)]
+fn copy_link(from: &Path, to: &Path) -> Result<(), Stop> {
+    let target = fs::read_link(from).map_err(failed(from))?;
+    std::os::unix::fs::symlink(target, to).map_err(failed(to))
+}
+
+#[cfg(not(unix))]
+fn copy_link(from: &Path, to: &Path) -> Result<(), Stop> {
+    fs::copy(from, to).map(|_| ()).map_err(failed(from))
+}
+
+// Deletes `path` and everything below it but `excluded`; with `count`
+// each entry removed counts as progress
+fn remove_tree(path: &Path, excluded: &[PathBuf], reporter: &mut Reporter, count: bool) -> Result<(), Stop> {
+    // Folders still holding something excluded or skipped stay too
+    let mut kept = excluded.to_vec();
+    for entry in WalkDir::new(path).contents_first(true) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                kept.push(reporter.walk_failed(path, error)?);
+                continue;
+            }
+        };
+        if kept.iter().any(|kept| kept.starts_with(entry.path())) || is_excluded(entry.path(), excluded) {
+            continue;
+        }
+
+        let is_dir = entry.file_type().is_dir();
+        let change = if is_dir { elevate::Step::RemoveDir(entry.path()) } else { elevate::Step::RemoveFile(entry.path()) };
+        let removed = reporter.attempt_change(change, u64::from(count), |reporter| {
+            let result = if is_dir { fs::remove_dir(entry.path()) } else { fs::remove_file(entry.path()) };
+            result.map_err(failed(entry.path()))?;
+            if count { reporter.advance(1) } else { Ok(()) }
+        })?;
+        if !removed {
+            kept.push(entry.path().to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+fn write_archive(sources: &[PathBuf], archive: &Path, reporter: &mut Reporter) -> Result<(), Stop> {
+    let file = File::create(archive).map_err(failed(archive))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for source in sources {
+        // Entries are named from the folder holding the source, so it
+        // unpacks into a folder of its own
+        let base = source.parent().unwrap_or(source);
+        for entry in WalkDir::new(source).into_iter().filter_entry(Visited::filter()) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => {
+                    reporter.walk_failed(source, error)?;
+                    continue;
+                }
+            };
+            let name = entry_name(entry.path().strip_prefix(base).unwrap_or(entry.path()));
+
+            if entry.file_type().is_dir() {
+                zip.add_directory(name, options).map_err(failed(entry.path()))?;
+            } else if entry.file_type().is_file() {
+                // Opened first so an unreadable file can still be skipped;
+                // once its entry is started a failure ends the job. Links
+                // are left out, only real files are packed.
+                let Some(mut reader) = reporter.attempt(|_| File::open(entry.path()).map_err(failed(entry.path())))? else {
+                    continue;
+                };
+                let size = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+                zip.start_file(name, options.large_file(size >= u32::MAX as u64)).map_err(failed(entry.path()))?;
+                copy_chunks(&mut reader, &mut zip, entry.path(), archive, reporter)?;
+            }
+        }
+    }
+
+    zip.finish().map_err(failed(archive))?;
+    Ok(())
+}
+
+// Zip entries use forward slashes whatever the platform
+fn entry_name(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::{self, Receiver};
+
+    #[test]
+    fn preview_sums_folders_and_leaves_out_unticked_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("a.txt"), "abc").unwrap();
+        fs::write(root.join("sub/b.txt"), "abcde").unwrap();
+        fs::write(root.join("sub/c.txt"), "ab").unwrap();
+
+        let mut preview = preview(std::slice::from_ref(&root));
+        let size_of = |preview: &Preview, path: &Path| preview.items.iter().find(|item| item.path == path).unwrap().size;
+        assert_eq!((preview.total_items, preview.total_size), (5, 10));
+        assert_eq!(size_of(&preview, &root), 10);
+        assert_eq!(size_of(&preview, &root.join("sub")), 7);
+        assert!(!preview.is_truncated());
+
+        let sub = preview.items.iter_mut().find(|item| item.path == root.join("sub")).unwrap();
+        sub.included = false;
+        assert_eq!(preview.affected(), (2, 3));
+        assert_eq!(preview.excluded(), vec![root.join("sub")]);
+    }
+
+    // Runs `operation` as the only job, on this thread, and hands it back
+    // with everything it reported applied. A failure is answered with
+    // `answer`, which is given in advance as nobody is there to click.
+    fn finish(operation: Operation, answer: Decision) -> Job {
+        let mut jobs = Jobs::default();
+        jobs.push(operation);
+        *jobs.jobs[0].control.decision.lock().unwrap() = Some(answer);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        run(&jobs.all()[0], sender);
+        for event in receiver.try_iter() {
+            jobs.apply(0, event);
+        }
+        jobs.all()[0].clone()
+    }
+
+    #[test]
+    fn copies_replace_only_when_asked_to() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("from"), dir.path().join("to"));
+        fs::create_dir_all(from.join("folder")).unwrap();
+        fs::create_dir_all(to.join("folder")).unwrap();
+        fs::write(from.join("a.txt"), "new").unwrap();
+        fs::write(from.join("folder/b.txt"), "new").unwrap();
+        fs::write(to.join("a.txt"), "old").unwrap();
+        fs::write(to.join("folder/old.txt"), "old").unwrap();
+        let sources = vec![from.join("a.txt"), from.join("folder")];
+        assert_eq!(replaced(&sources, &to), vec![to.join("a.txt"), to.join("folder")]);
+
+        let refused = finish(Operation::Copy { sources: sources.clone(), target: to.clone(), replace: false }, Decision::Abort);
+        assert!(matches!(refused.state, JobState::Failed(_)));
+        assert_eq!(fs::read_to_string(to.join("a.txt")).unwrap(), "old");
+
+        let replacing = finish(Operation::Copy { sources, target: to.clone(), replace: true }, Decision::Abort);
+        assert_eq!(replacing.state, JobState::Done);
+        assert_eq!(fs::read_to_string(to.join("a.txt")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(to.join("folder/b.txt")).unwrap(), "new");
+        assert!(!to.join("folder/old.txt").exists());
+    }
+
+    #[test]
+    fn a_move_never_replaces_its_own_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("a/a");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("keep.txt"), "keep").unwrap();
+        // The source is inside the one thing named like it in the target
+        assert!(replaced(std::slice::from_ref(&source), dir.path()).is_empty());
+
+        let job = finish(Operation::Move { sources: vec![source.clone()], target: dir.path().to_path_buf(), excluded: Vec::new(), replace: true }, Decision::Abort);
+        assert!(matches!(job.state, JobState::Failed(_)));
+        assert_eq!(fs::read_to_string(source.join("keep.txt")).unwrap(), "keep");
+    }
+
+    // Runs `job` on a thread of its own, as the app does
+    fn start(job: &Job) -> (thread::JoinHandle<()>, Receiver<Event>) {
+        let job = job.clone();
+        let (sender, receiver) = mpsc::channel();
+        (thread::spawn(move || run(&job, sender)), receiver)
+    }
+
+    // Applies what job `id` reports until an event `until` picks, and hands
+    // that back
+    fn wait_for(jobs: &mut Jobs, id: u64, events: &Receiver<Event>, until: impl Fn(&Event) -> bool) -> Event {
+        loop {
+            let event = events.recv_timeout(Duration::from_secs(10)).expect("the job went quiet");
+            let picked = until(&event);
+            jobs.apply(id, event.clone());
+            if picked {
+                return event;
+            }
+        }
+    }
+
+    fn is_finished(event: &Event) -> bool {
+        matches!(event, Event::Finished(_))
+    }
+
+    // Two files to copy into an empty folder, in the job queued first
+    fn queued_copy(dir: &Path) -> Jobs {
+        let (from, to) = (dir.join("from"), dir.join("to"));
+        fs::create_dir_all(&from).unwrap();
+        fs::create_dir_all(&to).unwrap();
+        fs::write(from.join("a.txt"), "abc").unwrap();
+        fs::write(from.join("b.txt"), "de").unwrap();
+        let mut jobs = Jobs::default();
+        jobs.push(Operation::Copy { sources: vec![from.join("a.txt"), from.join("b.txt")], target: to, replace: false });
+        jobs
+    }
+
+    #[test]
+    fn a_paused_job_holds_its_place_until_resumed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut jobs = queued_copy(dir.path());
+        // Paused from the start, so it stops after its first chunk
+        jobs.jobs[0].control.paused.store(true, Ordering::Relaxed);
+        let (worker, events) = start(&jobs.all()[0]);
+
+        wait_for(&mut jobs, 0, &events, |event| matches!(event, Event::Progress(_)));
+        let job = &jobs.all()[0];
+        assert!(job.is_paused());
+        assert_eq!((job.state.clone(), job.done, job.total), (JobState::Running, 3, 5));
+        thread::sleep(PAUSE_POLL * 2);
+        assert!(events.try_recv().is_err());
+        assert!(!dir.path().join("to/b.txt").exists());
+
+        jobs.set_paused(0, false);
+        wait_for(&mut jobs, 0, &events, is_finished);
+        worker.join().unwrap();
+        assert_eq!((jobs.all()[0].state.clone(), jobs.all()[0].done), (JobState::Done, 5));
+        assert_eq!(fs::read_to_string(dir.path().join("to/b.txt")).unwrap(), "de");
+    }
+
+    #[test]
+    fn cancelling_stops_a_running_job_and_drops_a_queued_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut jobs = queued_copy(dir.path());
+        jobs.push(Operation::Delete { paths: vec![dir.path().join("from")], excluded: Vec::new() });
+        jobs.jobs[0].control.paused.store(true, Ordering::Relaxed);
+        let (worker, events) = start(&jobs.all()[0]);
+        wait_for(&mut jobs, 0, &events, |event| matches!(event, Event::Progress(_)));
+
+        jobs.cancel(1);
+        assert_eq!(jobs.all()[1].state, JobState::Cancelled);
+        jobs.cancel(0);
+        wait_for(&mut jobs, 0, &events, is_finished);
+        worker.join().unwrap();
+        assert_eq!(jobs.all()[0].state, JobState::Cancelled);
+        assert!(jobs.current().is_none());
+        assert!(!dir.path().join("to/b.txt").exists());
+        assert!(dir.path().join("from/b.txt").exists());
+    }
+
+    // Three files to copy into a folder that has the first two already
+    fn conflicting_copy(dir: &Path) -> Jobs {
+        let (from, to) = (dir.join("from"), dir.join("to"));
+        fs::create_dir_all(&from).unwrap();
+        fs::create_dir_all(&to).unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(from.join(name), "new").unwrap();
+        }
+        fs::write(to.join("a.txt"), "old").unwrap();
+        fs::write(to.join("b.txt"), "old").unwrap();
+        let sources = ["a.txt", "b.txt", "c.txt"].map(|name| from.join(name)).to_vec();
+        let mut jobs = Jobs::default();
+        jobs.push(Operation::Copy { sources, target: to, replace: false });
+        jobs
+    }
+
+    #[test]
+    fn a_failed_step_is_retried_or_skipped_as_decided() {
+        let dir = tempfile::tempdir().unwrap();
+        let to = dir.path().join("to");
+        let mut jobs = conflicting_copy(dir.path());
+        let (worker, events) = start(&jobs.all()[0]);
+
+        let Event::Problem(problem) = wait_for(&mut jobs, 0, &events, |event| matches!(event, Event::Problem(_))) else {
+            unreachable!()
+        };
+        assert!(problem.can_retry && !problem.can_elevate);
+        assert!(jobs.all()[0].problem.is_some());
+        // What was in the way is gone by the retry
+        fs::remove_file(to.join("a.txt")).unwrap();
+        jobs.decide(0, Decision::Retry);
+        assert!(jobs.all()[0].problem.is_none());
+
+        wait_for(&mut jobs, 0, &events, |event| matches!(event, Event::Problem(_)));
+        jobs.decide(0, Decision::Skip);
+        wait_for(&mut jobs, 0, &events, is_finished);
+        worker.join().unwrap();
+
+        let job = &jobs.all()[0];
+        assert_eq!((job.state.clone(), job.skipped.len()), (JobState::Done, 1));
+        let read = |name: &str| fs::read_to_string(to.join(name)).unwrap();
+        assert_eq!([read("a.txt"), read("b.txt"), read("c.txt")], ["new", "old", "new"]);
+    }
+
+    #[test]
+    fn skip_all_stops_asking_and_abort_ends_the_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut jobs = conflicting_copy(dir.path());
+        let (worker, events) = start(&jobs.all()[0]);
+        wait_for(&mut jobs, 0, &events, |event| matches!(event, Event::Problem(_)));
+        jobs.decide(0, Decision::SkipAll);
+        // The second failure is skipped without a question
+        let problems = std::cell::Cell::new(0);
+        wait_for(&mut jobs, 0, &events, |event| {
+            problems.set(problems.get() + usize::from(matches!(event, Event::Problem(_))));
+            is_finished(event)
+        });
+        worker.join().unwrap();
+        assert_eq!(problems.get(), 0);
+        assert_eq!((jobs.all()[0].state.clone(), jobs.all()[0].skipped.len()), (JobState::Done, 2));
+        assert_eq!(fs::read_to_string(dir.path().join("to/c.txt")).unwrap(), "new");
+
+        let dir = tempfile::tempdir().unwrap();
+        let jobs = conflicting_copy(dir.path());
+        let job = finish(jobs.all()[0].operation.clone(), Decision::Abort);
+        assert!(matches!(job.state, JobState::Failed(_)));
+        assert!(!dir.path().join("to/c.txt").exists());
+    }
+
+    // A folder holding a file to move and one left out, which makes a move
+    // copy and delete instead of renaming
+    fn partial_move(dir: &Path) -> Operation {
+        let source = dir.join("folder");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(dir.join("to")).unwrap();
+        fs::write(source.join("go.txt"), "go").unwrap();
+        fs::write(source.join("keep.txt"), "keep").unwrap();
+        Operation::Move { sources: vec![source.clone()], target: dir.join("to"), excluded: vec![source.join("keep.txt")], replace: false }
+    }
+
+    #[test]
+    fn a_move_renames_or_copies_what_it_can_not_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let job = finish(partial_move(dir.path()), Decision::Abort);
+        assert_eq!(job.state, JobState::Done);
+        assert_eq!(fs::read_to_string(dir.path().join("to/folder/go.txt")).unwrap(), "go");
+        assert!(!dir.path().join("folder/go.txt").exists());
+        assert!(dir.path().join("folder/keep.txt").exists());
+        // Part of it stayed, so there is nothing to put back
+        assert!(job.moved.is_empty());
+
+        let source = dir.path().join("folder/keep.txt");
+        let job = finish(Operation::Move { sources: vec![source.clone()], target: dir.path().join("to"), excluded: Vec::new(), replace: false }, Decision::Abort);
+        assert_eq!(job.moved, vec![(source, dir.path().join("to/keep.txt"))]);
+    }
+
+    #[test]
+    fn a_move_that_stops_part_way_takes_its_copy_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut jobs = Jobs::default();
+        jobs.push(partial_move(dir.path()));
+        jobs.jobs[0].control.paused.store(true, Ordering::Relaxed);
+        let (worker, events) = start(&jobs.all()[0]);
+        wait_for(&mut jobs, 0, &events, |event| matches!(event, Event::Progress(_)));
+        assert!(dir.path().join("to/folder/go.txt").exists());
+
+        jobs.cancel(0);
+        wait_for(&mut jobs, 0, &events, is_finished);
+        worker.join().unwrap();
+        assert_eq!(jobs.all()[0].state, JobState::Cancelled);
+        assert!(!dir.path().join("to/folder").exists());
+        assert_eq!(fs::read_to_string(dir.path().join("folder/go.txt")).unwrap(), "go");
+    }
+
+    #[test]
+    fn roll_back_returns_sources_to_the_folders_they_came_from() {
+        let dir = tempfile::tempdir().unwrap();
+        let (one, two, to) = (dir.path().join("one"), dir.path().join("two"), dir.path().join("to"));
+        for folder in [&one, &two, &to] {
+            fs::create_dir_all(folder).unwrap();
+        }
+        let sources = vec![one.join("a"), two.join("b"), one.join("c"), two.join("missing")];
+        for source in &sources[..3] {
+            fs::write(source, "x").unwrap();
+        }
+        // The last source is gone, so the move fails after the others
+        let mut jobs = Jobs::default();
+        jobs.push(Operation::Move { sources, target: to.clone(), excluded: Vec::new(), replace: false });
+        *jobs.jobs[0].control.decision.lock().unwrap() = Some(Decision::Abort);
+        let (worker, events) = start(&jobs.all()[0]);
+        wait_for(&mut jobs, 0, &events, is_finished);
+        worker.join().unwrap();
+        assert!(jobs.all()[0].can_roll_back());
+
+        jobs.roll_back(0);
+        assert!(jobs.all()[0].moved.is_empty());
+        let rollbacks: Vec<(Vec<PathBuf>, PathBuf)> = jobs.all()[1..]
+            .iter()
+            .map(|job| match &job.operation {
+                Operation::Move { sources, target, .. } => (sources.clone(), target.clone()),
+                operation => panic!("not a move: {}", operation),
+            })
+            .collect();
+        assert_eq!(rollbacks, vec![(vec![to.join("a"), to.join("c")], one.clone()), (vec![to.join("b")], two.clone())]);
+
+        for id in [1, 2] {
+            let (worker, events) = start(&jobs.all()[id as usize]);
+            wait_for(&mut jobs, id, &events, is_finished);
+            worker.join().unwrap();
+        }
+        assert!(one.join("a").exists() && two.join("b").exists() && one.join("c").exists());
+        assert_eq!(fs::read_dir(&to).unwrap().count(), 0);
+    }
+}
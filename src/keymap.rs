@@ -0,0 +1,280 @@
+use crate::i18n::{fill, tr};
+use iced::keyboard::{KeyCode, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// Things a keyboard shortcut can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    StopSearch,
+    Refresh,
+    GoUp,
+    GoHome,
+    CreateNew,
+    Delete,
+    DeletePermanently,
+    Print,
+    CopyPath,
+    CopyName,
+    Properties,
+    Settings,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    Palette,
+}
+
+impl Action {
+    pub const ALL: [Action; 16] = [
+        Action::StopSearch,
+        Action::Refresh,
+        Action::GoUp,
+        Action::GoHome,
+        Action::CreateNew,
+        Action::Delete,
+        Action::DeletePermanently,
+        Action::Print,
+        Action::CopyPath,
+        Action::CopyName,
+        Action::Properties,
+        Action::Settings,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ZoomReset,
+        Action::Palette,
+    ];
+
+    // The key for this action in the config file
+    fn name(self) -> &'static str {
+        match self {
+            Action::StopSearch => "stop_search",
+            Action::Refresh => "refresh",
+            Action::GoUp => "go_up",
+            Action::GoHome => "go_home",
+            Action::CreateNew => "create_new",
+            Action::Delete => "delete",
+            Action::DeletePermanently => "delete_permanently",
+            Action::Print => "print",
+            Action::CopyPath => "copy_path",
+            Action::CopyName => "copy_name",
+            Action::Properties => "properties",
+            Action::Settings => "settings",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::ZoomReset => "zoom_reset",
+            Action::Palette => "palette",
+        }
+    }
+
+    fn default_chords(self) -> &'static str {
+        match self {
+            Action::StopSearch => "Escape",
+            Action::Refresh => "F5, Ctrl+R",
+            Action::GoUp => "Alt+Up, Backspace",
+            Action::GoHome => "Alt+Home",
+            Action::CreateNew => "Ctrl+N",
+            Action::Delete => "Delete",
+            Action::DeletePermanently => "Shift+Delete",
+            Action::Print => "Ctrl+P",
+            Action::CopyPath => "Ctrl+Shift+C",
+            Action::CopyName => "Ctrl+Alt+C",
+            Action::Properties => "Alt+Enter",
+            Action::Settings => "Ctrl+,",
+            Action::ZoomIn => "Ctrl+=, Ctrl++, Ctrl+NumpadAdd",
+            Action::ZoomOut => "Ctrl+-, Ctrl+NumpadSubtract",
+            Action::ZoomReset => "Ctrl+0, Ctrl+Numpad0",
+            Action::Palette => "Ctrl+Shift+P",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Action::StopSearch => "Stop search",
+            Action::Refresh => "Refresh",
+            Action::GoUp => "Go up",
+            Action::GoHome => "Go home",
+            Action::CreateNew => "New file or folder",
+            Action::Delete => "Delete selection",
+            Action::DeletePermanently => "Delete selection permanently",
+            Action::Print => "Print",
+            Action::CopyPath => "Copy full path",
+            Action::CopyName => "Copy name",
+            Action::Properties => "Properties",
+            Action::Settings => "Settings",
+            Action::ZoomIn => "Zoom in",
+            Action::ZoomOut => "Zoom out",
+            Action::ZoomReset => "Reset zoom",
+            Action::Palette => "Command palette",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+// A key plus modifiers, written like "Ctrl+Shift+N". Ctrl stands for the
+// platform's command key, so it is Cmd on macOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Chord {
+    key: KeyCode,
+    command: bool,
+    alt: bool,
+    shift: bool,
+}
+
+impl Chord {
+    fn parse(text: &str) -> Option<Chord> {
+        // "Ctrl++" binds the plus key itself
+        let (modifiers, key) = match text.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => text.rsplit_once('+').unwrap_or(("", text)),
+        };
+
+        let mut chord = Chord {
+            key: parse_key(key.trim())?,
+            command: false,
+            alt: false,
+            shift: false,
+        };
+        for modifier in modifiers.split('+').map(str::trim).filter(|modifier| !modifier.is_empty()) {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" | "cmd" | "command" => chord.command = true,
+                "alt" | "option" => chord.alt = true,
+                "shift" => chord.shift = true,
+                _ => return None,
+            }
+        }
+        Some(chord)
+    }
+
+    fn matches(&self, key: KeyCode, modifiers: Modifiers, exact_shift: bool) -> bool {
+        self.key == key
+            && self.command == modifiers.command()
+            && self.alt == modifiers.alt()
+            && (self.shift == modifiers.shift() || !exact_shift && !self.shift)
+    }
+
+    // Plain keys like Delete belong to a text field while one is focused
+    fn is_typing(&self) -> bool {
+        !self.command && !self.alt && self.key != KeyCode::Escape && !(KeyCode::F1..=KeyCode::F24).contains(&self.key)
+    }
+}
+
+// Keys that can be bound, by the names used in the config file
+const KEYS: &[KeyCode] = &[
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6,
+    KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8,
+    KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::Escape, KeyCode::Enter, KeyCode::Space, KeyCode::Tab, KeyCode::Backspace, KeyCode::Delete,
+    KeyCode::Insert, KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Numpad0, KeyCode::Numpad1, KeyCode::Numpad2, KeyCode::Numpad3, KeyCode::Numpad4,
+    KeyCode::Numpad5, KeyCode::Numpad6, KeyCode::Numpad7, KeyCode::Numpad8, KeyCode::Numpad9,
+    KeyCode::NumpadAdd, KeyCode::NumpadSubtract, KeyCode::NumpadMultiply, KeyCode::NumpadDivide,
+    KeyCode::NumpadEnter,
+];
+
+// Punctuation is written as itself rather than by its key name
+const SYMBOLS: &[(&str, KeyCode)] = &[
+    ("=", KeyCode::Equals),
+    ("+", KeyCode::Plus),
+    ("-", KeyCode::Minus),
+    (",", KeyCode::Comma),
+    (".", KeyCode::Period),
+    ("/", KeyCode::Slash),
+    (";", KeyCode::Semicolon),
+];
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    if let Some((_, key)) = SYMBOLS.iter().find(|(symbol, _)| *symbol == name) {
+        return Some(*key);
+    }
+    if name.eq_ignore_ascii_case("esc") {
+        return Some(KeyCode::Escape);
+    }
+    // Digits are written without the "Key" prefix of their KeyCode
+    KEYS.iter().copied().find(|key| {
+        let debug = format!("{:?}", key);
+        let written = debug.strip_prefix("Key").unwrap_or(&debug);
+        written.eq_ignore_ascii_case(name)
+    })
+}
+
+// Shortcuts changed from the defaults, stored in the settings file as
+// action name -> comma-separated chords; an empty list unbinds the action
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Keymap {
+    overrides: BTreeMap<String, String>,
+}
+
+impl Keymap {
+    // The chords bound to `action` as written, e.g. "F5, Ctrl+R"
+    pub fn chords_text(&self, action: Action) -> &str {
+        self.overrides.get(action.name()).map_or(action.default_chords(), String::as_str)
+    }
+
+    pub fn set(&mut self, action: Action, text: String) {
+        if text == action.default_chords() {
+            self.overrides.remove(action.name());
+        } else {
+            self.overrides.insert(action.name().to_string(), text);
+        }
+    }
+
+    fn chords(&self, action: Action) -> impl Iterator<Item = Chord> + '_ {
+        split(self.chords_text(action)).filter_map(Chord::parse)
+    }
+
+    // The action bound to a key press. `captured` means a widget such as a
+    // text field already used the key, which rules out plain keys.
+    pub fn action_for(&self, key: KeyCode, modifiers: Modifiers, captured: bool) -> Option<Action> {
+        let find = |exact_shift: bool| {
+            Action::ALL.into_iter().find(|&action| {
+                self.chords(action)
+                    .any(|chord| chord.matches(key, modifiers, exact_shift) && !(captured && chord.is_typing()))
+            })
+        };
+        // Prefer a binding that names Shift exactly, then one that ignores it
+        // so that e.g. Ctrl++ works however the plus is typed
+        find(true).or_else(|| find(false))
+    }
+
+    // Unreadable chords and chords also bound to another action
+    pub fn problems(&self, action: Action) -> Vec<String> {
+        let mut problems = Vec::new();
+        for written in split(self.chords_text(action)) {
+            let Some(chord) = Chord::parse(written) else {
+                problems.push(fill(tr("'{}' is not a key this app knows"), &[&written]));
+                continue;
+            };
+            for other in Action::ALL.into_iter().filter(|&other| other != action) {
+                if self.chords(other).any(|bound| bound == chord) {
+                    problems.push(fill(tr("{} is also bound to {}"), &[&written, &other]));
+                }
+            }
+        }
+        problems
+    }
+}
+
+// Splits a list of chords on commas; a comma straight after a modifier's
+// '+' is the comma key itself, as in "Ctrl+,"
+fn split(text: &str) -> impl Iterator<Item = &str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        let before = text[start..index].trim_end();
+        if c == ',' && (!before.ends_with('+') || before.ends_with("++")) {
+            parts.push(&text[start..index]);
+            start = index + 1;
+        }
+    }
+    parts.push(&text[start..]);
+    parts.into_iter().map(str::trim).filter(|part| !part.is_empty())
+}
@@ -0,0 +1,418 @@
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// How often the accepting thread looks whether it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+// A connection that sends nothing for this long is dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Limits on what a request may send before it is answered; browsers stay
+// far below them
+const MAX_LINE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+
+// Connections served at once; more are closed straight away, which
+// browsers take as a sign to try again
+const MAX_CONNECTIONS: usize = 16;
+
+// Downloads are sent in pieces this big, looking between them whether the
+// share was stopped
+const CHUNK: usize = 64 * 1024;
+
+// A small HTTP server handing out one file or folder to the local network
+// until it is stopped or dropped
+#[derive(Debug)]
+pub struct Server {
+    pub url: String,
+    pub root: PathBuf,
+    stop: Arc<AtomicBool>,
+}
+
+impl Server {
+    // Serves `root` on a free port. With a password, browsers ask for it
+    // and any user name is accepted.
+    pub fn start(root: PathBuf, password: Option<String>) -> io::Result<Server> {
+        let listener = TcpListener::bind(("0.0.0.0", 0))?;
+        listener.set_nonblocking(true)?;
+        let url = format!("http://{}:{}/", local_address()?, listener.local_addr()?.port());
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (stopped, served) = (stop.clone(), root.clone());
+        thread::spawn(move || {
+            // Only this thread adds to the count, so checking it first is enough
+            let active = Arc::new(AtomicUsize::new(0));
+            while !stopped.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) if active.load(Ordering::Relaxed) >= MAX_CONNECTIONS => drop(stream),
+                    Ok((stream, _)) => {
+                        let (root, password, stopped) = (served.clone(), password.clone(), stopped.clone());
+                        active.fetch_add(1, Ordering::Relaxed);
+                        let slot = Slot(active.clone());
+                        thread::spawn(move || {
+                            if let Err(error) = respond(stream, &root, password.as_deref(), &stopped) {
+                                tracing::debug!("lan share: {}", error);
+                            }
+                            drop(slot);
+                        });
+                    }
+                    Err(error) if error.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(error) => {
+                        tracing::warn!("lan share stopped: {}", error);
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(Server { url, root, stop })
+    }
+}
+
+// Ends the accept loop and downloads still being sent
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// A connection being served; given back when dropped, also when serving
+// it panics
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// The address other machines reach this one at. Connecting a UDP socket
+// only picks the route, nothing is sent.
+fn local_address() -> io::Result<std::net::IpAddr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+    socket
+        .connect(("10.255.255.255", 1))
+        .map_err(|_| io::Error::new(io::ErrorKind::NetworkUnreachable, "not connected to a network"))?;
+    Ok(socket.local_addr()?.ip())
+}
+
+fn respond(stream: TcpStream, root: &Path, password: Option<&str>, stopped: &AtomicBool) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+
+    let request = read_line(&mut reader)?;
+    let mut authorized = password.is_none();
+    for _ in 0..MAX_HEADERS {
+        let header = read_line(&mut reader)?;
+        if header.trim().is_empty() {
+            break;
+        }
+        if let (Some(password), Some((name, value))) = (password, header.split_once(':'))
+            && name.eq_ignore_ascii_case("authorization")
+            && let Some(credentials) = value
+                .trim()
+                .strip_prefix("Basic ")
+                .and_then(|encoded| base64_decode(encoded.trim()))
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+        {
+            authorized |= credentials.split_once(':').is_some_and(|(_, given)| same_secret(given, password));
+        }
+    }
+
+    let mut parts = request.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or("/"));
+    if method != "GET" && method != "HEAD" {
+        return reply(&mut stream, "405 Method Not Allowed", "text/plain", b"Only downloads are offered", true);
+    }
+    let with_body = method == "GET";
+    if !authorized {
+        let head = "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Basic realm=\"Shared files\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        return stream.write_all(head.as_bytes());
+    }
+
+    let Some(path) = resolve(root, target) else {
+        return reply(&mut stream, "404 Not Found", "text/plain", b"Not found", with_body);
+    };
+    if path.is_dir() {
+        let page = listing(root, &path)?;
+        return reply(&mut stream, "200 OK", "text/html; charset=utf-8", page.as_bytes(), with_body);
+    }
+
+    let mut file = File::open(&path)?;
+    let name = path.file_name().map(|name| name.to_string_lossy().replace('"', "")).unwrap_or_default();
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nContent-Disposition: attachment; filename=\"{}\"\r\nConnection: close\r\n\r\n",
+        file.metadata()?.len(),
+        name
+    );
+    stream.write_all(head.as_bytes())?;
+    if with_body {
+        send(&mut file, &mut stream, stopped)?;
+    }
+    Ok(())
+}
+
+// Copies `file` to `stream` until it ends or the share is stopped
+fn send(file: &mut impl Read, stream: &mut impl Write, stopped: &AtomicBool) -> io::Result<()> {
+    let mut chunk = vec![0; CHUNK];
+    loop {
+        if stopped.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "sharing stopped"));
+        }
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            return Ok(());
+        }
+        stream.write_all(&chunk[..read])?;
+    }
+}
+
+// Answers with `body`, or only with what it would be for a HEAD request
+fn reply(stream: &mut TcpStream, status: &str, kind: &str, body: &[u8], with_body: bool) -> io::Result<()> {
+    let head = format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, kind, body.len());
+    stream.write_all(head.as_bytes())?;
+    if with_body {
+        stream.write_all(body)?;
+    }
+    Ok(())
+}
+
+// One line of the request; empty at its end. Longer lines than any browser
+// sends end the connection instead of being buffered.
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    reader.take(MAX_LINE as u64).read_line(&mut line)?;
+    if line.len() >= MAX_LINE && !line.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "request line too long"));
+    }
+    Ok(line)
+}
+
+// Compares without stopping at the first difference, so the time taken
+// tells nothing about how much of a guess was right; digests are compared
+// so the length doesn't show either
+fn same_secret(given: &str, expected: &str) -> bool {
+    let (given, expected) = (Sha256::digest(given), Sha256::digest(expected));
+    given.iter().zip(expected.iter()).fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+}
+
+// The file or folder a request asks for, if it is within what is shared.
+// A shared file is offered as the only entry of a listing.
+fn resolve(root: &Path, target: &str) -> Option<PathBuf> {
+    let relative = PathBuf::from(percent_decode(target.split('?').next()?.trim_start_matches('/'))?);
+    if relative.components().any(|component| !matches!(component, Component::Normal(_))) {
+        return None;
+    }
+
+    if root.is_file() {
+        if relative.as_os_str().is_empty() {
+            return root.parent().map(Path::to_path_buf);
+        }
+        return (Some(relative.as_os_str()) == root.file_name()).then(|| root.to_path_buf());
+    }
+    // Links may not lead out of the shared folder
+    let path = root.join(relative).canonicalize().ok()?;
+    path.starts_with(root.canonicalize().ok()?).then_some(path)
+}
+
+fn listing(root: &Path, dir: &Path) -> io::Result<String> {
+    let mut entries: Vec<(String, bool)> = if root.is_file() {
+        root.file_name().map(|name| (name.to_string_lossy().into_owned(), false)).into_iter().collect()
+    } else {
+        fs::read_dir(dir)?
+            .flatten()
+            .map(|entry| (entry.file_name().to_string_lossy().into_owned(), entry.path().is_dir()))
+            .collect()
+    };
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.to_lowercase().cmp(&b.0.to_lowercase())));
+
+    let named = if root.is_file() { root } else { dir };
+    let title = escape(&named.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default());
+    let mut page = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>{0}</title></head><body><h1>{0}</h1><ul>",
+        title
+    );
+    if root.is_dir() && root.canonicalize().is_ok_and(|root| dir != root) {
+        page.push_str("<li><a href=\"../\">..</a></li>");
+    }
+    for (name, is_dir) in entries {
+        let slash = if is_dir { "/" } else { "" };
+        page.push_str(&format!("<li><a href=\"{}{}\">{}{}</a></li>", percent_encode(&name), slash, escape(&name), slash));
+    }
+    page.push_str("</ul></body></html>");
+    Ok(page)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn percent_encode(name: &str) -> String {
+    name.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&first, tail)) = rest.split_first() {
+        if first == b'%' {
+            // from_str_radix alone would take a sign, as in "%+1"
+            let hex = tail.get(..2).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(first);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+// Also reads the paths ripgrep gives as bytes
+pub(crate) fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    // Padding may only end the text, and a lone last character holds less
+    // than a byte
+    let unpadded = text.strip_suffix("==").or_else(|| text.strip_suffix('=')).unwrap_or(text);
+    if unpadded.len() % 4 == 1 || (unpadded.len() != text.len() && !text.len().is_multiple_of(4)) {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in unpadded.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = buffer << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn requests_stay_within_the_shared_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("shared");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("sub/a b.txt"), "a").unwrap();
+        fs::write(dir.path().join("secret.txt"), "s").unwrap();
+        let inside = root.canonicalize().unwrap();
+
+        assert_eq!(resolve(&root, "/"), Some(inside.clone()));
+        assert_eq!(resolve(&root, "/sub/a%20b.txt?download"), Some(inside.join("sub/a b.txt")));
+        assert_eq!(resolve(&root, "/sub/missing.txt"), None);
+        for escape in ["/../secret.txt", "/sub/../../secret.txt", "/%2e%2e/secret.txt", "/%2E%2E%2Fsecret.txt", "/sub%2F..%2F..%2Fsecret.txt"] {
+            assert_eq!(resolve(&root, escape), None, "{}", escape);
+        }
+        // Absolute paths, given as such or encoded, are read below the root
+        let secret = dir.path().join("secret.txt").canonicalize().unwrap();
+        assert_eq!(resolve(&root, &format!("/{}", secret.display())), None);
+        assert_eq!(resolve(&root, &format!("/{}", percent_encode(&secret.to_string_lossy()))), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_may_not_lead_out_of_the_shared_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("shared");
+        fs::create_dir(&root).unwrap();
+        fs::write(dir.path().join("secret.txt"), "s").unwrap();
+        fs::write(root.join("inside.txt"), "i").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("secret.txt"), root.join("out")).unwrap();
+        std::os::unix::fs::symlink(dir.path(), root.join("up")).unwrap();
+        std::os::unix::fs::symlink(root.join("inside.txt"), root.join("in")).unwrap();
+
+        assert_eq!(resolve(&root, "/out"), None);
+        assert_eq!(resolve(&root, "/up/secret.txt"), None);
+        assert_eq!(resolve(&root, "/in"), Some(root.canonicalize().unwrap().join("inside.txt")));
+    }
+
+    #[test]
+    fn a_shared_file_is_all_that_is_offered() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("report.pdf");
+        fs::write(&file, "r").unwrap();
+        fs::write(dir.path().join("other.txt"), "o").unwrap();
+
+        assert_eq!(resolve(&file, "/"), Some(dir.path().to_path_buf()));
+        assert_eq!(resolve(&file, "/report.pdf"), Some(file.clone()));
+        assert_eq!(resolve(&file, "/other.txt"), None);
+        assert_eq!(resolve(&file, "/../report.pdf"), None);
+
+        let page = listing(&file, dir.path()).unwrap();
+        assert!(page.contains("report.pdf"));
+        assert!(!page.contains("other.txt"));
+    }
+
+    #[test]
+    fn percent_escapes_must_be_whole() {
+        assert_eq!(percent_decode("a%20b%2Fc").as_deref(), Some("a b/c"));
+        assert_eq!(percent_decode("caf%C3%A9").as_deref(), Some("café"));
+        for bad in ["%", "%2", "%zz", "%+1", "%-1", "%C3"] {
+            assert_eq!(percent_decode(bad), None, "{}", bad);
+        }
+        assert_eq!(percent_decode(&percent_encode("a b&c/ü")).as_deref(), Some("a b&c/ü"));
+    }
+
+    #[test]
+    fn base64_takes_padding_only_at_the_end() {
+        assert_eq!(base64_decode("dXNlcjpwYXNz").as_deref(), Some(&b"user:pass"[..]));
+        assert_eq!(base64_decode("YQ==").as_deref(), Some(&b"a"[..]));
+        assert_eq!(base64_decode("YWI=").as_deref(), Some(&b"ab"[..]));
+        assert_eq!(base64_decode("YQ").as_deref(), Some(&b"a"[..]));
+        assert_eq!(base64_decode("").as_deref(), Some(&b""[..]));
+        for bad in ["Y", "YQ=", "YQ==YQ==", "YQ=x", "Y===", "dXNl*jpw", "dXNl cjpw"] {
+            assert_eq!(base64_decode(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn over_long_lines_are_refused() {
+        let mut request = Cursor::new(b"GET / HTTP/1.1\r\nHost: x\r\n\r\n".to_vec());
+        assert_eq!(read_line(&mut request).unwrap(), "GET / HTTP/1.1\r\n");
+        assert_eq!(read_line(&mut request).unwrap(), "Host: x\r\n");
+        assert_eq!(read_line(&mut request).unwrap(), "\r\n");
+        assert_eq!(read_line(&mut request).unwrap(), "");
+
+        let mut long = Cursor::new(vec![b'a'; MAX_LINE + 10]);
+        assert_eq!(read_line(&mut long).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn stopping_the_share_ends_a_download() {
+        let mut sent = Vec::new();
+        send(&mut Cursor::new(vec![7; 3 * CHUNK]), &mut sent, &AtomicBool::new(false)).unwrap();
+        assert_eq!(sent.len(), 3 * CHUNK);
+
+        let mut sent = Vec::new();
+        let error = send(&mut Cursor::new(vec![7; 3 * CHUNK]), &mut sent, &AtomicBool::new(true)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Interrupted);
+        assert!(sent.is_empty());
+    }
+}
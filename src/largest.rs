@@ -0,0 +1,39 @@
+use crate::excludes::Excludes;
+use crate::loader::{self, Sink};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::path::Path;
+
+// How many files the report keeps
+pub const REPORT_SIZE: usize = 100;
+
+// Finds the `REPORT_SIZE` biggest files below `root`, hidden ones included,
+// and delivers them once the walk is complete
+pub fn files(root: &Path, excludes: &Excludes, sink: &mut Sink) {
+    // Min-heap of the biggest files seen so far
+    let mut biggest = BinaryHeap::with_capacity(REPORT_SIZE + 1);
+
+    for entry in excludes.walk(root, true).build().flatten() {
+        if sink.is_cancelled() {
+            return;
+        }
+
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+
+        biggest.push(Reverse((meta.len(), entry.into_path())));
+        if biggest.len() > REPORT_SIZE {
+            biggest.pop();
+        }
+    }
+
+    for Reverse((_, path)) in biggest.into_sorted_vec() {
+        if !sink.push(loader::read_entry(path)) {
+            return;
+        }
+    }
+}
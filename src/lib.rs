@@ -0,0 +1,56 @@
+// The file manager's core: listing, sorting, searching and the operations
+// on files, without the window. The app is built on it, and other tools may
+// use it too. Long-running work reports through an `outlet::Outlet`, so any
+// front end can carry the results where it needs them.
+pub mod acl;
+pub mod associations;
+pub mod boolean;
+pub mod checksum;
+pub mod cleanup;
+pub mod cloud;
+pub mod commands;
+pub mod crypt;
+pub mod diagnostics;
+pub mod dirsize;
+pub mod diskspace;
+pub mod elevate;
+pub mod entry;
+pub mod excludes;
+pub mod external;
+pub mod filetype;
+pub mod git;
+pub mod guard;
+pub mod history;
+pub mod icons;
+pub mod imaging;
+pub mod index;
+pub mod iso;
+pub mod jobs;
+pub mod lan;
+pub mod largest;
+pub mod loader;
+pub mod logging;
+pub mod manifest;
+pub mod media;
+pub mod mounts;
+pub mod notes;
+pub mod notices;
+pub mod ocr;
+pub mod outlet;
+pub mod paths;
+pub mod permissions;
+pub mod photo;
+pub mod plugins;
+pub mod previews;
+pub mod print;
+pub mod recent;
+pub mod search;
+pub mod share;
+pub mod signature;
+pub mod sizes;
+pub mod starred;
+pub mod tags;
+pub mod trash;
+pub mod translate;
+pub mod visited;
+pub mod xattr;
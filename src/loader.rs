@@ -0,0 +1,217 @@
+use crate::entry::{is_hidden, FileEntry};
+use crate::excludes::Excludes;
+use crate::icons::IconKind;
+use crate::outlet::Outlet;
+use crate::translate::{fill, tr};
+use crate::{cloud, filetype, tags};
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::{metadata, read_dir};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+// Entries are handed on in batches of this size
+const BATCH_SIZE: usize = 500;
+
+#[derive(Debug, Clone)]
+pub enum Progress {
+    Batch(Vec<FileEntry>),
+    // The walk could not start, e.g. the folder is unreadable
+    Failed(Failure),
+    Finished,
+}
+
+#[derive(Debug, Clone)]
+pub enum Failure {
+    // The user may not read the folder
+    Denied,
+    // The folder is on a network drive that stopped answering
+    Offline(PathBuf),
+    Other(String),
+}
+
+impl From<io::Error> for Failure {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::PermissionDenied => Failure::Denied,
+            _ => Failure::Other(error.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Denied => write!(f, "{}", tr("Permission denied")),
+            Failure::Offline(mount) => write!(f, "{}", fill(tr("{} is offline"), &[&mount.display()])),
+            Failure::Other(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+// What a sink hands on: a batch of entries, or why nothing (more) can be read
+pub type Batch = Result<Vec<FileEntry>, Failure>;
+
+// Collects entries produced by a worker and forwards them in batches
+pub struct Sink {
+    outlet: Arc<dyn Outlet<Batch>>,
+    batch: Vec<FileEntry>,
+}
+
+impl Sink {
+    pub fn new(outlet: impl Outlet<Batch> + 'static) -> Sink {
+        Sink {
+            outlet: Arc::new(outlet),
+            batch: Vec::with_capacity(BATCH_SIZE),
+        }
+    }
+
+    // Queues an entry; returns false once nobody is listening
+    pub fn push(&mut self, entry: FileEntry) -> bool {
+        self.batch.push(entry);
+        if self.batch.len() < BATCH_SIZE {
+            return true;
+        }
+
+        let full = std::mem::replace(&mut self.batch, Vec::with_capacity(BATCH_SIZE));
+        self.outlet.send(Ok(full))
+    }
+
+    // Reports why nothing (more) can be read
+    pub fn fail(&mut self, error: Failure) {
+        self.outlet.send(Err(error));
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.outlet.is_closed()
+    }
+
+    // Another sink feeding the same outlet, for work spread over threads;
+    // each sends what is left of its batch when dropped
+    pub fn fork(&self) -> Sink {
+        Sink {
+            outlet: Arc::clone(&self.outlet),
+            batch: Vec::with_capacity(BATCH_SIZE),
+        }
+    }
+}
+
+impl Drop for Sink {
+    fn drop(&mut self) {
+        if !self.batch.is_empty() {
+            self.outlet.send(Ok(std::mem::take(&mut self.batch)));
+        }
+    }
+}
+
+// Reads the entries of `dir` into `sink`, dotfiles only with `show_hidden`;
+// entries matching a hide pattern never
+pub fn read_directory(dir: &Path, show_hidden: bool, excludes: &Excludes, sink: &mut Sink) {
+    // The walk skips what it cannot read, so check the folder itself first
+    if let Err(error) = read_dir(dir) {
+        sink.fail(error.into());
+        return;
+    }
+
+    for entry in WalkDir::new(dir).min_depth(1).max_depth(1).into_iter().flatten() {
+        let path = entry.path().to_path_buf();
+
+        if !is_listed(&path, show_hidden, excludes) {
+            continue;
+        }
+
+        if !sink.push(read_entry(path)) {
+            // Nobody wants this folder any more
+            return;
+        }
+    }
+}
+
+// The entries of `dir` all at once, unsorted, for callers with nothing to
+// stream them to; the same entries `read_directory` would deliver
+pub fn list_dir(dir: &Path, show_hidden: bool, excludes: &Excludes) -> io::Result<Vec<FileEntry>> {
+    let mut entries = Vec::new();
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if is_listed(&path, show_hidden, excludes) {
+            entries.push(read_entry(path));
+        }
+    }
+    Ok(entries)
+}
+
+fn is_listed(path: &Path, show_hidden: bool, excludes: &Excludes) -> bool {
+    (show_hidden || !is_hidden(path)) && !excludes.hides(path)
+}
+
+pub fn read_entry(path: PathBuf) -> FileEntry {
+    let name = path.file_name().map(OsStr::to_os_string).unwrap_or_else(|| path.as_os_str().to_os_string());
+
+    // Everything the list shows is read here once, so redraws and sorting
+    // never go back to the disk. If metadata can't be read, still show the
+    // file with default values.
+    let meta = metadata(&path).ok();
+    // Sniffing a placeholder's contents would download it
+    let cloud = meta.as_ref().is_some_and(cloud::is_placeholder);
+    let (file_type, sniffed) = if cloud { (filetype::describe_by_name(&path), None) } else { filetype::detect(&path) };
+    let tags = tags::read(&path);
+    FileEntry {
+        name,
+        cloud,
+        is_dir: meta.as_ref().is_some_and(|meta| meta.is_dir()),
+        icon: IconKind::for_sniffed(&path, meta.as_ref(), sniffed),
+        size: meta.as_ref().map_or(0, |meta| meta.len()),
+        modified: meta.and_then(|meta| meta.modified().ok()).unwrap_or(SystemTime::now()),
+        path,
+        file_type,
+        tags,
+        line_matches: Vec::new(),
+        detail: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn listed(dir: &Path, show_hidden: bool, excludes: &Excludes) -> Vec<String> {
+        let mut names: Vec<String> = list_dir(dir, show_hidden, excludes)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.display_name().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn lists_a_folder_without_what_is_hidden_or_excluded() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("notes.txt"), "hello").unwrap();
+        fs::write(dir.path().join(".profile"), "").unwrap();
+        fs::write(dir.path().join("build.log"), "").unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        // Hide patterns apply even with the other excludes turned off
+        let excludes = Excludes::new(false, &[], &["*.log".to_string()]);
+
+        assert_eq!(listed(dir.path(), false, &excludes), ["notes.txt", "src"]);
+        assert_eq!(listed(dir.path(), true, &excludes), [".profile", "notes.txt", "src"]);
+        assert_eq!(listed(dir.path(), true, &Excludes::new(false, &[], &[])).len(), 4);
+
+        let entries = list_dir(dir.path(), false, &excludes).unwrap();
+        let notes = entries.iter().find(|entry| entry.name == "notes.txt").unwrap();
+        assert_eq!((notes.is_dir, notes.size, notes.path.clone()), (false, 5, dir.path().join("notes.txt")));
+        assert!(entries.iter().find(|entry| entry.name == "src").unwrap().is_dir);
+    }
+
+    #[test]
+    fn a_missing_folder_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let error = list_dir(&dir.path().join("gone"), false, &Excludes::new(false, &[], &[])).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
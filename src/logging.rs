@@ -0,0 +1,57 @@
+use crate::paths;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+const LOG_PREFIX: &str = "filemanager";
+const LOG_SUFFIX: &str = "log";
+// A new file is started each day and only the last week is kept
+const KEPT_FILES: usize = 7;
+// The log dialog shows the end of the newest file
+const SHOWN_LINES: usize = 500;
+
+// Where the log files go: next to the other state kept between runs
+pub fn log_dir() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join("logs"))
+}
+
+// Starts writing the log. Lines are written on a background thread, which
+// flushes when the returned guard is dropped, so keep it until exit.
+pub fn init() -> Option<WorkerGuard> {
+    let dir = log_dir()?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix(LOG_SUFFIX)
+        .max_log_files(KEPT_FILES)
+        .build(dir)
+        .ok()?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_max_level(tracing::Level::INFO)
+        .try_init()
+        .ok()?;
+    Some(guard)
+}
+
+// The last lines of the newest log file
+pub fn recent() -> io::Result<String> {
+    let dir = log_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data folder"))?;
+    let newest = fs::read_dir(&dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with(LOG_PREFIX)))
+        .max_by_key(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok());
+    let Some(newest) = newest else {
+        return Ok(String::new());
+    };
+
+    let contents = fs::read_to_string(newest)?;
+    let lines: Vec<&str> = contents.lines().collect();
+    Ok(lines[lines.len().saturating_sub(SHOWN_LINES)..].join("\n"))
+}
@@ -1,44 +1,347 @@
-use iced::widget::{button, checkbox, column, container, horizontal_rule, row, scrollable, text, text_input};
-use iced::{executor, theme, Application, Color, Command, Element, Length, Settings, Theme};
+use iced::widget::{button, canvas, checkbox, column, container, horizontal_rule, pick_list, progress_bar, row, scrollable, slider, text, text_input, tooltip};
+use iced::{event, executor, keyboard, subscription, theme, Application, Color, Command, Element, Event, Length, Settings, Subscription, Theme};
 use iced::alignment::Horizontal;
 use iced::widget::Space;
-use std::fs::{create_dir_all, metadata, remove_dir_all, remove_file, File};
+use iced::Font;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs::{create_dir_all, metadata, read, remove_dir, remove_file, write, File};
+use std::io;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
-use walkdir::WalkDir;
-use chrono::{DateTime, Local};
-use humansize::{format_size, BINARY};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use file_explorer::{
+    acl, associations, cleanup, cloud, commands, crypt, diagnostics, dirsize, diskspace, entry, excludes,
+    filetype, git, guard, history, icons, imaging, index, iso, jobs, lan, largest, loader, logging,
+    manifest, media, mounts, notes, notices, ocr, paths, photo, plugins, previews, print, recent, search,
+    share, signature, sizes, starred, tags, trash, translate, xattr,
+};
+use entry::{FileEntry, SortMode};
+use icons::{IconKind, IconSet};
+use diagnostics::Diagnostic;
+use excludes::Excludes;
+use i18n::{fill, tr, Language};
+use settings::{DateFormat, Density, Preferences, SavedSearch, SizeUnits, ThemeChoice};
+use toolbar::{MenuItem, Place, Tool};
+
+mod i18n;
+mod keymap;
+mod qr;
+mod settings;
+mod streams;
+mod toolbar;
+mod tui;
+mod usage;
+mod views;
+
+// Files larger than this are refused by the built-in editor
+const MAX_EDITABLE_SIZE: u64 = 256 * 1024;
+// Matches the command palette lists; typing narrows them down
+const PALETTE_ROWS: usize = 8;
+
+// Text size of the file list at 100% zoom
+const BASE_TEXT_SIZE: f32 = 16.0;
+
+// Rows rendered at once; "Show more" reveals the next page
+const PAGE_SIZE: usize = 1000;
+
+// Frames of the activity indicator shown while loading or searching
+const SPINNER_FRAMES: [&str; 4] = ["◐", "◓", "◑", "◒"];
+
+// Placeholder rows at the end of a list that is still loading
+const SKELETON_ROWS: usize = 3;
+
+// Width of the "Show in folder" column of the search results
+const REVEAL_BUTTON_WIDTH: f32 = 130.0;
+// Wide enough for the two-letter status of untracked files
+const GIT_COLUMN_WIDTH: f32 = 40.0;
+
+// The window cannot be made smaller than this
+const MIN_WINDOW_SIZE: (u32, u32) = (600, 400);
+
+// Pause in typing after which a shown search is run again
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(200);
 
 pub fn main() -> iced::Result {
+    translate::install(i18n::tr);
+    if let Some(profile) = profile_arg() {
+        settings::set_profile(Some(profile));
+    }
+    let _log = logging::init();
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), profile = ?settings::profile(), "starting");
+    if std::env::args().skip(1).any(|arg| arg == "--tui") {
+        let home = dirs::home_dir().filter(|dir| dir.is_dir()).unwrap_or_else(fallback_home);
+        if let Err(error) = tui::run(home) {
+            tracing::error!("the terminal mode stopped: {}", error);
+            // Exiting skips destructors, so the log is flushed first
+            drop(_log);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    let geometry = settings::load().window;
+    let position = match geometry.position {
+        Some((x, y)) => iced::window::Position::Specific(x, y),
+        None => iced::window::Position::Default,
+    };
+
     FileManager::run(Settings {
         window: iced::window::Settings {
-            size: (900, 700),
-            min_size: Some((600, 400)),
+            size: (geometry.width.max(MIN_WINDOW_SIZE.0), geometry.height.max(MIN_WINDOW_SIZE.1)),
+            position,
+            min_size: Some(MIN_WINDOW_SIZE),
             ..Default::default()
         },
+        // The geometry is saved before closing
+        exit_on_close_request: false,
         ..Default::default()
     })
 }
 
+// The name after `--profile`, or in `--profile=name`
+fn profile_arg() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next().filter(|name| settings::is_valid_profile_name(name));
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string()).filter(|name| settings::is_valid_profile_name(name));
+        }
+    }
+    None
+}
+
 #[derive(Debug, Clone)]
 enum Message {
     FileSelected(PathBuf),
+    CloudDownloaded(PathBuf, Result<(), String>),
+    RevealInFolder(PathBuf),
     NavigateUp,
     NavigateHome,
     Refresh,
     CreateNew,
     Delete,
+    ConfirmTrash,
+    DeletePermanently,
     NameInputChanged(String),
     IsDirectoryToggled(bool),
     ConfirmCreate,
-    ConfirmDelete,
+    ConfirmOverwrite,
+    DontAskAgainToggled(bool),
+    ConfirmOverwriteToggled(bool),
+    ConfirmTrashToggled(bool),
+    KeyPressed(keyboard::KeyCode, keyboard::Modifiers, bool),
+    RunCustomCommand(commands::CustomCommand),
+    CustomCommandFinished(Result<commands::Output, String>),
+    CommandNameChanged(String),
+    CommandTemplateChanged(String),
+    AddCustomCommand,
+    HideInputChanged(String),
+    AddHidePattern,
+    RemoveHidePattern(String),
+    ClearCaches,
+    ProfileSelected(settings::Profile),
+    ProfileNameChanged(String),
+    CreateProfile,
+    RemoveCustomCommand(String),
+    RunPluginAction(plugins::ActionRef),
+    PluginActionFinished(Result<plugins::Reply, String>),
+    PluginColumnsLoaded(PathBuf, Vec<HashMap<PathBuf, String>>),
+    PluginPreviewLoaded(PathBuf, previews::Stamp, Result<String, String>),
+    OpenWith,
+    Share,
+    ShareCommandChanged(String),
+    ShareOverLan,
+    Print,
+    Printed(PathBuf, Result<String, String>),
+    LanPasswordChanged(String),
+    StartLanShare,
+    StopLanShare,
+    OpenWithCommandChanged(String),
+    OpenWithAlwaysToggled(bool),
+    ConfirmOpenWith,
+    RemoveAssociation(String),
+    KeymapChanged(keymap::Action, String),
+    MenuItemChosen(MenuItem),
+    DismissToast(u64),
+    JobEvent(u64, jobs::Event),
+    CancelJob(u64),
+    PauseJob(u64, bool),
+    JobDecision(u64, jobs::Decision),
+    RollBackJob(u64),
+    ClearFinishedJobs,
+    OperationsToggled,
+    OpenTransfer(Transfer),
+    TransferTargetChanged(String),
+    ConfirmTransfer,
+    Compress,
+    OpenCrypt(Crypt),
+    CryptKeyChanged(String),
+    CryptPassphraseToggled(bool),
+    CryptPassphraseChanged(String),
+    CryptPassphraseAgainChanged(String),
+    ConfirmCrypt,
+    OpenConvertImages,
+    CopySelection(CopyAs),
+    ConversionFormatSelected(imaging::Format),
+    ConversionMaxChanged(String),
+    ConversionQualityChanged(String),
+    ConversionTargetChanged(String),
+    ConfirmConvertImages,
+    ExpireToasts,
+    DismissBanner,
+    ShowErrorLog,
+    ClearErrorLog,
+    // Opens the error log with this error's details shown
+    ShowErrorDetails(u64),
+    ToggleErrorDetails(u64),
+    CopyErrorDetails(u64),
+    ShowLog,
+    ShowHistory,
+    ShowTags,
+    TagToggled(String, bool),
+    NewTagChanged(String),
+    NewTagColorChanged(tags::TagColor),
+    CreateTag,
+    RemoveTag(String),
+    TagFilterChanged(TagFilter),
+    ExportHistory,
+    ShowGit,
+    GitAction(git::FileAction),
+    GitDiffLoaded(PathBuf, Result<String, String>),
+    VerifySignature,
+    SignatureVerified(PathBuf, Result<signature::Verification, String>),
+    VerifyChecksums,
+    ChecksumEvent(u64, manifest::Event),
+    ToolPlaced(Tool, Place),
+    ToolShifted(Tool, isize),
+    PreviewLoaded(Vec<PathBuf>, Option<jobs::Preview>),
+    PreviewToggled(usize, bool),
+    ConfirmPreview,
+    GuardInputChanged(String),
     ShowProperties,
+    NoteChanged(String),
+    SaveNote,
+    AttributeNameChanged(String),
+    AttributeValueChanged(String),
+    AddAttribute,
+    RemoveAttribute(OsString),
+    PropertiesTabChanged(PropertiesTab),
+    AclWhoChanged(String),
+    AclRightsChanged(String),
+    GrantAcl,
+    RevokeAcl(String),
     CloseDialog,
     SearchInputChanged(String),
+    SearchSettled(u64),
+    RefineInputChanged(String),
+    DatePresetSelected(search::DatePreset),
+    DateFromChanged(String),
+    DateUntilChanged(String),
+    RegexToggled(bool),
+    FuzzyToggled(bool),
+    ScopeSelected(search::Scope),
+    SaveSearch,
+    SavedSearchNameChanged(String),
+    ConfirmSaveSearch,
+    RunSavedSearch(SavedSearch),
+    ForgetSavedSearch(String),
+    IndexToggled(bool),
+    SkipIgnoredToggled(bool),
+    ExternalSearchToggled(bool),
+    OcrSearchToggled(bool),
+    IndexLoaded(Option<Arc<index::Index>>),
+    IndexBuilt(Option<Arc<index::Index>>),
     PerformSearch,
     SortByName,
     SortBySize,
     SortByDate,
+    Edit,
+    EditorLineChanged(usize, String),
+    EditorInsertLine(usize),
+    EditorRemoveLine(usize),
+    SaveEdit,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    OpenPalette,
+    PaletteQueryChanged(String),
+    // Enter in the palette runs the best match
+    SubmitPalette,
+    PaletteChosen(Tool),
+    DensitySelected(Density),
+    IconSetSelected(IconSet),
+    EntriesLoaded(u64, loader::Progress),
+    ShowMore,
+    StopSearch,
+    Tick,
+    FolderSizesToggled(bool),
+    HiddenFilesToggled(bool),
+    ThemeSelected(ThemeChoice),
+    LanguageSelected(Language),
+    DateFormatSelected(DateFormat),
+    ProtectedDepthSelected(usize),
+    PreviewMemorySelected(previews::CacheSize),
+    PreviewDiskSelected(previews::CacheSize),
+    SizeUnitsSelected(SizeUnits),
+    UiScaleChanged(f32),
+    UiScaleReleased,
+    DefaultSortSelected(SortMode),
+    DirsFirstToggled(bool),
+    WindowResized(u32, u32),
+    WindowMoved(i32, i32),
+    CloseRequested,
+    OpenSettings,
+    CloseSettings,
+    StartDirChanged(String),
+    UseCurrentAsStart,
+    AddIndexRoot,
+    RemoveIndexRoot(PathBuf),
+    ExcludeInputChanged(String),
+    AddExcludePattern,
+    RemoveExcludePattern(String),
+    FolderSizeComputed(PathBuf, u64),
+    FolderCountComputed(PathBuf, u64),
+    GitStatusLoaded(PathBuf, Option<git::RepoStatus>),
+    // Load generation, network mount point, whether it answered in time
+    MountProbed(u64, PathBuf, bool),
+    DiskSpaceLoaded(PathBuf, Option<diskspace::DiskSpace>),
+    MediaProbed(PathBuf, Result<media::MediaInfo, String>),
+    AnalyzeUsage,
+    UsageScanned(PathBuf, usage::Node),
+    UsageOpen(PathBuf),
+    UsageUp,
+    UsageDelete(PathBuf),
+    CloseUsage,
+    FindEmpty,
+    EmptyFound(PathBuf, Vec<cleanup::EmptyEntry>),
+    EmptyToggled(PathBuf, bool),
+    EmptySelectAll(bool),
+    DeleteEmpty,
+    ConfirmDeleteEmpty,
+    CloseCleanup,
+    FindLargest,
+    LargestLoaded(u64, loader::Progress),
+    LargestDelete(PathBuf),
+    CloseLargest,
+    ShowTrash,
+    TrashListed(Result<Vec<trash::Item>, String>),
+    RestoreFromTrash(usize),
+    EmptyTrash,
+    TrashEmptied(Result<(), String>),
+    CloseTrash,
+    ImageOpened(PathBuf, Result<(iso::Image, Vec<iso::Entry>), String>),
+    ImageListed(iso::Entry, Result<Vec<iso::Entry>, String>),
+    OpenImageEntry(usize),
+    ImageUp,
+    ExtractFromImage(usize),
+    ImageFileExtracted(Result<PathBuf, String>),
+    CloseImage,
+    ShowRecent(recent::Window),
+    ShowStarred,
+    ToggleStar,
 }
 
 struct FileManager {
@@ -46,37 +349,232 @@ struct FileManager {
     home_dir: PathBuf,
     entries: Vec<FileEntry>,
     selected_entry: Option<PathBuf>,
+    // An entry shown in its folder from elsewhere, scrolled to once the
+    // folder is listed
+    reveal: Option<PathBuf>,
     new_name: String,
     is_directory: bool,
+    // "Don't ask again" in the overwrite dialog
+    dont_ask_again: bool,
+    // Paths the open overwrite or delete dialog would destroy that changed
+    // on disk after they were listed
+    changed_on_disk: Vec<PathBuf>,
+    // What the copy or move waiting in `pending` would replace; empty when
+    // the overwrite dialog is about a new file
+    replaced: Vec<PathBuf>,
+    // Critical folders the open delete or move dialog would touch, and what
+    // has been typed so far to confirm them
+    guarded: Vec<PathBuf>,
+    guard_input: String,
     dialog: DialogState,
     properties: Option<FileProperties>,
     search_query: String,
+    // Bumped on every edit of the search box, so only the last one re-searches
+    search_edit: u64,
+    refine_query: String,
+    refinement: search::Refinement,
+    date_preset: search::DatePreset,
+    date_from: String,
+    date_until: String,
+    // Query of the recursive search whose results are listed, if any
+    active_search: Option<search::Query>,
+    // Set while the list shows recently modified files instead of a folder
+    recent: Option<recent::Window>,
+    starred: starred::Starred,
+    // Set while the list shows the starred files instead of a folder
+    starred_view: bool,
+    use_regex: bool,
+    use_fuzzy: bool,
+    search_scope: search::Scope,
+    saved_search_name: String,
+    // Open With dialog inputs
+    open_with_command: String,
+    // What the rules know the file as, read when the dialog opens
+    open_with_extension: Option<String>,
+    open_with_always: bool,
+    open_with_error: Option<String>,
+    // What has been typed into the command palette
+    palette_query: String,
+    // Text of the preferences dialog's inputs until they are applied
+    settings_start_dir: String,
+    settings_exclude: String,
+    settings_hide: String,
+    settings_command_name: String,
+    settings_command_template: String,
+    // Result of the last "Clear caches"
+    cache_notice: Option<String>,
+    // The end of the log file, read when the log dialog opens
+    log_text: Result<String, String>,
+    // The error in the error log whose details are shown
+    expanded_error: Option<u64>,
+    // Changes made so far, oldest first, read when the history dialog opens
+    history: Vec<history::Record>,
+    history_notice: Option<String>,
+    settings_profile_name: String,
+    // The custom command last run: its name, then its output once it ends
+    command_output: Option<(String, Option<Result<commands::Output, String>>)>,
+    settings_error: Option<String>,
+    search_error: Option<String>,
+    // Why the current folder could not be listed
+    load_error: Option<loader::Failure>,
+    // The listing was stopped before it was complete
+    load_stopped: bool,
+    // Mount points of network drives that stopped answering; paths on them
+    // aren't looked at from the UI until a probe gets through again
+    offline: HashSet<PathBuf>,
+    // Of the filesystem holding the current folder, for the status bar
+    disk_space: Option<diskspace::DiskSpace>,
+    // The file or folder being served to the local network, with the code
+    // for phones to scan
+    lan_share: Option<(lan::Server, Option<qr::QrCode>)>,
+    lan_password: String,
+    // Placeholders being downloaded so they can be opened
+    downloading: HashSet<PathBuf>,
+    // A lasting notice about how the app started, until dismissed
+    banner: Option<String>,
+    // Failed operations, shown as toasts and kept in the error log
+    notices: notices::Notices,
+    // Copies, moves, deletes and archives, run one after another
+    jobs: jobs::Jobs,
+    // The delete or move shown for confirmation, and what it would touch
+    // once that is known
+    pending: Option<jobs::Operation>,
+    preview: Option<jobs::Preview>,
+    operations_collapsed: bool,
+    transfer: Transfer,
+    transfer_target: String,
+    transfer_error: Option<String>,
+    // What the encrypt or decrypt dialog does, and the keys last given to it
+    crypt: Crypt,
+    recipients: String,
+    identity: String,
+    // Passphrases are dropped as soon as the dialog is done with them
+    use_passphrase: bool,
+    passphrase: String,
+    passphrase_again: String,
+    crypt_error: Option<String>,
+    // The images the conversion dialog works on and what it was last given,
+    // as typed
+    conversion_sources: Vec<PathBuf>,
+    conversion_format: imaging::Format,
+    conversion_max: String,
+    conversion_quality: String,
+    conversion_target: String,
+    conversion_error: Option<String>,
+    spinner_frame: usize,
     sort_mode: SortMode,
-}
-
-#[derive(Debug, Clone)]
-struct FileEntry {
-    path: PathBuf,
-    size: u64,
-    modified: SystemTime,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-enum SortMode {
-    NameAsc,
-    NameDesc,
-    SizeAsc,
-    SizeDesc,
-    DateAsc,
-    DateDesc,
+    editor: Option<EditorState>,
+    preferences: Preferences,
+    // View settings of the folder being shown
+    density: Density,
+    show_hidden: bool,
+    folder_views: views::FolderViews,
+    // Whether the desktop was dark when last asked, for ThemeChoice::System
+    system_dark: bool,
+    load_generation: u64,
+    loading: bool,
+    visible_limit: usize,
+    folder_sizes: HashMap<PathBuf, u64>,
+    folder_counts: HashMap<PathBuf, u64>,
+    usage: Option<UsageState>,
+    cleanup: Option<CleanupState>,
+    largest: Option<LargestState>,
+    trash: Option<TrashState>,
+    image: Option<ImageState>,
+    git_status: Option<git::RepoStatus>,
+    // The file open in the git dialog
+    git_file: Option<GitFile>,
+    signature_check: Option<SignatureCheck>,
+    checksum_check: Option<ChecksumCheck>,
+    tags: tags::Tags,
+    notes: notes::Notes,
+    // Only entries with this tag are listed
+    tag_filter: TagFilter,
+    // The tag being made in the tags dialog
+    new_tag: String,
+    new_tag_color: tags::TagColor,
+    plugins: Arc<Vec<plugins::Plugin>>,
+    // Plugin previews already made, so showing them again is instant
+    preview_cache: previews::PreviewCache,
+    // Plugin column values for the current folder, one map per column
+    plugin_values: Vec<HashMap<PathBuf, String>>,
+    // What the last plugin action reported
+    plugin_message: Option<String>,
+    // What the spooler said about the last print
+    print_message: Option<String>,
+    index: Option<Arc<index::Index>>,
+    indexing: bool,
+    excludes: Excludes,
 }
 
 #[derive(Debug, Clone)]
 enum DialogState {
     None,
     Create,
-    Delete,
+    // A delete or move waiting for the paths it touches to be confirmed
+    Preview,
     Properties,
+    Editor,
+    SaveSearch,
+    DeleteEmpty,
+    Settings,
+    // Creating a file, or a copy or move, would replace what already exists
+    Overwrite,
+    // Moving the selection to the trash, when that is asked about
+    Trash,
+    Lan,
+    OpenWith,
+    CommandOutput,
+    ErrorLog,
+    Log,
+    History,
+    Git,
+    Transfer,
+    Crypt,
+    ConvertImages,
+    Signature,
+    Checksums,
+    Tags,
+    // Runs any toolbar command by typing part of its name
+    Palette,
+}
+
+// What the tag filter in the status bar lets through
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TagFilter {
+    All,
+    Tagged(String),
+}
+
+impl std::fmt::Display for TagFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagFilter::All => write!(f, "{}", tr("🏷 All tags")),
+            TagFilter::Tagged(name) => write!(f, "🏷 {}", name),
+        }
+    }
+}
+
+// Whether the age dialog encrypts or decrypts the selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Crypt {
+    Encrypt,
+    Decrypt,
+}
+
+// What of the selection goes to the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CopyAs {
+    Path,
+    Name,
+    Uri,
+}
+
+// Whether the transfer dialog copies or moves the selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transfer {
+    Copy,
+    Move,
 }
 
 #[derive(Debug, Clone)]
@@ -87,6 +585,140 @@ struct FileProperties {
     modified: SystemTime,
     created: Option<SystemTime>,
     permissions: String,
+    exif: Option<photo::ExifSummary>,
+    media: Option<media::MediaProbe>,
+    // From the first plugin that previews this kind of file
+    preview: Option<Result<String, String>>,
+    // The note as being edited; saved with the Save note button
+    note: String,
+    // Extended attributes by name, where the system has them
+    attributes: Option<Result<Vec<(OsString, String)>, String>>,
+    // The attribute being added
+    new_attribute: String,
+    new_attribute_value: String,
+    tab: PropertiesTab,
+    // The access list, read when its tab is first opened
+    acl: Option<Result<Vec<acl::Entry>, String>>,
+    // The entry being granted
+    acl_who: String,
+    acl_rights: String,
+}
+
+// Properties shows general details, or the access list where there is one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PropertiesTab {
+    General,
+    Permissions,
+}
+
+#[derive(Debug, Clone)]
+struct LargestState {
+    root: PathBuf,
+    generation: u64,
+    files: Vec<FileEntry>,
+    scanning: bool,
+}
+
+// A disk image being looked through, read-only
+#[derive(Debug, Clone)]
+struct ImageState {
+    path: PathBuf,
+    // None while it is being opened
+    image: Option<Result<iso::Image, String>>,
+    // The folders opened below its root
+    dirs: Vec<iso::Entry>,
+    // None while the folder is being read
+    entries: Option<Result<Vec<iso::Entry>, String>>,
+}
+
+impl ImageState {
+    fn dir(&self) -> Option<&iso::Entry> {
+        self.dirs.last().or_else(|| self.image.as_ref()?.as_ref().ok().map(|image| &image.root))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TrashState {
+    // None while the trash is being read
+    items: Option<Result<Vec<trash::Item>, String>>,
+    // Empty Trash was pressed once and waits for the second press
+    confirm_empty: bool,
+}
+
+#[derive(Debug, Clone)]
+struct CleanupState {
+    root: PathBuf,
+    // None while the scan is still running
+    found: Option<Vec<cleanup::EmptyEntry>>,
+    selected: HashSet<PathBuf>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct UsageState {
+    root_path: PathBuf,
+    // None while the scan is still running
+    root: Option<usage::Node>,
+    focus: PathBuf,
+}
+
+// A file shown in the git dialog and the changes it has
+#[derive(Debug, Clone)]
+struct GitFile {
+    path: PathBuf,
+    diff: Option<Result<String, String>>,
+    // Discard was pressed once and waits to be pressed again
+    confirm_discard: bool,
+}
+
+// A file and its detached signature shown in the signature dialog, with
+// what gpg made of them once it has answered
+#[derive(Debug, Clone)]
+struct SignatureCheck {
+    file: PathBuf,
+    signature: PathBuf,
+    result: Option<Result<signature::Verification, String>>,
+}
+
+// A checksum manifest being verified, line by line
+#[derive(Debug, Clone)]
+struct ChecksumCheck {
+    manifest: PathBuf,
+    generation: u64,
+    lines: Vec<manifest::Line>,
+    // Numbers of the lines that named no file or digest
+    unreadable: Vec<usize>,
+    outcomes: Vec<Option<manifest::Outcome>>,
+    // The line being hashed
+    checking: Option<usize>,
+}
+
+impl ChecksumCheck {
+    fn count(&self, outcome: manifest::Outcome) -> usize {
+        self.outcomes.iter().filter(|checked| checked.as_ref() == Some(&outcome)).count()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.outcomes.iter().all(Option::is_some)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EditorState {
+    path: PathBuf,
+    lines: Vec<String>,
+    // Written back between lines as the file had it, "\r\n" or "\n"
+    line_ending: &'static str,
+    trailing_newline: bool,
+    modified: bool,
+    // The file could not be opened, so there is nothing to edit
+    error: Option<String>,
+    // Why the last save didn't go through, shown above the lines
+    save_error: Option<String>,
+    // When the file was last written, as of opening it
+    opened_at: Option<SystemTime>,
+    // The user was told the file changed meanwhile and may save over it
+    replace_changes: bool,
 }
 
 impl Application for FileManager {
@@ -96,22 +728,144 @@ impl Application for FileManager {
     type Flags = ();
 
     fn new(_flags: ()) -> (Self, Command<Message>) {
-        let home_dir = dirs::home_dir().expect("Could not find home directory");
-        
-        let manager = FileManager {
-            current_dir: home_dir.clone(),
+        let (home_dir, banner) = match dirs::home_dir().filter(|dir| dir.is_dir()) {
+            Some(home_dir) => (home_dir, None),
+            None => {
+                let fallback = fallback_home();
+                tracing::warn!(fallback = %fallback.display(), "no home folder");
+                let banner = fill(tr("No home folder was found, so {} is used instead."), &[&fallback.display()]);
+                (fallback, Some(banner))
+            }
+        };
+
+        let preferences = settings::load();
+        let preview_cache = previews::PreviewCache::new(preferences.preview_memory_mb);
+        i18n::set_language(preferences.language);
+        let excludes = Excludes::new(preferences.skip_ignored, &preferences.exclude_patterns, &preferences.hide_patterns);
+        let start_dir = preferences.start_dir.clone().filter(|dir| dir.is_dir()).unwrap_or_else(|| home_dir.clone());
+
+        let mut manager = FileManager {
+            current_dir: start_dir,
             home_dir: home_dir.clone(),
             entries: Vec::new(),
             selected_entry: None,
+            reveal: None,
             new_name: String::new(),
             is_directory: false,
+            dont_ask_again: false,
+            changed_on_disk: Vec::new(),
+            replaced: Vec::new(),
+            guarded: Vec::new(),
+            guard_input: String::new(),
             dialog: DialogState::None,
             properties: None,
             search_query: String::new(),
-            sort_mode: SortMode::NameAsc,
+            search_edit: 0,
+            refine_query: String::new(),
+            refinement: search::Refinement::default(),
+            date_preset: search::DatePreset::AnyTime,
+            date_from: String::new(),
+            date_until: String::new(),
+            active_search: None,
+            recent: None,
+            starred: starred::Starred::load(),
+            starred_view: false,
+            use_regex: false,
+            use_fuzzy: false,
+            search_scope: search::Scope::Names,
+            saved_search_name: String::new(),
+            open_with_command: String::new(),
+            open_with_extension: None,
+            open_with_always: false,
+            open_with_error: None,
+            palette_query: String::new(),
+            settings_start_dir: String::new(),
+            settings_exclude: String::new(),
+            settings_hide: String::new(),
+            settings_command_name: String::new(),
+            settings_command_template: String::new(),
+            cache_notice: None,
+            log_text: Ok(String::new()),
+            expanded_error: None,
+            history: Vec::new(),
+            history_notice: None,
+            settings_profile_name: String::new(),
+            command_output: None,
+            settings_error: None,
+            search_error: None,
+            load_error: None,
+            offline: HashSet::new(),
+            disk_space: None,
+            downloading: HashSet::new(),
+            lan_share: None,
+            lan_password: String::new(),
+            load_stopped: false,
+            banner,
+            notices: notices::Notices::default(),
+            jobs: jobs::Jobs::default(),
+            pending: None,
+            preview: None,
+            operations_collapsed: false,
+            transfer: Transfer::Copy,
+            transfer_target: String::new(),
+            transfer_error: None,
+            crypt: Crypt::Encrypt,
+            recipients: String::new(),
+            identity: String::new(),
+            use_passphrase: false,
+            passphrase: String::new(),
+            passphrase_again: String::new(),
+            crypt_error: None,
+            conversion_sources: Vec::new(),
+            conversion_format: imaging::Format::default(),
+            conversion_max: String::new(),
+            conversion_quality: imaging::DEFAULT_QUALITY.to_string(),
+            conversion_target: String::new(),
+            conversion_error: None,
+            spinner_frame: 0,
+            sort_mode: preferences.sort_mode.clone(),
+            density: preferences.density,
+            show_hidden: preferences.show_hidden,
+            folder_views: views::load(),
+            editor: None,
+            system_dark: settings::system_prefers_dark(),
+            preferences,
+            load_generation: 0,
+            loading: false,
+            visible_limit: PAGE_SIZE,
+            folder_sizes: HashMap::new(),
+            folder_counts: HashMap::new(),
+            usage: None,
+            cleanup: None,
+            largest: None,
+            trash: None,
+            image: None,
+            git_status: None,
+            git_file: None,
+            signature_check: None,
+            checksum_check: None,
+            tags: tags::Tags::load(),
+            notes: notes::Notes::load(),
+            tag_filter: TagFilter::All,
+            new_tag: String::new(),
+            new_tag_color: tags::TagColor::Blue,
+            plugins: Arc::new(plugins::load()),
+            preview_cache,
+            plugin_values: Vec::new(),
+            plugin_message: None,
+            print_message: None,
+            index: None,
+            indexing: false,
+            excludes,
         };
-        
-        (manager, Command::perform(load_directory(home_dir), |_| Message::Refresh))
+
+        manager.apply_folder_view();
+        let mut commands = vec![manager.reload()];
+        if manager.preferences.index_enabled {
+            commands.push(load_index());
+        }
+
+        (manager, Command::batch(commands))
     }
 
     fn title(&self) -> String {
@@ -123,35 +877,65 @@ impl Application for FileManager {
             Message::FileSelected(path) => {
                 self.selected_entry = Some(path.clone());
                 
-                if path.is_dir() {
-                    self.current_dir = path;
-                    self.selected_entry = None;
-                    return Command::perform(load_directory(self.current_dir.clone()), |_| Message::Refresh);
+                if self.is_dir(&path) {
+                    return self.navigate_to(path);
+                } else if self.entries.iter().any(|entry| entry.path == path && entry.cloud) {
+                    // Opened once the sync client has fetched it, so the
+                    // opening app doesn't stall on the download
+                    if self.downloading.insert(path.clone()) {
+                        tracing::info!(path = %path.display(), "download");
+                        let target = path.clone();
+                        return Command::perform(
+                            async move {
+                                run_blocking(move || cloud::download(&target).map_err(|error| error.to_string()))
+                                    .await
+                                    .unwrap_or_else(|| Err(tr("The command was interrupted").to_string()))
+                            },
+                            move |downloaded| Message::CloudDownloaded(path.clone(), downloaded),
+                        );
+                    }
+                } else if iso::is_image(&path) {
+                    return self.open_image(path);
                 } else {
-                    let _ = open::that(&path);
+                    tracing::info!(path = %path.display(), "open");
+                    let opened = associations::open(&path, &self.preferences.associations);
+                    self.report_at(opened, fill(tr("Could not open {}"), &[&path.display()]), Some(&path));
                 }
                 
                 Command::none()
             }
-            Message::NavigateUp => {
-                if let Some(parent) = self.current_dir.parent() {
-                    if parent.starts_with(&self.home_dir) || parent == self.home_dir.as_path() {
-                        self.current_dir = parent.to_path_buf();
-                        self.selected_entry = None;
-                        return Command::perform(load_directory(self.current_dir.clone()), |_| Message::Refresh);
-                    }
+            Message::CloudDownloaded(path, downloaded) => {
+                self.downloading.remove(&path);
+                if self.report_at(downloaded, fill(tr("Could not download {}"), &[&path.display()]), Some(&path)).is_some() {
+                    tracing::info!(path = %path.display(), "open");
+                    let opened = associations::open(&path, &self.preferences.associations);
+                    self.report_at(opened, fill(tr("Could not open {}"), &[&path.display()]), Some(&path));
+                    self.invalidate(&path);
                 }
                 Command::none()
             }
-            Message::NavigateHome => {
-                self.current_dir = self.home_dir.clone();
-                self.selected_entry = None;
-                Command::perform(load_directory(self.current_dir.clone()), |_| Message::Refresh)
+            Message::RevealInFolder(path) => {
+                let Some(parent) = path.parent() else {
+                    return Command::none();
+                };
+                let command = self.navigate_to(parent.to_path_buf());
+                // Trashed entries are no longer there to select
+                self.selected_entry = path.symlink_metadata().is_ok().then(|| path.clone());
+                self.reveal = Some(path);
+                self.largest = None;
+                self.trash = None;
+                command
             }
-            Message::Refresh => {
-                self.load_entries();
+            Message::NavigateUp => {
+                if let Some(parent) = self.current_dir.parent()
+                    && (parent.starts_with(&self.home_dir) || parent == self.home_dir.as_path())
+                {
+                    return self.navigate_to(parent.to_path_buf());
+                }
                 Command::none()
             }
+            Message::NavigateHome => self.navigate_to(self.home_dir.clone()),
+            Message::Refresh => self.refresh(),
             Message::CreateNew => {
                 self.dialog = DialogState::Create;
                 self.new_name = String::new();
@@ -159,11 +943,40 @@ impl Application for FileManager {
                 Command::none()
             }
             Message::Delete => {
-                if self.selected_entry.is_some() {
-                    self.dialog = DialogState::Delete;
+                let Some(path) = self.selected_entry.clone() else {
+                    return Command::none();
+                };
+                // Without a trash every delete is permanent, and asked about
+                if !trash::is_supported() {
+                    return self.update(Message::DeletePermanently);
                 }
-                Command::none()
+                self.guarded = if guard::is_protected(&path, &self.home_dir, self.preferences.protected_depth) {
+                    vec![path]
+                } else {
+                    Vec::new()
+                };
+                self.guard_input.clear();
+                if self.preferences.confirm_trash || !self.guarded.is_empty() {
+                    self.dont_ask_again = false;
+                    self.dialog = DialogState::Trash;
+                    return Command::none();
+                }
+                self.trash_selected()
+            }
+            Message::ConfirmTrash => {
+                if !self.guard_satisfied() {
+                    return Command::none();
+                }
+                if self.dont_ask_again {
+                    self.preferences.confirm_trash = false;
+                    self.save_preferences();
+                }
+                self.trash_selected()
             }
+            Message::DeletePermanently => match self.selected_entry.clone() {
+                Some(path) => self.confirm(jobs::Operation::Delete { paths: vec![path], excluded: Vec::new() }),
+                None => Command::none(),
+            },
             Message::NameInputChanged(name) => {
                 self.new_name = name;
                 Command::none()
@@ -173,568 +986,5782 @@ impl Application for FileManager {
                 Command::none()
             }
             Message::ConfirmCreate => {
-                if !self.new_name.is_empty() {
-                    let path = self.current_dir.join(&self.new_name);
-                    
-                    if self.is_directory {
-                        let _ = create_dir_all(&path);
-                    } else {
-                        let _ = File::create(&path);
+                if self.new_name.is_empty() {
+                    self.dialog = DialogState::None;
+                    return Command::none();
+                }
+                if let Some(problem) = entry::name_problem(&self.new_name) {
+                    self.report(Err::<(), _>(problem), fill(tr("Could not create {}"), &[&self.new_name]));
+                    self.dialog = DialogState::None;
+                    return Command::none();
+                }
+
+                let path = self.current_dir.join(&self.new_name);
+                // A file that changed since it was listed is always asked about
+                self.changed_on_disk = self.changed_on_disk(&path).into_iter().collect();
+                if !self.is_directory && path.is_file() && (self.preferences.confirm_overwrite || !self.changed_on_disk.is_empty()) {
+                    self.replaced.clear();
+                    self.dont_ask_again = false;
+                    self.dialog = DialogState::Overwrite;
+                    return Command::none();
+                }
+                self.create_entry()
+            }
+            Message::ConfirmOverwrite => {
+                if self.dont_ask_again {
+                    self.preferences.confirm_overwrite = false;
+                    self.save_preferences();
+                }
+                self.replaced.clear();
+                match self.pending.take() {
+                    Some(operation) => self.queue_transfer(operation),
+                    None => self.create_entry(),
+                }
+            }
+            Message::DontAskAgainToggled(checked) => {
+                self.dont_ask_again = checked;
+                Command::none()
+            }
+            Message::KeyPressed(key, modifiers, captured) => {
+                let Some(action) = self.preferences.keymap.action_for(key, modifiers, captured) else {
+                    return Command::none();
+                };
+                // While a dialog is open only Esc and zoom reach the window
+                let global = matches!(action, keymap::Action::StopSearch | keymap::Action::ZoomIn | keymap::Action::ZoomOut | keymap::Action::ZoomReset);
+                if !global && !matches!(self.dialog, DialogState::None) {
+                    return Command::none();
+                }
+                self.update(shortcut_message(action))
+            }
+            Message::OpenWith => {
+                if let Some(path) = &self.selected_entry
+                    && !self.is_dir(path)
+                {
+                    self.open_with_extension = associations::extension_of(path);
+                    let rule = self.open_with_extension.as_ref().and_then(|ext| self.preferences.associations.get(ext));
+                    self.open_with_command = rule.cloned().unwrap_or_default();
+                    self.open_with_always = rule.is_some();
+                    self.open_with_error = None;
+                    self.dialog = DialogState::OpenWith;
+                }
+                Command::none()
+            }
+            Message::OpenWithCommandChanged(command) => {
+                self.open_with_command = command;
+                self.open_with_error = None;
+                Command::none()
+            }
+            Message::OpenWithAlwaysToggled(always) => {
+                self.open_with_always = always;
+                Command::none()
+            }
+            Message::ConfirmOpenWith => {
+                let Some(path) = self.selected_entry.clone() else {
+                    return Command::none();
+                };
+                let command = self.open_with_command.trim().to_string();
+                if let Err(error) = associations::launch(&command, &path) {
+                    self.open_with_error = Some(fill(tr("Could not start '{}': {}"), &[&command, &error]));
+                    return Command::none();
+                }
+
+                if let Some(ext) = self.open_with_extension.clone() {
+                    if self.open_with_always {
+                        self.preferences.associations.insert(ext, command);
+                    } else if self.preferences.associations.get(&ext) == Some(&command) {
+                        // Unticking the box for the current rule drops it
+                        self.preferences.associations.remove(&ext);
                     }
+                    self.save_preferences();
                 }
-                
                 self.dialog = DialogState::None;
-                Command::perform(load_directory(self.current_dir.clone()), |_| Message::Refresh)
+                Command::none()
             }
-            Message::ConfirmDelete => {
-                if let Some(path) = &self.selected_entry {
-                    if path.is_dir() {
-                        let _ = remove_dir_all(path);
-                    } else {
-                        let _ = remove_file(path);
-                    }
-                    
-                    self.selected_entry = None;
-                    self.dialog = DialogState::None;
-                    return Command::perform(load_directory(self.current_dir.clone()), |_| Message::Refresh);
+            Message::RemoveAssociation(ext) => {
+                self.preferences.associations.remove(&ext);
+                self.save_preferences();
+                Command::none()
+            }
+            Message::Share => {
+                let Some(path) = self.selected_entry.clone() else {
+                    return Command::none();
+                };
+                tracing::info!(path = %path.display(), "share");
+                let shared = share::share(std::slice::from_ref(&path), &self.preferences.share_command);
+                self.report_at(shared, fill(tr("Could not share {}"), &[&path.display()]), Some(&path));
+                Command::none()
+            }
+            Message::Print => {
+                let Some(path) = self.selected_entry.clone().filter(|path| !self.is_dir(path)) else {
+                    return Command::none();
+                };
+                if !print::can_print(&path) {
+                    self.notices.push(fill(tr("{} can't be printed from here; open it and print from its app"), &[&path.display()]));
+                    return Command::none();
+                }
+                tracing::info!(path = %path.display(), "print");
+                self.print_message = Some(fill(tr("🖨 Sending {} to the printer…"), &[&path.display()]));
+                let target = path.clone();
+                Command::perform(
+                    async move {
+                        run_blocking(move || print::print(&target))
+                            .await
+                            .unwrap_or_else(|| Err(tr("The command was interrupted").to_string()))
+                    },
+                    move |printed| Message::Printed(path.clone(), printed),
+                )
+            }
+            Message::Printed(path, printed) => {
+                self.print_message = None;
+                if let Some(reply) = self.report_at(printed, fill(tr("Could not print {}"), &[&path.display()]), Some(&path)) {
+                    let sent = fill(tr("🖨 Sent {} to the printer"), &[&path.display()]);
+                    self.print_message = Some(if reply.is_empty() { sent } else { format!("{} ({})", sent, reply) });
                 }
                 Command::none()
             }
-            Message::ShowProperties => {
-                if let Some(path) = &self.selected_entry {
-                    if let Ok(meta) = metadata(path) {
-                        let permissions = if cfg!(unix) {
-                            use std::os::unix::fs::PermissionsExt;
-                            format!("{:o}", meta.permissions().mode() & 0o777)
-                        } else {
-                            if meta.permissions().readonly() {
-                                "Read-only".to_string()
-                            } else {
-                                "Read-write".to_string()
-                            }
-                        };
-                        
-                        self.properties = Some(FileProperties {
-                            path: path.clone(),
-                            file_type: if path.is_dir() { "Directory".to_string() } else { "File".to_string() },
-                            size: meta.len(),
-                            modified: meta.modified().unwrap_or(SystemTime::now()),
-                            created: meta.created().ok(),
-                            permissions,
-                        });
-                        
-                        self.dialog = DialogState::Properties;
+            Message::ShareOverLan => {
+                self.dialog = DialogState::Lan;
+                Command::none()
+            }
+            Message::LanPasswordChanged(password) => {
+                self.lan_password = password;
+                Command::none()
+            }
+            Message::StartLanShare => {
+                let root = self.selected_entry.clone().unwrap_or_else(|| self.current_dir.clone());
+                let password = Some(self.lan_password.clone()).filter(|password| !password.is_empty());
+                tracing::info!(path = %root.display(), password = password.is_some(), "share over lan");
+                let started = lan::Server::start(root.clone(), password);
+                if let Some(server) = self.report_at(started, fill(tr("Could not share {} over the network"), &[&root.display()]), Some(&root)) {
+                    let code = qr::QrCode::encode(&server.url);
+                    self.lan_share = Some((server, code));
+                }
+                Command::none()
+            }
+            Message::StopLanShare => {
+                // Dropping the server stops it
+                self.lan_share = None;
+                Command::none()
+            }
+            Message::ShareCommandChanged(command) => {
+                self.preferences.share_command = command;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::KeymapChanged(action, chords) => {
+                self.preferences.keymap.set(action, chords);
+                self.save_preferences();
+                Command::none()
+            }
+            Message::JobEvent(id, event) => {
+                let Some(job) = self.jobs.apply(id, event) else {
+                    return Command::none();
+                };
+                let operation = job.operation.clone();
+                let (state, skipped) = (job.state.clone(), job.skipped.clone());
+                // Tags kept in the database and notes follow what was moved
+                for (from, to) in job.moved.clone() {
+                    self.tags.moved(&from, &to);
+                    self.notes.moved(&from, &to);
+                    self.starred.moved(&from, &to);
+                }
+                let size = if operation.is_delete() { job.freed } else { Some(job.done) };
+                let outcome = match &state {
+                    jobs::JobState::Failed(error) => history::Outcome::Failed(error.to_string()),
+                    jobs::JobState::Cancelled => history::Outcome::Cancelled,
+                    _ if !skipped.is_empty() => history::Outcome::Partial(skipped.len()),
+                    _ => history::Outcome::Done,
+                };
+                self.remember_job(&operation, size, outcome);
+                if let jobs::JobState::Failed(error) = state {
+                    self.notices.push(error);
+                }
+                // Each skipped item goes to the log, with one toast for them all
+                if !skipped.is_empty() {
+                    let summary = fill(tr("{}: {} item(s) skipped"), &[&operation, &skipped.len()]);
+                    for error in skipped {
+                        self.notices.record(error);
+                    }
+                    self.notices.push(summary);
+                }
+
+                if let jobs::Operation::Delete { paths, .. } = &operation {
+                    for path in paths.iter().filter(|path| path.symlink_metadata().is_err()) {
+                        self.forget_removed(path);
                     }
                 }
+                self.refresh()
+            }
+            Message::CancelJob(id) => {
+                tracing::info!(id, "cancel");
+                self.jobs.cancel(id);
                 Command::none()
             }
-            Message::CloseDialog => {
-                self.dialog = DialogState::None;
+            Message::JobDecision(id, decision) => {
+                tracing::info!(id, "decision: {:?}", decision);
+                self.jobs.decide(id, decision);
                 Command::none()
             }
-            Message::SearchInputChanged(query) => {
-                self.search_query = query;
+            Message::PauseJob(id, paused) => {
+                self.jobs.set_paused(id, paused);
                 Command::none()
             }
-            Message::PerformSearch => {
-                self.load_entries();
+            Message::RollBackJob(id) => {
+                self.jobs.roll_back(id);
                 Command::none()
             }
-            Message::SortByName => {
-                self.sort_mode = if self.sort_mode == SortMode::NameAsc {
-                    SortMode::NameDesc
-                } else {
-                    SortMode::NameAsc
+            Message::ClearFinishedJobs => {
+                self.jobs.clear_finished();
+                Command::none()
+            }
+            Message::OperationsToggled => {
+                self.operations_collapsed = !self.operations_collapsed;
+                Command::none()
+            }
+            Message::OpenTransfer(transfer) => {
+                if self.selected_entry.is_none() {
+                    return Command::none();
+                }
+                self.transfer = transfer;
+                self.transfer_target = self.current_dir.display().to_string();
+                self.transfer_error = None;
+                self.dialog = DialogState::Transfer;
+                Command::none()
+            }
+            Message::TransferTargetChanged(target) => {
+                self.transfer_target = target;
+                self.transfer_error = None;
+                Command::none()
+            }
+            Message::ConfirmTransfer => {
+                let Some(source) = self.selected_entry.clone() else {
+                    return Command::none();
                 };
-                self.sort_entries();
+                let target = PathBuf::from(self.transfer_target.trim());
+                if !target.is_dir() {
+                    self.transfer_error = Some(fill(tr("{} is not a folder"), &[&target.display()]));
+                    return Command::none();
+                }
+
+                let sources = vec![source];
+                let replaced = jobs::replaced(&sources, &target);
+                let replace = !replaced.is_empty();
+                let operation = match self.transfer {
+                    Transfer::Copy => jobs::Operation::Copy { sources, target, replace },
+                    Transfer::Move => jobs::Operation::Move { sources, target, excluded: Vec::new(), replace },
+                };
+                // Names already taken in the target are asked about like a
+                // new file's, and always when they changed since listed
+                self.changed_on_disk = replaced.iter().filter_map(|path| self.changed_on_disk(path)).collect();
+                if replace && (self.preferences.confirm_overwrite || !self.changed_on_disk.is_empty()) {
+                    self.replaced = replaced;
+                    self.pending = Some(operation);
+                    self.dont_ask_again = false;
+                    self.dialog = DialogState::Overwrite;
+                    return Command::none();
+                }
+                self.queue_transfer(operation)
+            }
+            Message::Compress => {
+                if let Some(source) = self.selected_entry.clone() {
+                    let archive = jobs::archive_path(&source);
+                    self.jobs.push(jobs::Operation::Archive { sources: vec![source], archive });
+                }
                 Command::none()
             }
-            Message::SortBySize => {
-                self.sort_mode = if self.sort_mode == SortMode::SizeAsc {
-                    SortMode::SizeDesc
-                } else {
-                    SortMode::SizeAsc
+            Message::OpenCrypt(crypt) => {
+                let Some(path) = self.selected_entry.clone() else {
+                    return Command::none();
                 };
-                self.sort_entries();
+                if self.is_dir(&path) {
+                    self.notices.push(fill(tr("{} is a folder; compress it first to encrypt it"), &[&path.display()]));
+                    return Command::none();
+                }
+                // A file tells whether it was locked with a passphrase
+                if crypt == Crypt::Decrypt {
+                    self.use_passphrase = crypt::needs_passphrase(&path);
+                }
+                self.crypt = crypt;
+                self.passphrase.clear();
+                self.passphrase_again.clear();
+                self.crypt_error = None;
+                self.dialog = DialogState::Crypt;
                 Command::none()
             }
-            Message::SortByDate => {
-                self.sort_mode = if self.sort_mode == SortMode::DateAsc {
-                    SortMode::DateDesc
+            Message::CryptKeyChanged(key) => {
+                match self.crypt {
+                    Crypt::Encrypt => self.recipients = key,
+                    Crypt::Decrypt => self.identity = key,
+                }
+                self.crypt_error = None;
+                Command::none()
+            }
+            Message::CryptPassphraseToggled(enabled) => {
+                self.use_passphrase = enabled;
+                self.crypt_error = None;
+                Command::none()
+            }
+            Message::CryptPassphraseChanged(passphrase) => {
+                self.passphrase = passphrase;
+                self.crypt_error = None;
+                Command::none()
+            }
+            Message::CryptPassphraseAgainChanged(passphrase) => {
+                self.passphrase_again = passphrase;
+                self.crypt_error = None;
+                Command::none()
+            }
+            Message::ConfirmCrypt => {
+                let Some(source) = self.selected_entry.clone() else {
+                    return Command::none();
+                };
+                if self.use_passphrase && self.passphrase.is_empty() {
+                    self.crypt_error = Some(tr("Enter a passphrase").to_string());
+                    return Command::none();
+                }
+                let operation = match self.crypt {
+                    Crypt::Encrypt if self.use_passphrase => {
+                        if self.passphrase != self.passphrase_again {
+                            self.crypt_error = Some(tr("The passphrases don't match").to_string());
+                            return Command::none();
+                        }
+                        let lock = crypt::Lock::Passphrase(crypt::Passphrase::new(self.passphrase.clone()));
+                        jobs::Operation::Encrypt { output: crypt::encrypted_path(&source), source, lock }
+                    }
+                    Crypt::Encrypt => {
+                        let recipients = crypt::parse_recipients(&self.recipients);
+                        if recipients.is_empty() {
+                            self.crypt_error = Some(tr("Give at least one recipient").to_string());
+                            return Command::none();
+                        }
+                        let lock = crypt::Lock::Recipients(recipients);
+                        jobs::Operation::Encrypt { output: crypt::encrypted_path(&source), source, lock }
+                    }
+                    Crypt::Decrypt if self.use_passphrase => {
+                        let unlock = crypt::Unlock::Passphrase(crypt::Passphrase::new(self.passphrase.clone()));
+                        jobs::Operation::Decrypt { output: crypt::decrypted_path(&source), source, unlock }
+                    }
+                    Crypt::Decrypt => {
+                        let identity = PathBuf::from(self.identity.trim());
+                        if !identity.is_file() {
+                            self.crypt_error = Some(fill(tr("{} is not a key file"), &[&identity.display()]));
+                            return Command::none();
+                        }
+                        let unlock = crypt::Unlock::Identity(identity);
+                        jobs::Operation::Decrypt { output: crypt::decrypted_path(&source), source, unlock }
+                    }
+                };
+                self.passphrase.clear();
+                self.passphrase_again.clear();
+                self.jobs.push(operation);
+                self.dialog = DialogState::None;
+                Command::none()
+            }
+            Message::CopySelection(copy_as) => {
+                let Some(path) = self.selected_entry.as_deref() else {
+                    return Command::none();
+                };
+                let copied = match copy_as {
+                    CopyAs::Path => path.display().to_string(),
+                    CopyAs::Name => path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned()),
+                    CopyAs::Uri => file_uri(path),
+                };
+                self.notices.push(fill(tr("Copied {}"), &[&copied]));
+                iced::clipboard::write(copied)
+            }
+            Message::OpenConvertImages => {
+                let Some(path) = self.selected_entry.clone() else {
+                    return Command::none();
+                };
+                // A folder stands for the images directly inside it
+                let sources = if self.is_dir(&path) {
+                    imaging::images_in(&path)
+                } else if imaging::can_convert(&path) {
+                    vec![path.clone()]
                 } else {
-                    SortMode::DateAsc
+                    Vec::new()
                 };
-                self.sort_entries();
+                if sources.is_empty() {
+                    self.notices.push(fill(tr("{} holds no images to convert"), &[&path.display()]));
+                    return Command::none();
+                }
+                self.conversion_sources = sources;
+                self.conversion_error = None;
+                self.dialog = DialogState::ConvertImages;
                 Command::none()
             }
-        }
-    }
-
-    fn view(&self) -> Element<Message> {
-        let title = text(format!("Current Directory: {}", self.current_dir.display()))
-            .size(20)
-            .width(Length::Fill);
-
-        // Navigation buttons with improved styling
-        let nav_button = button(
-            row![text("⬆️ Up").horizontal_alignment(Horizontal::Center)]
-                .width(Length::Fill)
-                .align_items(iced::Alignment::Center)
-        )
-        .on_press(Message::NavigateUp)
-        .padding(10)
-        .width(Length::Fill)
-        .style(theme::Button::Secondary);
-
-        let home_button = button(
-            row![text("🏠 Home").horizontal_alignment(Horizontal::Center)]
-                .width(Length::Fill)
-                .align_items(iced::Alignment::Center)
-        )
-        .on_press(Message::NavigateHome)
-        .padding(10)
-        .width(Length::Fill)
-        .style(theme::Button::Secondary);
-
-        let refresh_button = button(
-            row![text("🔄 Refresh").horizontal_alignment(Horizontal::Center)]
-                .width(Length::Fill)
+            Message::ConversionFormatSelected(format) => {
+                self.conversion_format = format;
+                Command::none()
+            }
+            Message::ConversionMaxChanged(max) => {
+                self.conversion_max = max;
+                self.conversion_error = None;
+                Command::none()
+            }
+            Message::ConversionQualityChanged(quality) => {
+                self.conversion_quality = quality;
+                self.conversion_error = None;
+                Command::none()
+            }
+            Message::ConversionTargetChanged(target) => {
+                self.conversion_target = target;
+                self.conversion_error = None;
+                Command::none()
+            }
+            Message::ConfirmConvertImages => {
+                let max = self.conversion_max.trim();
+                let max_dimension = match max.parse::<u32>() {
+                    _ if max.is_empty() => None,
+                    Ok(max) if max > 0 => Some(max),
+                    _ => {
+                        self.conversion_error = Some(tr("The largest size must be a number of pixels").to_string());
+                        return Command::none();
+                    }
+                };
+                let quality = match self.conversion_quality.trim().parse::<u8>() {
+                    Ok(quality) if (1..=100).contains(&quality) => quality,
+                    _ => {
+                        self.conversion_error = Some(tr("Quality must be between 1 and 100").to_string());
+                        return Command::none();
+                    }
+                };
+                let target = self.conversion_target.trim();
+                // Left empty, each image is written beside its original
+                let target = (!target.is_empty()).then(|| PathBuf::from(target));
+                if let Some(target) = &target
+                    && !target.is_dir()
+                {
+                    self.conversion_error = Some(fill(tr("{} is not a folder"), &[&target.display()]));
+                    return Command::none();
+                }
+                let conversion = imaging::Conversion { format: self.conversion_format, max_dimension, quality };
+                self.jobs.push(jobs::Operation::ConvertImages { sources: std::mem::take(&mut self.conversion_sources), target, conversion });
+                self.dialog = DialogState::None;
+                Command::none()
+            }
+            Message::DismissToast(id) => {
+                self.notices.dismiss(id);
+                Command::none()
+            }
+            Message::ExpireToasts => {
+                self.notices.expire();
+                Command::none()
+            }
+            Message::DismissBanner => {
+                self.banner = None;
+                Command::none()
+            }
+            Message::ShowErrorLog => {
+                self.expanded_error = None;
+                self.dialog = DialogState::ErrorLog;
+                Command::none()
+            }
+            Message::ShowErrorDetails(id) => {
+                self.expanded_error = Some(id);
+                self.dialog = DialogState::ErrorLog;
+                Command::none()
+            }
+            Message::ToggleErrorDetails(id) => {
+                self.expanded_error = if self.expanded_error == Some(id) { None } else { Some(id) };
+                Command::none()
+            }
+            Message::CopyErrorDetails(id) => match self.notices.find(id) {
+                Some(notice) => iced::clipboard::write(notice.diagnostic.report()),
+                None => Command::none(),
+            },
+            Message::ShowLog => {
+                self.log_text = logging::recent().map_err(|error| error.to_string());
+                self.dialog = DialogState::Log;
+                Command::none()
+            }
+            Message::ShowGit => {
+                let Some(path) = self.selected_entry.clone().filter(|path| !self.is_dir(path)) else {
+                    return Command::none();
+                };
+                self.git_file = Some(GitFile { path: path.clone(), diff: None, confirm_discard: false });
+                self.dialog = DialogState::Git;
+                Command::perform(git_diff(path), |(path, diff)| Message::GitDiffLoaded(path, diff))
+            }
+            Message::GitAction(action) => {
+                let Some(file) = &mut self.git_file else {
+                    return Command::none();
+                };
+                // Discarding can't be taken back, so it takes a second press
+                if action == git::FileAction::Discard && !file.confirm_discard {
+                    file.confirm_discard = true;
+                    return Command::none();
+                }
+                file.confirm_discard = false;
+                let path = file.path.clone();
+                tracing::info!(path = %path.display(), ?action, "git");
+                let applied = git::apply(&path, action);
+                self.report_at(applied, fill(tr("Git could not update {}"), &[&path.display()]), Some(&path));
+                if action == git::FileAction::Discard {
+                    self.invalidate(&path);
+                    self.sort_entries();
+                }
+                Command::batch([
+                    self.load_git_status(),
+                    Command::perform(git_diff(path), |(path, diff)| Message::GitDiffLoaded(path, diff)),
+                ])
+            }
+            Message::VerifySignature => {
+                let Some((file, signature)) = self.selected_entry.as_deref().and_then(signature::pair) else {
+                    return Command::none();
+                };
+                tracing::info!(path = %file.display(), signature = %signature.display(), "verify signature");
+                self.signature_check = Some(SignatureCheck { file: file.clone(), signature: signature.clone(), result: None });
+                self.dialog = DialogState::Signature;
+                let checked = file.clone();
+                Command::perform(
+                    async move {
+                        run_blocking(move || signature::verify(&file, &signature))
+                            .await
+                            .unwrap_or_else(|| Err(tr("The command was interrupted").to_string()))
+                    },
+                    move |result| Message::SignatureVerified(checked.clone(), result),
+                )
+            }
+            Message::VerifyChecksums => {
+                let Some(path) = self.selected_entry.clone().filter(|path| manifest::is_manifest(path)) else {
+                    return Command::none();
+                };
+                let Some((lines, unreadable)) = self.report_at(manifest::parse(&path), fill(tr("Could not read {}"), &[&path.display()]), Some(&path)) else {
+                    return Command::none();
+                };
+                tracing::info!(path = %path.display(), lines = lines.len(), "verify checksums");
+                let generation = self.checksum_check.as_ref().map_or(0, |check| check.generation + 1);
+                self.checksum_check = Some(ChecksumCheck {
+                    manifest: path,
+                    generation,
+                    outcomes: vec![None; lines.len()],
+                    lines,
+                    unreadable,
+                    checking: None,
+                });
+                self.dialog = DialogState::Checksums;
+                Command::none()
+            }
+            Message::ChecksumEvent(generation, event) => {
+                if let Some(check) = self.checksum_check.as_mut().filter(|check| check.generation == generation) {
+                    match event {
+                        manifest::Event::Checking(index) => check.checking = Some(index),
+                        manifest::Event::Checked(index, outcome) => {
+                            if let Some(slot) = check.outcomes.get_mut(index) {
+                                *slot = Some(outcome);
+                            }
+                            check.checking = None;
+                        }
+                    }
+                }
+                Command::none()
+            }
+            Message::SignatureVerified(path, result) => {
+                if let Some(check) = &mut self.signature_check
+                    && check.file == path
+                {
+                    check.result = Some(result);
+                }
+                Command::none()
+            }
+            Message::GitDiffLoaded(path, diff) => {
+                if let Some(file) = &mut self.git_file
+                    && file.path == path
+                {
+                    file.diff = Some(diff);
+                }
+                Command::none()
+            }
+            Message::ShowTags => {
+                if self.selected_entry.is_none() {
+                    return Command::none();
+                }
+                self.new_tag.clear();
+                self.dialog = DialogState::Tags;
+                Command::none()
+            }
+            Message::TagToggled(name, on) => {
+                let Some(path) = self.selected_entry.clone() else {
+                    return Command::none();
+                };
+                let mut names = self.entry_tags(&path);
+                names.retain(|tagged| *tagged != name);
+                if on {
+                    names.push(name);
+                }
+                tracing::info!(path = %path.display(), tags = ?names, "tag");
+                let set = self.tags.set(&path, names);
+                self.report_at(set, fill(tr("Could not save the tags of {}"), &[&path.display()]), Some(&path));
+                self.invalidate(&path);
+                Command::none()
+            }
+            Message::NewTagChanged(name) => {
+                self.new_tag = name;
+                Command::none()
+            }
+            Message::NewTagColorChanged(color) => {
+                self.new_tag_color = color;
+                Command::none()
+            }
+            Message::CreateTag => {
+                let name = self.new_tag.trim().to_string();
+                if !tags::is_valid_name(&name) {
+                    return Command::none();
+                }
+                let defined = self.tags.define(&name, self.new_tag_color);
+                self.report(defined, tr("Could not save the tags").to_string());
+                self.new_tag.clear();
+                // A new tag is meant for the file the dialog is open for
+                self.update(Message::TagToggled(name, true))
+            }
+            Message::RemoveTag(name) => {
+                let removed = self.tags.remove(&name);
+                self.report(removed, tr("Could not save the tags").to_string());
+                if self.tag_filter == TagFilter::Tagged(name) {
+                    self.tag_filter = TagFilter::All;
+                }
+                Command::none()
+            }
+            Message::TagFilterChanged(filter) => {
+                self.tag_filter = filter;
+                Command::none()
+            }
+            Message::ShowHistory => {
+                self.history = history::load();
+                self.history_notice = None;
+                self.dialog = DialogState::History;
+                Command::none()
+            }
+            Message::ExportHistory => {
+                let name = format!("file-history-{}.csv", chrono::Local::now().format("%Y-%m-%d-%H%M%S"));
+                let path = self.current_dir.join(name);
+                self.history_notice = Some(match history::export(&self.history, &path) {
+                    Ok(()) => fill(tr("Exported to {}"), &[&path.display()]),
+                    Err(error) => fill(tr("Could not export: {}"), &[&error]),
+                });
+                self.refresh()
+            }
+            Message::ClearErrorLog => {
+                self.notices.clear_log();
+                self.dialog = DialogState::None;
+                Command::none()
+            }
+            Message::MenuItemChosen(item) => match item {
+                MenuItem::Tool(tool) => self.update(tool_message(tool)),
+                MenuItem::Recent(window) => self.update(Message::ShowRecent(window)),
+            },
+            Message::ToolPlaced(tool, place) => {
+                self.preferences.toolbar.put(tool, place);
+                self.save_preferences();
+                Command::none()
+            }
+            Message::ToolShifted(tool, offset) => {
+                self.preferences.toolbar.shift(tool, offset);
+                self.save_preferences();
+                Command::none()
+            }
+            Message::ConfirmOverwriteToggled(enabled) => {
+                self.preferences.confirm_overwrite = enabled;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::ConfirmTrashToggled(enabled) => {
+                self.preferences.confirm_trash = enabled;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::PreviewLoaded(roots, preview) => {
+                let Some(pending) = &self.pending else {
+                    return Command::none();
+                };
+                if pending.sources() != roots.as_slice() {
+                    return Command::none();
+                }
+                match preview {
+                    Some(preview) => self.preview = Some(preview),
+                    None => {
+                        self.notices.push(tr("Could not list what the operation would change").to_string());
+                        self.pending = None;
+                        self.dialog = DialogState::None;
+                    }
+                }
+                Command::none()
+            }
+            Message::PreviewToggled(index, included) => {
+                if let Some(item) = self.preview.as_mut().and_then(|preview| preview.items.get_mut(index)) {
+                    item.included = included;
+                }
+                Command::none()
+            }
+            Message::ConfirmPreview => {
+                if !self.guard_satisfied() {
+                    return Command::none();
+                }
+                // Large folders take a while, so the operation runs as a job
+                if let (Some(operation), Some(preview)) = (self.pending.take(), self.preview.take()) {
+                    if operation.is_delete() {
+                        self.selected_entry = None;
+                    }
+                    if let Some(operation) = operation.without(preview.excluded()) {
+                        self.jobs.push(operation);
+                    }
+                }
+                self.dialog = DialogState::None;
+                Command::none()
+            }
+            Message::GuardInputChanged(input) => {
+                self.guard_input = input;
+                Command::none()
+            }
+            Message::ShowProperties => {
+                if let Some(path) = &self.selected_entry
+                    && let Ok(meta) = metadata(path)
+                {
+                    let permissions = if cfg!(unix) {
+                        use std::os::unix::fs::PermissionsExt;
+                        format!("{:o}", meta.permissions().mode() & 0o777)
+                    } else if meta.permissions().readonly() {
+                        tr("Read-only").to_string()
+                    } else {
+                        tr("Read-write").to_string()
+                    };
+                    
+                    // Nothing is read from a placeholder, which would download it
+                    let placeholder = cloud::is_placeholder(&meta);
+                    let is_media = !placeholder && matches!(IconKind::for_file(path, Some(&meta)), IconKind::Audio | IconKind::Video);
+                    let stamp = previews::Stamp::of(&meta);
+                    let preview_plugin = self.plugins.iter().position(|plugin| !placeholder && plugin.previews(path));
+                    let cached_preview = preview_plugin.and_then(|_| self.preview_cache.get(path, stamp));
+
+                    self.properties = Some(FileProperties {
+                        path: path.clone(),
+                        file_type: if meta.is_dir() {
+                            tr("Directory").to_string()
+                        } else if placeholder {
+                            filetype::describe_by_name(path)
+                        } else {
+                            filetype::describe(path)
+                        },
+                        size: meta.len(),
+                        modified: meta.modified().unwrap_or(SystemTime::now()),
+                        created: meta.created().ok(),
+                        permissions,
+                        exif: if placeholder { None } else { photo::read_exif(path) },
+                        media: is_media.then_some(media::MediaProbe::Pending),
+                        preview: cached_preview.clone().map(Ok),
+                        note: self.notes.get(path).unwrap_or_default().to_string(),
+                        attributes: xattr::SUPPORTED.then(|| read_attributes(path)),
+                        new_attribute: String::new(),
+                        new_attribute_value: String::new(),
+                        tab: PropertiesTab::General,
+                        acl: None,
+                        acl_who: String::new(),
+                        acl_rights: "r".to_string(),
+                    });
+                    
+                    self.dialog = DialogState::Properties;
+
+                    // Probing can take a while, so the dialog opens first
+                    let mut commands = Vec::new();
+                    if is_media {
+                        commands.push(Command::perform(probe_media(path.clone()), |(path, result)| {
+                            Message::MediaProbed(path, result)
+                        }));
+                    }
+                    if let Some(index) = preview_plugin
+                        && cached_preview.is_none()
+                    {
+                        let disk_mb = self.preferences.preview_disk_mb;
+                        commands.push(Command::perform(plugin_preview(self.plugins.clone(), index, path.clone(), stamp, disk_mb), move |(path, result)| {
+                            Message::PluginPreviewLoaded(path, stamp, result)
+                        }));
+                    }
+                    return Command::batch(commands);
+                }
+                Command::none()
+            }
+            Message::NoteChanged(note) => {
+                if let Some(props) = &mut self.properties {
+                    props.note = note;
+                }
+                Command::none()
+            }
+            Message::SaveNote => {
+                let Some(props) = &self.properties else {
+                    return Command::none();
+                };
+                let path = props.path.clone();
+                let saved = self.notes.set(&path, &props.note);
+                self.report_at(saved, fill(tr("Could not save the note on {}"), &[&path.display()]), Some(&path));
+                Command::none()
+            }
+            Message::AttributeNameChanged(name) => {
+                if let Some(props) = &mut self.properties {
+                    props.new_attribute = name;
+                }
+                Command::none()
+            }
+            Message::AttributeValueChanged(value) => {
+                if let Some(props) = &mut self.properties {
+                    props.new_attribute_value = value;
+                }
+                Command::none()
+            }
+            Message::AddAttribute => {
+                let Some(props) = &mut self.properties else {
+                    return Command::none();
+                };
+                if props.new_attribute.trim().is_empty() {
+                    return Command::none();
+                }
+                let (path, name) = (props.path.clone(), xattr::user_name(&props.new_attribute));
+                tracing::info!(path = %path.display(), name, "set attribute");
+                let set = xattr::set(&path, &name, props.new_attribute_value.as_bytes());
+                if set.is_ok() {
+                    props.new_attribute.clear();
+                    props.new_attribute_value.clear();
+                }
+                props.attributes = Some(read_attributes(&path));
+                self.report_at(set, fill(tr("Could not set {} on {}"), &[&name, &path.display()]), Some(&path));
+                Command::none()
+            }
+            Message::RemoveAttribute(name) => {
+                let Some(props) = &mut self.properties else {
+                    return Command::none();
+                };
+                let path = props.path.clone();
+                tracing::info!(path = %path.display(), name = %name.to_string_lossy(), "remove attribute");
+                let removed = xattr::remove(&path, &name);
+                props.attributes = Some(read_attributes(&path));
+                self.report_at(removed, fill(tr("Could not remove {} from {}"), &[&name.to_string_lossy(), &path.display()]), Some(&path));
+                Command::none()
+            }
+            Message::PropertiesTabChanged(tab) => {
+                if let Some(props) = &mut self.properties {
+                    props.tab = tab;
+                    if tab == PropertiesTab::Permissions && props.acl.is_none() {
+                        props.acl = Some(read_acl(&props.path));
+                    }
+                }
+                Command::none()
+            }
+            Message::AclWhoChanged(who) => {
+                if let Some(props) = &mut self.properties {
+                    props.acl_who = who;
+                }
+                Command::none()
+            }
+            Message::AclRightsChanged(rights) => {
+                if let Some(props) = &mut self.properties {
+                    props.acl_rights = rights;
+                }
+                Command::none()
+            }
+            Message::GrantAcl => {
+                let Some(props) = &mut self.properties else {
+                    return Command::none();
+                };
+                if props.acl_who.trim().is_empty() {
+                    return Command::none();
+                }
+                let (path, who) = (props.path.clone(), props.acl_who.trim().to_string());
+                tracing::info!(path = %path.display(), who, rights = props.acl_rights, "grant access");
+                let granted = acl::grant(&path, &who, &props.acl_rights);
+                if granted.is_ok() {
+                    props.acl_who.clear();
+                }
+                props.acl = Some(read_acl(&path));
+                self.report_at(granted, fill(tr("Could not give {} access to {}"), &[&who, &path.display()]), Some(&path));
+                Command::none()
+            }
+            Message::RevokeAcl(key) => {
+                let Some(props) = &mut self.properties else {
+                    return Command::none();
+                };
+                let path = props.path.clone();
+                tracing::info!(path = %path.display(), key, "revoke access");
+                let revoked = acl::revoke(&path, &key);
+                props.acl = Some(read_acl(&path));
+                self.report_at(revoked, fill(tr("Could not take {}'s access to {} away"), &[&key, &path.display()]), Some(&path));
+                Command::none()
+            }
+            Message::CloseDialog => {
+                self.dialog = DialogState::None;
+                // Stops hashing what is left
+                self.checksum_check = None;
+                self.pending = None;
+                self.preview = None;
+                self.replaced.clear();
+                Command::none()
+            }
+            Message::SearchInputChanged(query) => {
+                self.search_query = query;
+                self.search_error = None;
+                self.search_edit += 1;
+                // The plain listing filters as it is typed; search results
+                // are searched for again once typing pauses
+                if self.active_search.is_none() {
+                    return Command::none();
+                }
+                let edit = self.search_edit;
+                Command::perform(tokio::time::sleep(SEARCH_DEBOUNCE), move |_| Message::SearchSettled(edit))
+            }
+            Message::SearchSettled(edit) => {
+                if edit != self.search_edit || self.active_search.is_none() {
+                    return Command::none();
+                }
+                self.perform_search()
+            }
+            Message::RefineInputChanged(text) => {
+                // Filters the results already found rather than searching again
+                self.refinement = search::Refinement::parse(&text);
+                self.refine_query = text;
+                Command::none()
+            }
+            Message::DatePresetSelected(preset) => {
+                self.date_preset = preset;
+                self.search_error = None;
+                Command::none()
+            }
+            Message::DateFromChanged(text) => {
+                self.date_from = text;
+                self.search_error = None;
+                Command::none()
+            }
+            Message::DateUntilChanged(text) => {
+                self.date_until = text;
+                self.search_error = None;
+                Command::none()
+            }
+            Message::PerformSearch => self.perform_search(),
+            Message::RegexToggled(use_regex) => {
+                self.use_regex = use_regex;
+                self.use_fuzzy &= !use_regex;
+                self.search_error = None;
+                Command::none()
+            }
+            Message::FuzzyToggled(use_fuzzy) => {
+                self.use_fuzzy = use_fuzzy;
+                self.use_regex &= !use_fuzzy;
+                self.search_error = None;
+                Command::none()
+            }
+            Message::ScopeSelected(scope) => {
+                self.search_scope = scope;
+                self.search_error = None;
+                Command::none()
+            }
+            Message::SaveSearch => {
+                self.dialog = DialogState::SaveSearch;
+                self.saved_search_name = self.search_query.trim().to_string();
+                Command::none()
+            }
+            Message::SavedSearchNameChanged(name) => {
+                self.saved_search_name = name;
+                Command::none()
+            }
+            Message::ConfirmSaveSearch => {
+                let name = self.saved_search_name.trim().to_string();
+                if !name.is_empty() {
+                    let saved = SavedSearch {
+                        name: name.clone(),
+                        folder: self.current_dir.clone(),
+                        query: self.search_query.trim().to_string(),
+                        use_regex: self.use_regex,
+                        fuzzy: self.use_fuzzy,
+                        scope: self.search_scope,
+                    };
+                    // Saving under an existing name replaces it
+                    let searches = &mut self.preferences.saved_searches;
+                    match searches.iter_mut().find(|search| search.name == name) {
+                        Some(existing) => *existing = saved,
+                        None => searches.push(saved),
+                    }
+                    self.save_preferences();
+                    self.dialog = DialogState::None;
+                }
+                Command::none()
+            }
+            Message::RunSavedSearch(saved) => {
+                self.current_dir = saved.folder;
+                self.selected_entry = None;
+                self.search_query = saved.query;
+                self.use_regex = saved.use_regex;
+                self.use_fuzzy = saved.fuzzy && !saved.use_regex;
+                self.search_scope = saved.scope;
+                self.search_error = None;
+                self.perform_search()
+            }
+            Message::ForgetSavedSearch(name) => {
+                self.preferences.saved_searches.retain(|search| search.name != name);
+                self.save_preferences();
+                Command::none()
+            }
+            Message::StopSearch => {
+                // Dropping the subscription closes its channel, which the
+                // walk checks before every entry. A slow folder keeps what
+                // was listed so far.
+                if self.loading {
+                    self.loading = false;
+                    self.load_stopped = !self.showing_results();
+                }
+                Command::none()
+            }
+            Message::Tick => {
+                self.spinner_frame = (self.spinner_frame + 1) % SPINNER_FRAMES.len();
+                Command::none()
+            }
+            Message::SortByName => {
+                self.sort_mode = if self.sort_mode == SortMode::NameAsc {
+                    SortMode::NameDesc
+                } else {
+                    SortMode::NameAsc
+                };
+                self.remember_sort();
+                Command::none()
+            }
+            Message::SortBySize => {
+                self.sort_mode = if self.sort_mode == SortMode::SizeAsc {
+                    SortMode::SizeDesc
+                } else {
+                    SortMode::SizeAsc
+                };
+                self.remember_sort();
+                Command::none()
+            }
+            Message::SortByDate => {
+                self.sort_mode = if self.sort_mode == SortMode::DateAsc {
+                    SortMode::DateDesc
+                } else {
+                    SortMode::DateAsc
+                };
+                self.remember_sort();
+                Command::none()
+            }
+            Message::Edit => {
+                if let Some(path) = &self.selected_entry
+                    && !self.is_dir(path)
+                {
+                    let opened_at = metadata(path).and_then(|meta| meta.modified()).ok();
+                    self.editor = Some(match read_text_file(path, self.preferences.size_units) {
+                        Ok(contents) => EditorState {
+                            path: path.clone(),
+                            line_ending: if contents.contains("\r\n") { "\r\n" } else { "\n" },
+                            trailing_newline: contents.ends_with('\n'),
+                            lines: contents.lines().map(String::from).collect(),
+                            modified: false,
+                            error: None,
+                            save_error: None,
+                            opened_at,
+                            replace_changes: false,
+                        },
+                        Err(error) => EditorState {
+                            path: path.clone(),
+                            lines: Vec::new(),
+                            line_ending: "\n",
+                            trailing_newline: false,
+                            modified: false,
+                            error: Some(error),
+                            save_error: None,
+                            opened_at,
+                            replace_changes: false,
+                        },
+                    });
+                    self.dialog = DialogState::Editor;
+                }
+                Command::none()
+            }
+            Message::EditorLineChanged(index, line) => {
+                if let Some(editor) = &mut self.editor
+                    && let Some(current) = editor.lines.get_mut(index)
+                {
+                    *current = line;
+                    editor.modified = true;
+                }
+                Command::none()
+            }
+            Message::EditorInsertLine(index) => {
+                if let Some(editor) = &mut self.editor {
+                    let at = (index + 1).min(editor.lines.len());
+                    editor.lines.insert(at, String::new());
+                    editor.modified = true;
+                    return text_input::focus(editor_line_id(at));
+                }
+                Command::none()
+            }
+            Message::EditorRemoveLine(index) => {
+                if let Some(editor) = &mut self.editor
+                    && index < editor.lines.len()
+                {
+                    editor.lines.remove(index);
+                    editor.modified = true;
+                }
+                Command::none()
+            }
+            Message::SaveEdit => {
+                if let Some(editor) = &mut self.editor {
+                    // Another program wrote the file while it was open here
+                    let written_at = metadata(&editor.path).and_then(|meta| meta.modified()).ok();
+                    if written_at != editor.opened_at && !editor.replace_changes {
+                        editor.save_error = Some(tr("The file changed on disk after it was opened. Save again to replace those changes.").to_string());
+                        editor.replace_changes = true;
+                        return Command::none();
+                    }
+
+                    let mut contents = editor.lines.join(editor.line_ending);
+                    if editor.trailing_newline {
+                        contents.push_str(editor.line_ending);
+                    }
+
+                    tracing::info!(path = %editor.path.display(), "save edit");
+                    let size = contents.len() as u64;
+                    let written = write(&editor.path, contents);
+                    let saved = editor.path.clone();
+                    self.remember_change(history::Action::Save, saved, None, Some(size), history::Outcome::of(&written));
+                    let Some(editor) = &mut self.editor else {
+                        return Command::none();
+                    };
+                    match written {
+                        Ok(()) => {
+                            let path = editor.path.clone();
+                            self.editor = None;
+                            self.dialog = DialogState::None;
+                            // Only the saved file changed, so there's no need to reload
+                            self.invalidate(&path);
+                            self.sort_entries();
+                            return self.load_git_status();
+                        }
+                        Err(error) => editor.save_error = Some(fill(tr("Could not save: {}"), &[&error])),
+                    }
+                }
+                Command::none()
+            }
+            Message::ZoomIn => {
+                self.set_zoom(self.preferences.zoom + settings::ZOOM_STEP);
+                Command::none()
+            }
+            Message::ZoomOut => {
+                self.set_zoom(self.preferences.zoom - settings::ZOOM_STEP);
+                Command::none()
+            }
+            Message::ZoomReset => {
+                self.set_zoom(1.0);
+                Command::none()
+            }
+            Message::OpenPalette => {
+                self.palette_query.clear();
+                self.dialog = DialogState::Palette;
+                text_input::focus(palette_input_id())
+            }
+            Message::PaletteQueryChanged(query) => {
+                self.palette_query = query;
+                Command::none()
+            }
+            Message::SubmitPalette => match toolbar::matching(&self.palette_query).first() {
+                Some(&tool) => self.update(Message::PaletteChosen(tool)),
+                None => Command::none(),
+            },
+            Message::PaletteChosen(tool) => {
+                self.dialog = DialogState::None;
+                self.update(tool_message(tool))
+            }
+            Message::DensitySelected(density) => {
+                self.density = density;
+                self.remember_view();
+                Command::none()
+            }
+            Message::IconSetSelected(icon_set) => {
+                self.preferences.icon_set = icon_set;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::EntriesLoaded(generation, progress) => {
+                // Ignore batches from a load that has since been replaced
+                if generation == self.load_generation {
+                    match progress {
+                        loader::Progress::Batch(batch) => {
+                            self.entries.extend(batch);
+                            self.sort_entries();
+                        }
+                        loader::Progress::Failed(error) => {
+                            tracing::warn!(dir = %self.current_dir.display(), "could not read folder: {}", error);
+                            if let Some(command) = self.leave_missing_dir() {
+                                return command;
+                            }
+                            // A denied folder explains itself in the list
+                            if let loader::Failure::Other(message) = &error {
+                                let dir = self.current_dir.display().to_string();
+                                let message = format!("{}: {}", fill(tr("Could not read {}"), &[&dir]), message);
+                                self.notices.push(Diagnostic::new(message).at(&self.current_dir));
+                            }
+                            self.load_error = Some(error);
+                        }
+                        loader::Progress::Finished => {
+                            self.loading = false;
+                            return self.scroll_to_revealed();
+                        }
+                    }
+                }
+                Command::none()
+            }
+            Message::ShowMore => {
+                self.visible_limit += PAGE_SIZE;
+                Command::none()
+            }
+            Message::HiddenFilesToggled(enabled) => {
+                self.show_hidden = enabled;
+                self.remember_view();
+                self.reload()
+            }
+            Message::FolderSizesToggled(enabled) => {
+                self.preferences.folder_sizes = enabled;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::FolderSizeComputed(path, size) => {
+                self.folder_sizes.insert(path, size);
+                Command::none()
+            }
+            Message::FolderCountComputed(path, count) => {
+                self.folder_counts.insert(path, count);
+                Command::none()
+            }
+            Message::PluginPreviewLoaded(path, stamp, result) => {
+                if let Ok(text) = &result {
+                    self.preview_cache.insert(path.clone(), stamp, text.clone());
+                }
+                if let Some(props) = &mut self.properties
+                    && props.path == path
+                {
+                    props.preview = Some(result);
+                }
+                Command::none()
+            }
+            Message::PluginColumnsLoaded(dir, values) => {
+                if dir == self.current_dir {
+                    self.plugin_values = values;
+                }
+                Command::none()
+            }
+            Message::RunCustomCommand(command) => {
+                let Some(path) = self.selected_entry.clone() else {
+                    return Command::none();
+                };
+                self.command_output = Some((command.name.clone(), None));
+                self.dialog = DialogState::CommandOutput;
+                Command::perform(run_custom_command(command, path), Message::CustomCommandFinished)
+            }
+            Message::CustomCommandFinished(result) => {
+                if let Some((_, output)) = &mut self.command_output {
+                    *output = Some(result);
+                }
+                // The command may have written files next to the selection
+                self.refresh()
+            }
+            Message::CommandNameChanged(name) => {
+                self.settings_command_name = name;
+                Command::none()
+            }
+            Message::CommandTemplateChanged(template) => {
+                self.settings_command_template = template;
+                Command::none()
+            }
+            Message::AddCustomCommand => {
+                let name = self.settings_command_name.trim().to_string();
+                let template = self.settings_command_template.trim().to_string();
+                if name.is_empty() || template.is_empty() {
+                    return Command::none();
+                }
+                // Adding a name again replaces that command
+                self.preferences.custom_commands.retain(|command| command.name != name);
+                self.preferences.custom_commands.push(commands::CustomCommand { name, template });
+                self.save_preferences();
+                self.settings_command_name.clear();
+                self.settings_command_template.clear();
+                Command::none()
+            }
+            Message::ClearCaches => {
+                self.cache_notice = Some(match paths::clear_caches() {
+                    Ok(size) => fill(tr("Cleared {} of cached data"), &[&self.preferences.size_units.format(size)]),
+                    Err(error) => fill(tr("Could not clear the caches: {}"), &[&error]),
+                });
+                // Folder sizes are cached in memory too; they get measured again
+                self.folder_sizes.clear();
+                self.folder_counts.clear();
+                self.index = None;
+                self.refresh_index(None)
+            }
+            Message::RemoveCustomCommand(name) => {
+                self.preferences.custom_commands.retain(|command| command.name != name);
+                self.save_preferences();
+                Command::none()
+            }
+            Message::RunPluginAction(action) => {
+                let paths = vec![self.selected_entry.clone().unwrap_or_else(|| self.current_dir.clone())];
+                self.plugin_message = None;
+                Command::perform(run_plugin_action(self.plugins.clone(), action, paths), Message::PluginActionFinished)
+            }
+            Message::PluginActionFinished(result) => {
+                match result {
+                    Ok(reply) => {
+                        self.plugin_message = reply.message;
+                        if reply.refresh {
+                            return self.reload();
+                        }
+                    }
+                    Err(error) => self.plugin_message = Some(error),
+                }
+                Command::none()
+            }
+            Message::MediaProbed(path, result) => {
+                if let Some(props) = &mut self.properties
+                    && props.path == path
+                {
+                    props.media = Some(match result {
+                        Ok(info) => media::MediaProbe::Ready(info),
+                        Err(error) => media::MediaProbe::Failed(error),
+                    });
+                }
+                Command::none()
+            }
+            Message::IndexToggled(enabled) => {
+                self.preferences.index_enabled = enabled;
+                self.save_preferences();
+                if enabled {
+                    load_index()
+                } else {
+                    self.index = None;
+                    Command::none()
+                }
+            }
+            Message::IndexLoaded(index) => self.refresh_index(index),
+            Message::ExternalSearchToggled(enabled) => {
+                self.preferences.external_search = enabled;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::OcrSearchToggled(enabled) => {
+                if enabled && !ocr::is_available() {
+                    self.notices.push(tr("Reading text in images needs tesseract, which isn't installed").to_string());
+                    return Command::none();
+                }
+                self.preferences.ocr_search = enabled;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::WindowResized(width, height) => {
+                self.preferences.window.width = width;
+                self.preferences.window.height = height;
+                Command::none()
+            }
+            Message::WindowMoved(x, y) => {
+                self.preferences.window.position = Some((x, y));
+                Command::none()
+            }
+            Message::CloseRequested => {
+                self.save_preferences();
+                iced::window::close()
+            }
+            Message::DefaultSortSelected(sort_mode) => {
+                self.preferences.sort_mode = sort_mode;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::DirsFirstToggled(enabled) => {
+                self.preferences.dirs_first = enabled;
+                self.save_preferences();
+                self.sort_entries();
+                Command::none()
+            }
+            Message::UiScaleChanged(scale) => {
+                self.preferences.ui_scale = scale.clamp(settings::MIN_UI_SCALE, settings::MAX_UI_SCALE);
+                Command::none()
+            }
+            Message::UiScaleReleased => {
+                self.save_preferences();
+                Command::none()
+            }
+            Message::PreviewMemorySelected(size) => {
+                self.preferences.preview_memory_mb = size.0;
+                self.preview_cache.set_budget(size.0);
+                self.save_preferences();
+                Command::none()
+            }
+            Message::PreviewDiskSelected(size) => {
+                self.preferences.preview_disk_mb = size.0;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::ProtectedDepthSelected(depth) => {
+                self.preferences.protected_depth = depth;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::DateFormatSelected(date_format) => {
+                self.preferences.date_format = date_format;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::SizeUnitsSelected(size_units) => {
+                self.preferences.size_units = size_units;
+                self.save_preferences();
+                Command::none()
+            }
+            Message::LanguageSelected(language) => {
+                self.preferences.language = language;
+                self.save_preferences();
+                i18n::set_language(language);
+                Command::none()
+            }
+            Message::ThemeSelected(choice) => {
+                self.preferences.theme = choice;
+                self.save_preferences();
+                if choice == ThemeChoice::System {
+                    self.system_dark = settings::system_prefers_dark();
+                }
+                Command::none()
+            }
+            Message::OpenSettings => {
+                self.dialog = DialogState::Settings;
+                self.reset_settings_inputs();
+                Command::none()
+            }
+            Message::ProfileSelected(profile) => {
+                if profile.0 == settings::profile() {
+                    return Command::none();
+                }
+                self.save_preferences();
+                settings::set_profile(profile.0);
+                self.switch_preferences(settings::load())
+            }
+            Message::ProfileNameChanged(name) => {
+                self.settings_profile_name = name;
+                self.settings_error = None;
+                Command::none()
+            }
+            Message::CreateProfile => {
+                let name = self.settings_profile_name.trim().to_string();
+                if !settings::is_valid_profile_name(&name) {
+                    self.settings_error = Some(tr("Profile names may only use letters, digits, spaces, - and _").to_string());
+                    return Command::none();
+                }
+                if settings::profiles().contains(&settings::Profile(Some(name.clone()))) {
+                    self.settings_error = Some(fill(tr("There already is a profile called {}"), &[&name]));
+                    return Command::none();
+                }
+                // A new profile starts out as a copy of the current one
+                self.save_preferences();
+                settings::set_profile(Some(name));
+                self.save_preferences();
+                self.reset_settings_inputs();
+                Command::none()
+            }
+            Message::CloseSettings => {
+                // An empty start folder means home
+                let start_dir = self.settings_start_dir.trim();
+                let start_dir = (!start_dir.is_empty()).then(|| PathBuf::from(start_dir));
+                if let Some(dir) = &start_dir
+                    && !dir.is_dir()
+                {
+                    self.settings_error = Some(fill(tr("{} is not a folder"), &[&dir.display()]));
+                    return Command::none();
+                }
+                self.preferences.start_dir = start_dir;
+                self.save_preferences();
+                self.dialog = DialogState::None;
+                Command::none()
+            }
+            Message::StartDirChanged(dir) => {
+                self.settings_start_dir = dir;
+                self.settings_error = None;
+                Command::none()
+            }
+            Message::UseCurrentAsStart => {
+                self.settings_start_dir = self.current_dir.display().to_string();
+                self.settings_error = None;
+                Command::none()
+            }
+            Message::AddIndexRoot => {
+                if !self.preferences.index_roots.contains(&self.current_dir) {
+                    self.preferences.index_roots.push(self.current_dir.clone());
+                    self.save_preferences();
+                }
+                self.refresh_index(self.index.clone())
+            }
+            Message::RemoveIndexRoot(root) => {
+                self.preferences.index_roots.retain(|known| known != &root);
+                self.save_preferences();
+                self.refresh_index(self.index.clone())
+            }
+            Message::HideInputChanged(pattern) => {
+                self.settings_hide = pattern;
+                Command::none()
+            }
+            Message::AddHidePattern => {
+                let pattern = self.settings_hide.trim().to_string();
+                if pattern.is_empty() || self.preferences.hide_patterns.contains(&pattern) {
+                    return Command::none();
+                }
+                self.preferences.hide_patterns.push(pattern);
+                self.settings_hide.clear();
+                // Unlike the exclusions these also change the folder listing
+                Command::batch([self.apply_excludes(), self.reload()])
+            }
+            Message::RemoveHidePattern(pattern) => {
+                self.preferences.hide_patterns.retain(|known| known != &pattern);
+                Command::batch([self.apply_excludes(), self.reload()])
+            }
+            Message::ExcludeInputChanged(pattern) => {
+                self.settings_exclude = pattern;
+                Command::none()
+            }
+            Message::AddExcludePattern => {
+                let pattern = self.settings_exclude.trim().to_string();
+                if pattern.is_empty() || self.preferences.exclude_patterns.contains(&pattern) {
+                    return Command::none();
+                }
+                self.preferences.exclude_patterns.push(pattern);
+                self.settings_exclude.clear();
+                self.apply_excludes()
+            }
+            Message::RemoveExcludePattern(pattern) => {
+                self.preferences.exclude_patterns.retain(|known| known != &pattern);
+                self.apply_excludes()
+            }
+            Message::SkipIgnoredToggled(enabled) => {
+                self.preferences.skip_ignored = enabled;
+                self.save_preferences();
+                self.excludes = Excludes::new(enabled, &self.preferences.exclude_patterns, &self.preferences.hide_patterns);
+                self.refresh_index(self.index.clone())
+            }
+            Message::IndexBuilt(index) => {
+                self.indexing = false;
+                if self.preferences.index_enabled && index.is_some() {
+                    self.index = index;
+                }
+                Command::none()
+            }
+            Message::MountProbed(generation, mount, answered) => {
+                if answered {
+                    self.offline.remove(&mount);
+                    return Command::none();
+                }
+                tracing::warn!(mount = %mount.display(), "network drive not answering");
+                self.offline.insert(mount.clone());
+                // Give up on a listing that is stuck waiting for the drive
+                if generation == self.load_generation && self.loading && self.entries.is_empty() {
+                    self.loading = false;
+                    self.load_error = Some(loader::Failure::Offline(mount));
+                }
+                Command::none()
+            }
+            Message::DiskSpaceLoaded(dir, space) => {
+                if dir == self.current_dir {
+                    self.disk_space = space;
+                }
+                Command::none()
+            }
+            Message::GitStatusLoaded(dir, status) => {
+                if dir == self.current_dir {
+                    self.git_status = status;
+                }
+                Command::none()
+            }
+            Message::AnalyzeUsage => {
+                let root_path = self.current_dir.clone();
+                self.usage = Some(UsageState {
+                    root_path: root_path.clone(),
+                    root: None,
+                    focus: root_path.clone(),
+                });
+                Command::perform(scan_usage(root_path, self.excludes.clone()), |(path, node)| {
+                    Message::UsageScanned(path, node)
+                })
+            }
+            Message::UsageScanned(path, node) => {
+                // The view may have been closed or restarted elsewhere meanwhile
+                if let Some(usage) = &mut self.usage
+                    && usage.root_path == path
+                {
+                    usage.root = Some(node);
+                }
+                Command::none()
+            }
+            Message::UsageOpen(path) => {
+                if let Some(usage) = &mut self.usage {
+                    usage.focus = path;
+                }
+                Command::none()
+            }
+            Message::UsageUp => {
+                if let Some(usage) = &mut self.usage
+                    && usage.focus != usage.root_path
+                    && let Some(parent) = usage.focus.parent()
+                {
+                    usage.focus = parent.to_path_buf();
+                }
+                Command::none()
+            }
+            Message::UsageDelete(path) => {
+                self.selected_entry = Some(path);
+                self.update(Message::Delete)
+            }
+            Message::CloseUsage => {
+                self.usage = None;
+                Command::none()
+            }
+            Message::FindEmpty => {
+                let root = self.current_dir.clone();
+                self.cleanup = Some(CleanupState {
+                    root: root.clone(),
+                    found: None,
+                    selected: HashSet::new(),
+                    error: None,
+                });
+                Command::perform(find_empty(root, self.excludes.clone()), |(root, found)| {
+                    Message::EmptyFound(root, found)
+                })
+            }
+            Message::EmptyFound(root, found) => {
+                if let Some(cleanup) = &mut self.cleanup
+                    && cleanup.root == root
+                {
+                    cleanup.found = Some(found);
+                }
+                Command::none()
+            }
+            Message::EmptyToggled(path, checked) => {
+                if let Some(cleanup) = &mut self.cleanup {
+                    if checked {
+                        cleanup.selected.insert(path);
+                    } else {
+                        cleanup.selected.remove(&path);
+                    }
+                }
+                Command::none()
+            }
+            Message::EmptySelectAll(checked) => {
+                if let Some(cleanup) = &mut self.cleanup {
+                    cleanup.selected = match (&cleanup.found, checked) {
+                        (Some(found), true) => found.iter().map(|entry| entry.path.clone()).collect(),
+                        _ => HashSet::new(),
+                    };
+                }
+                Command::none()
+            }
+            Message::DeleteEmpty => {
+                if self.cleanup.as_ref().is_some_and(|cleanup| !cleanup.selected.is_empty()) {
+                    self.dialog = DialogState::DeleteEmpty;
+                }
+                Command::none()
+            }
+            Message::ConfirmDeleteEmpty => {
+                self.dialog = DialogState::None;
+                let Some(cleanup) = &mut self.cleanup else {
+                    return Command::none();
+                };
+
+                // remove_dir refuses folders that gained children meanwhile
+                let mut failures = Vec::new();
+                let mut history_records = Vec::new();
+                for path in std::mem::take(&mut cleanup.selected) {
+                    let removed = if path.is_dir() { remove_dir(&path) } else { remove_file(&path) };
+                    let outcome = history::Outcome::of(&removed);
+                    history_records.push((path.clone(), outcome));
+                    match removed {
+                        Ok(()) => {
+                            if let Some(found) = &mut cleanup.found {
+                                found.retain(|entry| entry.path != path);
+                            }
+                        }
+                        Err(error) => failures.push((path, error)),
+                    }
+                }
+                cleanup.error = (!failures.is_empty()).then(|| fill(tr("{} item(s) could not be deleted"), &[&failures.len()]));
+                for (path, outcome) in history_records {
+                    self.remember_change(history::Action::Delete, path, None, Some(0), outcome);
+                }
+                for (path, error) in failures {
+                    self.report_at(Err::<(), _>(error), fill(tr("Could not delete {}"), &[&path.display()]), Some(&path));
+                }
+
+                self.refresh()
+            }
+            Message::CloseCleanup => {
+                self.cleanup = None;
+                Command::none()
+            }
+            Message::ShowRecent(window) => {
+                self.recent = Some(window);
+                self.starred_view = false;
+                self.active_search = None;
+                self.selected_entry = None;
+                self.search_query.clear();
+                self.search_error = None;
+                self.clear_refinement();
+                self.sort_mode = SortMode::DateDesc;
+                self.reload()
+            }
+            Message::ShowStarred => {
+                self.starred_view = true;
+                self.recent = None;
+                self.active_search = None;
+                self.selected_entry = None;
+                self.search_query.clear();
+                self.search_error = None;
+                self.clear_refinement();
+                self.reload()
+            }
+            Message::ToggleStar => {
+                let Some(path) = self.selected_entry.clone() else {
+                    return Command::none();
+                };
+                let toggled = self.starred.toggle(&path);
+                if let Some(starred) = self.report_at(toggled, fill(tr("Could not save the star on {}"), &[&path.display()]), Some(&path)) {
+                    tracing::info!(path = %path.display(), starred, "star");
+                    // The starred list loses what is no longer starred
+                    if self.starred_view && !starred {
+                        self.entries.retain(|entry| entry.path != path);
+                        self.selected_entry = None;
+                    }
+                }
+                Command::none()
+            }
+            Message::FindLargest => {
+                let generation = self.largest.as_ref().map_or(0, |largest| largest.generation + 1);
+                self.largest = Some(LargestState {
+                    root: self.current_dir.clone(),
+                    generation,
+                    files: Vec::new(),
+                    scanning: true,
+                });
+                Command::none()
+            }
+            Message::LargestLoaded(generation, progress) => {
+                if let Some(largest) = &mut self.largest
+                    && largest.generation == generation
+                {
+                    match progress {
+                        loader::Progress::Batch(batch) => largest.files.extend(batch),
+                        loader::Progress::Failed(error) => {
+                            let root = largest.root.display().to_string();
+                            self.report(Err::<(), _>(error), fill(tr("Could not read {}"), &[&root]));
+                        }
+                        loader::Progress::Finished => largest.scanning = false,
+                    }
+                }
+                Command::none()
+            }
+            Message::LargestDelete(path) => {
+                self.selected_entry = Some(path);
+                self.update(Message::Delete)
+            }
+            Message::CloseLargest => {
+                // Dropping the subscription stops a scan that is still running
+                self.largest = None;
+                Command::none()
+            }
+            Message::ShowTrash => {
+                self.trash = Some(TrashState { items: None, confirm_empty: false });
+                Command::perform(
+                    async {
+                        run_blocking(|| trash::list().map_err(|error| error.to_string()))
+                            .await
+                            .unwrap_or_else(|| Err(tr("The command was interrupted").to_string()))
+                    },
+                    Message::TrashListed,
+                )
+            }
+            Message::TrashListed(items) => {
+                if let Some(state) = &mut self.trash {
+                    state.items = Some(items);
+                }
+                Command::none()
+            }
+            Message::RestoreFromTrash(index) => {
+                let Some(item) = self.trash.as_ref().and_then(|state| state.items.as_ref()?.as_ref().ok()?.get(index)).cloned() else {
+                    return Command::none();
+                };
+                tracing::info!(path = %item.original.display(), "restore");
+                let restored = trash::restore(&item);
+                self.remember_change(history::Action::Restore, item.file.clone(), Some(item.original.clone()), Some(item.size), history::Outcome::of(&restored));
+                if self.report_at(restored, fill(tr("Could not restore {}"), &[&item.original.display()]), Some(&item.original)).is_some()
+                    && let Some(Some(Ok(items))) = self.trash.as_mut().map(|state| state.items.as_mut())
+                {
+                    items.remove(index);
+                }
+                self.refresh()
+            }
+            Message::EmptyTrash => {
+                let Some(state) = &mut self.trash else {
+                    return Command::none();
+                };
+                // Emptying can't be taken back, so it takes a second press
+                if !state.confirm_empty {
+                    state.confirm_empty = true;
+                    return Command::none();
+                }
+                state.confirm_empty = false;
+                tracing::info!("empty trash");
+                Command::perform(
+                    async {
+                        run_blocking(|| trash::empty().map_err(|error| error.to_string()))
+                            .await
+                            .unwrap_or_else(|| Err(tr("The command was interrupted").to_string()))
+                    },
+                    Message::TrashEmptied,
+                )
+            }
+            Message::TrashEmptied(emptied) => {
+                self.report(emptied, tr("Could not empty the trash").to_string());
+                self.update(Message::ShowTrash)
+            }
+            Message::CloseTrash => {
+                self.trash = None;
+                Command::none()
+            }
+            Message::ImageOpened(path, opened) => {
+                if let Some(state) = self.image.as_mut().filter(|state| state.path == path) {
+                    match opened {
+                        Ok((image, entries)) => {
+                            state.image = Some(Ok(image));
+                            state.entries = Some(Ok(entries));
+                        }
+                        Err(error) => state.image = Some(Err(error)),
+                    }
+                }
+                Command::none()
+            }
+            Message::ImageListed(dir, entries) => {
+                if let Some(state) = self.image.as_mut().filter(|state| state.dir() == Some(&dir)) {
+                    state.entries = Some(entries);
+                }
+                Command::none()
+            }
+            Message::OpenImageEntry(index) => {
+                let Some(state) = &mut self.image else {
+                    return Command::none();
+                };
+                let Some(entry) = state.entries.as_ref().and_then(|entries| entries.as_ref().ok()?.get(index)).cloned() else {
+                    return Command::none();
+                };
+                if entry.is_dir {
+                    state.dirs.push(entry);
+                    return self.list_image_dir();
+                }
+                let Some(Ok(image)) = state.image.clone() else {
+                    return Command::none();
+                };
+                // Opened from a copy in the cache, named as in the image
+                let Some(cache) = paths::cache_dir() else {
+                    return Command::none();
+                };
+                let image_name = image.path.file_name().unwrap_or_default();
+                let copy = state.dirs.iter().fold(cache.join("disk-images").join(image_name), |path, dir| path.join(&dir.name)).join(&entry.name);
+                tracing::info!(image = %image.path.display(), entry = entry.name, "open from image");
+                Command::perform(
+                    async move {
+                        run_blocking(move || {
+                            if let Some(parent) = copy.parent() {
+                                create_dir_all(parent)?;
+                            }
+                            io::copy(&mut image.read(&entry)?, &mut File::create(&copy)?)?;
+                            Ok(copy)
+                        })
+                        .await
+                        .unwrap_or_else(|| Err(io::Error::other(tr("The command was interrupted"))))
+                        .map_err(|error: io::Error| error.to_string())
+                    },
+                    Message::ImageFileExtracted,
+                )
+            }
+            Message::ImageFileExtracted(extracted) => {
+                if let Some(path) = self.report(extracted, tr("Could not extract the file from the image").to_string()) {
+                    let opened = associations::open(&path, &self.preferences.associations);
+                    self.report_at(opened, fill(tr("Could not open {}"), &[&path.display()]), Some(&path));
+                }
+                Command::none()
+            }
+            Message::ImageUp => {
+                if let Some(state) = &mut self.image
+                    && state.dirs.pop().is_some()
+                {
+                    return self.list_image_dir();
+                }
+                Command::none()
+            }
+            Message::ExtractFromImage(index) => {
+                let Some(state) = &self.image else {
+                    return Command::none();
+                };
+                let (Some(Ok(image)), Some(Ok(entries))) = (&state.image, &state.entries) else {
+                    return Command::none();
+                };
+                let Some(entry) = entries.get(index) else {
+                    return Command::none();
+                };
+                // Beside the image, where its folder is
+                let target = image.path.parent().map(Path::to_path_buf).unwrap_or_else(|| self.current_dir.clone());
+                self.jobs.push(jobs::Operation::Extract { image: image.clone(), entries: vec![entry.clone()], target });
+                Command::none()
+            }
+            Message::CloseImage => {
+                self.image = None;
+                Command::none()
+            }
+        }
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.preferences.ui_scale.clamp(settings::MIN_UI_SCALE, settings::MAX_UI_SCALE) as f64
+    }
+
+    fn theme(&self) -> Theme {
+        let dark = match self.preferences.theme {
+            ThemeChoice::System => self.system_dark,
+            ThemeChoice::Light => false,
+            ThemeChoice::Dark => true,
+        };
+        self.preferences.colors.apply(dark)
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let mut subscriptions = vec![
+            subscription::events_with(handle_shortcut),
+            subscription::events_with(handle_window_event),
+        ];
+
+        if self.loading {
+            let progress = match &self.active_search {
+                _ if self.starred_view => streams::starred(self.load_generation, self.starred.paths().to_vec()),
+                _ if let Some(window) = self.recent => streams::recent(
+                    self.load_generation,
+                    self.recent_roots(),
+                    window,
+                    self.excludes.clone(),
+                ),
+                Some(query) => match self.usable_index(query) {
+                    Some(index) => streams::indexed_search(self.load_generation, self.current_dir.clone(), query.clone(), index),
+                    None => streams::search(
+                        self.load_generation,
+                        self.current_dir.clone(),
+                        query.clone(),
+                        self.excludes.clone(),
+                        self.preferences.external_search,
+                    ),
+                },
+                None => streams::folder(self.load_generation, self.current_dir.clone(), self.show_hidden, self.excludes.clone()),
+            };
+            subscriptions.push(
+                progress.map(|(generation, progress)| Message::EntriesLoaded(generation, progress)),
+            );
+        }
+
+        if let Some(largest) = &self.largest
+            && largest.scanning
+        {
+            subscriptions.push(
+                streams::largest(largest.generation, largest.root.clone(), self.excludes.clone())
+                    .map(|(generation, progress)| Message::LargestLoaded(generation, progress)),
+            );
+        }
+
+        if self.loading || self.largest.as_ref().is_some_and(|largest| largest.scanning) {
+            subscriptions.push(iced::time::every(Duration::from_millis(120)).map(|_| Message::Tick));
+        }
+
+        if let Some(check) = self.checksum_check.as_ref().filter(|check| !check.is_finished()) {
+            subscriptions.push(
+                streams::verify(check.generation, check.lines.clone()).map(|(generation, event)| Message::ChecksumEvent(generation, event)),
+            );
+        }
+
+        if let Some(job) = self.jobs.current() {
+            subscriptions.push(streams::job(job).map(|(id, event)| Message::JobEvent(id, event)));
+        }
+
+        if !self.notices.toasts().is_empty() {
+            subscriptions.push(iced::time::every(Duration::from_secs(1)).map(|_| Message::ExpireToasts));
+        }
+
+        let pending = self.pending_folders(&self.folder_counts);
+        if !pending.is_empty() {
+            subscriptions.push(
+                streams::measure(self.load_generation, dirsize::Measure::Count, pending, self.excludes.clone())
+                    .map(|(path, count)| Message::FolderCountComputed(path, count)),
+            );
+        }
+
+        if self.preferences.folder_sizes {
+            let pending = self.pending_folders(&self.folder_sizes);
+            if !pending.is_empty() {
+                subscriptions.push(
+                    streams::measure(self.load_generation, dirsize::Measure::Size, pending, self.excludes.clone())
+                        .map(|(path, size)| Message::FolderSizeComputed(path, size)),
+                );
+            }
+        }
+
+        Subscription::batch(subscriptions)
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let heading = match self.recent {
+            _ if self.starred_view => tr("⭐ Starred").to_string(),
+            Some(window) => fill(tr("Recently Modified: {}"), &[&window]),
+            None => fill(tr("Current Directory: {}"), &[&self.current_dir.display()]),
+        };
+        let title = text(heading)
+            .size(20)
+            .width(Length::Fill);
+
+        // Search bar
+        let search_input = text_input(
+            tr("Filter this folder, or press Enter to search subfolders (try *.rs, a OR b, -skip)..."),
+            &self.search_query,
+        )
+            .on_input(Message::SearchInputChanged)
+            .on_submit(Message::PerformSearch)
+            .padding(10);
+
+        let search_button = button(
+            row![text(tr("🔍 Search")).horizontal_alignment(Horizontal::Center)]
+                .width(Length::Fill)
+                .align_items(iced::Alignment::Center)
+        )
+        .on_press(Message::PerformSearch)
+        .padding(10)
+        .width(Length::Fixed(100.0))
+        .style(theme::Button::Secondary);
+
+        let density_picker = pick_list(
+            &Density::ALL[..],
+            Some(self.density),
+            Message::DensitySelected,
+        )
+        .padding(10)
+        .width(Length::Fixed(140.0));
+
+        let icon_set_picker = pick_list(
+            &IconSet::ALL[..],
+            Some(self.preferences.icon_set),
+            Message::IconSetSelected,
+        )
+        .padding(10)
+        .width(Length::Fixed(150.0));
+
+        let regex_toggle = checkbox(tr("Regex"), self.use_regex, Message::RegexToggled);
+        let fuzzy_toggle = checkbox(tr("Fuzzy"), self.use_fuzzy, Message::FuzzyToggled);
+        let scope_picker = pick_list(&search::Scope::ALL[..], Some(self.search_scope), Message::ScopeSelected)
+            .padding(10)
+            .width(Length::Fixed(120.0));
+        let ignored_toggle = checkbox(tr("Skip ignored"), self.preferences.skip_ignored, Message::SkipIgnoredToggled);
+
+        let save_button = button(text(tr("☆ Save")).horizontal_alignment(Horizontal::Center))
+            .padding(10)
+            .style(theme::Button::Secondary);
+        let save_button = if self.search_query.trim().is_empty() {
+            save_button
+        } else {
+            save_button.on_press(Message::SaveSearch)
+        };
+
+        let saved_picker = pick_list(
+            &self.preferences.saved_searches[..],
+            None::<SavedSearch>,
+            Message::RunSavedSearch,
+        )
+        .placeholder(tr("Saved searches"))
+        .padding(10)
+        .width(Length::Fixed(160.0));
+
+        let search_row = row![
+            search_input,
+            regex_toggle,
+            fuzzy_toggle,
+            scope_picker,
+            ignored_toggle,
+            search_button,
+            save_button,
+            saved_picker,
+            density_picker,
+            icon_set_picker
+        ]
+            .spacing(10)
+            .padding(10)
+            .align_items(iced::Alignment::Center);
+
+        let mut search_area = match &self.search_error {
+            Some(error) => column![
+                search_row,
+                text(error)
+                    .size(14)
+                    .style(theme::Text::Color(Color::from_rgb(0.85, 0.2, 0.2)))
+            ]
+            .padding([0, 10]),
+            None => column![search_row],
+        };
+
+        let mut date_row = row![
+            text(tr("Modified:")).size(14),
+            pick_list(&search::DatePreset::ALL[..], Some(self.date_preset), Message::DatePresetSelected)
+                .padding(6)
+                .text_size(14)
+        ]
+        .spacing(10)
+        .padding([0, 10])
+        .align_items(iced::Alignment::Center);
+
+        if self.date_preset == search::DatePreset::Custom {
+            date_row = date_row
+                .push(
+                    text_input(tr("From YYYY-MM-DD"), &self.date_from)
+                        .on_input(Message::DateFromChanged)
+                        .on_submit(Message::PerformSearch)
+                        .padding(6)
+                        .size(14)
+                        .width(Length::Fixed(150.0)),
+                )
+                .push(
+                    text_input(tr("To YYYY-MM-DD"), &self.date_until)
+                        .on_input(Message::DateUntilChanged)
+                        .on_submit(Message::PerformSearch)
+                        .padding(6)
+                        .size(14)
+                        .width(Length::Fixed(150.0)),
+                );
+        }
+        search_area = search_area.push(date_row);
+        // Flagged as it is typed, as such a search wouldn't start
+        if let Some(problem) = self.date_problem() {
+            search_area = search_area.push(
+                container(text(problem).size(14).style(theme::Text::Color(Color::from_rgb(0.85, 0.2, 0.2)))).padding([0, 10]),
+            );
+        }
+
+        // Once there are results they can be narrowed without searching again
+        if self.showing_results() && (!self.entries.is_empty() || !self.refine_query.is_empty()) {
+            search_area = search_area.push(
+                row![
+                    text_input(tr("Refine results (use -term to exclude)..."), &self.refine_query)
+                        .on_input(Message::RefineInputChanged)
+                        .padding(8)
+                        .size(14)
+                ]
+                .padding([0, 10]),
+            );
+        }
+
+        // The toolbar rows as the user arranged them; the rest go in the More menu
+        let toolbar = &self.preferences.toolbar;
+        let nav_controls = toolbar.navigation.iter().fold(row![], |controls, &tool| controls.push(self.tool_widget(tool)))
+            .spacing(10)
+            .padding(10);
+        let action_controls = toolbar.actions.iter().fold(row![], |controls, &tool| controls.push(self.tool_widget(tool)))
+            .spacing(10)
+            .padding(10);
+
+        let overflow = toolbar.overflow();
+        let action_controls = if overflow.is_empty() {
+            action_controls
+        } else {
+            action_controls.push(
+                pick_list(overflow, None::<MenuItem>, Message::MenuItemChosen)
+                    .placeholder(tr("⋯ More"))
+                    .padding(10),
+            )
+        };
+
+        // Custom commands run on the selection
+        let action_controls = if self.preferences.custom_commands.is_empty() {
+            action_controls
+        } else {
+            action_controls.push(
+                pick_list(self.preferences.custom_commands.clone(), None::<commands::CustomCommand>, Message::RunCustomCommand)
+                    .placeholder(tr("🛠 Tools"))
+                    .padding(10),
+            )
+        };
+
+        // Plugin actions run on the selection, or the folder if nothing is selected
+        let plugin_actions = plugins::actions(&self.plugins);
+        let action_controls = if plugin_actions.is_empty() {
+            action_controls
+        } else {
+            action_controls.push(
+                pick_list(plugin_actions, None::<plugins::ActionRef>, Message::RunPluginAction)
+                    .placeholder(tr("🧩 Plugins"))
+                    .padding(10),
+            )
+        };
+
+        let text_size = self.list_text_size();
+        let row_padding = self.density.row_padding();
+
+        // File list header, clicking a column sorts by it
+        let header_button = |label: &str, ascending: SortMode, descending: SortMode, message: Message, portion: u16| {
+            let arrow = if self.sort_mode == ascending {
+                " ▲"
+            } else if self.sort_mode == descending {
+                " ▼"
+            } else {
+                ""
+            };
+
+            button(text(format!("{}{}", label, arrow)).size(text_size))
+                .on_press(message)
+                .padding(0)
+                .width(Length::FillPortion(portion))
+                .style(theme::Button::Text)
+        };
+
+        let header_row = row![
+            header_button(tr("Name"), SortMode::NameAsc, SortMode::NameDesc, Message::SortByName, 3),
+            header_button(tr("Size"), SortMode::SizeAsc, SortMode::SizeDesc, Message::SortBySize, 1),
+            text(tr("Type")).size(text_size).width(Length::FillPortion(2)),
+            header_button(tr("Modified"), SortMode::DateAsc, SortMode::DateDesc, Message::SortByDate, 2)
+        ];
+        // Inside a repository each entry's status gets a column too
+        let header_row = if self.git_status.is_some() {
+            header_row.push(text(tr("Git")).size(text_size).width(Length::Fixed(GIT_COLUMN_WIDTH)))
+        } else {
+            header_row
+        };
+        // Plugin columns show once their values arrive
+        let plugin_labels = self.plugins.iter().flat_map(|plugin| plugin.columns.iter()).take(self.plugin_values.len());
+        let header_row = plugin_labels
+            .fold(header_row, |header_row, column| {
+                header_row.push(text(&column.label).size(text_size).width(Length::FillPortion(1)))
+            })
+            // Matches the inner padding of the row buttons below
+            .padding(row_padding + 5)
+            .spacing(10);
+
+        let shown = self.shown_entries();
+        let file_list = if self.showing_results() {
+            // Search results get their own list showing where each match lives
+            let results_header = row![
+                header_button(tr("Name"), SortMode::NameAsc, SortMode::NameDesc, Message::SortByName, 3),
+                text(tr("Folder")).size(text_size).width(Length::FillPortion(3)),
+                header_button(tr("Size"), SortMode::SizeAsc, SortMode::SizeDesc, Message::SortBySize, 1),
+                header_button(tr("Modified"), SortMode::DateAsc, SortMode::DateDesc, Message::SortByDate, 2),
+                Space::with_width(Length::Fixed(REVEAL_BUTTON_WIDTH))
+            ]
+            .padding(row_padding + 5)
+            .spacing(10);
+
+            shown.iter().take(self.visible_limit).fold(
+                column![results_header].spacing(self.density.row_spacing()),
+                |column, entry| column.push(self.result_row(entry)),
+            )
+        } else {
+            // File list with improved styling
+            shown.iter().take(self.visible_limit).fold(
+                column![header_row].spacing(self.density.row_spacing()),
+                |column, entry| {
+                    let path = &entry.path;
+                    let is_selected = self
+                        .selected_entry
+                        .as_ref()
+                        .is_some_and(|selected| selected == path);
+                
+                    let name = entry.display_name();
+                
+                    let icon = self.preferences.icon_set.glyph(entry.icon);
+                
+                    let date_text = self.preferences.date_format.format(entry.modified, false);
+                
+                    let mut name_cell = row![highlighted_name(icon, &name, self.live_filter(), text_size)]
+                        .spacing(8)
+                        .align_items(iced::Alignment::Center)
+                        .width(Length::FillPortion(3));
+
+                    if entry.has_lossy_name() {
+                        name_cell = name_cell.push(lossy_name_badge(text_size));
+                    }
+
+                    if entry.cloud {
+                        name_cell = name_cell.push(cloud_badge(text_size));
+                    }
+
+                    if self.starred.contains(&entry.path) {
+                        name_cell = name_cell.push(text("⭐").size(text_size * 0.75));
+                    }
+
+                    if let Some(note) = self.notes.get(&entry.path) {
+                        name_cell = name_cell.push(note_badge(note, text_size));
+                    }
+
+                    for name in self.tags.of(&entry.path, &entry.tags) {
+                        name_cell = name_cell.push(tag_dot(name, self.tags.color(name), text_size));
+                    }
+
+                    if let Some(badge) = self.git_status.as_ref().and_then(|status| status.badges.get(path)) {
+                        name_cell = name_cell.push(
+                            text(badge.label())
+                                .size(text_size * 0.75)
+                                .style(theme::Text::Color(git_badge_color(*badge))),
+                        );
+                    }
+
+                    let file_row = row![
+                        name_cell,
+                        self.size_cell(entry, text_size),
+                        text(&entry.file_type).size(text_size).width(Length::FillPortion(2)),
+                        text(date_text).size(text_size).width(Length::FillPortion(2))
+                    ];
+                    let file_row = match &self.git_status {
+                        Some(status) => {
+                            let letter = status.badges.get(path).map(|badge| {
+                                text(badge.letter()).size(text_size).style(theme::Text::Color(git_badge_color(*badge)))
+                            });
+                            file_row.push(letter.unwrap_or_else(|| text("")).width(Length::Fixed(GIT_COLUMN_WIDTH)))
+                        }
+                        None => file_row,
+                    };
+                    let file_row = self.plugin_values.iter().fold(file_row, |file_row, values| {
+                        let value = values.get(path).cloned().unwrap_or_default();
+                        file_row.push(text(value).size(text_size).width(Length::FillPortion(1)))
+                    })
+                    .spacing(10)
+                    .padding(row_padding)
+                    .width(Length::Fill);
+                
+                    let file_button = button(file_row)
+                        .width(Length::Fill)
+                        .on_press(Message::FileSelected(path.clone()))
+                        .style(if is_selected {
+                            theme::Button::Primary
+                        } else {
+                            theme::Button::Text
+                        });
+                
+                    column.push(file_button)
+                },
+            )
+        };
+
+        let hidden_rows = shown.len().saturating_sub(self.visible_limit);
+        let file_list = if hidden_rows > 0 {
+            file_list.push(
+                button(text(fill(tr("Show more ({} not shown)"), &[&hidden_rows])).size(text_size))
+                    .on_press(Message::ShowMore)
+                    .padding(row_padding)
+                    .style(theme::Button::Secondary),
+            )
+        } else {
+            file_list
+        };
+
+        // Create scrollable with updated API
+        // Say why a folder that could not be read shows nothing
+        let file_list = match &self.load_error {
+            Some(failure) if !self.showing_results() => file_list.push(self.unreadable_notice(failure)),
+            _ => file_list,
+        };
+        // Rows still to come show as placeholders below those already in
+        let file_list = if self.loading && hidden_rows == 0 {
+            file_list.push(self.loading_rows(text_size, row_padding))
+        } else {
+            file_list
+        };
+
+        let files_scrollable = scrollable(file_list)
+            .id(file_list_id())
+            .height(Length::Fill)
+            .width(Length::Fill);
+
+        // Status bar showing item count and zoom level
+        let spinner = SPINNER_FRAMES[self.spinner_frame];
+        let mut count_text = match (&self.active_search, self.loading) {
+            _ if self.recent.is_some() && self.loading => {
+                fill(tr("{} Looking for recent changes… {} files so far"), &[&spinner, &self.entries.len()])
+            }
+            _ if self.recent.is_some() => fill(tr("{} recently modified files"), &[&self.entries.len()]),
+            _ if self.starred_view && !self.loading => fill(tr("{} starred items"), &[&self.entries.len()]),
+            (Some(query), true) => fill(tr("{} Searching for '{}'… {} matches so far"), &[&spinner, &query.matcher.pattern(), &self.entries.len()]),
+            (Some(query), false) => fill(tr("{} matches for '{}'"), &[&self.entries.len(), &query.matcher.pattern()]),
+            (None, true) => fill(tr("{} Loading… {} entries so far"), &[&spinner, &self.entries.len()]),
+            (None, false) if let Some(error) = &self.load_error => fill(tr("Could not read this folder: {}"), &[error]),
+            (None, false) if self.load_stopped => fill(tr("{} items listed before loading was stopped"), &[&self.entries.len()]),
+            (None, false) => match self.live_filter() {
+                Some(filter) => fill(tr("{} of {} items match '{}'"), &[&shown.len(), &self.entries.len(), &filter]),
+                None => fill(tr("{} items"), &[&self.entries.len()]),
+            },
+        };
+
+        if self.showing_results() && !self.refinement.is_empty() {
+            count_text = fill(tr("{} · {} shown after refining"), &[&count_text, &shown.len()]);
+        }
+
+        if let Some(status) = &self.git_status {
+            count_text = format!("{} · ⎇ {}", count_text, status.branch);
+            match status.ahead_behind {
+                Some((0, 0)) => count_text = fill(tr("{} (up to date)"), &[&count_text]),
+                Some((ahead, behind)) => count_text = format!("{} ↑{} ↓{}", count_text, ahead, behind),
+                None => {}
+            }
+        }
+
+        if let Some(space) = self.disk_space {
+            let units = self.preferences.size_units;
+            count_text = fill(tr("{} · 💾 {} free of {}"), &[&count_text, &units.format(space.free), &units.format(space.total)]);
+        }
+
+        if self.indexing {
+            count_text = fill(tr("{} · Indexing…"), &[&count_text]);
+        }
+
+        for mount in &self.offline {
+            count_text = fill(tr("{} · 📴 {} offline"), &[&count_text, &mount.display()]);
+        }
+
+        if let Some((server, _)) = &self.lan_share {
+            count_text = fill(tr("{} · 📡 Sharing at {}"), &[&count_text, &server.url]);
+        }
+
+        if !self.downloading.is_empty() {
+            count_text = fill(tr("{} · ☁ Downloading {} file(s)…"), &[&count_text, &self.downloading.len()]);
+        }
+
+        if let Some(message) = &self.print_message {
+            count_text = format!("{} · {}", count_text, message);
+        }
+
+        if let Some(message) = &self.plugin_message {
+            count_text = format!("{} · 🧩 {}", count_text, message);
+        }
+
+        let mut status_row = row![
+            text(fill(tr("{} · Zoom {}%"), &[&count_text, &format!("{:.0}", self.preferences.zoom * 100.0)]))
+                .size(14)
+                .width(Length::Fill)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        if self.notices.log_len() > 0 {
+            status_row = status_row.push(
+                button(text(fill(tr("⚠ {} error(s)"), &[&self.notices.log_len()])).size(14))
+                    .on_press(Message::ShowErrorLog)
+                    .padding(4)
+                    .style(theme::Button::Text),
+            );
+        }
+
+        if self.loading {
+            status_row = status_row.push(
+                button(text(tr("⏹ Stop (Esc)")).size(14))
+                    .on_press(Message::StopSearch)
+                    .padding(4)
+                    .style(theme::Button::Destructive),
+            );
+        }
+
+        if !self.tags.defined.is_empty() {
+            let filters: Vec<TagFilter> =
+                std::iter::once(TagFilter::All).chain(self.tags.defined.iter().map(|tag| TagFilter::Tagged(tag.name.clone()))).collect();
+            status_row = status_row.push(
+                pick_list(filters, Some(self.tag_filter.clone()), Message::TagFilterChanged)
+                    .text_size(14)
+                    .padding(4),
+            );
+        }
+
+        let status_bar = container(
+            status_row.push(
+                checkbox(tr("Hidden files"), self.show_hidden, Message::HiddenFilesToggled)
+                    .size(14)
+                    .text_size(14)
+            )
+            .push(
+                checkbox(tr("Folder sizes"), self.preferences.folder_sizes, Message::FolderSizesToggled)
+                    .size(14)
+                    .text_size(14)
+            )
+            .push(
+                checkbox(tr("Index"), self.preferences.index_enabled, Message::IndexToggled)
+                    .size(14)
+                    .text_size(14)
+            )
+            .push(
+                checkbox(tr("Use rg/fd"), self.preferences.external_search, Message::ExternalSearchToggled)
+                    .size(14)
+                    .text_size(14)
+            )
+            .push(
+                checkbox(tr("OCR"), self.preferences.ocr_search, Message::OcrSearchToggled)
+                    .size(14)
+                    .text_size(14)
+            )
+        )
+        .width(Length::Fill)
+        .padding(5)
+        .style(theme::Container::Box);
+
+        // Main content layout
+        let content = if self.trash.is_some() {
+            self.trash_view()
+        } else if self.image.is_some() {
+            self.image_view()
+        } else if self.usage.is_some() {
+            self.usage_view()
+        } else if self.cleanup.is_some() {
+            self.cleanup_view()
+        } else if self.largest.is_some() {
+            self.largest_view()
+        } else {
+            column![
+                title,
+                search_area,
+                row![
+                    column![nav_controls].width(Length::FillPortion(1)),
+                    column![action_controls].width(Length::FillPortion(1))
+                ],
+                horizontal_rule(1),
+                files_scrollable,
+                self.operations_panel(),
+                status_bar
+            ]
+            .spacing(5)
+            .padding(20)
+            .into()
+        };
+
+        // Recent errors sit above whatever is shown until they time out
+        let content: Element<Message> = if self.notices.toasts().is_empty() {
+            content
+        } else {
+            column![self.toasts(), content].into()
+        };
+        let content: Element<Message> = match &self.banner {
+            Some(banner) => column![banner_row(banner), content].into(),
+            None => content,
+        };
+
+        // Main container
+        let main_content = container(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(theme::Container::Box);
+
+        // If we have a dialog active, create an overlay
+        match &self.dialog {
+            DialogState::None => main_content.into(),
+            DialogState::Create => self.create_dialog(),
+            DialogState::Preview => self.preview_dialog(),
+            DialogState::Properties => self.properties_dialog(),
+            DialogState::Editor => self.editor_dialog(),
+            DialogState::SaveSearch => self.save_search_dialog(),
+            DialogState::DeleteEmpty => self.delete_empty_dialog(),
+            DialogState::Settings => self.settings_dialog(),
+            DialogState::Overwrite => self.overwrite_dialog(),
+            DialogState::Trash => self.trash_dialog(),
+            DialogState::Lan => self.lan_dialog(),
+            DialogState::OpenWith => self.open_with_dialog(),
+            DialogState::CommandOutput => self.command_output_dialog(),
+            DialogState::ErrorLog => self.error_log_dialog(),
+            DialogState::Log => self.log_dialog(),
+            DialogState::History => self.history_dialog(),
+            DialogState::Git => self.git_dialog(),
+            DialogState::Transfer => self.transfer_dialog(),
+            DialogState::Crypt => self.crypt_dialog(),
+            DialogState::ConvertImages => self.convert_images_dialog(),
+            DialogState::Signature => self.signature_dialog(),
+            DialogState::Checksums => self.checksums_dialog(),
+            DialogState::Tags => self.tags_dialog(),
+            DialogState::Palette => self.palette_dialog(),
+        }
+    }
+}
+
+// Helper methods for FileManager
+impl FileManager {
+    // Switches to `dir`, leaving any search results behind
+    fn navigate_to(&mut self, dir: PathBuf) -> Command<Message> {
+        tracing::info!(dir = %dir.display(), "navigate");
+        self.current_dir = dir;
+        self.selected_entry = None;
+        self.reveal = None;
+        self.active_search = None;
+        self.recent = None;
+        self.starred_view = false;
+        self.apply_folder_view();
+        self.search_query.clear();
+        self.clear_refinement();
+        self.search_error = None;
+        self.plugin_values.clear();
+        self.plugin_message = None;
+        self.print_message = None;
+        self.refresh()
+    }
+
+    // What is wrong with the dates typed for a custom date filter
+    fn date_problem(&self) -> Option<String> {
+        if self.date_preset != search::DatePreset::Custom {
+            return None;
+        }
+        search::DateRange::from_days(&self.date_from, &self.date_until).err()
+    }
+
+    // Builds a query from the search controls and starts it; an empty query
+    // goes back to the plain listing
+    fn perform_search(&mut self) -> Command<Message> {
+        self.clear_refinement();
+        self.recent = None;
+        self.starred_view = false;
+
+        // The date row already says what is wrong with the dates
+        let modified = match self.date_preset {
+            search::DatePreset::Custom => match search::DateRange::from_days(&self.date_from, &self.date_until) {
+                Ok(range) => range,
+                Err(_) => return Command::none(),
+            },
+            preset => preset.range(),
+        };
+
+        // A date filter alone lists everything modified in that range
+        let query = self.search_query.trim();
+        if query.is_empty() && modified.is_none() {
+            self.active_search = None;
+        } else {
+            let scope = self.search_scope;
+            let syntax = if self.use_regex {
+                search::Syntax::Regex
+            } else if self.use_fuzzy {
+                search::Syntax::Fuzzy
+            } else {
+                search::Syntax::Plain
+            };
+            match search::Query::new(query, syntax, scope, &self.tags) {
+                Ok(mut query) => {
+                    query.modified = modified;
+                    query.ocr = self.preferences.ocr_search && scope == search::Scope::Contents;
+                    // Uninstalled since the setting was turned on; said once,
+                    // as the setting is turned off with it
+                    if query.ocr && !ocr::is_available() {
+                        query.ocr = false;
+                        self.preferences.ocr_search = false;
+                        self.save_preferences();
+                        self.notices.push(tr("Text in images is not searched, as tesseract isn't installed").to_string());
+                    }
+                    // Fuzzy name matches are ranked; anything else keeps the column sort
+                    let ranked = query.scope == search::Scope::Names && syntax == search::Syntax::Fuzzy;
+                    if ranked {
+                        self.sort_mode = SortMode::Relevance;
+                    } else if self.sort_mode == SortMode::Relevance {
+                        self.sort_mode = self.folder_view(&self.current_dir).sort_mode;
+                    }
+                    self.active_search = Some(query);
+                }
+                Err(error) => {
+                    self.search_error = Some(error);
+                    return Command::none();
+                }
+            }
+        }
+        self.reload()
+    }
+
+    // Rebuilds the exclusion rules after the pattern list changed
+    fn apply_excludes(&mut self) -> Command<Message> {
+        self.save_preferences();
+        self.excludes = Excludes::new(self.preferences.skip_ignored, &self.preferences.exclude_patterns, &self.preferences.hide_patterns);
+        self.refresh_index(self.index.clone())
+    }
+
+    fn clear_refinement(&mut self) {
+        self.refine_query.clear();
+        self.refinement = search::Refinement::default();
+    }
+
+    // Text typed in the search box narrows the plain listing right away;
+    // Enter turns it into a recursive search instead
+    fn live_filter(&self) -> Option<&str> {
+        let filter = self.search_query.trim();
+        (!self.showing_results() && !filter.is_empty()).then_some(filter)
+    }
+
+    // Search results and the recent and starred lists are shown alike:
+    // with a folder column, refinable and stoppable
+    fn showing_results(&self) -> bool {
+        self.active_search.is_some() || self.recent.is_some() || self.starred_view
+    }
+
+    // Where recently modified files are looked for: the indexed folders if
+    // indexing is on, otherwise the home folder
+    fn recent_roots(&self) -> Vec<PathBuf> {
+        if self.preferences.index_enabled && !self.preferences.index_roots.is_empty() {
+            self.preferences.index_roots.clone()
+        } else {
+            vec![self.home_dir.clone()]
+        }
+    }
+
+    // Entries the list shows: everything, or the search results that pass
+    // the refinement
+    fn shown_entries(&self) -> Vec<&FileEntry> {
+        let tagged = |entry: &&FileEntry| match &self.tag_filter {
+            TagFilter::All => true,
+            TagFilter::Tagged(name) => self.tags.of(&entry.path, &entry.tags).contains(name),
+        };
+        if let Some(filter) = self.live_filter() {
+            return self
+                .entries
+                .iter()
+                .filter(|entry| search::find_ignore_case(&entry.display_name(), filter).is_some())
+                .filter(tagged)
+                .collect();
+        }
+        if !self.showing_results() || self.refinement.is_empty() {
+            return self.entries.iter().filter(tagged).collect();
+        }
+        self.entries
+            .iter()
+            .filter(|entry| {
+                let relative = entry.path.strip_prefix(&self.current_dir).unwrap_or(&entry.path);
+                self.refinement.accepts(relative)
+            })
+            .filter(tagged)
+            .collect()
+    }
+
+    // Scrolls the list to the entry being revealed, showing more pages when
+    // it lies beyond them. Rows are about the same height, so its place in
+    // the list says where to scroll.
+    fn scroll_to_revealed(&mut self) -> Command<Message> {
+        let Some(path) = self.reveal.take() else {
+            return Command::none();
+        };
+        let shown = self.shown_entries();
+        let Some(index) = shown.iter().position(|entry| entry.path == path) else {
+            return Command::none();
+        };
+        let rows = shown.len();
+        if index >= self.visible_limit {
+            self.visible_limit = (index / PAGE_SIZE + 1) * PAGE_SIZE;
+        }
+        let rows = rows.min(self.visible_limit);
+        let y = if rows > 1 { index as f32 / (rows - 1) as f32 } else { 0.0 };
+        scrollable::snap_to(file_list_id(), scrollable::RelativeOffset { x: 0.0, y })
+    }
+
+    // The tags of `path`, as listed or else read from it
+    fn entry_tags(&self, path: &Path) -> Vec<String> {
+        let on_file = self.entries.iter().find(|entry| entry.path == path).map_or_else(|| tags::read(path), |entry| entry.tags.clone());
+        self.tags.of(path, &on_file).to_vec()
+    }
+
+    // Reloads after the folder changed on disk; unlike `reload` this also
+    // re-measures the folders shown here
+    fn refresh(&mut self) -> Command<Message> {
+        if let Some(command) = self.leave_missing_dir() {
+            return command;
+        }
+        let current_dir = self.current_dir.clone();
+        self.folder_sizes.retain(|path, _| path.parent() != Some(current_dir.as_path()));
+        self.folder_counts.retain(|path, _| path.parent() != Some(current_dir.as_path()));
+        self.reload()
+    }
+
+    // When the folder shown is gone, deleted or on a drive that was
+    // unplugged, moves to the nearest folder above it that still exists
+    fn leave_missing_dir(&mut self) -> Option<Command<Message>> {
+        // A dead network drive would hang the check; the probe started by
+        // the reload finds out instead
+        if mounts::network_mount(&self.current_dir).is_some() || self.current_dir.is_dir() {
+            return None;
+        }
+        let gone = self.current_dir.clone();
+        let existing = gone
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.is_dir())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| if self.home_dir.is_dir() { self.home_dir.clone() } else { fallback_home() });
+
+        tracing::warn!(gone = %gone.display(), shown = %existing.display(), "folder vanished");
+        self.notices.push(fill(tr("{} is no longer available, so {} is shown instead"), &[&gone.display(), &existing.display()]));
+        Some(self.navigate_to(existing))
+    }
+
+    // Starts streaming the current directory (or the active search),
+    // discarding the old listing along with the metadata cached in it
+    fn reload(&mut self) -> Command<Message> {
+        self.load_generation += 1;
+        self.loading = true;
+        self.load_error = None;
+        self.load_stopped = false;
+        self.entries.clear();
+        self.visible_limit = PAGE_SIZE;
+
+        let mut commands = vec![self.load_git_status()];
+        let network_mount = mounts::network_mount(&self.current_dir);
+        // Asking a drive known to be offline would only hang
+        if network_mount.as_ref().is_some_and(|mount| self.offline.contains(mount)) {
+            self.disk_space = None;
+        } else {
+            commands.push(self.load_disk_space());
+        }
+        if let Some(mount) = network_mount {
+            let generation = self.load_generation;
+            commands.push(Command::perform(
+                async move {
+                    let probed = mount.clone();
+                    let answered = run_blocking(move || mounts::answers(&probed, mounts::PROBE_TIMEOUT)).await;
+                    (mount, answered.unwrap_or(false))
+                },
+                move |(mount, answered)| Message::MountProbed(generation, mount, answered),
+            ));
+        }
+        if self.plugins.iter().any(|plugin| !plugin.columns.is_empty()) {
+            commands.push(Command::perform(plugin_columns(self.plugins.clone(), self.current_dir.clone()), |(dir, values)| {
+                Message::PluginColumnsLoaded(dir, values)
+            }));
+        }
+        Command::batch(commands)
+    }
+    
+    // Read again on every reload, so it follows navigation across mount
+    // points and what file operations freed or used
+    fn load_disk_space(&self) -> Command<Message> {
+        let dir = self.current_dir.clone();
+        Command::perform(
+            async move {
+                let read = dir.clone();
+                let space = run_blocking(move || diskspace::of(&read).ok()).await.flatten();
+                (dir, space)
+            },
+            |(dir, space)| Message::DiskSpaceLoaded(dir, space),
+        )
+    }
+
+    fn load_git_status(&self) -> Command<Message> {
+        Command::perform(git_status(self.current_dir.clone()), |(dir, status)| {
+            Message::GitStatusLoaded(dir, status)
+        })
+    }
+
+    // Re-reads the cached metadata of the listed entry at `path`, dropping it
+    // if it is gone
+    fn invalidate(&mut self, path: &Path) {
+        let Some(index) = self.entries.iter().position(|entry| entry.path == path) else {
+            return;
+        };
+        if path.symlink_metadata().is_err() {
+            self.entries.remove(index);
+            return;
+        }
+        let old = &self.entries[index];
+        let fresh = FileEntry {
+            line_matches: old.line_matches.clone(),
+            detail: old.detail.clone(),
+            ..loader::read_entry(path.to_path_buf())
+        };
+        self.entries[index] = fresh;
+    }
+
+    // `path` if it is listed and was changed or removed since, judging by
+    // its size and modification time
+    fn changed_on_disk(&self, path: &Path) -> Option<PathBuf> {
+        let largest = self.largest.iter().flat_map(|largest| &largest.files);
+        let entry = self.entries.iter().chain(largest).find(|entry| entry.path == path)?;
+        let unchanged = metadata(path).is_ok_and(|meta| {
+            meta.modified().is_ok_and(|modified| modified == entry.modified) && (meta.is_dir() || meta.len() == entry.size)
+        });
+        (!unchanged).then(|| path.to_path_buf())
+    }
+
+    // Whether `path` is a folder, from the listing when it is shown there
+    fn is_dir(&self, path: &Path) -> bool {
+        self.entries
+            .iter()
+            .find(|entry| entry.path == path)
+            .map_or_else(|| !self.is_offline(path) && path.is_dir(), |entry| entry.is_dir)
+    }
+
+    // Whether `path` is on a network drive known not to be answering
+    fn is_offline(&self, path: &Path) -> bool {
+        self.offline.iter().any(|mount| path.starts_with(mount))
+    }
+
+    // Adopts `index` and rebuilds it in the background if it is missing or no
+    // longer matches the preferences
+    fn refresh_index(&mut self, index: Option<Arc<index::Index>>) -> Command<Message> {
+        if !self.preferences.index_enabled {
+            return Command::none();
+        }
+        let roots = self.preferences.index_roots.clone();
+        let outdated = index.as_ref().is_none_or(|index| index.is_outdated(&roots, &self.excludes));
+        // A stale index still answers searches until the new one is ready
+        self.index = index;
+        if outdated && !self.indexing {
+            self.indexing = true;
+            return Command::perform(build_index(roots, self.excludes.clone()), Message::IndexBuilt);
+        }
+        Command::none()
+    }
+
+    // The index, if it can answer `query` for the current folder; hidden
+    // entries and file contents are not indexed
+    fn usable_index(&self, query: &search::Query) -> Option<Arc<index::Index>> {
+        self.index
+            .as_ref()
+            .filter(|index| {
+                query.scope == search::Scope::Names
+                    && !query.matcher.wants_hidden()
+                    && index.covers(&self.current_dir)
+            })
+            .cloned()
+    }
+
+    // Folders in the listing that have no entry in `cache` yet
+    fn pending_folders(&self, cache: &HashMap<PathBuf, u64>) -> Vec<PathBuf> {
+        if self.loading {
+            return Vec::new();
+        }
+
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_dir && !cache.contains_key(&entry.path))
+            .map(|entry| entry.path.clone())
+            .collect()
+    }
+
+    // Sorts by a column the user picked and keeps it for next time
+    fn remember_sort(&mut self) {
+        self.remember_view();
+        self.sort_entries();
+    }
+
+    // How `dir` should be shown: as it was left, or with the defaults
+    fn folder_view(&self, dir: &Path) -> views::FolderView {
+        self.folder_views.get(dir).cloned().unwrap_or_else(|| views::FolderView {
+            sort_mode: self.preferences.sort_mode.clone(),
+            density: self.preferences.density,
+            show_hidden: self.preferences.show_hidden,
+        })
+    }
+
+    fn apply_folder_view(&mut self) {
+        let view = self.folder_view(&self.current_dir);
+        self.sort_mode = view.sort_mode;
+        self.density = view.density;
+        self.show_hidden = view.show_hidden;
+    }
+
+    // Keeps the current view settings for this folder; density and hidden
+    // files also become the defaults for folders that have none of their own
+    fn remember_view(&mut self) {
+        // Ranked results and the recent list pick their own order
+        let sort_mode = match self.sort_mode {
+            SortMode::Relevance => self.folder_view(&self.current_dir).sort_mode,
+            _ if self.recent.is_some() || self.starred_view => self.folder_view(&self.current_dir).sort_mode,
+            ref sort_mode => sort_mode.clone(),
+        };
+        let view = views::FolderView {
+            sort_mode,
+            density: self.density,
+            show_hidden: self.show_hidden,
+        };
+
+        self.preferences.density = view.density;
+        self.preferences.show_hidden = view.show_hidden;
+        self.save_preferences();
+
+        if self.recent.is_none() && !self.starred_view {
+            let remembered = self.folder_views.remember(&self.current_dir, view);
+            self.report(remembered, tr("Could not save the folder view").to_string());
+        }
+    }
+
+    fn sort_entries(&mut self) {
+        // Without grouping, folders sort among the files
+        let dirs_first = self.preferences.dirs_first;
+        match self.sort_mode {
+            SortMode::Relevance => {
+                // Sort by fuzzy score, then by shorter path
+                let Some(query) = &self.active_search else {
+                    return;
+                };
+                let root = &self.current_dir;
+                self.entries.sort_by_cached_key(|entry| {
+                    let relative = entry.path.strip_prefix(root).unwrap_or(&entry.path);
+                    (Reverse(query.matcher.score(relative)), relative.as_os_str().len())
+                });
+            },
+            ref mode => entry::sort(&mut self.entries, mode, dirs_first),
+        }
+    }
+
+    // Fills the settings dialog's inputs from the preferences
+    fn reset_settings_inputs(&mut self) {
+        self.settings_start_dir = self
+            .preferences
+            .start_dir
+            .as_ref()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        self.settings_exclude.clear();
+        self.settings_hide.clear();
+        self.settings_profile_name.clear();
+        self.settings_error = None;
+        self.cache_notice = None;
+    }
+
+    // Adopts the preferences of another profile: its language, exclusions,
+    // window and start folder
+    fn switch_preferences(&mut self, preferences: Preferences) -> Command<Message> {
+        i18n::set_language(preferences.language);
+        self.excludes = Excludes::new(preferences.skip_ignored, &preferences.exclude_patterns, &preferences.hide_patterns);
+        self.preview_cache.set_budget(preferences.preview_memory_mb);
+        let window = preferences.window;
+        let start_dir = preferences.start_dir.clone().filter(|dir| dir.is_dir()).unwrap_or_else(|| self.home_dir.clone());
+        self.preferences = preferences;
+        self.reset_settings_inputs();
+
+        let mut commands = vec![
+            self.navigate_to(start_dir),
+            iced::window::resize(iced::Size::new(window.width.max(MIN_WINDOW_SIZE.0), window.height.max(MIN_WINDOW_SIZE.1))),
+        ];
+        if let Some((x, y)) = window.position {
+            commands.push(iced::window::move_to(x, y));
+        }
+        commands.push(self.refresh_index(self.index.clone()));
+        Command::batch(commands)
+    }
+
+    // Shows a failed operation as a toast and keeps it in the error log;
+    // hands back the value when it worked
+    fn report<T, E: std::fmt::Display + 'static>(&mut self, result: Result<T, E>, what: String) -> Option<T> {
+        self.report_at(result, what, None)
+    }
+
+    // Like `report`, keeping the path and any OS error for the details view
+    fn report_at<T, E: std::fmt::Display + 'static>(&mut self, result: Result<T, E>, what: String, path: Option<&Path>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                let mut diagnostic = Diagnostic::new(format!("{}: {}", what, error));
+                if let Some(io_error) = (&error as &dyn std::any::Any).downcast_ref::<std::io::Error>() {
+                    diagnostic = diagnostic.with_io(io_error);
+                }
+                if let Some(path) = path {
+                    diagnostic = diagnostic.at(path);
+                }
+                self.notices.push(diagnostic);
+                None
+            }
+        }
+    }
+
+    fn save_preferences(&mut self) {
+        let saved = settings::save(&self.preferences);
+        self.report(saved, tr("Could not save settings").to_string());
+    }
+
+    // Queues a copy, or asks about a move first as it takes the originals
+    fn queue_transfer(&mut self, operation: jobs::Operation) -> Command<Message> {
+        match operation {
+            jobs::Operation::Move { .. } => self.confirm(operation),
+            operation => {
+                self.jobs.push(operation);
+                self.dialog = DialogState::None;
+                Command::none()
+            }
+        }
+    }
+
+    // Lists what `operation` would touch and asks before queueing it
+    fn confirm(&mut self, operation: jobs::Operation) -> Command<Message> {
+        let roots = operation.sources().to_vec();
+        self.changed_on_disk = if operation.is_delete() {
+            roots.iter().filter_map(|path| self.changed_on_disk(path)).collect()
+        } else {
+            Vec::new()
+        };
+        self.guarded = roots
+            .iter()
+            .filter(|path| guard::is_protected(path, &self.home_dir, self.preferences.protected_depth))
+            .cloned()
+            .collect();
+        self.guard_input.clear();
+        self.pending = Some(operation);
+        self.preview = None;
+        self.dialog = DialogState::Preview;
+        Command::perform(
+            async move {
+                let walked = roots.clone();
+                (roots, run_blocking(move || jobs::preview(&walked)).await)
+            },
+            |(roots, preview)| Message::PreviewLoaded(roots, preview),
+        )
+    }
+
+    // Whether the names of any critical folders in the open delete or move
+    // have been typed
+    fn guard_satisfied(&self) -> bool {
+        self.guarded.is_empty() || self.guard_input.trim() == guard::confirmation_text(&self.guarded)
+    }
+
+    // Adds a change to the history, saying so if it can't be written
+    fn remember_change(
+        &mut self,
+        action: history::Action,
+        path: PathBuf,
+        target: Option<PathBuf>,
+        size: Option<u64>,
+        outcome: history::Outcome,
+    ) {
+        let record = history::Record::new(action, path, target, size, outcome);
+        self.report(history::append(&record), tr("Could not write the history").to_string());
+    }
+
+    // One history record per source of a finished job; the size is only
+    // known for the job as a whole, so it goes with a single source
+    fn remember_job(&mut self, operation: &jobs::Operation, size: Option<u64>, outcome: history::Outcome) {
+        let sources = operation.sources();
+        let size = size.filter(|_| sources.len() == 1);
+        for source in sources {
+            let (action, target) = match operation {
+                jobs::Operation::Copy { target, .. } => (history::Action::Copy, source.file_name().map(|name| target.join(name))),
+                jobs::Operation::Move { target, .. } => (history::Action::Move, source.file_name().map(|name| target.join(name))),
+                jobs::Operation::Delete { .. } => (history::Action::Delete, None),
+                jobs::Operation::Archive { archive, .. } => (history::Action::Compress, Some(archive.clone())),
+                jobs::Operation::Encrypt { output, .. } => (history::Action::Encrypt, Some(output.clone())),
+                jobs::Operation::Decrypt { output, .. } => (history::Action::Decrypt, Some(output.clone())),
+                jobs::Operation::Extract { target, .. } => (history::Action::Extract, Some(target.clone())),
+                jobs::Operation::ConvertImages { target, .. } => (history::Action::Convert, target.clone()),
+            };
+            self.remember_change(action, source.clone(), target, size, outcome.clone());
+        }
+    }
+
+    // Shows what is in disk image `path`, starting at its root
+    fn open_image(&mut self, path: PathBuf) -> Command<Message> {
+        tracing::info!(path = %path.display(), "browse image");
+        self.image = Some(ImageState { path: path.clone(), image: None, dirs: Vec::new(), entries: None });
+        Command::perform(
+            async move {
+                let opened = path.clone();
+                let listed = run_blocking(move || iso::open(&opened).and_then(|image| Ok((image.list(&image.root)?, image))))
+                    .await
+                    .unwrap_or_else(|| Err(io::Error::other(tr("The command was interrupted"))))
+                    .map(|(entries, image)| (image, entries))
+                    .map_err(|error| error.to_string());
+                (path, listed)
+            },
+            |(path, listed)| Message::ImageOpened(path, listed),
+        )
+    }
+
+    // Reads the folder of the image being looked through
+    fn list_image_dir(&mut self) -> Command<Message> {
+        let Some(state) = &mut self.image else {
+            return Command::none();
+        };
+        let (Some(Ok(image)), Some(dir)) = (state.image.clone(), state.dir().cloned()) else {
+            return Command::none();
+        };
+        state.entries = None;
+        Command::perform(
+            async move {
+                let listed = dir.clone();
+                let entries = run_blocking(move || image.list(&listed).map_err(|error| error.to_string()))
+                    .await
+                    .unwrap_or_else(|| Err(tr("The command was interrupted").to_string()));
+                (dir, entries)
+            },
+            |(dir, entries)| Message::ImageListed(dir, entries),
+        )
+    }
+
+    // Moves the selected file or folder to the trash
+    fn trash_selected(&mut self) -> Command<Message> {
+        self.dialog = DialogState::None;
+        let Some(path) = self.selected_entry.clone() else {
+            return Command::none();
+        };
+        tracing::info!(path = %path.display(), "trash");
+        let trashed = trash::put(&path);
+        self.remember_change(history::Action::Trash, path.clone(), trashed.as_ref().ok().cloned(), None, history::Outcome::of(&trashed));
+        if self.report_at(trashed, fill(tr("Could not move {} to the trash"), &[&path.display()]), Some(&path)).is_some() {
+            self.forget_removed(&path);
+            self.selected_entry = None;
+        }
+        self.refresh()
+    }
+
+    // Drops something no longer on disk from the usage and largest-files views
+    fn forget_removed(&mut self, path: &Path) {
+        if let Some(root) = self.usage.as_mut().and_then(|usage| usage.root.as_mut()) {
+            root.remove(path);
+        }
+        if let Some(largest) = &mut self.largest {
+            largest.files.retain(|entry| entry.path != path);
+        }
+    }
+
+    // Creates the file or folder named in the create dialog
+    fn create_entry(&mut self) -> Command<Message> {
+        let path = self.current_dir.join(&self.new_name);
+        tracing::info!(path = %path.display(), folder = self.is_directory, "create");
+        let created = if self.is_directory { create_dir_all(&path) } else { File::create(&path).map(|_| ()) };
+        self.remember_change(history::Action::Create, path.clone(), None, None, history::Outcome::of(&created));
+        self.report_at(created, fill(tr("Could not create {}"), &[&path.display()]), Some(&path));
+
+        self.dialog = DialogState::None;
+        self.refresh()
+    }
+
+    // Size column text; folders show their size or item count once measured
+    fn size_text(&self, entry: &FileEntry) -> String {
+        let path = &entry.path;
+        if entry.is_dir {
+            let size = self.folder_sizes.get(path).filter(|_| self.preferences.folder_sizes);
+            match (size, self.folder_counts.get(path)) {
+                (Some(size), _) => self.preferences.size_units.format(*size),
+                (None, Some(1)) => tr("1 item").to_string(),
+                (None, Some(count)) => fill(tr("{} items"), &[&count]),
+                (None, None) => "…".to_string(),
+            }
+        } else {
+            self.preferences.size_units.format(entry.size)
+        }
+    }
+
+    // The toolbar control for `tool`, styled by whether it applies to the selection
+    fn tool_widget<'a>(&self, tool: Tool) -> Element<'a, Message> {
+        let file_selected = self.selected_entry.as_ref().is_some_and(|path| !self.is_dir(path));
+        let (message, style) = match tool {
+            Tool::Recent => {
+                return pick_list(&recent::Window::ALL[..], self.recent, Message::ShowRecent)
+                    .placeholder(tr("🕘 Recent"))
+                    .padding(10)
+                    .width(Length::Fill)
+                    .into();
+            }
+            Tool::New => (Message::CreateNew, theme::Button::Primary),
+            Tool::Delete if self.selected_entry.is_some() => (Message::Delete, theme::Button::Destructive),
+            Tool::DeletePermanently if self.selected_entry.is_some() => (Message::DeletePermanently, theme::Button::Destructive),
+            Tool::Share if self.selected_entry.is_some() => (Message::Share, theme::Button::Primary),
+            Tool::Print if file_selected && self.selected_entry.as_deref().is_some_and(print::can_print) => (Message::Print, theme::Button::Primary),
+            Tool::Star if self.selected_entry.is_some() => (Message::ToggleStar, theme::Button::Primary),
+            Tool::Starred if self.starred_view => (Message::ShowStarred, theme::Button::Primary),
+            Tool::Tags if self.selected_entry.is_some() => (Message::ShowTags, theme::Button::Primary),
+            Tool::Encrypt if file_selected => (Message::OpenCrypt(Crypt::Encrypt), theme::Button::Primary),
+            Tool::Decrypt if self.selected_entry.as_deref().is_some_and(crypt::is_encrypted) => {
+                (Message::OpenCrypt(Crypt::Decrypt), theme::Button::Primary)
+            }
+            Tool::CopyPath if self.selected_entry.is_some() => (Message::CopySelection(CopyAs::Path), theme::Button::Primary),
+            Tool::CopyName if self.selected_entry.is_some() => (Message::CopySelection(CopyAs::Name), theme::Button::Primary),
+            Tool::CopyUri if self.selected_entry.is_some() => (Message::CopySelection(CopyAs::Uri), theme::Button::Primary),
+            Tool::ConvertImages if self.selected_entry.is_some() => (Message::OpenConvertImages, theme::Button::Primary),
+            Tool::VerifyChecksums if file_selected && self.selected_entry.as_deref().is_some_and(manifest::is_manifest) => {
+                (Message::VerifyChecksums, theme::Button::Primary)
+            }
+            Tool::VerifySignature if file_selected && self.selected_entry.as_deref().and_then(signature::pair).is_some() => {
+                (Message::VerifySignature, theme::Button::Primary)
+            }
+            Tool::ShareLan if self.lan_share.is_some() => (Message::ShareOverLan, theme::Button::Primary),
+            Tool::Properties if self.selected_entry.is_some() => (Message::ShowProperties, theme::Button::Primary),
+            Tool::Edit if file_selected => (Message::Edit, theme::Button::Primary),
+            Tool::OpenWith if file_selected => (Message::OpenWith, theme::Button::Primary),
+            Tool::Git if file_selected && self.git_status.is_some() => (Message::ShowGit, theme::Button::Primary),
+            tool => (tool_message(tool), theme::Button::Secondary),
+        };
+
+        button(
+            row![text(tool.to_string()).horizontal_alignment(Horizontal::Center)]
+                .width(Length::Fill)
+                .align_items(iced::Alignment::Center)
+        )
+        .on_press(message)
+        .padding(10)
+        .width(Length::Fill)
+        .style(style)
+        .into()
+    }
+
+    // The size column cell; hovering it shows the exact byte count once known
+    fn size_cell<'a>(&self, entry: &FileEntry, text_size: f32) -> Element<'a, Message> {
+        let cell = text(self.size_text(entry)).size(text_size).width(Length::FillPortion(1));
+        let bytes = if entry.is_dir {
+            self.folder_sizes.get(&entry.path).filter(|_| self.preferences.folder_sizes).copied()
+        } else {
+            Some(entry.size)
+        };
+        match bytes {
+            Some(bytes) => tooltip(cell, settings::exact_bytes(bytes), tooltip::Position::FollowCursor)
+                .style(theme::Container::Box)
+                .into(),
+            None => cell.into(),
+        }
+    }
+
+    // A row of the search results: clicking opens the entry, the button
+    // jumps to the folder holding it
+    fn result_row<'a>(&self, entry: &FileEntry) -> Element<'a, Message> {
+        let text_size = self.list_text_size();
+        let path = &entry.path;
+        let is_selected = self.selected_entry.as_ref().is_some_and(|selected| selected == path);
+
+        let name = entry.display_name();
+        let icon = self.preferences.icon_set.glyph(entry.icon);
+
+        let folder = path
+            .parent()
+            .map(|parent| parent.strip_prefix(&self.current_dir).unwrap_or(parent))
+            .map(|relative| relative.display().to_string())
+            .filter(|relative| !relative.is_empty())
+            .unwrap_or_else(|| ".".to_string());
+
+        let mut name_cell = row![text(format!("{} {}", icon, name)).size(text_size)]
+            .spacing(8)
+            .align_items(iced::Alignment::Center)
+            .width(Length::FillPortion(3));
+        if entry.has_lossy_name() {
+            name_cell = name_cell.push(lossy_name_badge(text_size));
+        }
+
+        let result_row = row![
+            name_cell,
+            text(folder).size(text_size).width(Length::FillPortion(3)),
+            self.size_cell(entry, text_size),
+            text(self.preferences.date_format.format(entry.modified, false))
+                .size(text_size)
+                .width(Length::FillPortion(2))
+        ]
+        .spacing(10)
+        .width(Length::Fill);
+
+        let mut details = column![result_row].spacing(2).padding(self.density.row_padding());
+        if let Some(detail) = &entry.detail {
+            details = details.push(
+                text(detail)
+                    .size(text_size * 0.8)
+                    .font(Font::MONOSPACE)
+                    .style(theme::Text::Color(Color::from_rgb(0.45, 0.45, 0.45))),
+            );
+        }
+
+        // Content matches are listed under the row that holds them
+        let details = entry.line_matches.iter().fold(
+            details,
+            |column, line_match| {
+                column.push(
+                    text(format!("{:>6}: {}", line_match.line, line_match.text))
+                        .size(text_size * 0.8)
+                        .font(Font::MONOSPACE)
+                        .style(theme::Text::Color(Color::from_rgb(0.45, 0.45, 0.45))),
+                )
+            },
+        );
+
+        let open_button = button(details)
+            .width(Length::Fill)
+            .on_press(Message::FileSelected(path.clone()))
+            .style(if is_selected {
+                theme::Button::Primary
+            } else {
+                theme::Button::Text
+            });
+
+        let reveal_button = button(text(tr("📂 Show in folder")).size(text_size * 0.8))
+            .on_press(Message::RevealInFolder(path.clone()))
+            .padding(6)
+            .width(Length::Fixed(REVEAL_BUTTON_WIDTH))
+            .style(theme::Button::Secondary);
+
+        row![open_button, reveal_button]
+            .spacing(10)
+            .align_items(iced::Alignment::Center)
+            .into()
+    }
+
+    fn list_text_size(&self) -> f32 {
+        (BASE_TEXT_SIZE * self.preferences.zoom).round()
+    }
+
+    fn set_zoom(&mut self, zoom: f32) {
+        // Round to one decimal so repeated steps don't drift
+        let zoom = (settings::clamp_zoom(zoom) * 10.0).round() / 10.0;
+        if zoom != self.preferences.zoom {
+            self.preferences.zoom = zoom;
+            self.save_preferences();
+        }
+    }
+
+    fn create_dialog<'a>(&self) -> Element<'a, Message> {
+        // Create a semi-transparent overlay
+        let overlay = container(
+            // Dialog content
+            container(
+                column![
+                    text(tr("Create New")).size(24),
+                    Space::with_height(Length::Fixed(10.0)),
+                    text_input(tr("Enter name..."), &self.new_name)
+                        .on_input(Message::NameInputChanged)
+                        .padding(10),
+                    row![
+                        checkbox(tr("Is Directory"), self.is_directory, Message::IsDirectoryToggled)
+                    ]
+                    .padding(10),
+                    Space::with_height(Length::Fixed(10.0)),
+                    row![
+                        button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                            .on_press(Message::CloseDialog)
+                            .padding(10)
+                            .width(Length::Fixed(100.0))
+                            .style(theme::Button::Secondary),
+                        button(text(tr("Create")).horizontal_alignment(Horizontal::Center))
+                            .on_press(Message::ConfirmCreate)
+                            .padding(10)
+                            .width(Length::Fixed(100.0))
+                            .style(theme::Button::Primary)
+                    ]
+                    .spacing(10)
+                    .align_items(iced::Alignment::Center)
+                ]
+                .spacing(20)
+                .padding(20)
+                .width(Length::Fixed(400.0))
+                .align_items(iced::Alignment::Center)
+            )
+            .width(Length::Fixed(400.0))
+            .padding(20)
+            .center_x()
+            .center_y()
+            .style(theme::Container::Box)
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .style(overlay_style);
+
+        overlay.into()
+    }
+
+    fn save_search_dialog<'a>(&self) -> Element<'a, Message> {
+        let scope = self.search_scope.to_string().to_lowercase();
+        let syntax = if self.use_regex {
+            tr(", regex")
+        } else if self.use_fuzzy {
+            tr(", fuzzy")
+        } else {
+            ""
+        };
+        let summary = fill(
+            tr("'{}' in {} ({}{})"),
+            &[&self.search_query.trim(), &self.current_dir.display(), &scope, &syntax],
+        );
+
+        // Existing searches can be removed from here
+        let saved_list = self.preferences.saved_searches.iter().fold(
+            column![].spacing(5),
+            |column, saved| {
+                column.push(
+                    row![
+                        text(&saved.name).size(14).width(Length::Fill),
+                        button(text(tr("Remove")).size(14))
+                            .on_press(Message::ForgetSavedSearch(saved.name.clone()))
+                            .padding(4)
+                            .style(theme::Button::Destructive)
+                    ]
+                    .spacing(10)
+                    .align_items(iced::Alignment::Center),
+                )
+            },
+        );
+
+        let save_button = button(text(tr("Save")).horizontal_alignment(Horizontal::Center))
+            .padding(10)
+            .width(Length::Fixed(100.0))
+            .style(theme::Button::Primary);
+        let save_button = if self.saved_search_name.trim().is_empty() {
+            save_button
+        } else {
+            save_button.on_press(Message::ConfirmSaveSearch)
+        };
+
+        // Create a semi-transparent overlay
+        let overlay = container(
+            // Dialog content
+            container(
+                column![
+                    text(tr("Save Search")).size(24),
+                    text(summary).size(14),
+                    text_input(tr("Name..."), &self.saved_search_name)
+                        .on_input(Message::SavedSearchNameChanged)
+                        .on_submit(Message::ConfirmSaveSearch)
+                        .padding(10),
+                    saved_list,
+                    row![
+                        button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                            .on_press(Message::CloseDialog)
+                            .padding(10)
+                            .width(Length::Fixed(100.0))
+                            .style(theme::Button::Secondary),
+                        save_button
+                    ]
+                    .spacing(10)
+                    .align_items(iced::Alignment::Center)
+                ]
+                .spacing(20)
+                .padding(20)
+                .width(Length::Fixed(400.0))
                 .align_items(iced::Alignment::Center)
+            )
+            .width(Length::Fixed(400.0))
+            .padding(20)
+            .center_x()
+            .center_y()
+            .style(theme::Container::Box)
         )
-        .on_press(Message::Refresh)
-        .padding(10)
         .width(Length::Fill)
-        .style(theme::Button::Secondary);
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .style(overlay_style);
+
+        overlay.into()
+    }
+
+    fn settings_dialog<'a>(&self) -> Element<'a, Message> {
+        let heading = |label: &str| text(label.to_string()).size(16);
+        let removable = |label: String, message: Message| {
+            row![
+                text(label).size(14).width(Length::Fill),
+                button(text(tr("Remove")).size(14))
+                    .on_press(message)
+                    .padding(4)
+                    .style(theme::Button::Destructive)
+            ]
+            .spacing(10)
+            .align_items(iced::Alignment::Center)
+        };
+
+        let start_row = row![
+            text_input(&self.home_dir.display().to_string(), &self.settings_start_dir)
+                .on_input(Message::StartDirChanged)
+                .on_submit(Message::CloseSettings)
+                .padding(8)
+                .size(14),
+            button(text(tr("Use current folder")).size(14))
+                .on_press(Message::UseCurrentAsStart)
+                .padding(8)
+                .style(theme::Button::Secondary)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let index_roots = self.preferences.index_roots.iter().fold(column![].spacing(5), |column, root| {
+            column.push(removable(root.display().to_string(), Message::RemoveIndexRoot(root.clone())))
+        });
+
+        let exclude_patterns = self.preferences.exclude_patterns.iter().fold(column![].spacing(5), |column, pattern| {
+            column.push(removable(pattern.clone(), Message::RemoveExcludePattern(pattern.clone())))
+        });
+
+        let association_list = self.preferences.associations.iter().fold(column![].spacing(5), |column, (ext, command)| {
+            column.push(removable(format!(".{} → {}", ext, command), Message::RemoveAssociation(ext.clone())))
+        });
+
+        let hide_patterns = self.preferences.hide_patterns.iter().fold(column![].spacing(5), |column, pattern| {
+            column.push(removable(pattern.clone(), Message::RemoveHidePattern(pattern.clone())))
+        });
+
+        let hide_row = row![
+            text_input(tr("Name or glob, e.g. *.pyc"), &self.settings_hide)
+                .on_input(Message::HideInputChanged)
+                .on_submit(Message::AddHidePattern)
+                .padding(8)
+                .size(14),
+            button(text(tr("Add")).size(14))
+                .on_press(Message::AddHidePattern)
+                .padding(8)
+                .style(theme::Button::Secondary)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let exclude_row = row![
+            text_input(tr("Name or glob, e.g. target or *.log"), &self.settings_exclude)
+                .on_input(Message::ExcludeInputChanged)
+                .on_submit(Message::AddExcludePattern)
+                .padding(8)
+                .size(14),
+            button(text(tr("Add")).size(14))
+                .on_press(Message::AddExcludePattern)
+                .padding(8)
+                .style(theme::Button::Secondary)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let toggles = column![
+            checkbox(tr("Show hidden files"), self.show_hidden, Message::HiddenFilesToggled).size(16),
+            checkbox(tr("Show folder sizes"), self.preferences.folder_sizes, Message::FolderSizesToggled).size(16),
+            checkbox(tr("Skip ignored files"), self.preferences.skip_ignored, Message::SkipIgnoredToggled).size(16),
+            checkbox(tr("Keep a search index"), self.preferences.index_enabled, Message::IndexToggled).size(16),
+            checkbox(tr("Search with ripgrep/fd when installed"), self.preferences.external_search, Message::ExternalSearchToggled)
+                .size(16),
+            checkbox(tr("Search text in images and scanned PDFs (tesseract)"), self.preferences.ocr_search, Message::OcrSearchToggled)
+                .size(16),
+            checkbox(tr("Ask before replacing an existing file"), self.preferences.confirm_overwrite, Message::ConfirmOverwriteToggled)
+                .size(16),
+            checkbox(tr("Ask before moving to the trash"), self.preferences.confirm_trash, Message::ConfirmTrashToggled).size(16)
+        ]
+        .spacing(8);
+
+        let custom_commands = self.preferences.custom_commands.iter().fold(column![].spacing(5), |column, command| {
+            column.push(removable(format!("{}: {}", command.name, command.template), Message::RemoveCustomCommand(command.name.clone())))
+        });
+
+        let command_row = row![
+            text_input(tr("Name"), &self.settings_command_name)
+                .on_input(Message::CommandNameChanged)
+                .padding(8)
+                .size(14)
+                .width(Length::FillPortion(1)),
+            text_input(tr("Command, e.g. convert {path} {stem}.png"), &self.settings_command_template)
+                .on_input(Message::CommandTemplateChanged)
+                .on_submit(Message::AddCustomCommand)
+                .padding(8)
+                .size(14)
+                .width(Length::FillPortion(2)),
+            button(text(tr("Add")).size(14))
+                .on_press(Message::AddCustomCommand)
+                .padding(8)
+                .style(theme::Button::Secondary)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let shown_dir = |dir: Option<PathBuf>| dir.map(|dir| dir.display().to_string()).unwrap_or_else(|| tr("Unknown").to_string());
+        let mut storage = column![
+            text(fill(tr("Settings: {}"), &[&shown_dir(paths::config_dir())])).size(13),
+            text(fill(tr("Data: {}"), &[&shown_dir(paths::data_dir())])).size(13),
+            text(fill(tr("Caches: {}"), &[&shown_dir(paths::cache_dir())])).size(13),
+            text(fill(tr("Log: {}"), &[&shown_dir(logging::log_dir())])).size(13),
+            row![
+                button(text(tr("Clear caches")).size(14))
+                    .on_press(Message::ClearCaches)
+                    .padding(8)
+                    .style(theme::Button::Secondary),
+                button(text(tr("View log")).size(14))
+                    .on_press(Message::ShowLog)
+                    .padding(8)
+                    .style(theme::Button::Secondary)
+            ]
+            .spacing(10)
+        ]
+        .spacing(6);
+        if let Some(notice) = &self.cache_notice {
+            storage = storage.push(text(notice).size(13));
+        }
+
+        let warning = Color::from_rgb(0.8, 0.2, 0.2);
+        let shortcuts = keymap::Action::ALL.into_iter().fold(column![].spacing(6), |column, action| {
+            let binding = row![
+                text(action.to_string()).size(14).width(Length::Fill),
+                text_input("", self.preferences.keymap.chords_text(action))
+                    .on_input(move |chords| Message::KeymapChanged(action, chords))
+                    .padding(6)
+                    .size(14)
+                    .width(Length::Fixed(240.0))
+            ]
+            .spacing(10)
+            .align_items(iced::Alignment::Center);
+
+            self.preferences.keymap.problems(action).into_iter().fold(column.push(binding), |column, problem| {
+                column.push(text(format!("⚠ {}", problem)).size(13).style(theme::Text::Color(warning)))
+            })
+        });
+
+        // One line per tool: which row it sits on, and arrows to order it there
+        let toolbar_rows = Tool::ALL.into_iter().fold(column![].spacing(6), |column, tool| {
+            let place = self.preferences.toolbar.place(tool);
+            let arrow = |label: &'static str, offset: isize| {
+                let arrow = button(text(label).size(14)).padding([4, 8]).style(theme::Button::Secondary);
+                if place == Place::Overflow {
+                    arrow
+                } else {
+                    arrow.on_press(Message::ToolShifted(tool, offset))
+                }
+            };
+            column.push(
+                row![
+                    text(tool.to_string()).size(14).width(Length::Fill),
+                    pick_list(&Place::ALL[..], Some(place), move |place| Message::ToolPlaced(tool, place))
+                        .padding(6)
+                        .text_size(14),
+                    arrow("◀", -1),
+                    arrow("▶", 1)
+                ]
+                .spacing(10)
+                .align_items(iced::Alignment::Center),
+            )
+        });
+
+        let profile_row = row![
+            text(tr("Profile")).size(16).width(Length::Fill),
+            pick_list(settings::profiles(), Some(settings::Profile(settings::profile())), Message::ProfileSelected)
+                .padding(6)
+                .text_size(14)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let new_profile_row = row![
+            text_input(tr("New profile, e.g. work"), &self.settings_profile_name)
+                .on_input(Message::ProfileNameChanged)
+                .on_submit(Message::CreateProfile)
+                .padding(8)
+                .size(14),
+            button(text(tr("Create")).size(14))
+                .on_press(Message::CreateProfile)
+                .padding(8)
+                .style(theme::Button::Secondary)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let language_row = row![
+            text(tr("Language")).size(16).width(Length::Fill),
+            pick_list(&Language::ALL[..], Some(self.preferences.language), Message::LanguageSelected)
+                .padding(6)
+                .text_size(14)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let theme_row = row![
+            text(tr("Theme")).size(16).width(Length::Fill),
+            pick_list(&ThemeChoice::ALL[..], Some(self.preferences.theme), Message::ThemeSelected)
+                .padding(6)
+                .text_size(14)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let scale_row = row![
+            text(fill(tr("Interface scale {}%"), &[&format!("{:.0}", self.preferences.ui_scale * 100.0)]))
+                .size(16)
+                .width(Length::Fill),
+            slider(
+                settings::MIN_UI_SCALE..=settings::MAX_UI_SCALE,
+                self.preferences.ui_scale,
+                Message::UiScaleChanged
+            )
+            .step(0.05)
+            .on_release(Message::UiScaleReleased)
+            .width(Length::Fixed(200.0))
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let date_row = row![
+            text(tr("Show dates as")).size(16).width(Length::Fill),
+            pick_list(&DateFormat::ALL[..], Some(self.preferences.date_format), Message::DateFormatSelected)
+                .padding(6)
+                .text_size(14)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let units_row = row![
+            text(tr("Show sizes in")).size(16).width(Length::Fill),
+            pick_list(&SizeUnits::ALL[..], Some(self.preferences.size_units), Message::SizeUnitsSelected)
+                .padding(6)
+                .text_size(14)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let sort_row = row![
+            text(tr("Sort new folders by")).size(16).width(Length::Fill),
+            pick_list(&SortMode::COLUMNS[..], Some(self.preferences.sort_mode.clone()), Message::DefaultSortSelected)
+                .padding(6)
+                .text_size(14)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let depths: Vec<usize> = (0..=guard::MAX_PROTECTED_DEPTH).collect();
+        let protected_row = row![
+            text(tr("Ask to type the name of folders this close to the root")).size(16).width(Length::Fill),
+            pick_list(depths, Some(self.preferences.protected_depth), Message::ProtectedDepthSelected)
+                .padding(6)
+                .text_size(14)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let sizes = previews::CACHE_SIZES_MB.map(previews::CacheSize).to_vec();
+        let preview_rows = column![
+            row![
+                text(tr("Previews kept in memory")).size(16).width(Length::Fill),
+                pick_list(sizes.clone(), Some(previews::CacheSize(self.preferences.preview_memory_mb)), Message::PreviewMemorySelected)
+                    .padding(6)
+                    .text_size(14)
+            ]
+            .spacing(10)
+            .align_items(iced::Alignment::Center),
+            row![
+                text(tr("Previews kept on disk")).size(16).width(Length::Fill),
+                pick_list(sizes, Some(previews::CacheSize(self.preferences.preview_disk_mb)), Message::PreviewDiskSelected)
+                    .padding(6)
+                    .text_size(14)
+            ]
+            .spacing(10)
+            .align_items(iced::Alignment::Center)
+        ]
+        .spacing(8);
+
+        let mut dialog = column![
+            text(tr("Settings")).size(24),
+            profile_row,
+            new_profile_row,
+            language_row,
+            theme_row,
+            scale_row,
+            date_row,
+            units_row,
+            sort_row,
+            checkbox(tr("List folders before files"), self.preferences.dirs_first, Message::DirsFirstToggled).size(16),
+            heading(tr("Start in (empty for home)")),
+            start_row,
+            heading(tr("Indexed folders")),
+            index_roots,
+            button(text(tr("Add current folder")).size(14))
+                .on_press(Message::AddIndexRoot)
+                .padding(8)
+                .style(theme::Button::Secondary),
+            heading(tr("Always hide")),
+            hide_patterns,
+            hide_row,
+            heading(tr("Never search or index")),
+            exclude_patterns,
+            exclude_row,
+            heading(tr("Open with")),
+            if self.preferences.associations.is_empty() {
+                column![text(tr("Tick \"Always use this app\" in Open With to add a rule")).size(13)]
+            } else {
+                association_list
+            },
+            heading(tr("Tools")),
+            text(tr("Commands run on the selected file; use {path}, {name}, {stem}, {ext} or {dir}")).size(13),
+            custom_commands,
+            command_row,
+            heading(tr("Share with (empty for the mail client)")),
+            text_input(tr("Command; the files go where it says {}"), &self.preferences.share_command)
+                .on_input(Message::ShareCommandChanged)
+                .padding(8)
+                .size(14),
+            toggles,
+            protected_row,
+            heading(tr("Storage")),
+            preview_rows,
+            storage,
+            heading(tr("Toolbar")),
+            text(tr("Tools on neither row are listed under ⋯ More")).size(13),
+            toolbar_rows,
+            heading(tr("Keyboard shortcuts")),
+            text(tr("Separate several shortcuts with commas, e.g. F5, Ctrl+R")).size(13),
+            shortcuts
+        ]
+        .spacing(12)
+        .padding(20)
+        .width(Length::Fixed(520.0));
+
+        if let Some(error) = &self.settings_error {
+            dialog = dialog.push(text(error).size(14).style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))));
+        }
+
+        dialog = dialog.push(
+            button(text(tr("Done")).horizontal_alignment(Horizontal::Center))
+                .on_press(Message::CloseSettings)
+                .padding(10)
+                .width(Length::Fixed(100.0))
+                .style(theme::Button::Primary),
+        );
+
+        // Create a semi-transparent overlay
+        let overlay = container(
+            container(scrollable(dialog))
+                .width(Length::Fixed(520.0))
+                .max_height(640.0)
+                .padding(20)
+                .center_x()
+                .center_y()
+                .style(theme::Container::Box)
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .style(overlay_style);
+
+        overlay.into()
+    }
+
+    // The paths a delete or move would touch, each of which can be left out
+    fn preview_dialog<'a>(&self) -> Element<'a, Message> {
+        let Some(operation) = &self.pending else {
+            return text(tr("Nothing to confirm")).into();
+        };
+        let units = self.preferences.size_units;
+
+        let mut content = column![text(fill(tr("{}?"), &[operation])).size(24)].spacing(10);
+        let mut confirm = button(text(if operation.is_delete() { tr("Delete") } else { tr("Move") }).horizontal_alignment(Horizontal::Center))
+            .padding(10)
+            .width(Length::Fixed(100.0))
+            .style(if operation.is_delete() { theme::Button::Destructive } else { theme::Button::Primary });
+
+        match &self.preview {
+            None => content = content.push(text(fill(tr("{} Listing what would change…"), &[&SPINNER_FRAMES[self.spinner_frame]])).size(14)),
+            Some(preview) => {
+                let (count, size) = preview.affected();
+                content = content.push(text(fill(tr("{} items, {} in total"), &[&count, &units.format(size)])).size(16));
+                if operation.is_delete() {
+                    content = content.push(text(tr("This action cannot be undone.")).size(14));
+                }
+                content = content.push(self.changed_warning());
+                if !self.guarded.is_empty() {
+                    let shown: Vec<String> = self.guarded.iter().map(|path| path.display().to_string()).collect();
+                    let expected = guard::confirmation_text(&self.guarded);
+                    content = content.push(
+                        text(fill(tr("⚠ {} is a system, home or top-level folder."), &[&shown.join(", ")]))
+                            .size(14)
+                            .style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))),
+                    );
+                    content = content.push(
+                        text_input(&fill(tr("Type {} to go ahead"), &[&expected]), &self.guard_input)
+                            .on_input(Message::GuardInputChanged)
+                            .padding(8)
+                            .size(14),
+                    );
+                }
+                content = content.push(text(tr("Untick anything that should stay where it is.")).size(13));
+
+                let holders_included = preview.holders_included();
+                let list = preview.items.iter().enumerate().fold(column![].spacing(2), |list, (index, item)| {
+                    let name = if item.depth == 0 {
+                        item.path.display().to_string()
+                    } else {
+                        item.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+                    };
+                    let icon = if item.is_dir { "📁" } else { "📄" };
+                    let label = format!("{} {}", icon, name);
+                    // Inside an unticked folder nothing can be ticked back
+                    let tick: Element<Message> = if holders_included[index] {
+                        checkbox(label, item.included, move |included| Message::PreviewToggled(index, included))
+                            .size(14)
+                            .text_size(13)
+                            .into()
+                    } else {
+                        text(label).size(13).style(theme::Text::Color(Color::from_rgb(0.5, 0.5, 0.5))).into()
+                    };
+                    list.push(
+                        row![
+                            Space::with_width(Length::Fixed(item.depth as f32 * 18.0)),
+                            tick,
+                            Space::with_width(Length::Fill),
+                            text(units.format(item.size)).size(13)
+                        ]
+                        .spacing(6)
+                        .align_items(iced::Alignment::Center),
+                    )
+                });
+                content = content.push(container(scrollable(list)).max_height(360.0).width(Length::Fill));
+                if preview.is_truncated() {
+                    content = content.push(
+                        text(fill(tr("Only the first {} of {} items are listed."), &[&preview.items.len(), &preview.total_items])).size(13),
+                    );
+                }
+                if self.guard_satisfied() {
+                    confirm = confirm.on_press(Message::ConfirmPreview);
+                }
+            }
+        }
+
+        content = content.push(
+            row![
+                button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary),
+                confirm
+            ]
+            .spacing(10),
+        );
+
+        container(container(content).width(Length::Fixed(640.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    fn toasts<'a>(&self) -> Element<'a, Message> {
+        let error_color = Color::from_rgb(0.8, 0.2, 0.2);
+        self.notices
+            .toasts()
+            .iter()
+            .fold(column![].spacing(6).padding([10, 20, 0, 20]), |column, toast| {
+                let mut line = row![text(format!("⚠ {}", toast.message))
+                    .size(14)
+                    .width(Length::Fill)
+                    .style(theme::Text::Color(error_color))]
+                .spacing(10)
+                .align_items(iced::Alignment::Center);
+                if toast.diagnostic.has_details() {
+                    line = line.push(
+                        button(text(tr("Details")).size(14))
+                            .on_press(Message::ShowErrorDetails(toast.id))
+                            .padding([2, 8])
+                            .style(theme::Button::Text),
+                    );
+                }
+                line = line.push(
+                    button(text("✕").size(14))
+                        .on_press(Message::DismissToast(toast.id))
+                        .padding([2, 8])
+                        .style(theme::Button::Text),
+                );
+                column.push(container(line).padding(8).width(Length::Fill).style(theme::Container::Box))
+            })
+            .into()
+    }
+
+    // The queued and finished jobs, folded away to one line on request
+    fn operations_panel<'a>(&self) -> Element<'a, Message> {
+        if self.jobs.all().is_empty() {
+            return column![].into();
+        }
+
+        let arrow = if self.operations_collapsed { "▸" } else { "▾" };
+        let mut header = row![
+            button(text(fill(tr("{} Operations ({} pending)"), &[&arrow, &self.jobs.pending()])).size(14))
+                .on_press(Message::OperationsToggled)
+                .padding(4)
+                .style(theme::Button::Text),
+            Space::with_width(Length::Fill)
+        ]
+        .align_items(iced::Alignment::Center);
+        if self.jobs.all().iter().any(jobs::Job::is_finished) {
+            header = header.push(
+                button(text(tr("Clear finished")).size(14))
+                    .on_press(Message::ClearFinishedJobs)
+                    .padding(4)
+                    .style(theme::Button::Secondary),
+            );
+        }
+
+        let mut panel = column![header].spacing(6);
+        if !self.operations_collapsed {
+            panel = self.jobs.all().iter().fold(panel, |panel, job| {
+                let mut line = row![
+                    text(job.operation.to_string()).size(14).width(Length::FillPortion(3)),
+                    text(job.status(self.preferences.size_units)).size(13).width(Length::FillPortion(2))
+                ]
+                .spacing(10)
+                .align_items(iced::Alignment::Center);
+                if job.state == jobs::JobState::Running && job.operation.can_pause() {
+                    let (label, paused) = if job.is_paused() { (tr("Resume"), false) } else { (tr("Pause"), true) };
+                    line = line.push(
+                        button(text(label).size(13))
+                            .on_press(Message::PauseJob(job.id, paused))
+                            .padding(4)
+                            .style(theme::Button::Secondary),
+                    );
+                }
+                if job.can_roll_back() {
+                    line = line.push(
+                        button(text(fill(tr("↩ Undo {} finished move(s)"), &[&job.moved.len()])).size(13))
+                            .on_press(Message::RollBackJob(job.id))
+                            .padding(4)
+                            .style(theme::Button::Secondary),
+                    );
+                }
+                if !job.is_finished() {
+                    line = line.push(
+                        button(text(tr("Cancel")).size(13))
+                            .on_press(Message::CancelJob(job.id))
+                            .padding(4)
+                            .style(theme::Button::Destructive),
+                    );
+                }
+                let panel = panel.push(line).push(progress_bar(0.0..=1.0, job.fraction()).height(Length::Fixed(6.0)));
+                match &job.problem {
+                    Some(problem) => panel.push(self.problem_row(job.id, problem)),
+                    None => panel,
+                }
+            });
+        }
+
+        container(scrollable(panel).height(Length::Shrink))
+            .max_height(220.0)
+            .width(Length::Fill)
+            .padding(8)
+            .style(theme::Container::Box)
+            .into()
+    }
+
+    // The end of a list that is still loading: how far it got, and a few
+    // blank rows standing in for the entries on their way
+    fn loading_rows<'a>(&self, text_size: f32, row_padding: u16) -> Element<'a, Message> {
+        let label = fill(tr("{} Loading… {} entries so far"), &[&SPINNER_FRAMES[self.spinner_frame], &self.entries.len()]);
+        (0..SKELETON_ROWS).fold(
+            column![text(label).size(text_size * 0.85)].spacing(self.density.row_spacing()).padding(row_padding),
+            |column, row| {
+                // Each placeholder is a little shorter, so they read as rows
+                let width = Length::FillPortion(8 - row as u16 * 2);
+                column.push(
+                    row![
+                        container(Space::with_height(Length::Fixed(text_size * 0.8))).width(width).style(skeleton_style),
+                        Space::with_width(Length::FillPortion(2 + row as u16 * 2))
+                    ]
+                    .spacing(10),
+                )
+            },
+        )
+        .into()
+    }
+
+    fn unreadable_notice<'a>(&self, failure: &loader::Failure) -> Element<'a, Message> {
+        let (title, detail) = match failure {
+            loader::Failure::Denied => (
+                tr("🔒 Permission denied").to_string(),
+                tr("Your account is not allowed to see what is in this folder. Copying, moving or deleting inside it can be retried as administrator from the Operations panel."),
+            ),
+            loader::Failure::Offline(mount) => (
+                fill(tr("📴 {} is offline"), &[&mount.display()]),
+                tr("The network drive isn't answering. Refresh once it is reachable again."),
+            ),
+            loader::Failure::Other(error) => (fill(tr("⚠ Could not read this folder: {}"), &[error]), ""),
+        };
+
+        container(
+            column![
+                text(title).size(18),
+                text(detail).size(14),
+                button(text(tr("⬆️ Up")).size(14)).on_press(Message::NavigateUp).padding(8).style(theme::Button::Secondary)
+            ]
+            .spacing(10)
+            .align_items(iced::Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(30)
+        .center_x()
+        .into()
+    }
+
+    // A failure a job is waiting on, with the choices for going on
+    fn problem_row<'a>(&self, id: u64, problem: &jobs::Problem) -> Element<'a, Message> {
+        let choice = |label: &'static str, decision: jobs::Decision, style: theme::Button| {
+            button(text(tr(label)).size(13)).on_press(Message::JobDecision(id, decision)).padding(4).style(style)
+        };
+
+        let mut line = row![
+            text(format!("⚠ {}", problem.error))
+                .size(13)
+                .width(Length::Fill)
+                .style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2)))
+        ]
+        .spacing(6)
+        .align_items(iced::Alignment::Center);
+        if problem.can_retry {
+            line = line.push(choice("Retry", jobs::Decision::Retry, theme::Button::Primary));
+        }
+        if problem.can_elevate {
+            line = line.push(choice("Retry as administrator", jobs::Decision::RetryAsAdmin, theme::Button::Primary));
+        }
+        line.push(choice("Skip", jobs::Decision::Skip, theme::Button::Secondary))
+            .push(choice("Skip all", jobs::Decision::SkipAll, theme::Button::Secondary))
+            .push(choice("Abort", jobs::Decision::Abort, theme::Button::Destructive))
+            .into()
+    }
+
+    fn transfer_dialog<'a>(&self) -> Element<'a, Message> {
+        let name = self
+            .selected_entry
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let (title, action) = match self.transfer {
+            Transfer::Copy => (fill(tr("Copy '{}' to"), &[&name]), tr("Copy")),
+            Transfer::Move => (fill(tr("Move '{}' to"), &[&name]), tr("Move")),
+        };
+
+        let mut content = column![
+            text(title).size(24),
+            text_input(tr("Folder..."), &self.transfer_target)
+                .on_input(Message::TransferTargetChanged)
+                .on_submit(Message::ConfirmTransfer)
+                .padding(10)
+        ]
+        .spacing(15)
+        .padding(20)
+        .width(Length::Fixed(480.0));
+
+        if let Some(error) = &self.transfer_error {
+            content = content.push(text(error).size(14).style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))));
+        }
+
+        content = content.push(
+            row![
+                button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary),
+                button(text(action).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::ConfirmTransfer)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Primary)
+            ]
+            .spacing(10),
+        );
+
+        container(container(content).width(Length::Fixed(480.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    fn crypt_dialog<'a>(&self) -> Element<'a, Message> {
+        let name = self
+            .selected_entry
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let (title, hint, placeholder, key, action) = match (self.crypt, self.use_passphrase) {
+            (Crypt::Encrypt, true) => (
+                fill(tr("Encrypt '{}'"), &[&name]),
+                tr("Anyone who knows the passphrase can open it."),
+                tr("Passphrase"),
+                &self.passphrase,
+                tr("Encrypt"),
+            ),
+            (Crypt::Encrypt, false) => (
+                fill(tr("Encrypt '{}'"), &[&name]),
+                tr("Only the holders of these keys can open it. Give age or SSH public keys, or files listing them, separated by commas."),
+                tr("age1…, ssh-ed25519 …"),
+                &self.recipients,
+                tr("Encrypt"),
+            ),
+            (Crypt::Decrypt, true) => (
+                fill(tr("Decrypt '{}'"), &[&name]),
+                tr("The passphrase the file was encrypted with."),
+                tr("Passphrase"),
+                &self.passphrase,
+                tr("Decrypt"),
+            ),
+            (Crypt::Decrypt, false) => (
+                fill(tr("Decrypt '{}'"), &[&name]),
+                tr("The file holding your age or SSH private key."),
+                tr("Key file..."),
+                &self.identity,
+                tr("Decrypt"),
+            ),
+        };
+
+        let key = if self.use_passphrase {
+            text_input(placeholder, key).on_input(Message::CryptPassphraseChanged).password()
+        } else {
+            text_input(placeholder, key).on_input(Message::CryptKeyChanged)
+        };
+        let mut content = column![
+            text(title).size(24),
+            checkbox(tr("Use a passphrase"), self.use_passphrase, Message::CryptPassphraseToggled),
+            text(hint).size(14),
+            key.on_submit(Message::ConfirmCrypt).padding(10)
+        ]
+        .spacing(15)
+        .padding(20)
+        .width(Length::Fixed(480.0));
+
+        if self.crypt == Crypt::Encrypt && self.use_passphrase {
+            content = content.push(
+                text_input(tr("Repeat the passphrase"), &self.passphrase_again)
+                    .on_input(Message::CryptPassphraseAgainChanged)
+                    .on_submit(Message::ConfirmCrypt)
+                    .password()
+                    .padding(10),
+            );
+        }
+
+        if let Some(error) = &self.crypt_error {
+            content = content.push(text(error).size(14).style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))));
+        }
+
+        content = content.push(
+            row![
+                button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary),
+                button(text(action).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::ConfirmCrypt)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Primary)
+            ]
+            .spacing(10),
+        );
+
+        container(container(content).width(Length::Fixed(480.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    fn convert_images_dialog<'a>(&self) -> Element<'a, Message> {
+        let title = match self.conversion_sources.as_slice() {
+            [source] => fill(tr("Convert '{}'"), &[&source.file_name().map(|name| name.to_string_lossy()).unwrap_or_default()]),
+            sources => fill(tr("Convert {} images"), &[&sources.len()]),
+        };
+        let field = |label: String, input: Element<'a, Message>| {
+            row![text(label).size(14).width(Length::Fixed(140.0)), input].spacing(10).align_items(iced::Alignment::Center)
+        };
+
+        let mut content = column![
+            text(title).size(24),
+            text(tr("Converted copies are saved with a new name; the originals stay as they are.")).size(14),
+            field(
+                tr("Format").to_string(),
+                pick_list(&imaging::Format::ALL[..], Some(self.conversion_format), Message::ConversionFormatSelected)
+                    .padding(10)
+                    .width(Length::Fill)
+                    .into()
+            ),
+            field(
+                tr("Largest side").to_string(),
+                text_input(tr("Pixels; empty keeps the size"), &self.conversion_max)
+                    .on_input(Message::ConversionMaxChanged)
+                    .on_submit(Message::ConfirmConvertImages)
+                    .padding(10)
+                    .into()
+            ),
+            field(
+                tr("JPEG quality").to_string(),
+                text_input("1–100", &self.conversion_quality)
+                    .on_input(Message::ConversionQualityChanged)
+                    .on_submit(Message::ConfirmConvertImages)
+                    .padding(10)
+                    .into()
+            ),
+            field(
+                tr("Save in").to_string(),
+                text_input(tr("Beside the originals"), &self.conversion_target)
+                    .on_input(Message::ConversionTargetChanged)
+                    .on_submit(Message::ConfirmConvertImages)
+                    .padding(10)
+                    .into()
+            ),
+        ]
+        .spacing(15)
+        .padding(20)
+        .width(Length::Fixed(480.0));
+
+        if let Some(error) = &self.conversion_error {
+            content = content.push(text(error).size(14).style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))));
+        }
+
+        content = content.push(
+            row![
+                button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary),
+                button(text(tr("Convert")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::ConfirmConvertImages)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Primary)
+            ]
+            .spacing(10),
+        );
+
+        container(container(content).width(Length::Fixed(480.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    fn error_log_dialog<'a>(&self) -> Element<'a, Message> {
+        let entries = self.notices.log().fold(column![].spacing(8), |column, notice| {
+            let expanded = self.expanded_error == Some(notice.id);
+            let mut entry = row![
+                text(self.preferences.date_format.format(notice.time, true)).size(13).width(Length::Fixed(160.0)),
+                text(&notice.message).size(14).width(Length::Fill)
+            ]
+            .spacing(10);
+            if notice.diagnostic.has_details() {
+                entry = entry.push(
+                    button(text(if expanded { tr("Details ▾") } else { tr("Details ▸") }).size(13))
+                        .on_press(Message::ToggleErrorDetails(notice.id))
+                        .padding([2, 8])
+                        .style(theme::Button::Text),
+                );
+            }
+            let column = column.push(entry);
+            if expanded {
+                column.push(self.error_details(notice))
+            } else {
+                column
+            }
+        });
+
+        let mut content = column![text(tr("Error log")).size(24)].spacing(10).padding(20).width(Length::Fixed(640.0));
+        content = if self.notices.log_len() == 0 {
+            content.push(text(tr("Nothing has gone wrong so far.")).size(14))
+        } else {
+            content.push(container(scrollable(entries)).max_height(400.0).width(Length::Fill))
+        };
+
+        content = content.push(
+            row![
+                button(text(tr("Clear")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::ClearErrorLog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Destructive),
+                button(text(tr("View log")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::ShowLog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary),
+                button(text(tr("Close")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary)
+            ]
+            .spacing(10),
+        );
+
+        container(container(content).width(Length::Fixed(640.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    // The path, OS error and likely cause behind an error, with a button
+    // to copy them for a bug report
+    fn error_details<'a>(&self, notice: &notices::Notice) -> Element<'a, Message> {
+        let diagnostic = &notice.diagnostic;
+        let mut details = column![].spacing(4);
+        if let Some(path) = &diagnostic.path {
+            details = details.push(text(fill(tr("Path: {}"), &[&path.display()])).size(13));
+        }
+        if let Some(code) = diagnostic.code {
+            details = details.push(text(fill(tr("OS error code: {}"), &[&code])).size(13));
+        }
+        if let Some(kind) = diagnostic.kind {
+            details = details.push(text(fill(tr("Kind: {}"), &[&format!("{:?}", kind)])).size(13));
+        }
+        if let Some(cause) = diagnostic.cause() {
+            details = details.push(text(fill(tr("Likely cause: {}"), &[&cause])).size(13));
+        }
+        details = details.push(
+            button(text(tr("Copy details")).size(13))
+                .on_press(Message::CopyErrorDetails(notice.id))
+                .padding([4, 10])
+                .style(theme::Button::Secondary),
+        );
+        container(details).padding([4, 0, 4, 170]).width(Length::Fill).into()
+    }
+
+    // Stage, unstage or discard the selected file, with its diff below
+    fn git_dialog<'a>(&self) -> Element<'a, Message> {
+        let Some(file) = &self.git_file else {
+            return text(tr("Nothing to show")).into();
+        };
+        let badge = self.git_status.as_ref().and_then(|status| status.badges.get(&file.path)).copied();
+        let name = file.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+
+        let mut title = row![text(name).size(24)].spacing(10).align_items(iced::Alignment::Center);
+        if let Some(badge) = badge {
+            title = title.push(text(badge.label()).size(14).style(theme::Text::Color(git_badge_color(badge))));
+        }
+
+        let diff: Element<'a, Message> = match &file.diff {
+            None => text(tr("Loading…")).size(14).into(),
+            Some(Err(error)) => text(fill(tr("Could not read the changes: {}"), &[error])).size(14).into(),
+            Some(Ok(diff)) if diff.is_empty() => text(tr("No changes since the last commit.")).size(14).into(),
+            Some(Ok(diff)) => {
+                let lines = diff.lines().fold(column![], |lines, line| {
+                    let color = match line.chars().next() {
+                        Some('+') => Some(Color::from_rgb(0.2, 0.6, 0.3)),
+                        Some('-') => Some(Color::from_rgb(0.8, 0.25, 0.25)),
+                        Some('@') => Some(Color::from_rgb(0.3, 0.5, 0.8)),
+                        _ => None,
+                    };
+                    let line = text(line).size(13).font(Font::MONOSPACE);
+                    lines.push(match color {
+                        Some(color) => line.style(theme::Text::Color(color)),
+                        None => line,
+                    })
+                });
+                container(scrollable(lines)).height(Length::Fixed(400.0)).width(Length::Fill).into()
+            }
+        };
+
+        let action = |label: &str, action: git::FileAction, style: theme::Button, enabled: bool| {
+            let button = button(text(label.to_string()).horizontal_alignment(Horizontal::Center)).padding(10).style(style);
+            if enabled { button.on_press(Message::GitAction(action)) } else { button }
+        };
+        let changed = badge.is_some_and(|badge| badge != git::GitBadge::Ignored);
+        let tracked = changed && badge != Some(git::GitBadge::Untracked);
+        let discard_label = if file.confirm_discard { tr("Really discard?") } else { tr("Discard changes") };
+        let buttons = row![
+            action(tr("Stage"), git::FileAction::Stage, theme::Button::Primary, changed),
+            action(tr("Unstage"), git::FileAction::Unstage, theme::Button::Secondary, tracked),
+            action(discard_label, git::FileAction::Discard, theme::Button::Destructive, tracked),
+            Space::with_width(Length::Fill),
+            button(text(tr("Close")).horizontal_alignment(Horizontal::Center))
+                .on_press(Message::CloseDialog)
+                .padding(10)
+                .width(Length::Fixed(100.0))
+                .style(theme::Button::Secondary)
+        ]
+        .spacing(10);
+
+        let content = column![title, diff, buttons].spacing(15).padding(20).width(Length::Fixed(760.0));
+        container(container(content).width(Length::Fixed(760.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
 
-        // Action buttons with improved styling
-        let create_button = button(
-            row![text("➕ New").horizontal_alignment(Horizontal::Center)]
+    fn tags_dialog<'a>(&self) -> Element<'a, Message> {
+        let Some(path) = &self.selected_entry else {
+            return text(tr("Nothing to show")).into();
+        };
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let current = self.entry_tags(path);
+
+        // Tags made here, then any the file has from elsewhere
+        let mut names: Vec<String> = self.tags.defined.iter().map(|tag| tag.name.clone()).collect();
+        names.extend(current.iter().filter(|tagged| !names.contains(tagged)).cloned().collect::<Vec<_>>());
+        let list = names.into_iter().fold(column![].spacing(8), |list, tag| {
+            let on = current.contains(&tag);
+            let color = self.tags.color(&tag);
+            let mut line = row![
+                text("●").size(18).style(theme::Text::Color(tag_color(color))),
+                checkbox(tag.clone(), on, {
+                    let tag = tag.clone();
+                    move |on| Message::TagToggled(tag.clone(), on)
+                })
+                .size(16)
                 .width(Length::Fill)
+            ]
+            .spacing(8)
+            .align_items(iced::Alignment::Center);
+            if self.tags.defined.iter().any(|defined| defined.name == tag) {
+                line = line.push(
+                    button(text(tr("Remove tag")).size(13))
+                        .on_press(Message::RemoveTag(tag))
+                        .padding(4)
+                        .style(theme::Button::Text),
+                );
+            }
+            list.push(line)
+        });
+
+        let valid = tags::is_valid_name(&self.new_tag);
+        let mut create = button(text(tr("Add tag")).horizontal_alignment(Horizontal::Center)).padding(8).style(theme::Button::Primary);
+        if valid {
+            create = create.on_press(Message::CreateTag);
+        }
+        let mut new_tag = text_input(tr("New tag..."), &self.new_tag).on_input(Message::NewTagChanged).padding(8).width(Length::Fill);
+        if valid {
+            new_tag = new_tag.on_submit(Message::CreateTag);
+        }
+
+        let content = column![
+            text(fill(tr("Tags of '{}'"), &[&name])).size(24),
+            scrollable(list).height(Length::Shrink),
+            row![new_tag, pick_list(&tags::TagColor::ALL[..], Some(self.new_tag_color), Message::NewTagColorChanged).padding(8), create]
+                .spacing(8)
+                .align_items(iced::Alignment::Center),
+            row![
+                Space::with_width(Length::Fill),
+                button(text(tr("Close")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary)
+            ]
+        ]
+        .spacing(15);
+
+        container(container(content).width(Length::Fixed(480.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    fn signature_dialog<'a>(&self) -> Element<'a, Message> {
+        let Some(check) = &self.signature_check else {
+            return text(tr("Nothing to show")).into();
+        };
+        let name = |path: &Path| path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut content = column![
+            text(fill(tr("Signature of '{}'"), &[&name(&check.file)])).size(24),
+            text(fill(tr("Checked against {}"), &[&name(&check.signature)])).size(14)
+        ]
+        .spacing(12);
+
+        content = match &check.result {
+            None => content.push(text(tr("Checking…")).size(14)),
+            Some(Err(error)) => content.push(text(fill(tr("Could not check the signature: {}"), &[error])).size(14)),
+            Some(Ok(verification)) => {
+                let color = match verification.validity {
+                    signature::Validity::Good => Color::from_rgb(0.2, 0.6, 0.3),
+                    signature::Validity::Bad => Color::from_rgb(0.8, 0.2, 0.2),
+                    _ => Color::from_rgb(0.8, 0.55, 0.1),
+                };
+                let field = |label: &str, value: String| {
+                    row![text(label.to_string()).size(14).width(Length::Fixed(120.0)), text(value).size(14).width(Length::Fill)].spacing(10)
+                };
+                let mut content = content.push(text(verification.validity.to_string()).size(18).style(theme::Text::Color(color)));
+                if let Some(signer) = &verification.signer {
+                    content = content.push(field(tr("Signed by"), signer.clone()));
+                }
+                if let Some(key) = &verification.key {
+                    content = content.push(field(tr("Key"), key.clone()));
+                }
+                if let Some(signed) = verification.signed {
+                    content = content.push(field(tr("Signed on"), self.preferences.date_format.format(signed, true)));
+                }
+                if let Some(trust) = verification.trust {
+                    content = content.push(field(tr("Trust"), trust.to_string()));
+                }
+                content.push(
+                    container(scrollable(text(&verification.report).size(12).font(Font::MONOSPACE)))
+                        .height(Length::Fixed(160.0))
+                        .width(Length::Fill),
+                )
+            }
+        };
+
+        content = content.push(
+            row![
+                Space::with_width(Length::Fill),
+                button(text(tr("Close")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary)
+            ],
+        );
+
+        container(container(content).width(Length::Fixed(560.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    fn checksums_dialog<'a>(&self) -> Element<'a, Message> {
+        let Some(check) = &self.checksum_check else {
+            return text(tr("Nothing to show")).into();
+        };
+        let name = check.manifest.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let checked = check.outcomes.iter().flatten().count();
+        let (passed, failed) = (check.count(manifest::Outcome::Passed), checked - check.count(manifest::Outcome::Passed));
+        let summary = fill(tr("{} of {} checked · {} passed · {} failed"), &[&checked, &check.lines.len(), &passed, &failed]);
+        let summary_color = if failed > 0 {
+            Color::from_rgb(0.8, 0.2, 0.2)
+        } else if check.is_finished() {
+            Color::from_rgb(0.2, 0.6, 0.3)
+        } else {
+            Color::from_rgb(0.5, 0.5, 0.5)
+        };
+        let mut content = column![
+            text(fill(tr("Checksums in '{}'"), &[&name])).size(24),
+            text(summary).size(16).style(theme::Text::Color(summary_color))
+        ]
+        .spacing(12);
+        if !check.unreadable.is_empty() {
+            let numbers: Vec<String> = check.unreadable.iter().map(usize::to_string).collect();
+            content = content.push(text(fill(tr("Lines without a file and an MD5 or SHA-256 digest: {}"), &[&numbers.join(", ")])).size(13));
+        }
+
+        let lines = check.lines.iter().zip(&check.outcomes).enumerate().fold(column![].spacing(4), |column, (index, (line, outcome))| {
+            let (mark, label, color) = match outcome {
+                Some(manifest::Outcome::Passed) => ("✔", tr("Passed").to_string(), Color::from_rgb(0.2, 0.6, 0.3)),
+                Some(outcome) => ("✘", outcome.to_string(), Color::from_rgb(0.8, 0.2, 0.2)),
+                None if check.checking == Some(index) => ("…", tr("Checking…").to_string(), Color::from_rgb(0.5, 0.5, 0.5)),
+                None => ("·", tr("Waiting").to_string(), Color::from_rgb(0.5, 0.5, 0.5)),
+            };
+            column.push(
+                row![
+                    text(mark).size(14).style(theme::Text::Color(color)).width(Length::Fixed(20.0)),
+                    text(&line.name).size(14).width(Length::Fill),
+                    text(label).size(14).style(theme::Text::Color(color)).width(Length::Fixed(130.0))
+                ]
+                .spacing(8),
+            )
+        });
+        content = content.push(scrollable(lines).height(Length::Fixed(300.0)));
+
+        content = content.push(
+            row![
+                Space::with_width(Length::Fill),
+                button(text(if check.is_finished() { tr("Close") } else { tr("Stop") }).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary)
+            ],
+        );
+
+        container(container(content).width(Length::Fixed(600.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    // Every change the app made, newest first
+    fn history_dialog<'a>(&self) -> Element<'a, Message> {
+        let units = self.preferences.size_units;
+        let records = self.history.iter().rev().take(history::SHOWN_RECORDS).fold(column![].spacing(6), |column, record| {
+            let what = match &record.target {
+                Some(target) => format!("{} → {}", record.path.display(), target.display()),
+                None => record.path.display().to_string(),
+            };
+            let failed = matches!(record.outcome, history::Outcome::Failed(_) | history::Outcome::Cancelled);
+            let outcome = text(record.outcome.to_string()).size(13).width(Length::Fixed(150.0));
+            column.push(
+                row![
+                    text(self.preferences.date_format.format(record.system_time(), true)).size(13).width(Length::Fixed(150.0)),
+                    text(record.action.to_string()).size(13).width(Length::Fixed(90.0)),
+                    text(what).size(13).width(Length::Fill),
+                    text(record.size.map(|size| units.format(size)).unwrap_or_default()).size(13).width(Length::Fixed(80.0)),
+                    if failed { outcome.style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))) } else { outcome }
+                ]
+                .spacing(10),
+            )
+        });
+
+        let mut content = column![text(tr("History")).size(24)].spacing(10).padding(20).width(Length::Fixed(860.0));
+        content = if self.history.is_empty() {
+            content.push(text(tr("No changes have been made yet.")).size(14))
+        } else {
+            content.push(container(scrollable(records)).max_height(440.0).width(Length::Fill))
+        };
+        if let Some(notice) = &self.history_notice {
+            content = content.push(text(notice).size(13));
+        }
+
+        let mut export = button(text(tr("Export CSV")).horizontal_alignment(Horizontal::Center))
+            .padding(10)
+            .width(Length::Fixed(120.0))
+            .style(theme::Button::Secondary);
+        if !self.history.is_empty() {
+            export = export.on_press(Message::ExportHistory);
+        }
+        content = content.push(
+            row![
+                export,
+                button(text(tr("Close")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary)
+            ]
+            .spacing(10),
+        );
+
+        container(container(content).width(Length::Fixed(860.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    // The recent part of the log file, to look at or paste into a bug report
+    fn log_dialog<'a>(&self) -> Element<'a, Message> {
+        let mut content = column![text(tr("Log")).size(24)].spacing(10).padding(20).width(Length::Fixed(760.0));
+        content = match &self.log_text {
+            Err(error) => content.push(
+                text(fill(tr("Could not read the log: {}"), &[error]))
+                    .size(14)
+                    .style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))),
+            ),
+            Ok(log) if log.is_empty() => content.push(text(tr("The log is empty.")).size(14)),
+            Ok(log) => content.push(
+                container(scrollable(text(log).size(12).font(Font::MONOSPACE)))
+                    .max_height(460.0)
+                    .width(Length::Fill),
+            ),
+        };
+        content = content.push(
+            button(text(tr("Close")).horizontal_alignment(Horizontal::Center))
+                .on_press(Message::CloseDialog)
+                .padding(10)
+                .width(Length::Fixed(100.0))
+                .style(theme::Button::Secondary),
+        );
+
+        container(container(content).width(Length::Fixed(760.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    fn command_output_dialog<'a>(&self) -> Element<'a, Message> {
+        let mut content = column![].spacing(10).padding(20).width(Length::Fixed(640.0));
+
+        if let Some((name, output)) = &self.command_output {
+            content = content.push(text(fill(tr("Output of '{}'"), &[name])).size(24));
+            content = match output {
+                None => content.push(text(tr("Running…")).size(16)),
+                Some(Err(error)) => content.push(
+                    text(fill(tr("Could not run the command: {}"), &[error]))
+                        .size(14)
+                        .style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))),
+                ),
+                Some(Ok(output)) => {
+                    let status_color = if output.success { Color::from_rgb(0.3, 0.65, 0.3) } else { Color::from_rgb(0.8, 0.2, 0.2) };
+                    let mut printed = column![].spacing(8);
+                    if !output.stdout.is_empty() {
+                        printed = printed.push(text(&output.stdout).size(13).font(Font::MONOSPACE));
+                    }
+                    if !output.stderr.is_empty() {
+                        printed = printed.push(
+                            text(&output.stderr)
+                                .size(13)
+                                .font(Font::MONOSPACE)
+                                .style(theme::Text::Color(Color::from_rgb(0.85, 0.45, 0.2))),
+                        );
+                    }
+                    if output.stdout.is_empty() && output.stderr.is_empty() {
+                        printed = printed.push(text(tr("The command printed nothing.")).size(14));
+                    }
+
+                    content
+                        .push(text(&output.command_line).size(13).font(Font::MONOSPACE))
+                        .push(text(&output.status).size(14).style(theme::Text::Color(status_color)))
+                        .push(container(scrollable(printed)).max_height(360.0).width(Length::Fill))
+                }
+            };
+        }
+
+        content = content.push(
+            button(text(tr("Close")).horizontal_alignment(Horizontal::Center))
+                .on_press(Message::CloseDialog)
+                .padding(10)
+                .width(Length::Fixed(100.0))
+                .style(theme::Button::Secondary),
+        );
+
+        container(container(content).width(Length::Fixed(640.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    fn open_with_dialog<'a>(&self) -> Element<'a, Message> {
+        let path = self.selected_entry.clone().unwrap_or_default();
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+
+        let open_button = button(text(tr("Open")).horizontal_alignment(Horizontal::Center))
+            .padding(10)
+            .width(Length::Fixed(100.0))
+            .style(theme::Button::Primary);
+        let open_button = if self.open_with_command.trim().is_empty() {
+            open_button
+        } else {
+            open_button.on_press(Message::ConfirmOpenWith)
+        };
+
+        let mut content = column![
+            text(fill(tr("Open '{}' with"), &[&name])).size(24),
+            text_input(tr("Command, e.g. gimp or xterm -e lnav"), &self.open_with_command)
+                .on_input(Message::OpenWithCommandChanged)
+                .on_submit(Message::ConfirmOpenWith)
+                .padding(10),
+        ]
+        .spacing(20)
+        .padding(20)
+        .width(Length::Fixed(440.0));
+
+        // Rules are per extension, so files without one can only be opened once
+        if let Some(ext) = &self.open_with_extension {
+            content = content.push(
+                checkbox(fill(tr("Always use this app for .{} files"), &[&ext]), self.open_with_always, Message::OpenWithAlwaysToggled)
+                    .size(16),
+            );
+        }
+        if let Some(error) = &self.open_with_error {
+            content = content.push(text(error).size(14).style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))));
+        }
+        content = content.push(
+            row![
+                button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary),
+                open_button
+            ]
+            .spacing(10),
+        );
+
+        container(container(content).width(Length::Fixed(440.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    // The commands matching what was typed, best first; Enter runs the
+    // first one
+    fn palette_dialog<'a>(&self) -> Element<'a, Message> {
+        let matches = toolbar::matching(&self.palette_query);
+        let mut list = column![].spacing(4);
+        for (index, &tool) in matches.iter().take(PALETTE_ROWS).enumerate() {
+            let style = if index == 0 { theme::Button::Primary } else { theme::Button::Text };
+            list = list.push(button(text(tool.to_string())).on_press(Message::PaletteChosen(tool)).width(Length::Fill).style(style));
+        }
+        if matches.is_empty() {
+            list = list.push(text(tr("No command matches")).size(14));
+        }
+
+        let content = column![
+            text_input(tr("Type a command"), &self.palette_query)
+                .id(palette_input_id())
+                .on_input(Message::PaletteQueryChanged)
+                .on_submit(Message::SubmitPalette)
+                .padding(10),
+            list,
+            button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                .on_press(Message::CloseDialog)
+                .padding(10)
+                .width(Length::Fixed(100.0))
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(20)
+        .padding(20)
+        .width(Length::Fixed(440.0));
+
+        container(container(content).width(Length::Fixed(440.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    // Says which of the paths about to be destroyed changed since they were
+    // listed, as they may hold data the user hasn't seen
+    fn changed_warning<'a>(&self) -> Element<'a, Message> {
+        let warning = Color::from_rgb(0.85, 0.55, 0.1);
+        self.changed_on_disk
+            .iter()
+            .fold(column![].spacing(4), |column, path| {
+                column.push(
+                    text(fill(tr("⚠ {} changed on disk after the list was loaded."), &[&path.display()]))
+                        .size(14)
+                        .style(theme::Text::Color(warning)),
+                )
+            })
+            .into()
+    }
+
+    fn overwrite_dialog<'a>(&self) -> Element<'a, Message> {
+        let (name, explanation) = if self.replaced.is_empty() {
+            (self.new_name.clone(), tr("A file with this name already exists. Replacing it empties it."))
+        } else {
+            let names: Vec<String> =
+                self.replaced.iter().filter_map(|path| path.file_name()).map(|name| name.to_string_lossy().into_owned()).collect();
+            (names.join("', '"), tr("Something with this name is already there. Replacing it deletes it first, a folder with everything in it."))
+        };
+        let overlay = container(
+            container(
+                column![
+                    text(fill(tr("Replace '{}'?"), &[&name])).size(24),
+                    Space::with_height(Length::Fixed(10.0)),
+                    text(explanation).size(16),
+                    self.changed_warning(),
+                    checkbox(tr("Don't ask again"), self.dont_ask_again, Message::DontAskAgainToggled).size(16),
+                    Space::with_height(Length::Fixed(20.0)),
+                    row![
+                        button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                            .on_press(Message::CloseDialog)
+                            .padding(10)
+                            .width(Length::Fixed(100.0))
+                            .style(theme::Button::Secondary),
+                        button(text(tr("Replace")).horizontal_alignment(Horizontal::Center))
+                            .on_press(Message::ConfirmOverwrite)
+                            .padding(10)
+                            .width(Length::Fixed(100.0))
+                            .style(theme::Button::Destructive)
+                    ]
+                    .spacing(10)
+                    .align_items(iced::Alignment::Center)
+                ]
+                .spacing(20)
+                .padding(20)
+                .width(Length::Fixed(400.0))
                 .align_items(iced::Alignment::Center)
+            )
+            .width(Length::Fixed(400.0))
+            .padding(20)
+            .center_x()
+            .center_y()
+            .style(theme::Container::Box)
         )
-        .on_press(Message::CreateNew)
-        .padding(10)
         .width(Length::Fill)
-        .style(theme::Button::Primary);
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .style(overlay_style);
+
+        overlay.into()
+    }
+
+    fn lan_dialog<'a>(&self) -> Element<'a, Message> {
+        let close = button(text(tr("Close")).horizontal_alignment(Horizontal::Center))
+            .on_press(Message::CloseDialog)
+            .padding(10)
+            .width(Length::Fixed(100.0))
+            .style(theme::Button::Secondary);
+
+        let content = match &self.lan_share {
+            Some((server, code)) => {
+                let mut content = column![
+                    text(fill(tr("Sharing {}"), &[&server.root.display()])).size(24),
+                    text(tr("Open this address on a phone or computer on the same network:")).size(14),
+                    text(&server.url).size(20)
+                ]
+                .spacing(12)
+                .align_items(iced::Alignment::Center);
+                if let Some(code) = code {
+                    content = content.push(canvas(code.clone()).width(Length::Fixed(240.0)).height(Length::Fixed(240.0)));
+                }
+                content.push(
+                    row![
+                        close,
+                        button(text(tr("Stop sharing")).horizontal_alignment(Horizontal::Center))
+                            .on_press(Message::StopLanShare)
+                            .padding(10)
+                            .style(theme::Button::Destructive)
+                    ]
+                    .spacing(10),
+                )
+            }
+            None => {
+                let shared = self.selected_entry.as_ref().unwrap_or(&self.current_dir);
+                column![
+                    text(fill(tr("Share {} over the local network?"), &[&shared.display()])).size(24),
+                    text(tr("Anyone on the network who has the address can download it until sharing is stopped.")).size(14),
+                    text_input(tr("Password (optional)"), &self.lan_password)
+                        .on_input(Message::LanPasswordChanged)
+                        .on_submit(Message::StartLanShare)
+                        .padding(8)
+                        .size(14),
+                    row![
+                        close,
+                        button(text(tr("Start sharing")).horizontal_alignment(Horizontal::Center))
+                            .on_press(Message::StartLanShare)
+                            .padding(10)
+                            .style(theme::Button::Primary)
+                    ]
+                    .spacing(10)
+                ]
+                .spacing(12)
+            }
+        };
+
+        container(container(content).width(Length::Fixed(440.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
+
+    fn trash_dialog<'a>(&self) -> Element<'a, Message> {
+        let name = self
+            .selected_entry
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
 
-        let delete_button = button(
-            row![text("🗑️ Delete").horizontal_alignment(Horizontal::Center)]
-                .width(Length::Fill)
-                .align_items(iced::Alignment::Center)
-        )
-        .on_press(Message::Delete)
-        .padding(10)
-        .width(Length::Fill)
-        .style(if self.selected_entry.is_some() {
-            theme::Button::Destructive
+        let mut content = column![
+            text(fill(tr("Move '{}' to the trash?"), &[&name])).size(24),
+            text(tr("It can be restored from the Trash view until the trash is emptied.")).size(16)
+        ]
+        .spacing(20)
+        .width(Length::Fill);
+        let mut confirm = button(text(tr("Move to Trash")).horizontal_alignment(Horizontal::Center))
+            .padding(10)
+            .width(Length::Fixed(140.0))
+            .style(theme::Button::Destructive);
+        if self.guarded.is_empty() {
+            content = content.push(checkbox(tr("Don't ask again"), self.dont_ask_again, Message::DontAskAgainToggled).size(16));
         } else {
-            theme::Button::Secondary
-        });
+            let expected = guard::confirmation_text(&self.guarded);
+            let shown: Vec<String> = self.guarded.iter().map(|path| path.display().to_string()).collect();
+            content = content.push(
+                text(fill(tr("⚠ {} is a system, home or top-level folder."), &[&shown.join(", ")]))
+                    .size(14)
+                    .style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))),
+            );
+            content = content.push(
+                text_input(&fill(tr("Type {} to go ahead"), &[&expected]), &self.guard_input)
+                    .on_input(Message::GuardInputChanged)
+                    .padding(8)
+                    .size(14),
+            );
+        }
+        if self.guard_satisfied() {
+            confirm = confirm.on_press(Message::ConfirmTrash);
+        }
+        content = content.push(
+            row![
+                button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::CloseDialog)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Secondary),
+                confirm
+            ]
+            .spacing(10),
+        );
 
-        let properties_button = button(
-            row![text("ℹ️ Properties").horizontal_alignment(Horizontal::Center)]
-                .width(Length::Fill)
-                .align_items(iced::Alignment::Center)
-        )
-        .on_press(Message::ShowProperties)
-        .padding(10)
-        .width(Length::Fill)
-        .style(if self.selected_entry.is_some() {
-            theme::Button::Primary
-        } else {
-            theme::Button::Secondary
-        });
+        container(container(content).width(Length::Fixed(440.0)).padding(20).style(theme::Container::Box))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(overlay_style)
+            .into()
+    }
 
-        // Search bar
-        let search_input = text_input("Search files...", &self.search_query)
-            .on_input(Message::SearchInputChanged)
-            .on_submit(Message::PerformSearch)
-            .padding(10);
+    fn properties_dialog<'a>(&self) -> Element<'a, Message> {
+        let properties = if let Some(props) = self.properties.as_ref().filter(|props| props.tab == PropertiesTab::Permissions) {
+            self.acl_rows(props)
+        } else if let Some(props) = &self.properties {
+            let date_format = self.preferences.date_format;
+            let created_text = if let Some(created) = props.created {
+                date_format.format(created, true)
+            } else {
+                tr("Unknown").to_string()
+            };
+            
+            let details = column![
+                row![
+                    text(tr("Path:")).width(Length::Fixed(100.0)),
+                    text(format!("{}", props.path.display())).width(Length::Fill)
+                ].padding(5),
+                row![
+                    text(tr("Type:")).width(Length::Fixed(100.0)),
+                    text(props.file_type.clone()).width(Length::Fill)
+                ].padding(5),
+                row![
+                    text(tr("Size:")).width(Length::Fixed(100.0)),
+                    text(format!("{} ({})", self.preferences.size_units.format(props.size), settings::exact_bytes(props.size)))
+                        .width(Length::Fill)
+                ].padding(5),
+                row![
+                    text(tr("Modified:")).width(Length::Fixed(100.0)),
+                    text(date_format.format(props.modified, true)).width(Length::Fill)
+                ].padding(5),
+                row![
+                    text(tr("Created:")).width(Length::Fixed(100.0)),
+                    text(created_text).width(Length::Fill)
+                ].padding(5),
+                row![
+                    text(tr("Permissions:")).width(Length::Fixed(100.0)),
+                    text(props.permissions.clone()).width(Length::Fill)
+                ].padding(5),
+            ];
 
-        let search_button = button(
-            row![text("🔍 Search").horizontal_alignment(Horizontal::Center)]
-                .width(Length::Fill)
-                .align_items(iced::Alignment::Center)
-        )
-        .on_press(Message::PerformSearch)
-        .padding(10)
-        .width(Length::Fixed(100.0))
-        .style(theme::Button::Secondary);
+            let saved = self.notes.get(&props.path).unwrap_or_default() == props.note.trim();
+            let mut save = button(text(tr("Save note"))).padding(6).style(theme::Button::Primary);
+            if !saved {
+                save = save.on_press(Message::SaveNote);
+            }
+            let details = details.push(
+                row![
+                    text(tr("Note:")).width(Length::Fixed(100.0)),
+                    text_input(tr("Why is this here?"), &props.note)
+                        .on_input(Message::NoteChanged)
+                        .on_submit(Message::SaveNote)
+                        .padding(6)
+                        .width(Length::Fill),
+                    save
+                ]
+                .spacing(8)
+                .padding(5)
+                .align_items(iced::Alignment::Center),
+            );
 
-        let search_row = row![search_input, search_button]
-            .spacing(10)
-            .padding(10);
+            let details = match &props.attributes {
+                Some(attributes) => details.push(self.attribute_rows(props, attributes)),
+                None => details,
+            };
 
-        // Navigation controls
-        let nav_controls = row![nav_button, home_button, refresh_button]
-            .spacing(10)
-            .padding(10);
+            let details = match &props.exif {
+                Some(exif) => details.push(exif_rows(exif)),
+                None => details,
+            };
 
-        // Action controls
-        let action_controls = row![create_button, delete_button, properties_button]
-            .spacing(10)
-            .padding(10);
+            let details = match &props.media {
+                Some(probe) => details.push(media_rows(probe)),
+                None => details,
+            };
 
-        // Sort buttons
-        let sort_name_button = button(
-            row![text("Sort by Name").horizontal_alignment(Horizontal::Center)]
-                .width(Length::Fill)
-                .align_items(iced::Alignment::Center)
-        )
-        .on_press(Message::SortByName)
-        .padding(5)
-        .width(Length::Fill)
-        .style(if matches!(self.sort_mode, SortMode::NameAsc | SortMode::NameDesc) {
-            theme::Button::Primary
+            match &props.preview {
+                Some(Ok(preview)) => details.push(
+                    column![text(tr("Preview:")), text(preview).size(13).font(Font::MONOSPACE)].spacing(5).padding(5),
+                ),
+                Some(Err(error)) => details.push(property_row(tr("Preview:"), error.clone())),
+                None => details,
+            }
         } else {
-            theme::Button::Secondary
-        });
+            column![text(tr("No properties available")).size(16)]
+        };
 
-        let sort_size_button = button(
-            row![text("Sort by Size").horizontal_alignment(Horizontal::Center)]
-                .width(Length::Fill)
+        // Create a semi-transparent overlay
+        let overlay = container(
+            // Dialog content
+            container(
+                column![
+                    text(tr("File Properties")).size(24),
+                    self.properties_tabs(),
+                    properties,
+                    Space::with_height(Length::Fixed(20.0)),
+                    button(text(tr("Close")).horizontal_alignment(Horizontal::Center))
+                        .on_press(Message::CloseDialog)
+                        .padding(10)
+                        .width(Length::Fixed(100.0))
+                        .style(theme::Button::Secondary)
+                ]
+                .spacing(10)
+                .padding(20)
+                .width(Length::Fixed(500.0))
                 .align_items(iced::Alignment::Center)
+            )
+            .width(Length::Fixed(500.0))
+            .padding(20)
+            .center_x()
+            .center_y()
+            .style(theme::Container::Box)
         )
-        .on_press(Message::SortBySize)
-        .padding(5)
         .width(Length::Fill)
-        .style(if matches!(self.sort_mode, SortMode::SizeAsc | SortMode::SizeDesc) {
-            theme::Button::Primary
-        } else {
-            theme::Button::Secondary
-        });
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .style(overlay_style);
 
-        let sort_date_button = button(
-            row![text("Sort by Date").horizontal_alignment(Horizontal::Center)]
-                .width(Length::Fill)
-                .align_items(iced::Alignment::Center)
+        overlay.into()
+    }
+
+    // The tab buttons, where there is more than the General tab to show
+    fn properties_tabs<'a>(&self) -> Element<'a, Message> {
+        let Some(props) = self.properties.as_ref().filter(|_| acl::SUPPORTED) else {
+            return Space::with_height(Length::Fixed(20.0)).into();
+        };
+        let tab = |label: &'static str, tab: PropertiesTab| {
+            button(text(tr(label)).size(14))
+                .on_press(Message::PropertiesTabChanged(tab))
+                .padding(6)
+                .style(if props.tab == tab { theme::Button::Primary } else { theme::Button::Secondary })
+        };
+        row![tab("General", PropertiesTab::General), tab("Permissions", PropertiesTab::Permissions)].spacing(6).into()
+    }
+
+    // Who may do what with the file, with entries of named users and
+    // groups to grant and revoke
+    fn acl_rows<'a>(&self, props: &FileProperties) -> iced::widget::Column<'a, Message> {
+        let mut rows = column![text(tr("Access control list:"))].spacing(4).padding(5);
+        match &props.acl {
+            None => {}
+            Some(Err(error)) => rows = rows.push(text(error.clone()).size(13)),
+            Some(Ok(entries)) if entries.is_empty() => rows = rows.push(text(tr("None")).size(13)),
+            Some(Ok(entries)) => {
+                for entry in entries {
+                    let mut line = row![
+                        text(entry.who.clone()).size(13).width(Length::FillPortion(3)),
+                        text(entry.rights.clone()).size(13).font(Font::MONOSPACE).width(Length::FillPortion(2))
+                    ]
+                    .spacing(8)
+                    .align_items(iced::Alignment::Center);
+                    if let Some(key) = &entry.key {
+                        line = line.push(
+                            button(text("✕").size(13)).on_press(Message::RevokeAcl(key.clone())).padding(2).style(theme::Button::Text),
+                        );
+                    }
+                    rows = rows.push(line);
+                }
+            }
+        }
+        let valid = !props.acl_who.trim().is_empty() && acl::parse_rights(&props.acl_rights).is_some();
+        let mut grant = button(text(tr("Grant")).size(13)).padding(4).style(theme::Button::Secondary);
+        if valid {
+            grant = grant.on_press(Message::GrantAcl);
+        }
+        rows.push(
+            row![
+                text_input(tr(acl::WHO_HINT), &props.acl_who)
+                    .on_input(Message::AclWhoChanged)
+                    .on_submit(Message::GrantAcl)
+                    .size(13)
+                    .padding(4)
+                    .width(Length::FillPortion(3)),
+                text_input("rwx", &props.acl_rights)
+                    .on_input(Message::AclRightsChanged)
+                    .on_submit(Message::GrantAcl)
+                    .size(13)
+                    .padding(4)
+                    .width(Length::FillPortion(1)),
+                grant
+            ]
+            .spacing(6)
+            .align_items(iced::Alignment::Center),
         )
-        .on_press(Message::SortByDate)
-        .padding(5)
-        .width(Length::Fill)
-        .style(if matches!(self.sort_mode, SortMode::DateAsc | SortMode::DateDesc) {
-            theme::Button::Primary
-        } else {
-            theme::Button::Secondary
-        });
+    }
 
-        let sort_controls = row![sort_name_button, sort_size_button, sort_date_button]
-            .spacing(10)
-            .padding(5);
+    // Extended attributes in Properties; the user's own can be removed and
+    // new ones added
+    fn attribute_rows<'a>(&self, props: &FileProperties, attributes: &Result<Vec<(OsString, String)>, String>) -> Element<'a, Message> {
+        let mut rows = column![text(tr("Extended attributes:"))].spacing(4).padding(5);
+        match attributes {
+            Err(error) => rows = rows.push(text(error.clone()).size(13)),
+            Ok(attributes) if attributes.is_empty() => rows = rows.push(text(tr("None")).size(13)),
+            Ok(attributes) => {
+                for (name, value) in attributes {
+                    let mut line = row![
+                        text(name.to_string_lossy()).size(13).font(Font::MONOSPACE).width(Length::FillPortion(2)),
+                        text(value.clone()).size(13).width(Length::FillPortion(3))
+                    ]
+                    .spacing(8)
+                    .align_items(iced::Alignment::Center);
+                    if xattr::is_user(name) {
+                        line = line.push(
+                            button(text("✕").size(13))
+                                .on_press(Message::RemoveAttribute(name.clone()))
+                                .padding(2)
+                                .style(theme::Button::Text),
+                        );
+                    }
+                    rows = rows.push(line);
+                }
+            }
+        }
+        let mut add = button(text(tr("Add")).size(13)).padding(4).style(theme::Button::Secondary);
+        if !props.new_attribute.trim().is_empty() {
+            add = add.on_press(Message::AddAttribute);
+        }
+        rows.push(
+            row![
+                text_input(tr("Name"), &props.new_attribute).on_input(Message::AttributeNameChanged).size(13).padding(4),
+                text_input(tr("Value"), &props.new_attribute_value)
+                    .on_input(Message::AttributeValueChanged)
+                    .on_submit(Message::AddAttribute)
+                    .size(13)
+                    .padding(4),
+                add
+            ]
+            .spacing(6)
+            .align_items(iced::Alignment::Center),
+        )
+        .into()
+    }
 
-        // File list header
-        let header_row = row![
-            text("Name").width(Length::FillPortion(3)),
-            text("Size").width(Length::FillPortion(1)),
-            text("Modified").width(Length::FillPortion(2))
-        ]
-        .padding(10)
-        .spacing(10);
+    fn usage_view(&self) -> Element<'_, Message> {
+        let Some(state) = &self.usage else {
+            return text("").into();
+        };
 
-        // File list with improved styling
-        let file_list = self.entries.iter().fold(
-            column![header_row].spacing(2),
-            |column, entry| {
-                let path = &entry.path;
-                let is_selected = self
-                    .selected_entry
-                    .as_ref()
-                    .map_or(false, |selected| selected == path);
-                
-                let name = path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("Unknown");
-                
-                let icon = if path.is_dir() { "📁 " } else { "📄 " };
-                
-                let size_text = if path.is_dir() {
-                    "Folder".to_string()
-                } else {
-                    format_size(entry.size, BINARY)
-                };
-                
-                let modified: DateTime<Local> = entry.modified.into();
-                let date_text = modified.format("%Y-%m-%d %H:%M").to_string();
-                
-                let file_row = row![
-                    text(format!("{}{}", icon, name)).width(Length::FillPortion(3)),
-                    text(size_text).width(Length::FillPortion(1)),
-                    text(date_text).width(Length::FillPortion(2))
+        let close_button = button(text(tr("✕ Close")))
+            .on_press(Message::CloseUsage)
+            .padding(10)
+            .style(theme::Button::Secondary);
+
+        let Some(root) = &state.root else {
+            return column![
+                row![
+                    text(fill(tr("Analyzing {}…"), &[&state.root_path.display()])).size(20).width(Length::Fill),
+                    close_button
                 ]
-                .spacing(10)
-                .padding(10)
-                .width(Length::Fill);
-                
-                let file_button = button(file_row)
-                    .width(Length::Fill)
-                    .on_press(Message::FileSelected(path.clone()))
-                    .style(if is_selected {
-                        theme::Button::Primary
-                    } else {
-                        theme::Button::Text
-                    });
-                
-                column.push(file_button)
-            },
-        );
+                .align_items(iced::Alignment::Center)
+            ]
+            .spacing(10)
+            .padding(20)
+            .into();
+        };
 
-        // Create scrollable with updated API
-        let files_scrollable = scrollable(file_list)
-            .height(Length::Fill)
-            .width(Length::Fill);
+        let node = root.find(&state.focus).unwrap_or(root);
 
-        // Status bar showing item count
-        let status_bar = container(
-            text(format!("{} items", self.entries.len()))
-                .size(14)
-        )
-        .width(Length::Fill)
-        .padding(5)
-        .style(theme::Container::Box);
+        let up_button = button(text(tr("⬆️ Up")))
+            .on_press(Message::UsageUp)
+            .padding(10)
+            .style(theme::Button::Secondary);
 
-        // Main content layout
-        let content = column![
-            title,
-            search_row,
-            row![
-                column![nav_controls].width(Length::FillPortion(1)),
-                column![action_controls].width(Length::FillPortion(1))
-            ],
-            sort_controls,
-            horizontal_rule(1),
-            files_scrollable,
-            status_bar
+        let header = row![
+            up_button,
+            text(format!("{} — {}", node.path.display(), self.preferences.size_units.format(node.size)))
+                .size(20)
+                .width(Length::Fill),
+            close_button
         ]
-        .spacing(5)
-        .padding(20);
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
 
-        // Main container
-        let main_content = container(content)
+        let treemap = canvas(usage::Treemap { node, icons: self.preferences.icon_set, units: self.preferences.size_units })
             .width(Length::Fill)
-            .height(Length::Fill)
-            .style(theme::Container::Box);
+            .height(Length::Fill);
 
-        // If we have a dialog active, create an overlay
-        match &self.dialog {
-            DialogState::None => main_content.into(),
-            DialogState::Create => self.create_dialog(),
-            DialogState::Delete => self.delete_dialog(),
-            DialogState::Properties => self.properties_dialog(),
-        }
+        column![
+            header,
+            treemap,
+            text(tr("Click a folder to open it · Right-click to delete")).size(14)
+        ]
+        .spacing(10)
+        .padding(20)
+        .into()
     }
-}
 
-// Helper methods for FileManager
-impl FileManager {
-    fn load_entries(&mut self) {
-        self.entries.clear();
-        
-        for entry in WalkDir::new(&self.current_dir).max_depth(1) {
-            if let Ok(entry) = entry {
-                let path = entry.path().to_path_buf();
-                
-                // Skip the current directory
-                if path == self.current_dir {
-                    continue;
-                }
-                
-                // Skip hidden files unless explicitly searching for them
-                if is_hidden(&path) && !self.search_query.starts_with('.') {
-                    continue;
-                }
-                
-                // Apply search filter if query is not empty
-                if !self.search_query.is_empty() {
-                    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                        if !name.to_lowercase().contains(&self.search_query.to_lowercase()) {
-                            continue;
-                        }
-                    }
-                }
-                
-                // Get file metadata
-                if let Ok(meta) = metadata(&path) {
-                    self.entries.push(FileEntry {
-                        path,
-                        size: meta.len(),
-                        modified: meta.modified().unwrap_or(SystemTime::now()),
-                    });
-                } else {
-                    // If metadata can't be read, still show the file with default values
-                    self.entries.push(FileEntry {
-                        path,
-                        size: 0,
-                        modified: SystemTime::now(),
-                    });
-                }
-            }
-        }
-        
-        self.sort_entries();
-    }
-    
-    fn sort_entries(&mut self) {
-        match self.sort_mode {
-            SortMode::NameAsc => {
-                // Sort directories first, then files alphabetically
-                self.entries.sort_by(|a, b| {
-                    let a_is_dir = a.path.is_dir();
-                    let b_is_dir = b.path.is_dir();
-                    
-                    match (a_is_dir, b_is_dir) {
-                        (true, false) => std::cmp::Ordering::Less,
-                        (false, true) => std::cmp::Ordering::Greater,
-                        _ => a.path.file_name().cmp(&b.path.file_name()),
-                    }
-                });
-            },
-            SortMode::NameDesc => {
-                // Sort directories first, then files reverse alphabetically
-                self.entries.sort_by(|a, b| {
-                    let a_is_dir = a.path.is_dir();
-                    let b_is_dir = b.path.is_dir();
-                    
-                    match (a_is_dir, b_is_dir) {
-                        (true, false) => std::cmp::Ordering::Less,
-                        (false, true) => std::cmp::Ordering::Greater,
-                        _ => b.path.file_name().cmp(&a.path.file_name()),
-                    }
-                });
-            },
-            SortMode::SizeAsc => {
-                // Sort by size (ascending)
-                self.entries.sort_by(|a, b| {
-                    let a_is_dir = a.path.is_dir();
-                    let b_is_dir = b.path.is_dir();
-                    
-                    match (a_is_dir, b_is_dir) {
-                        (true, true) => a.path.file_name().cmp(&b.path.file_name()),
-                        (true, false) => std::cmp::Ordering::Less,
-                        (false, true) => std::cmp::Ordering::Greater,
-                        (false, false) => a.size.cmp(&b.size),
-                    }
-                });
-            },
-            SortMode::SizeDesc => {
-                // Sort by size (descending)
-                self.entries.sort_by(|a, b| {
-                    let a_is_dir = a.path.is_dir();
-                    let b_is_dir = b.path.is_dir();
-                    
-                    match (a_is_dir, b_is_dir) {
-                        (true, true) => a.path.file_name().cmp(&b.path.file_name()),
-                        (true, false) => std::cmp::Ordering::Less,
-                        (false, true) => std::cmp::Ordering::Greater,
-                        (false, false) => b.size.cmp(&a.size),
-                    }
-                });
-            },
-            SortMode::DateAsc => {
-                // Sort by modification date (ascending)
-                self.entries.sort_by(|a, b| {
-                    let a_is_dir = a.path.is_dir();
-                    let b_is_dir = b.path.is_dir();
-                    
-                    match (a_is_dir, b_is_dir) {
-                        (true, false) => std::cmp::Ordering::Less,
-                        (false, true) => std::cmp::Ordering::Greater,
-                        _ => a.modified.cmp(&b.modified),
-                    }
-                });
-            },
-            SortMode::DateDesc => {
-                // Sort by modification date (descending)
-                self.entries.sort_by(|a, b| {
-                    let a_is_dir = a.path.is_dir();
-                    let b_is_dir = b.path.is_dir();
-                    
-                    match (a_is_dir, b_is_dir) {
-                        (true, false) => std::cmp::Ordering::Less,
-                        (false, true) => std::cmp::Ordering::Greater,
-                        _ => b.modified.cmp(&a.modified),
-                    }
-                });
+    fn cleanup_view(&self) -> Element<'_, Message> {
+        let Some(state) = &self.cleanup else {
+            return text("").into();
+        };
+
+        let close_button = button(text(tr("✕ Close")))
+            .on_press(Message::CloseCleanup)
+            .padding(10)
+            .style(theme::Button::Secondary);
+
+        let Some(found) = &state.found else {
+            return column![
+                row![
+                    text(fill(tr("Looking for empty files and folders in {}…"), &[&state.root.display()]))
+                        .size(20)
+                        .width(Length::Fill),
+                    close_button
+                ]
+                .align_items(iced::Alignment::Center)
+            ]
+            .spacing(10)
+            .padding(20)
+            .into();
+        };
+
+        let delete_button = button(text(fill(tr("🗑️ Delete {} selected"), &[&state.selected.len()])))
+            .padding(10)
+            .style(theme::Button::Destructive);
+        let delete_button = if state.selected.is_empty() {
+            delete_button
+        } else {
+            delete_button.on_press(Message::DeleteEmpty)
+        };
+
+        let header = row![
+            text(fill(tr("{} empty items in {}"), &[&found.len(), &state.root.display()]))
+                .size(20)
+                .width(Length::Fill),
+            delete_button,
+            close_button
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        let all_selected = !found.is_empty() && state.selected.len() == found.len();
+        let text_size = self.list_text_size();
+
+        let list = found.iter().fold(
+            column![checkbox(tr("Select all"), all_selected, Message::EmptySelectAll).text_size(text_size)].spacing(6),
+            |column, entry| {
+                let relative = entry.path.strip_prefix(&state.root).unwrap_or(&entry.path);
+                let kind = if entry.is_dir { IconKind::Folder } else { IconKind::Generic };
+                let label = format!("{} {}", self.preferences.icon_set.glyph(kind), relative.display());
+                let path = entry.path.clone();
+                column.push(
+                    checkbox(label, state.selected.contains(&entry.path), move |checked| {
+                        Message::EmptyToggled(path.clone(), checked)
+                    })
+                    .text_size(text_size),
+                )
             },
+        );
+
+        let mut content = column![header].spacing(10).padding(20);
+        if let Some(error) = &state.error {
+            content = content.push(text(error).size(14).style(theme::Text::Color(Color::from_rgb(0.85, 0.2, 0.2))));
         }
+
+        content
+            .push(scrollable(list).height(Length::Fill).width(Length::Fill))
+            .into()
     }
 
-    fn create_dialog<'a>(&self) -> Element<'a, Message> {
-        // Create a semi-transparent overlay
-        let overlay = container(
-            // Dialog content
-            container(
-                column![
-                    text("Create New").size(24),
-                    Space::with_height(Length::Fixed(10.0)),
-                    text_input("Enter name...", &self.new_name)
-                        .on_input(Message::NameInputChanged)
-                        .padding(10),
-                    row![
-                        checkbox("Is Directory", self.is_directory, Message::IsDirectoryToggled)
-                    ]
-                    .padding(10),
-                    Space::with_height(Length::Fixed(10.0)),
-                    row![
-                        button(text("Cancel").horizontal_alignment(Horizontal::Center))
-                            .on_press(Message::CloseDialog)
-                            .padding(10)
-                            .width(Length::Fixed(100.0))
-                            .style(theme::Button::Secondary),
-                        button(text("Create").horizontal_alignment(Horizontal::Center))
-                            .on_press(Message::ConfirmCreate)
-                            .padding(10)
-                            .width(Length::Fixed(100.0))
-                            .style(theme::Button::Primary)
-                    ]
-                    .spacing(10)
-                    .align_items(iced::Alignment::Center)
+    fn largest_view(&self) -> Element<'_, Message> {
+        let Some(state) = &self.largest else {
+            return text("").into();
+        };
+
+        let close_button = button(text(tr("✕ Close")))
+            .on_press(Message::CloseLargest)
+            .padding(10)
+            .style(theme::Button::Secondary);
+
+        let title = if state.scanning {
+            fill(tr("{} Finding the {} largest files in {}…"), &[&SPINNER_FRAMES[self.spinner_frame], &largest::REPORT_SIZE, &state.root.display()])
+        } else {
+            fill(tr("Largest files in {}"), &[&state.root.display()])
+        };
+
+        let header = row![text(title).size(20).width(Length::Fill), close_button]
+            .spacing(10)
+            .align_items(iced::Alignment::Center);
+
+        let text_size = self.list_text_size();
+        let list = state.files.iter().fold(column![].spacing(4), |column, entry| {
+            let relative = entry.path.strip_prefix(&state.root).unwrap_or(&entry.path);
+            let icon = self.preferences.icon_set.glyph(entry.icon);
+            column.push(
+                row![
+                    text(self.preferences.size_units.format(entry.size)).size(text_size).width(Length::Fixed(110.0)),
+                    text(format!("{} {}", icon, relative.display())).size(text_size).width(Length::Fill),
+                    button(text(tr("📂 Show in folder")).size(text_size * 0.8))
+                        .on_press(Message::RevealInFolder(entry.path.clone()))
+                        .padding(6)
+                        .style(theme::Button::Secondary),
+                    button(text(tr("🗑️ Delete")).size(text_size * 0.8))
+                        .on_press(Message::LargestDelete(entry.path.clone()))
+                        .padding(6)
+                        .style(theme::Button::Destructive)
                 ]
-                .spacing(20)
-                .padding(20)
-                .width(Length::Fixed(400.0))
-                .align_items(iced::Alignment::Center)
+                .spacing(10)
+                .align_items(iced::Alignment::Center),
             )
-            .width(Length::Fixed(400.0))
+        });
+
+        column![header, scrollable(list).height(Length::Fill).width(Length::Fill)]
+            .spacing(10)
             .padding(20)
-            .center_x()
-            .center_y()
-            .style(theme::Container::Box)
-        )
-        .width(Length::Fill)
-        .height(Length::Fill)
-        .center_x()
-        .center_y()
-        .style(theme::Container::Box);
+            .into()
+    }
 
-        overlay.into()
+    fn trash_view(&self) -> Element<'_, Message> {
+        let Some(state) = &self.trash else {
+            return text("").into();
+        };
+
+        let items = match &state.items {
+            Some(Ok(items)) => Some(items),
+            _ => None,
+        };
+        let mut empty_button = button(text(if state.confirm_empty { tr("Empty for good?") } else { tr("🧹 Empty Trash") }))
+            .padding(10)
+            .style(theme::Button::Destructive);
+        if items.is_some_and(|items| !items.is_empty()) {
+            empty_button = empty_button.on_press(Message::EmptyTrash);
+        }
+        let close_button = button(text(tr("✕ Close")))
+            .on_press(Message::CloseTrash)
+            .padding(10)
+            .style(theme::Button::Secondary);
+
+        let title = match items {
+            Some(items) => {
+                let size: u64 = items.iter().map(|item| item.size).sum();
+                fill(tr("Trash: {} items, {}"), &[&items.len(), &self.preferences.size_units.format(size)])
+            }
+            None => tr("Trash").to_string(),
+        };
+        let header = row![text(title).size(20).width(Length::Fill), empty_button, close_button]
+            .spacing(10)
+            .align_items(iced::Alignment::Center);
+
+        let text_size = self.list_text_size();
+        let body: Element<Message> = match &state.items {
+            None => text(fill(tr("{} Reading the trash…"), &[&SPINNER_FRAMES[self.spinner_frame]])).size(text_size).into(),
+            Some(Err(error)) => text(fill(tr("Could not read the trash: {}"), &[error])).size(text_size).into(),
+            Some(Ok(items)) if items.is_empty() => text(tr("The trash is empty.")).size(text_size).into(),
+            Some(Ok(items)) => {
+                let list = items.iter().enumerate().fold(column![].spacing(4), |column, (index, item)| {
+                    let icon = if item.is_dir { "📁" } else { "📄" };
+                    let deleted = item.deleted.map(|time| self.preferences.date_format.format(time, false)).unwrap_or_default();
+                    column.push(
+                        row![
+                            text(format!("{} {}", icon, item.original.display())).size(text_size).width(Length::Fill),
+                            text(deleted).size(text_size).width(Length::Fixed(160.0)),
+                            text(self.preferences.size_units.format(item.size)).size(text_size).width(Length::Fixed(110.0)),
+                            button(text(tr("↩ Restore")).size(text_size * 0.8))
+                                .on_press(Message::RestoreFromTrash(index))
+                                .padding(6)
+                                .style(theme::Button::Primary),
+                            button(text(tr("📂 Show in folder")).size(text_size * 0.8))
+                                .on_press(Message::RevealInFolder(item.original.clone()))
+                                .padding(6)
+                                .width(Length::Fixed(REVEAL_BUTTON_WIDTH))
+                                .style(theme::Button::Secondary)
+                        ]
+                        .spacing(10)
+                        .align_items(iced::Alignment::Center),
+                    )
+                });
+                let heading = row![
+                    text(tr("Original location")).size(text_size).width(Length::Fill),
+                    text(tr("Deleted")).size(text_size).width(Length::Fixed(160.0)),
+                    text(tr("Size")).size(text_size).width(Length::Fixed(110.0)),
+                    Space::with_width(Length::Fixed(100.0 + REVEAL_BUTTON_WIDTH))
+                ]
+                .spacing(10);
+                column![heading, horizontal_rule(1), scrollable(list).height(Length::Fill).width(Length::Fill)]
+                    .spacing(6)
+                    .into()
+            }
+        };
+
+        column![header, body].spacing(10).padding(20).into()
     }
 
-    fn delete_dialog<'a>(&self) -> Element<'a, Message> {
-        let name = self
-            .selected_entry
-            .as_ref()
-            .and_then(|p| p.file_name())
-            .and_then(|n| n.to_str())
-            .unwrap_or("this item");
+    fn image_view(&self) -> Element<'_, Message> {
+        let Some(state) = &self.image else {
+            return text("").into();
+        };
+
+        let name = state.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let inside: Vec<&str> = state.dirs.iter().map(|dir| dir.name.as_str()).collect();
+        let title = if inside.is_empty() { format!("💿 {}", name) } else { format!("💿 {} / {}", name, inside.join(" / ")) };
+        let mut up_button = button(text(tr("⬆ Up"))).padding(10).style(theme::Button::Secondary);
+        if !state.dirs.is_empty() {
+            up_button = up_button.on_press(Message::ImageUp);
+        }
+        let close_button = button(text(tr("✕ Close")))
+            .on_press(Message::CloseImage)
+            .padding(10)
+            .style(theme::Button::Secondary);
+        let header = row![text(title).size(20).width(Length::Fill), up_button, close_button]
+            .spacing(10)
+            .align_items(iced::Alignment::Center);
+
+        let text_size = self.list_text_size();
+        let body: Element<Message> = match (&state.image, &state.entries) {
+            (Some(Err(error)), _) => text(fill(tr("Could not read the image: {}"), &[error])).size(text_size).into(),
+            (None, _) | (_, None) => text(fill(tr("{} Reading the image…"), &[&SPINNER_FRAMES[self.spinner_frame]])).size(text_size).into(),
+            (_, Some(Err(error))) => text(fill(tr("Could not read the folder: {}"), &[error])).size(text_size).into(),
+            (_, Some(Ok(entries))) if entries.is_empty() => text(tr("This folder is empty.")).size(text_size).into(),
+            (_, Some(Ok(entries))) => {
+                let list = entries.iter().enumerate().fold(column![].spacing(4), |column, (index, entry)| {
+                    let icon = if entry.is_dir { "📁" } else { "📄" };
+                    let modified = entry.modified.map(|time| self.preferences.date_format.format(time, false)).unwrap_or_default();
+                    let size = if entry.is_dir { String::new() } else { self.preferences.size_units.format(entry.size) };
+                    column.push(
+                        row![
+                            button(text(format!("{} {}", icon, entry.name)).size(text_size))
+                                .on_press(Message::OpenImageEntry(index))
+                                .padding(2)
+                                .style(theme::Button::Text)
+                                .width(Length::Fill),
+                            text(modified).size(text_size).width(Length::Fixed(160.0)),
+                            text(size).size(text_size).width(Length::Fixed(110.0)),
+                            button(text(tr("⬇ Extract")).size(text_size * 0.8))
+                                .on_press(Message::ExtractFromImage(index))
+                                .padding(6)
+                                .style(theme::Button::Primary)
+                        ]
+                        .spacing(10)
+                        .align_items(iced::Alignment::Center),
+                    )
+                });
+                let heading = row![
+                    text(tr("Name")).size(text_size).width(Length::Fill),
+                    text(tr("Modified")).size(text_size).width(Length::Fixed(160.0)),
+                    text(tr("Size")).size(text_size).width(Length::Fixed(110.0)),
+                    Space::with_width(Length::Fixed(90.0))
+                ]
+                .spacing(10);
+                column![heading, horizontal_rule(1), scrollable(list).height(Length::Fill).width(Length::Fill)]
+                    .spacing(6)
+                    .into()
+            }
+        };
+
+        column![header, text(tr("Read-only. Files open from a copy; Extract puts them beside the image.")).size(13), body]
+            .spacing(10)
+            .padding(20)
+            .into()
+    }
+
+    fn delete_empty_dialog<'a>(&self) -> Element<'a, Message> {
+        let count = self.cleanup.as_ref().map_or(0, |cleanup| cleanup.selected.len());
 
         // Create a semi-transparent overlay
         let overlay = container(
             // Dialog content
             container(
                 column![
-                    text(format!("Delete '{}'?", name)).size(24),
+                    text(fill(tr("Delete {} empty items?"), &[&count])).size(24),
                     Space::with_height(Length::Fixed(10.0)),
-                    text("This action cannot be undone.").size(16),
+                    text(tr("This action cannot be undone.")).size(16),
                     Space::with_height(Length::Fixed(20.0)),
                     row![
-                        button(text("Cancel").horizontal_alignment(Horizontal::Center))
+                        button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
                             .on_press(Message::CloseDialog)
                             .padding(10)
                             .width(Length::Fixed(100.0))
                             .style(theme::Button::Secondary),
-                        button(text("Delete").horizontal_alignment(Horizontal::Center))
-                            .on_press(Message::ConfirmDelete)
+                        button(text(tr("Delete")).horizontal_alignment(Horizontal::Center))
+                            .on_press(Message::ConfirmDeleteEmpty)
                             .padding(10)
                             .width(Length::Fixed(100.0))
                             .style(theme::Button::Destructive)
@@ -757,73 +6784,105 @@ impl FileManager {
         .height(Length::Fill)
         .center_x()
         .center_y()
-        .style(theme::Container::Box);
+        .style(overlay_style);
 
         overlay.into()
     }
 
-    fn properties_dialog<'a>(&self) -> Element<'a, Message> {
-        let properties = if let Some(props) = &self.properties {
-            let modified: DateTime<Local> = props.modified.into();
-            
-            let created_text = if let Some(created) = props.created {
-                let created: DateTime<Local> = created.into();
-                created.format("%Y-%m-%d %H:%M:%S").to_string()
-            } else {
-                "Unknown".to_string()
-            };
-            
-            column![
-                row![
-                    text("Path:").width(Length::Fixed(100.0)),
-                    text(format!("{}", props.path.display())).width(Length::Fill)
-                ].padding(5),
-                row![
-                    text("Type:").width(Length::Fixed(100.0)),
-                    text(format!("{}", props.file_type)).width(Length::Fill)
-                ].padding(5),
-                row![
-                    text("Size:").width(Length::Fixed(100.0)),
-                    text(format!("{}", format_size(props.size, BINARY))).width(Length::Fill)
-                ].padding(5),
-                row![
-                    text("Modified:").width(Length::Fixed(100.0)),
-                    text(format!("{}", modified.format("%Y-%m-%d %H:%M:%S"))).width(Length::Fill)
-                ].padding(5),
-                row![
-                    text("Created:").width(Length::Fixed(100.0)),
-                    text(created_text).width(Length::Fill)
-                ].padding(5),
-                row![
-                    text("Permissions:").width(Length::Fixed(100.0)),
-                    text(format!("{}", props.permissions)).width(Length::Fill)
-                ].padding(5),
-            ]
+    fn editor_dialog<'a>(&self) -> Element<'a, Message> {
+        let Some(editor) = &self.editor else {
+            return text(tr("Nothing to edit")).into();
+        };
+
+        let name = editor
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| tr("Unknown").to_string());
+
+        let title = if editor.modified {
+            fill(tr("Editing '{}' (modified)"), &[&name])
+        } else {
+            fill(tr("Editing '{}'"), &[&name])
+        };
+
+        // Each line is its own input; Enter opens a new line below it
+        let lines = editor.lines.iter().enumerate().fold(
+            column![].spacing(2),
+            |column, (index, line)| {
+                column.push(
+                    row![
+                        text(format!("{:>4}", index + 1))
+                            .font(Font::MONOSPACE)
+                            .size(14)
+                            .width(Length::Fixed(40.0)),
+                        text_input("", line)
+                            .id(editor_line_id(index))
+                            .on_input(move |value| Message::EditorLineChanged(index, value))
+                            .on_submit(Message::EditorInsertLine(index))
+                            .font(Font::MONOSPACE)
+                            .size(14)
+                            .padding(2),
+                        button(text("✕").size(12))
+                            .on_press(Message::EditorRemoveLine(index))
+                            .padding(2)
+                            .style(theme::Button::Text)
+                    ]
+                    .spacing(5)
+                    .align_items(iced::Alignment::Center),
+                )
+            },
+        );
+
+        let body: Element<'a, Message> = if let Some(error) = &editor.error {
+            text(error).size(16).into()
+        } else if editor.lines.is_empty() {
+            button(text(tr("Add line")))
+                .on_press(Message::EditorInsertLine(0))
+                .padding(5)
+                .style(theme::Button::Secondary)
+                .into()
         } else {
-            column![text("No properties available").size(16)]
+            scrollable(lines).height(Length::Fixed(400.0)).into()
         };
 
+        let mut buttons = row![
+            button(text(tr("Cancel")).horizontal_alignment(Horizontal::Center))
+                .on_press(Message::CloseDialog)
+                .padding(10)
+                .width(Length::Fixed(100.0))
+                .style(theme::Button::Secondary)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+
+        if !editor.lines.is_empty() || editor.modified {
+            buttons = buttons.push(
+                button(text(tr("Save")).horizontal_alignment(Horizontal::Center))
+                    .on_press(Message::SaveEdit)
+                    .padding(10)
+                    .width(Length::Fixed(100.0))
+                    .style(theme::Button::Primary),
+            );
+        }
+
+        let mut content = column![text(title).size(24)].spacing(20);
+        if let Some(error) = &editor.save_error {
+            content = content.push(text(error).size(14).style(theme::Text::Color(Color::from_rgb(0.8, 0.2, 0.2))));
+        }
+
         // Create a semi-transparent overlay
         let overlay = container(
             // Dialog content
             container(
-                column![
-                    text("File Properties").size(24),
-                    Space::with_height(Length::Fixed(20.0)),
-                    properties,
-                    Space::with_height(Length::Fixed(20.0)),
-                    button(text("Close").horizontal_alignment(Horizontal::Center))
-                        .on_press(Message::CloseDialog)
-                        .padding(10)
-                        .width(Length::Fixed(100.0))
-                        .style(theme::Button::Secondary)
-                ]
-                .spacing(10)
-                .padding(20)
-                .width(Length::Fixed(500.0))
-                .align_items(iced::Alignment::Center)
+                content
+                    .push(body)
+                    .push(buttons)
+                    .padding(20)
+                    .width(Length::Fixed(700.0))
+                    .align_items(iced::Alignment::Center)
             )
-            .width(Length::Fixed(500.0))
+            .width(Length::Fixed(700.0))
             .padding(20)
             .center_x()
             .center_y()
@@ -833,21 +6892,454 @@ impl FileManager {
         .height(Length::Fill)
         .center_x()
         .center_y()
-        .style(theme::Container::Box);
+        .style(overlay_style);
 
         overlay.into()
     }
 }
 
-async fn load_directory(_path: PathBuf) -> () {
-    // This is a fake async function to make the Command happy
-    // The actual loading happens in load_entries
-    ()
+// Forwards key presses so they can be looked up in the keymap, noting
+// whether a widget such as a text field already handled them
+fn handle_shortcut(event: Event, status: event::Status) -> Option<Message> {
+    let Event::Keyboard(keyboard::Event::KeyPressed { key_code, modifiers }) = event else {
+        return None;
+    };
+    Some(Message::KeyPressed(key_code, modifiers, status == event::Status::Captured))
+}
+
+fn shortcut_message(action: keymap::Action) -> Message {
+    match action {
+        keymap::Action::StopSearch => Message::StopSearch,
+        keymap::Action::Refresh => Message::Refresh,
+        keymap::Action::GoUp => Message::NavigateUp,
+        keymap::Action::GoHome => Message::NavigateHome,
+        keymap::Action::CreateNew => Message::CreateNew,
+        keymap::Action::Delete => Message::Delete,
+        keymap::Action::DeletePermanently => Message::DeletePermanently,
+        keymap::Action::Print => Message::Print,
+        keymap::Action::CopyPath => Message::CopySelection(CopyAs::Path),
+        keymap::Action::CopyName => Message::CopySelection(CopyAs::Name),
+        keymap::Action::Properties => Message::ShowProperties,
+        keymap::Action::Settings => Message::OpenSettings,
+        keymap::Action::ZoomIn => Message::ZoomIn,
+        keymap::Action::ZoomOut => Message::ZoomOut,
+        keymap::Action::ZoomReset => Message::ZoomReset,
+        keymap::Action::Palette => Message::OpenPalette,
+    }
+}
+
+fn tool_message(tool: Tool) -> Message {
+    match tool {
+        Tool::Up => Message::NavigateUp,
+        Tool::Home => Message::NavigateHome,
+        Tool::Refresh => Message::Refresh,
+        Tool::Usage => Message::AnalyzeUsage,
+        Tool::Empty => Message::FindEmpty,
+        Tool::Largest => Message::FindLargest,
+        Tool::Recent => Message::ShowRecent(recent::Window::Day),
+        Tool::New => Message::CreateNew,
+        Tool::Delete => Message::Delete,
+        Tool::Edit => Message::Edit,
+        Tool::OpenWith => Message::OpenWith,
+        Tool::Properties => Message::ShowProperties,
+        Tool::Settings => Message::OpenSettings,
+        Tool::CopyTo => Message::OpenTransfer(Transfer::Copy),
+        Tool::MoveTo => Message::OpenTransfer(Transfer::Move),
+        Tool::Compress => Message::Compress,
+        Tool::History => Message::ShowHistory,
+        Tool::Git => Message::ShowGit,
+        Tool::Trash => Message::ShowTrash,
+        Tool::DeletePermanently => Message::DeletePermanently,
+        Tool::Share => Message::Share,
+        Tool::ShareLan => Message::ShareOverLan,
+        Tool::Print => Message::Print,
+        Tool::Encrypt => Message::OpenCrypt(Crypt::Encrypt),
+        Tool::Decrypt => Message::OpenCrypt(Crypt::Decrypt),
+        Tool::VerifySignature => Message::VerifySignature,
+        Tool::VerifyChecksums => Message::VerifyChecksums,
+        Tool::ConvertImages => Message::OpenConvertImages,
+        Tool::CopyPath => Message::CopySelection(CopyAs::Path),
+        Tool::CopyName => Message::CopySelection(CopyAs::Name),
+        Tool::CopyUri => Message::CopySelection(CopyAs::Uri),
+        Tool::Tags => Message::ShowTags,
+        Tool::Star => Message::ToggleStar,
+        Tool::Starred => Message::ShowStarred,
+    }
+}
+
+// Tracks the window geometry so it can be restored next time
+fn handle_window_event(event: Event, _status: event::Status) -> Option<Message> {
+    let Event::Window(event) = event else {
+        return None;
+    };
+
+    match event {
+        iced::window::Event::Resized { width, height } => Some(Message::WindowResized(width, height)),
+        iced::window::Event::Moved { x, y } => Some(Message::WindowMoved(x, y)),
+        iced::window::Event::CloseRequested => Some(Message::CloseRequested),
+        _ => None,
+    }
+}
+
+fn file_list_id() -> scrollable::Id {
+    scrollable::Id::new("file-list")
+}
+
+fn palette_input_id() -> text_input::Id {
+    text_input::Id::new("palette")
+}
+
+fn editor_line_id(index: usize) -> text_input::Id {
+    text_input::Id::new(format!("editor-line-{}", index))
+}
+
+// Reads a file for the built-in editor, refusing anything large or binary
+fn read_text_file(path: &Path, units: SizeUnits) -> Result<String, String> {
+    let meta = metadata(path).map_err(|e| fill(tr("Could not open: {}"), &[&e]))?;
+    if meta.len() > MAX_EDITABLE_SIZE {
+        return Err(fill(tr("File is too large to edit here ({}, limit {})"), &[&units.format(meta.len()), &units.format(MAX_EDITABLE_SIZE)]));
+    }
+
+    let bytes = read(path).map_err(|e| fill(tr("Could not open: {}"), &[&e]))?;
+    if bytes.contains(&0) {
+        return Err(tr("This looks like a binary file and can't be edited").to_string());
+    }
+
+    String::from_utf8(bytes).map_err(|_| tr("File is not valid UTF-8 text").to_string())
+}
+
+// Attributes with their values shown as text, for Properties
+fn read_attributes(path: &Path) -> Result<Vec<(OsString, String)>, String> {
+    xattr::list(path)
+        .map(|attributes| attributes.into_iter().map(|(name, value)| (name, xattr::display(&value))).collect())
+        .map_err(|error| error.to_string())
+}
+
+fn read_acl(path: &Path) -> Result<Vec<acl::Entry>, String> {
+    acl::read(path).map_err(|error| error.to_string())
+}
+
+fn property_row<'a>(label: &str, value: String) -> Element<'a, Message> {
+    row![
+        text(label).width(Length::Fixed(100.0)),
+        text(value).width(Length::Fill)
+    ]
+    .padding(5)
+    .into()
+}
+
+fn exif_rows<'a>(exif: &photo::ExifSummary) -> Element<'a, Message> {
+    let mut rows = column![];
+
+    if let Some(camera) = &exif.camera {
+        rows = rows.push(property_row(tr("Camera:"), camera.clone()));
+    }
+    if let Some((width, height)) = exif.dimensions {
+        rows = rows.push(property_row(tr("Dimensions:"), fill(tr("{} × {} px"), &[&width, &height])));
+    }
+    if let Some(taken) = &exif.taken {
+        rows = rows.push(property_row(tr("Taken:"), taken.clone()));
+    }
+    if let Some(location) = &exif.location {
+        rows = rows.push(property_row(tr("Location:"), location.clone()));
+    }
+
+    rows.into()
+}
+
+fn media_rows<'a>(probe: &media::MediaProbe) -> Element<'a, Message> {
+    let info = match probe {
+        media::MediaProbe::Pending => return property_row(tr("Media:"), tr("Reading…").to_string()),
+        media::MediaProbe::Failed(error) => return property_row(tr("Media:"), error.clone()),
+        media::MediaProbe::Ready(info) => info,
+    };
+
+    let mut rows = column![];
+
+    if let Some(duration) = info.duration {
+        rows = rows.push(property_row(tr("Duration:"), media::format_duration(duration)));
+    }
+    if let Some(container) = &info.container {
+        rows = rows.push(property_row(tr("Format:"), container.clone()));
+    }
+    if let Some(bitrate) = info.bitrate {
+        rows = rows.push(property_row(tr("Bitrate:"), format!("{} kb/s", bitrate / 1000)));
+    }
+    if let Some(video) = &info.video {
+        rows = rows.push(property_row(tr("Video:"), video.clone()));
+    }
+    if let Some(audio) = &info.audio {
+        rows = rows.push(property_row(tr("Audio:"), audio.clone()));
+    }
+    for (key, value) in &info.tags {
+        let mut label = key.clone();
+        label[..1].make_ascii_uppercase();
+        rows = rows.push(property_row(&format!("{}:", label), value.clone()));
+    }
+
+    rows.into()
+}
+
+// Shows `name` after its icon with the part matching `filter` picked out
+fn highlighted_name<'a>(icon: &str, name: &str, filter: Option<&str>, size: f32) -> Element<'a, Message> {
+    let Some(range) = filter.and_then(|filter| search::find_ignore_case(name, filter)) else {
+        return text(format!("{} {}", icon, name)).size(size).into();
+    };
+
+    row![
+        text(format!("{} {}", icon, &name[..range.start])).size(size),
+        text(&name[range.clone()])
+            .size(size)
+            .style(theme::Text::Color(Color::from_rgb(0.95, 0.6, 0.1))),
+        text(&name[range.end..]).size(size)
+    ]
+    .into()
+}
+
+// Where the app starts without a home folder: the working directory if
+// there is one, otherwise the file system root
+fn fallback_home() -> PathBuf {
+    std::env::current_dir()
+        .ok()
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(|| PathBuf::from(std::path::MAIN_SEPARATOR_STR))
+}
+
+// `path` as a file:// URI, with everything but letters, digits and the
+// separators percent-encoded. Windows drives become file:///C:/… and
+// network shares file://server/share/….
+fn file_uri(path: &Path) -> String {
+    let bytes = if cfg!(windows) {
+        let text = path.to_string_lossy().replace('\\', "/");
+        // Verbatim paths, as canonicalizing gives on Windows
+        let text = match text.strip_prefix("//?/") {
+            Some(rest) => rest.strip_prefix("UNC/").map_or_else(|| rest.to_string(), |share| format!("//{}", share)),
+            None => text,
+        };
+        text.into_bytes()
+    } else {
+        // Names need not be UTF-8 here, and their bytes are what the URI holds
+        path.as_os_str().as_encoded_bytes().to_vec()
+    };
+    let encoded: String = bytes
+        .iter()
+        .map(|&byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    match encoded.strip_prefix("//") {
+        Some(share) if cfg!(windows) => format!("file://{}", share),
+        _ if encoded.starts_with('/') => format!("file://{}", encoded),
+        _ => format!("file:///{}", encoded),
+    }
+}
+
+fn banner_row<'a>(banner: &str) -> Element<'a, Message> {
+    container(
+        row![
+            text(format!("ℹ {}", banner)).size(14).width(Length::Fill),
+            button(text("✕").size(14))
+                .on_press(Message::DismissBanner)
+                .padding([2, 8])
+                .style(theme::Button::Text)
+        ]
+        .spacing(10)
+        .align_items(iced::Alignment::Center),
+    )
+    .padding([8, 28])
+    .width(Length::Fill)
+    .style(theme::Container::Box)
+    .into()
+}
+
+// Marks a name shown with replaced characters because it isn't valid UTF-8
+fn lossy_name_badge<'a>(text_size: f32) -> Element<'a, Message> {
+    tooltip(
+        text("⚠").size(text_size * 0.75).style(theme::Text::Color(Color::from_rgb(0.85, 0.55, 0.1))),
+        tr("This name isn't valid UTF-8; some characters are shown as �"),
+        tooltip::Position::FollowCursor,
+    )
+    .into()
+}
+
+fn cloud_badge<'a>(text_size: f32) -> Element<'a, Message> {
+    tooltip(
+        text("☁").size(text_size * 0.75).style(theme::Text::Color(Color::from_rgb(0.35, 0.6, 0.9))),
+        tr("Online-only; it is downloaded when opened"),
+        tooltip::Position::FollowCursor,
+    )
+    .into()
+}
+
+fn note_badge<'a>(note: &str, text_size: f32) -> Element<'a, Message> {
+    tooltip(text("📝").size(text_size * 0.75), note.to_string(), tooltip::Position::FollowCursor).into()
+}
+
+fn tag_color(color: tags::TagColor) -> Color {
+    match color {
+        tags::TagColor::Red => Color::from_rgb(0.86, 0.24, 0.24),
+        tags::TagColor::Orange => Color::from_rgb(0.93, 0.55, 0.15),
+        tags::TagColor::Yellow => Color::from_rgb(0.90, 0.78, 0.15),
+        tags::TagColor::Green => Color::from_rgb(0.30, 0.68, 0.32),
+        tags::TagColor::Blue => Color::from_rgb(0.25, 0.52, 0.90),
+        tags::TagColor::Purple => Color::from_rgb(0.62, 0.36, 0.82),
+        tags::TagColor::Gray => Color::from_rgb(0.55, 0.55, 0.55),
+    }
+}
+
+fn tag_dot<'a>(name: &str, color: tags::TagColor, text_size: f32) -> Element<'a, Message> {
+    tooltip(
+        text("●").size(text_size * 0.75).style(theme::Text::Color(tag_color(color))),
+        name.to_string(),
+        tooltip::Position::FollowCursor,
+    )
+    .into()
+}
+
+fn git_badge_color(badge: git::GitBadge) -> Color {
+    match badge {
+        git::GitBadge::Ignored => Color::from_rgb(0.55, 0.55, 0.55),
+        git::GitBadge::Untracked => Color::from_rgb(0.30, 0.65, 0.30),
+        git::GitBadge::Added => Color::from_rgb(0.20, 0.55, 0.80),
+        git::GitBadge::Modified => Color::from_rgb(0.85, 0.60, 0.10),
+        git::GitBadge::Conflicted => Color::from_rgb(0.85, 0.20, 0.20),
+    }
+}
+
+// Runs blocking filesystem or process work on its own thread so the
+// executor stays responsive; None means the worker panicked
+async fn run_blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> Option<T> {
+    let (sender, receiver) = iced::futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(work());
+    });
+    receiver.await.ok()
+}
+
+// A preview kept on disk from an earlier run is used as is; a new one is
+// kept there too. Failing to keep it only means making it again next time.
+async fn plugin_preview(
+    plugins: Arc<Vec<plugins::Plugin>>,
+    index: usize,
+    path: PathBuf,
+    stamp: previews::Stamp,
+    disk_mb: u64,
+) -> (PathBuf, Result<String, String>) {
+    let target = path.clone();
+    let result = run_blocking(move || {
+        if let Some(text) = previews::load(&target, stamp) {
+            return Ok(text);
+        }
+        let made = plugins[index].preview(&target);
+        if let Ok(text) = &made {
+            let _ = previews::store(&target, stamp, text, disk_mb);
+        }
+        made
+    })
+    .await
+    .unwrap_or_else(|| Err(tr("Preview was interrupted").to_string()));
+    (path, result)
+}
+
+async fn plugin_columns(plugins: Arc<Vec<plugins::Plugin>>, dir: PathBuf) -> (PathBuf, Vec<HashMap<PathBuf, String>>) {
+    let target = dir.clone();
+    let values = run_blocking(move || {
+        let target = &target;
+        plugins
+            .iter()
+            .flat_map(|plugin| (0..plugin.columns.len()).map(move |index| plugin.column_values(index, target)))
+            .collect()
+    })
+    .await
+    .unwrap_or_default();
+    (dir, values)
+}
+
+async fn run_plugin_action(plugins: Arc<Vec<plugins::Plugin>>, action: plugins::ActionRef, paths: Vec<PathBuf>) -> Result<plugins::Reply, String> {
+    run_blocking(move || plugins[action.plugin].run_action(action.action, &paths))
+        .await
+        .unwrap_or_else(|| Err(tr("The plugin was interrupted").to_string()))
+}
+
+// The path is returned alongside each result so stale ones can be ignored
+async fn probe_media(path: PathBuf) -> (PathBuf, Result<media::MediaInfo, String>) {
+    let target = path.clone();
+    let result = run_blocking(move || media::probe(&target))
+        .await
+        .unwrap_or_else(|| Err(tr("Probe was interrupted").to_string()));
+    (path, result)
+}
+
+async fn run_custom_command(command: commands::CustomCommand, path: PathBuf) -> Result<commands::Output, String> {
+    run_blocking(move || command.run(&path))
+        .await
+        .unwrap_or_else(|| Err(tr("The command was interrupted").to_string()))
+}
+
+fn load_index() -> Command<Message> {
+    Command::perform(run_blocking(index::load), |index| Message::IndexLoaded(index.flatten().map(Arc::new)))
+}
+
+// Crawls the roots and writes the result to disk before handing it back
+async fn build_index(roots: Vec<PathBuf>, excludes: Excludes) -> Option<Arc<index::Index>> {
+    run_blocking(move || {
+        let index = index::build(roots, &excludes);
+        let _ = index::save(&index);
+        Arc::new(index)
+    })
+    .await
+}
+
+async fn git_diff(path: PathBuf) -> (PathBuf, Result<String, String>) {
+    let target = path.clone();
+    let diff = run_blocking(move || git::diff(&target).map_err(|error| error.to_string()))
+        .await
+        .unwrap_or_else(|| Err(tr("The command was interrupted").to_string()));
+    (path, diff)
+}
+
+async fn git_status(dir: PathBuf) -> (PathBuf, Option<git::RepoStatus>) {
+    let target = dir.clone();
+    (dir, run_blocking(move || git::status(&target)).await.flatten())
+}
+
+async fn find_empty(root: PathBuf, excludes: Excludes) -> (PathBuf, Vec<cleanup::EmptyEntry>) {
+    let target = root.clone();
+    let found = run_blocking(move || cleanup::find_empty(&target, &excludes)).await.unwrap_or_default();
+    (root, found)
 }
 
-fn is_hidden(path: &Path) -> bool {
-    path.file_name()
-        .and_then(|name| name.to_str())
-        .map(|name| name.starts_with('.'))
-        .unwrap_or(false)
-}
\ No newline at end of file
+async fn scan_usage(path: PathBuf, excludes: Excludes) -> (PathBuf, usage::Node) {
+    let target = path.clone();
+    let node = run_blocking(move || usage::scan(&target, &excludes))
+        .await
+        .unwrap_or_else(|| usage::Node {
+            path: path.clone(),
+            name: path.display().to_string(),
+            size: 0,
+            is_dir: true,
+            children: Vec::new(),
+        });
+    (path, node)
+}
+
+// Dims whatever is behind a dialog, in the theme's background color
+fn overlay_style(theme: &Theme) -> container::Appearance {
+    let palette = theme.palette();
+    container::Appearance {
+        background: Some(Color { a: 0.85, ..palette.background }.into()),
+        text_color: Some(palette.text),
+        ..container::Appearance::default()
+    }
+}
+
+// A grey bar standing in for a row that hasn't loaded yet
+fn skeleton_style(theme: &Theme) -> container::Appearance {
+    let palette = theme.extended_palette();
+    container::Appearance {
+        background: Some(palette.background.weak.color.into()),
+        border_radius: 4.0.into(),
+        ..container::Appearance::default()
+    }
+}
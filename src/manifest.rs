@@ -0,0 +1,211 @@
+use crate::checksum::{self, Algorithm};
+use crate::outlet::Outlet;
+use crate::translate::tr;
+use std::fmt;
+use std::fs::read_to_string;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// How manifests are named: SHA256SUMS, release.sha256, file.md5 and so on
+const EXTENSIONS: [&str; 6] = ["sha256", "sha256sum", "sha256sums", "md5", "md5sum", "md5sums"];
+const NAMES: [&str; 2] = ["sha256sums", "md5sums"];
+
+// What one line of a manifest says a file's digest is
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub name: String,
+    pub file: PathBuf,
+    algorithm: Algorithm,
+    digest: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed,
+    Missing,
+    Unreadable,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Outcome::Passed => "Passed",
+            Outcome::Failed => "Failed",
+            Outcome::Missing => "Missing",
+            Outcome::Unreadable => "Could not be read",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    // Line `index` is being hashed
+    Checking(usize),
+    Checked(usize, Outcome),
+}
+
+pub fn is_manifest(path: &Path) -> bool {
+    let lower = |text: &std::ffi::OsStr| text.to_string_lossy().to_lowercase();
+    path.extension().map(lower).is_some_and(|ext| EXTENSIONS.contains(&ext.as_str()))
+        || path.file_stem().map(lower).is_some_and(|stem| NAMES.contains(&stem.as_str()))
+}
+
+// The lines of `manifest` that name a file and an MD5 or SHA-256 digest,
+// and the numbers of those that don't. Names are relative to the manifest's
+// folder. Both the GNU form, `digest  name` with `*` before binary names,
+// and the BSD form, `SHA256 (name) = digest`, are read; a file holding only
+// a digest is about the file it is named after.
+pub fn parse(manifest: &Path) -> io::Result<(Vec<Line>, Vec<usize>)> {
+    let contents = read_to_string(manifest)?;
+    let dir = manifest.parent().unwrap_or(Path::new(""));
+    let (mut lines, mut unreadable) = (Vec::new(), Vec::new());
+    for (index, text) in contents.lines().enumerate() {
+        let number = index + 1;
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let named = parse_line(text).or_else(|| {
+            // Only a digest: the manifest is named after the file it checks
+            let name = manifest.file_stem()?.to_string_lossy().into_owned();
+            (!text.contains(char::is_whitespace)).then(|| (text.to_string(), name))
+        });
+        match named.and_then(|(digest, name)| Some((checksum::parse_hex(&digest)?, name))) {
+            Some(((algorithm, digest), name)) => lines.push(Line { file: dir.join(&name), name, algorithm, digest }),
+            None => unreadable.push(number),
+        }
+    }
+    Ok((lines, unreadable))
+}
+
+// The digest and file name on a line, in either form. GNU tools mark lines
+// with a backslash in front when they escaped a newline, carriage return
+// or backslash in the name.
+fn parse_line(text: &str) -> Option<(String, String)> {
+    let (escaped, text) = match text.strip_prefix('\\') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+    let (digest, name) = if let Some((label, rest)) = text.split_once(" (")
+        && matches!(label, "SHA256" | "MD5")
+    {
+        let (name, digest) = rest.rsplit_once(") = ")?;
+        (digest, name)
+    } else {
+        let (digest, name) = text.split_once(char::is_whitespace)?;
+        let name = name.trim_start_matches([' ', '\t']);
+        (digest, name.strip_prefix('*').unwrap_or(name))
+    };
+    let name = if escaped { unescape(name)? } else { name.to_string() };
+    (!name.is_empty()).then(|| (digest.to_string(), name))
+}
+
+// Undoes the escaping in one pass, so an escaped backslash followed by `n`
+// stays that; None for escapes the tools don't write
+fn unescape(name: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        unescaped.push(match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            '\\' => '\\',
+            _ => return None,
+        });
+    }
+    Some(unescaped)
+}
+
+// Hashes the file of each line, reporting each to `outlet` as it starts and
+// ends; stops between chunks once nobody is listening
+pub fn verify(lines: &[Line], outlet: &impl Outlet<Event>) {
+    for (index, line) in lines.iter().enumerate() {
+        if !outlet.send(Event::Checking(index)) {
+            return;
+        }
+        let outcome = if !line.file.is_file() {
+            Outcome::Missing
+        } else {
+            match checksum::hash_file(&line.file, line.algorithm, || outlet.is_closed()) {
+                Some(digest) if digest == line.digest => Outcome::Passed,
+                Some(_) => Outcome::Failed,
+                None if outlet.is_closed() => return,
+                None => Outcome::Unreadable,
+            }
+        };
+        if !outlet.send(Event::Checked(index, outcome)) {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn named(name: &str) -> Option<(String, String)> {
+        Some((SHA256.to_string(), name.to_string()))
+    }
+
+    #[test]
+    fn reads_both_forms() {
+        assert_eq!(parse_line(&format!("{}  a b.txt", SHA256)), named("a b.txt"));
+        assert_eq!(parse_line(&format!("{} *disk.iso", SHA256)), named("disk.iso"));
+        assert_eq!(parse_line(&format!("{}\tdisk.iso", SHA256)), named("disk.iso"));
+        assert_eq!(parse_line(&format!("SHA256 (a) = b.txt) = {}", SHA256)), named("a) = b.txt"));
+        assert_eq!(parse_line(&format!("MD5 (x.bin) = {}", SHA256)), named("x.bin"));
+    }
+
+    #[test]
+    fn unescapes_in_one_pass() {
+        assert_eq!(parse_line(&format!("\\{}  a\\nb", SHA256)), named("a\nb"));
+        assert_eq!(parse_line(&format!("\\{}  a\\\\nb", SHA256)), named("a\\nb"));
+        assert_eq!(parse_line(&format!("\\{}  a\\\\\\nb", SHA256)), named("a\\\nb"));
+        assert_eq!(parse_line(&format!("\\{} *a\\rb", SHA256)), named("a\rb"));
+        assert_eq!(parse_line(&format!("\\SHA256 (a\\nb) = {}", SHA256)), named("a\nb"));
+        // Only escaped lines are unescaped
+        assert_eq!(parse_line(&format!("{}  a\\nb", SHA256)), named("a\\nb"));
+    }
+
+    #[test]
+    fn malformed_lines_are_refused() {
+        assert_eq!(parse_line(SHA256), None);
+        assert_eq!(parse_line(&format!("{}  ", SHA256)), None);
+        assert_eq!(parse_line(&format!("{} *", SHA256)), None);
+        assert_eq!(parse_line(&format!("\\{}  a\\", SHA256)), None);
+        assert_eq!(parse_line(&format!("\\{}  a\\tb", SHA256)), None);
+        assert_eq!(parse_line(&format!("SHA256 (a.txt) {}", SHA256)), None);
+    }
+
+    #[test]
+    fn parses_a_manifest_beside_its_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("SHA256SUMS");
+        let md5 = "d41d8cd98f00b204e9800998ecf8427e";
+        std::fs::write(
+            &manifest,
+            format!("# release\n{}  a.txt\n\n{} *sub/b.bin\nnot a line\n{}  short\n\\{}  c\\nd\n", SHA256, md5, &SHA256[..10], SHA256),
+        )
+        .unwrap();
+
+        let (lines, unreadable) = parse(&manifest).unwrap();
+        let names: Vec<&str> = lines.iter().map(|line| line.name.as_str()).collect();
+        assert_eq!(names, ["a.txt", "sub/b.bin", "c\nd"]);
+        assert_eq!(lines[1].file, dir.path().join("sub/b.bin"));
+        assert_eq!(unreadable, [5, 6]);
+
+        // A file holding only a digest is about the file it is named after
+        let single = dir.path().join("disk.iso.sha256");
+        std::fs::write(&single, format!("{}\n", SHA256)).unwrap();
+        let (lines, unreadable) = parse(&single).unwrap();
+        assert_eq!((lines[0].name.as_str(), unreadable.len()), ("disk.iso", 0));
+    }
+}
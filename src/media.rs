@@ -0,0 +1,122 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+// Tags worth showing in Properties, in display order
+const INTERESTING_TAGS: [&str; 6] = ["title", "artist", "album", "date", "genre", "track"];
+
+#[derive(Debug, Clone)]
+pub enum MediaProbe {
+    Pending,
+    Ready(MediaInfo),
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MediaInfo {
+    pub duration: Option<f64>,
+    pub container: Option<String>,
+    pub bitrate: Option<u64>,
+    pub video: Option<String>,
+    pub audio: Option<String>,
+    pub tags: Vec<(String, String)>,
+}
+
+#[derive(Deserialize)]
+struct ProbeOutput {
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+    format: Option<ProbeFormat>,
+}
+
+#[derive(Deserialize)]
+struct ProbeStream {
+    codec_type: Option<String>,
+    codec_long_name: Option<String>,
+    codec_name: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    sample_rate: Option<String>,
+    channels: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    format_long_name: Option<String>,
+    duration: Option<String>,
+    bit_rate: Option<String>,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+}
+
+// Runs ffprobe on `path`; blocking, so call it off the UI thread
+pub fn probe(path: &Path) -> Result<MediaInfo, String> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "quiet", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(path)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => "ffprobe is not installed".to_string(),
+            _ => format!("Could not run ffprobe: {}", e),
+        })?;
+
+    if !output.status.success() {
+        return Err("ffprobe could not read this file".to_string());
+    }
+
+    let parsed: ProbeOutput =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("Unexpected ffprobe output: {}", e))?;
+
+    let mut info = MediaInfo::default();
+
+    if let Some(format) = parsed.format {
+        info.container = format.format_long_name;
+        info.duration = format.duration.and_then(|d| d.parse().ok());
+        info.bitrate = format.bit_rate.and_then(|b| b.parse().ok());
+
+        // Tag keys vary in case between containers
+        let tags: BTreeMap<String, String> =
+            format.tags.into_iter().map(|(key, value)| (key.to_lowercase(), value)).collect();
+        info.tags = INTERESTING_TAGS
+            .iter()
+            .filter_map(|key| tags.get(*key).map(|value| (key.to_string(), value.clone())))
+            .collect();
+    }
+
+    for stream in parsed.streams {
+        let codec = stream.codec_long_name.or(stream.codec_name).unwrap_or_else(|| "unknown".to_string());
+        match stream.codec_type.as_deref() {
+            Some("video") if info.video.is_none() => {
+                info.video = Some(match (stream.width, stream.height) {
+                    (Some(width), Some(height)) => format!("{}, {} × {}", codec, width, height),
+                    _ => codec,
+                });
+            }
+            Some("audio") if info.audio.is_none() => {
+                let mut description = codec;
+                if let Some(rate) = stream.sample_rate {
+                    description = format!("{}, {} Hz", description, rate);
+                }
+                if let Some(channels) = stream.channels {
+                    description = format!("{}, {} ch", description, channels);
+                }
+                info.audio = Some(description);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(info)
+}
+
+// Formats seconds as h:mm:ss or m:ss
+pub fn format_duration(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    let (hours, minutes, secs) = (total / 3600, (total % 3600) / 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
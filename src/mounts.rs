@@ -0,0 +1,66 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// How long a network drive gets to answer before it is taken as offline
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Filesystems that reach over the network, where a lost server makes any
+// stat hang instead of failing
+#[cfg(target_os = "linux")]
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "ncpfs", "afs", "9p", "ceph", "davfs", "fuse.sshfs", "fuse.rclone", "fuse.glusterfs",
+];
+
+// The mount point of the network drive holding `path`, if it is on one.
+// Reads only the mount table, so it never waits on the drive itself.
+#[cfg(target_os = "linux")]
+pub fn network_mount(path: &Path) -> Option<PathBuf> {
+    let table = std::fs::read_to_string("/proc/self/mounts").ok()?;
+    table
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (_, point, kind) = (fields.next()?, fields.next()?, fields.next()?);
+            NETWORK_FILESYSTEMS.contains(&kind).then(|| PathBuf::from(unescape(point)))
+        })
+        .filter(|point| path.starts_with(point))
+        .max_by_key(|point| point.components().count())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn network_mount(_path: &Path) -> Option<PathBuf> {
+    None
+}
+
+// The mount table writes spaces and the like as octal escapes, e.g. \040
+#[cfg(target_os = "linux")]
+fn unescape(field: &str) -> String {
+    let mut bytes = Vec::with_capacity(field.len());
+    let mut rest = field.as_bytes();
+    while let Some((&first, tail)) = rest.split_first() {
+        if first == b'\\'
+            && let Some(digits) = tail.get(..3)
+            && let Some(byte) = std::str::from_utf8(digits).ok().and_then(|digits| u8::from_str_radix(digits, 8).ok())
+        {
+            bytes.push(byte);
+            rest = &tail[3..];
+        } else {
+            bytes.push(first);
+            rest = tail;
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+// Whether `path` can be looked at within `timeout`. The look happens on its
+// own thread, which is left behind if the drive never answers.
+pub fn answers(path: &Path, timeout: Duration) -> bool {
+    let (sender, receiver) = mpsc::channel();
+    let path = path.to_path_buf();
+    thread::spawn(move || {
+        let _ = sender.send(std::fs::metadata(path).is_ok());
+    });
+    receiver.recv_timeout(timeout).unwrap_or(false)
+}
@@ -0,0 +1,69 @@
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, create_dir_all, read_to_string};
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Free text the user attached to files and folders, kept by the app rather
+// than in the files, so any path can have one
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Notes {
+    notes: HashMap<PathBuf, String>,
+}
+
+impl Notes {
+    // What was saved; a missing or damaged file means no notes
+    pub fn load() -> Notes {
+        store_path()
+            .and_then(|path| read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = store_path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, contents)
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&str> {
+        self.notes.get(path).map(String::as_str)
+    }
+
+    // An empty note takes the note off
+    pub fn set(&mut self, path: &Path, note: &str) -> io::Result<()> {
+        let note = note.trim();
+        if note.is_empty() {
+            self.notes.remove(path);
+        } else {
+            self.notes.insert(path.to_path_buf(), note.to_string());
+        }
+        self.save()
+    }
+
+    // Keeps the notes of a file the app moved, and of everything below it
+    pub fn moved(&mut self, from: &Path, to: &Path) {
+        let moved: Vec<PathBuf> = self.notes.keys().filter(|path| path.starts_with(from)).cloned().collect();
+        if moved.is_empty() {
+            return;
+        }
+        for path in moved {
+            if let (Some(note), Ok(relative)) = (self.notes.remove(&path), path.strip_prefix(from)) {
+                self.notes.insert(to.join(relative), note);
+            }
+        }
+        if let Err(error) = self.save() {
+            tracing::warn!("could not save notes: {}", error);
+        }
+    }
+}
+
+fn store_path() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join("notes.json"))
+}
@@ -0,0 +1,83 @@
+use crate::diagnostics::Diagnostic;
+use std::time::{Duration, Instant, SystemTime};
+
+// How long a toast stays on screen unless dismissed sooner
+const TOAST_LIFETIME: Duration = Duration::from_secs(8);
+// The log keeps only the most recent errors
+const MAX_LOG_LEN: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct Notice {
+    pub id: u64,
+    pub message: String,
+    pub diagnostic: Diagnostic,
+    pub time: SystemTime,
+    shown: Instant,
+}
+
+// Errors from file operations: each shows as a toast for a while and stays
+// in the log until it is cleared
+#[derive(Debug, Default)]
+pub struct Notices {
+    toasts: Vec<Notice>,
+    log: Vec<Notice>,
+    next_id: u64,
+}
+
+impl Notices {
+    pub fn push(&mut self, error: impl Into<Diagnostic>) {
+        let notice = self.record(error);
+        self.toasts.push(notice);
+    }
+
+    // Adds to the log only, for errors too many or too minor for a toast
+    pub fn record(&mut self, error: impl Into<Diagnostic>) -> Notice {
+        let diagnostic = error.into();
+        tracing::warn!(path = ?diagnostic.path, code = ?diagnostic.code, "{}", diagnostic);
+        let notice = Notice {
+            id: self.next_id,
+            message: diagnostic.to_string(),
+            diagnostic,
+            time: SystemTime::now(),
+            shown: Instant::now(),
+        };
+        self.next_id += 1;
+
+        self.log.push(notice.clone());
+        if self.log.len() > MAX_LOG_LEN {
+            self.log.remove(0);
+        }
+        notice
+    }
+
+    pub fn toasts(&self) -> &[Notice] {
+        &self.toasts
+    }
+
+    // Newest first
+    pub fn log(&self) -> impl Iterator<Item = &Notice> {
+        self.log.iter().rev()
+    }
+
+    pub fn find(&self, id: u64) -> Option<&Notice> {
+        self.log.iter().find(|notice| notice.id == id)
+    }
+
+    pub fn log_len(&self) -> usize {
+        self.log.len()
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.toasts.retain(|toast| toast.id != id);
+    }
+
+    // Drops toasts that have been up long enough
+    pub fn expire(&mut self) {
+        self.toasts.retain(|toast| toast.shown.elapsed() < TOAST_LIFETIME);
+    }
+
+    pub fn clear_log(&mut self) {
+        self.log.clear();
+        self.toasts.clear();
+    }
+}
@@ -0,0 +1,121 @@
+// Reads text in images and scanned PDFs with the tesseract command. The
+// tesseract bindings would need libtesseract and leptonica to build at all,
+// so OCR stays an optional program; the window says so when it is missing.
+use crate::filetype;
+use crate::paths;
+use crate::previews::Stamp;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, create_dir_all, read_to_string};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+// Images tesseract reads by itself
+const IMAGES: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "bmp", "gif", "webp", "pnm"];
+
+// Pages of a scanned PDF read at most, as each takes a few seconds
+const MAX_PDF_PAGES: usize = 20;
+
+// Resolution pages are rendered at for reading; tesseract does best at 300
+const PDF_DPI: &str = "300";
+
+// Numbers the scratch folders of PDFs read at the same time
+static SCRATCH: AtomicUsize = AtomicUsize::new(0);
+
+// Whether `path` is an image or PDF that OCR could read text from
+pub fn handles(path: &Path) -> bool {
+    filetype::extension(path).is_some_and(|ext| ext == "pdf" || IMAGES.contains(&ext.as_str()))
+}
+
+// Whether tesseract is installed; asked once per run
+pub fn is_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        Command::new("tesseract")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+// The text in an image or PDF. Reading is slow, so what was read is kept
+// on disk until the file changes.
+pub fn text(path: &Path) -> Option<String> {
+    let stamp = Stamp::of(&fs::metadata(path).ok()?);
+    if let Some(text) = load(path, stamp) {
+        return Some(text);
+    }
+    let text = if filetype::extension(path).as_deref() == Some("pdf") { read_pdf(path)? } else { read_image(path)? };
+    store(path, stamp, &text);
+    Some(text)
+}
+
+fn read_image(path: &Path) -> Option<String> {
+    let output = Command::new("tesseract").arg(path).arg("stdout").stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// A PDF's own text layer when it has one; otherwise its pages are rendered
+// with pdftoppm and read one by one
+fn read_pdf(path: &Path) -> Option<String> {
+    if let Ok(output) = Command::new("pdftotext").arg(path).arg("-").stdin(Stdio::null()).stderr(Stdio::null()).output()
+        && output.status.success()
+    {
+        let layer = String::from_utf8_lossy(&output.stdout).into_owned();
+        if !layer.trim().is_empty() {
+            return Some(layer);
+        }
+    }
+
+    let scratch = std::env::temp_dir().join(format!("file-explorer-ocr-{}-{}", std::process::id(), SCRATCH.fetch_add(1, Ordering::Relaxed)));
+    create_dir_all(&scratch).ok()?;
+    let rendered = Command::new("pdftoppm")
+        .args(["-r", PDF_DPI, "-l", &MAX_PDF_PAGES.to_string(), "-png"])
+        .arg(path)
+        .arg(scratch.join("page"))
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+
+    let text = rendered.then(|| {
+        let mut pages: Vec<PathBuf> = fs::read_dir(&scratch).into_iter().flatten().flatten().map(|entry| entry.path()).collect();
+        pages.sort();
+        pages.iter().filter_map(|page| read_image(page)).collect::<Vec<_>>().join("\n")
+    });
+    let _ = fs::remove_dir_all(&scratch);
+    text
+}
+
+#[derive(Serialize, Deserialize)]
+struct Stored {
+    path: PathBuf,
+    stamp: Stamp,
+    text: String,
+}
+
+fn stored_path(path: &Path) -> Option<PathBuf> {
+    let digest = Sha256::digest(path.as_os_str().as_encoded_bytes());
+    let name: String = digest.iter().take(16).map(|byte| format!("{:02x}", byte)).collect();
+    paths::cache_dir().map(|dir| dir.join("ocr").join(format!("{}.json", name)))
+}
+
+fn load(path: &Path, stamp: Stamp) -> Option<String> {
+    let stored: Stored = serde_json::from_str(&read_to_string(stored_path(path)?).ok()?).ok()?;
+    (stored.path == path && stored.stamp == stamp).then_some(stored.text)
+}
+
+// Failing to keep the text only means reading it again next time
+fn store(path: &Path, stamp: Stamp, text: &str) {
+    let Some(file) = stored_path(path) else {
+        return;
+    };
+    let stored = Stored { path: path.to_path_buf(), stamp, text: text.to_string() };
+    if let (Some(dir), Ok(contents)) = (file.parent(), serde_json::to_string(&stored)) {
+        let _ = create_dir_all(dir).and_then(|_| fs::write(&file, contents));
+    }
+}
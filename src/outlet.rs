@@ -0,0 +1,23 @@
+use std::sync::mpsc::Sender;
+
+// Where work running on its own thread sends what it finds or how far it
+// got. The app forwards it to its window; other callers can use a plain
+// channel, or anything else that tells when nobody is listening any more.
+pub trait Outlet<T>: Send + Sync {
+    // Passes `item` on; false once nobody is listening and the work should
+    // stop
+    fn send(&self, item: T) -> bool;
+
+    fn is_closed(&self) -> bool;
+}
+
+// A std channel only tells that its receiver is gone when sent to
+impl<T: Send> Outlet<T> for Sender<T> {
+    fn send(&self, item: T) -> bool {
+        Sender::send(self, item).is_ok()
+    }
+
+    fn is_closed(&self) -> bool {
+        false
+    }
+}
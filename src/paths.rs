@@ -0,0 +1,41 @@
+use std::fs::remove_dir_all;
+use std::io;
+use std::path::PathBuf;
+use walkdir::WalkDir;
+
+// Everything the app stores goes below one folder in each of the places
+// `dirs` reports: XDG_CONFIG_HOME, XDG_DATA_HOME and XDG_CACHE_HOME on
+// Linux, Application Support and Caches on macOS, AppData on Windows
+const APP_DIR: &str = "filemanager";
+
+// Settings and plugins, which the user may edit or back up
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIR))
+}
+
+// State worth keeping between runs, like the remembered folder views
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR))
+}
+
+// Anything that can be rebuilt, like the search index
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join(APP_DIR))
+}
+
+// Deletes the cache folder, returning how many bytes it held
+pub fn clear_caches() -> io::Result<u64> {
+    let Some(dir) = cache_dir().filter(|dir| dir.exists()) else {
+        return Ok(0);
+    };
+
+    let size = WalkDir::new(&dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .map(|meta| meta.len())
+        .sum();
+    remove_dir_all(&dir)?;
+    Ok(size)
+}
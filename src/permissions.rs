@@ -0,0 +1,166 @@
+use crate::translate::{fill, tr};
+use std::collections::HashMap;
+use std::fs::{read_to_string, Metadata};
+
+// Terms understood by permission searches
+const TERMS: &str = "world-writable, setuid, setgid, sticky, executable, mode:755, owner:NAME, group:NAME";
+
+// Conditions a permission search checks; every one given must hold
+#[derive(Debug, Clone, Default)]
+pub struct PermissionFilter {
+    world_writable: bool,
+    setuid: bool,
+    setgid: bool,
+    sticky: bool,
+    executable: bool,
+    mode: Option<u32>,
+    owner: Option<u32>,
+    group: Option<u32>,
+    // Id to name tables, for describing matches
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+impl PermissionFilter {
+    // Parses whitespace-separated terms such as `setuid owner:root`
+    pub fn parse(query: &str) -> Result<PermissionFilter, String> {
+        if !cfg!(unix) {
+            return Err(tr("Permission search is only available on Unix").to_string());
+        }
+
+        let mut filter = PermissionFilter {
+            users: users(),
+            groups: groups(),
+            ..PermissionFilter::default()
+        };
+
+        for term in query.split_whitespace() {
+            match term.split_once(':') {
+                None => match term.to_lowercase().as_str() {
+                    "world-writable" => filter.world_writable = true,
+                    "setuid" => filter.setuid = true,
+                    "setgid" => filter.setgid = true,
+                    "sticky" => filter.sticky = true,
+                    "executable" => filter.executable = true,
+                    _ => return Err(fill(tr("Unknown term '{}'; try {}"), &[&term, &TERMS])),
+                },
+                Some((key, value)) => match key.to_lowercase().as_str() {
+                    "mode" => {
+                        let mode = u32::from_str_radix(value, 8).map_err(|_| fill(tr("'{}' is not an octal mode"), &[&value]))?;
+                        filter.mode = Some(mode);
+                    }
+                    "owner" => filter.owner = Some(resolve(value, &filter.users)?),
+                    "group" => filter.group = Some(resolve(value, &filter.groups)?),
+                    _ => return Err(fill(tr("Unknown term '{}'; try {}"), &[&term, &TERMS])),
+                },
+            }
+        }
+
+        Ok(filter)
+    }
+
+    #[cfg(unix)]
+    pub fn accepts(&self, meta: &Metadata) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        // Symlinks always carry 0o777, which says nothing
+        if meta.file_type().is_symlink() {
+            return false;
+        }
+
+        let mode = meta.mode() & 0o7777;
+        (!self.world_writable || mode & 0o002 != 0)
+            && (!self.setuid || mode & 0o4000 != 0)
+            && (!self.setgid || mode & 0o2000 != 0)
+            && (!self.sticky || mode & 0o1000 != 0)
+            && (!self.executable || (meta.is_file() && mode & 0o111 != 0))
+            && self.mode.is_none_or(|wanted| mode == wanted)
+            && self.owner.is_none_or(|uid| meta.uid() == uid)
+            && self.group.is_none_or(|gid| meta.gid() == gid)
+    }
+
+    #[cfg(not(unix))]
+    pub fn accepts(&self, _meta: &Metadata) -> bool {
+        false
+    }
+
+    // Mode and ownership in `ls -l` style, e.g. "rwsr-xr-x root:root"
+    #[cfg(unix)]
+    pub fn describe(&self, meta: &Metadata) -> String {
+        use std::os::unix::fs::MetadataExt;
+
+        let name = |table: &HashMap<u32, String>, id: u32| table.get(&id).cloned().unwrap_or_else(|| id.to_string());
+        format!(
+            "{} {}:{}",
+            symbolic_mode(meta.mode()),
+            name(&self.users, meta.uid()),
+            name(&self.groups, meta.gid())
+        )
+    }
+
+    #[cfg(not(unix))]
+    pub fn describe(&self, _meta: &Metadata) -> String {
+        String::new()
+    }
+}
+
+// Looks a user or group up by name, or takes a numeric id as is
+pub fn resolve(name: &str, table: &HashMap<u32, String>) -> Result<u32, String> {
+    if let Ok(id) = name.parse() {
+        return Ok(id);
+    }
+    table
+        .iter()
+        .find(|(_, known)| known.as_str() == name)
+        .map(|(id, _)| *id)
+        .ok_or_else(|| fill(tr("No user or group named '{}'"), &[&name]))
+}
+
+// User and group names by id
+pub fn users() -> HashMap<u32, String> {
+    read_id_table("/etc/passwd")
+}
+
+pub fn groups() -> HashMap<u32, String> {
+    read_id_table("/etc/group")
+}
+
+// Reads the id and name columns of /etc/passwd or /etc/group; accounts only
+// known to directory services are shown by number
+fn read_id_table(path: &str) -> HashMap<u32, String> {
+    read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((id, name.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn symbolic_mode(mode: u32) -> String {
+    let bit = |mask: u32, c: char| if mode & mask != 0 { c } else { '-' };
+    let special = |exec: u32, special: u32, set: char, unset: char| match (mode & exec != 0, mode & special != 0) {
+        (true, true) => set,
+        (false, true) => unset,
+        (true, false) => 'x',
+        (false, false) => '-',
+    };
+
+    [
+        bit(0o400, 'r'),
+        bit(0o200, 'w'),
+        special(0o100, 0o4000, 's', 'S'),
+        bit(0o040, 'r'),
+        bit(0o020, 'w'),
+        special(0o010, 0o2000, 's', 'S'),
+        bit(0o004, 'r'),
+        bit(0o002, 'w'),
+        special(0o001, 0o1000, 't', 'T'),
+    ]
+    .iter()
+    .collect()
+}
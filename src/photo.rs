@@ -0,0 +1,79 @@
+use crate::filetype;
+use exif::{In, Reader, Tag, Value};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+// Containers the EXIF reader understands
+const EXIF_EXTENSIONS: [&str; 8] = ["jpg", "jpeg", "heic", "heif", "tif", "tiff", "png", "webp"];
+
+#[derive(Debug, Clone, Default)]
+pub struct ExifSummary {
+    pub camera: Option<String>,
+    pub dimensions: Option<(u32, u32)>,
+    pub taken: Option<String>,
+    pub location: Option<String>,
+}
+
+// Reads the camera details of an image, or None if it has no EXIF data
+pub fn read_exif(path: &Path) -> Option<ExifSummary> {
+    let ext = filetype::extension(path)?;
+    if !EXIF_EXTENSIONS.contains(&ext.as_str()) {
+        return None;
+    }
+
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let exif = Reader::new().read_from_container(&mut reader).ok()?;
+
+    let text = |tag: Tag| {
+        exif.get_field(tag, In::PRIMARY).and_then(|field| match &field.value {
+            Value::Ascii(values) => values
+                .first()
+                .map(|value| String::from_utf8_lossy(value).trim().to_string())
+                .filter(|value| !value.is_empty()),
+            _ => None,
+        })
+    };
+    let number = |tag: Tag| exif.get_field(tag, In::PRIMARY).and_then(|field| field.value.get_uint(0));
+
+    let camera = match (text(Tag::Make), text(Tag::Model)) {
+        // Many cameras repeat the make in the model name
+        (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => make.or(model),
+    };
+
+    let dimensions = number(Tag::PixelXDimension)
+        .zip(number(Tag::PixelYDimension))
+        .or_else(|| number(Tag::ImageWidth).zip(number(Tag::ImageLength)));
+
+    let taken = text(Tag::DateTimeOriginal).or_else(|| text(Tag::DateTime));
+
+    let coordinate = |value_tag: Tag, ref_tag: Tag| {
+        let field = exif.get_field(value_tag, In::PRIMARY)?;
+        let Value::Rational(parts) = &field.value else {
+            return None;
+        };
+        let degrees = parts.first()?.to_f64()
+            + parts.get(1).map_or(0.0, |minutes| minutes.to_f64() / 60.0)
+            + parts.get(2).map_or(0.0, |seconds| seconds.to_f64() / 3600.0);
+        Some(format!("{:.6}° {}", degrees, text(ref_tag).unwrap_or_default()))
+    };
+
+    let location = coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef)
+        .zip(coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef))
+        .map(|(latitude, longitude)| format!("{}, {}", latitude.trim(), longitude.trim()));
+
+    let summary = ExifSummary {
+        camera,
+        dimensions,
+        taken,
+        location,
+    };
+
+    if summary.camera.is_none() && summary.dimensions.is_none() && summary.taken.is_none() && summary.location.is_none() {
+        None
+    } else {
+        Some(summary)
+    }
+}
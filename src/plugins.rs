@@ -0,0 +1,275 @@
+use crate::filetype;
+use crate::paths;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{read_dir, read_to_string};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Longest preview shown, in characters
+const MAX_PREVIEW_LEN: usize = 4000;
+
+// How long a plugin may take to answer before it is stopped. Columns and
+// previews hold up what the user is looking at; actions may do real work.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+const ACTION_TIMEOUT: Duration = Duration::from_secs(120);
+
+// How often a running plugin is checked for having finished
+const WAIT_POLL: Duration = Duration::from_millis(50);
+
+// Plugins live in folders under `<config>/filemanager/plugins`, each with a
+// plugin.json like
+//
+//     { "name": "Word count", "command": "./wc.py",
+//       "actions": [{ "id": "count", "label": "Count words" }],
+//       "columns": [{ "id": "words", "label": "Words" }],
+//       "preview": ["md", "txt"] }
+//
+// The command is run in the plugin's folder for each request, which it gets
+// as JSON on stdin; it answers with JSON on stdout:
+//
+//     {"request": "action", "action": id, "paths": [...]}  -> {"message": "...", "refresh": true}
+//     {"request": "column", "column": id, "dir": "..."}    -> {"values": {"file name": "text"}}
+//     {"request": "preview", "path": "..."}                -> {"text": "..."}
+//
+// Every field of an answer is optional. Paths are sent as text, so files
+// whose names aren't UTF-8 can't be given to plugins. A plugin that takes
+// longer than 10 seconds for a column or preview, or 2 minutes for an
+// action, is stopped.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Plugin {
+    pub name: String,
+    command: String,
+    #[serde(default)]
+    pub actions: Vec<Labelled>,
+    #[serde(default)]
+    pub columns: Vec<Labelled>,
+    // Extensions the plugin can preview
+    #[serde(default)]
+    preview: Vec<String>,
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Labelled {
+    pub id: String,
+    pub label: String,
+}
+
+// What an action asks of the app once it is done
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Reply {
+    pub message: Option<String>,
+    // The action changed files, so the listing should be reloaded
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+// An action offered in the plugin menu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionRef {
+    pub plugin: usize,
+    pub action: usize,
+    label: String,
+}
+
+impl fmt::Display for ActionRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+pub fn plugins_dir() -> Option<PathBuf> {
+    paths::config_dir().map(|dir| dir.join("plugins"))
+}
+
+// Reads every plugin manifest; folders without a readable one are skipped
+pub fn load() -> Vec<Plugin> {
+    let Some(entries) = plugins_dir().and_then(|dir| read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+
+    let mut plugins: Vec<Plugin> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let dir = entry.path();
+            let manifest = read_to_string(dir.join("plugin.json")).ok()?;
+            let mut plugin: Plugin = serde_json::from_str(&manifest).ok()?;
+            plugin.dir = dir;
+            Some(plugin)
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+// All plugin actions, labelled with the plugin they come from
+pub fn actions(plugins: &[Plugin]) -> Vec<ActionRef> {
+    plugins
+        .iter()
+        .enumerate()
+        .flat_map(|(plugin_index, plugin)| {
+            plugin.actions.iter().enumerate().map(move |(action_index, action)| ActionRef {
+                plugin: plugin_index,
+                action: action_index,
+                label: format!("{}: {}", plugin.name, action.label),
+            })
+        })
+        .collect()
+}
+
+impl Plugin {
+    // By what the contents are where they tell, like the list's icons
+    pub fn previews(&self, path: &Path) -> bool {
+        filetype::extension(path).is_some_and(|ext| self.preview.iter().any(|wanted| wanted.eq_ignore_ascii_case(&ext)))
+    }
+
+    // Runs action `index` on `paths`; blocking, so call it off the UI thread
+    pub fn run_action(&self, index: usize, paths: &[PathBuf]) -> Result<Reply, String> {
+        let action = self.actions.get(index).ok_or("No such action")?;
+        let paths = paths.iter().map(|path| self.text_of(path)).collect::<Result<Vec<_>, _>>()?;
+        let answer = self.call(json!({ "request": "action", "action": action.id, "paths": paths }), ACTION_TIMEOUT)?;
+        serde_json::from_value(answer).map_err(|e| format!("{}: {}", self.name, e))
+    }
+
+    // Values for column `index` of the files in `dir`, keyed by path
+    pub fn column_values(&self, index: usize, dir: &Path) -> HashMap<PathBuf, String> {
+        let Some(column) = self.columns.get(index) else {
+            return HashMap::new();
+        };
+        let Ok(answer) = self.text_of(dir).and_then(|text| self.call(json!({ "request": "column", "column": column.id, "dir": text }), LOOKUP_TIMEOUT))
+        else {
+            return HashMap::new();
+        };
+
+        answer["values"]
+            .as_object()
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|(name, value)| Some((dir.join(name), value.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn preview(&self, path: &Path) -> Result<String, String> {
+        let answer = self.call(json!({ "request": "preview", "path": self.text_of(path)? }), LOOKUP_TIMEOUT)?;
+        Ok(answer["text"].as_str().unwrap_or_default().chars().take(MAX_PREVIEW_LEN).collect())
+    }
+
+    fn text_of<'a>(&self, path: &'a Path) -> Result<&'a str, String> {
+        path.to_str().ok_or_else(|| format!("{}: {} is not UTF-8 and can't be passed on", self.name, path.display()))
+    }
+
+    // Sends `request` and waits for the answer, stopping the plugin if it
+    // takes longer than `timeout`
+    fn call(&self, request: Value, timeout: Duration) -> Result<Value, String> {
+        // A command starting with ./ lives in the plugin folder
+        let program = match self.command.strip_prefix("./") {
+            Some(relative) => self.dir.join(relative),
+            None => PathBuf::from(&self.command),
+        };
+        let mut child = Command::new(&program)
+            .current_dir(&self.dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("{}: {}", self.name, e))?;
+
+        // Writing and reading on threads of their own, a plugin that reads
+        // or writes nothing can't hold up the clock
+        if let Some(mut stdin) = child.stdin.take() {
+            let request = request.to_string();
+            thread::spawn(move || stdin.write_all(request.as_bytes()));
+        }
+        let mut stdout = child.stdout.take().ok_or_else(|| format!("{}: no output", self.name))?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut answer = Vec::new();
+            let _ = sender.send(stdout.read_to_end(&mut answer).map(|_| answer));
+        });
+
+        // The answer is complete once the output is closed, which the plugin
+        // may do before it exits
+        let deadline = Instant::now() + timeout;
+        let answer = receiver.recv_timeout(timeout);
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) if answer.is_ok() => break status,
+                Ok(None) if answer.is_ok() && Instant::now() < deadline => thread::sleep(WAIT_POLL),
+                Ok(_) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!("{} did not answer within {} seconds and was stopped", self.name, timeout.as_secs()));
+                }
+                Err(e) => return Err(format!("{}: {}", self.name, e)),
+            }
+        };
+        if !status.success() {
+            return Err(format!("{} failed ({})", self.name, status));
+        }
+        let answer = answer.map_err(|_| format!("{}: no output", self.name))?.map_err(|e| format!("{}: {}", self.name, e))?;
+        serde_json::from_slice(&answer).map_err(|e| format!("{}: {}", self.name, e))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    // A plugin in a folder of its own whose command is the shell `script`
+    fn plugin(script: &str) -> (tempfile::TempDir, Plugin) {
+        let dir = tempfile::tempdir().unwrap();
+        let command = dir.path().join("run.sh");
+        std::fs::write(&command, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&command, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let plugin = Plugin {
+            name: "test".to_string(),
+            command: "./run.sh".to_string(),
+            actions: vec![Labelled { id: "go".to_string(), label: "Go".to_string() }],
+            columns: vec![Labelled { id: "size".to_string(), label: "Size".to_string() }],
+            preview: vec!["txt".to_string()],
+            dir: dir.path().to_path_buf(),
+        };
+        (dir, plugin)
+    }
+
+    #[test]
+    fn answers_are_read_from_stdout() {
+        let (_dir, plugin) = plugin(r#"grep -q '"path":"/tmp/a.txt"' && echo '{"text": "seen"}'"#);
+        assert_eq!(plugin.preview(Path::new("/tmp/a.txt")).unwrap(), "seen");
+    }
+
+    #[test]
+    fn a_plugin_that_hangs_is_stopped() {
+        let (_dir, plugin) = plugin("sleep 30");
+        let started = Instant::now();
+        let error = plugin.call(json!({ "request": "preview", "path": "/tmp/a.txt" }), Duration::from_millis(300)).unwrap_err();
+        assert!(error.contains("did not answer"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn paths_that_are_not_utf8_are_refused() {
+        use std::os::unix::ffi::OsStrExt;
+        let (dir, plugin) = plugin(r#"touch ran; echo '{"values": {"a.txt": "1"}}'"#);
+        assert_eq!(plugin.column_values(0, Path::new("/tmp")).get(Path::new("/tmp/a.txt")).map(String::as_str), Some("1"));
+        std::fs::remove_file(dir.path().join("ran")).unwrap();
+
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"/tmp/caf\xe9.txt"));
+        assert!(plugin.preview(path).is_err());
+        assert!(plugin.run_action(0, &[path.to_path_buf()]).is_err());
+        assert!(plugin.column_values(0, path).is_empty());
+        assert!(!dir.path().join("ran").exists());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Sizes offered in settings for the previews kept in memory and on disk
+pub const CACHE_SIZES_MB: [u64; 5] = [16, 64, 256, 1024, 4096];
+
+// A cache size in megabytes, as offered in settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheSize(pub u64);
+
+impl fmt::Display for CacheSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 >= 1024 {
+            write!(f, "{} GB", self.0 / 1024)
+        } else {
+            write!(f, "{} MB", self.0)
+        }
+    }
+}
+
+// What a file looked like when its preview was made; a preview of a file
+// that has changed since is thrown away
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stamp {
+    pub modified: u128,
+    pub len: u64,
+}
+
+impl Stamp {
+    pub fn of(meta: &fs::Metadata) -> Stamp {
+        let modified = meta.modified().ok().and_then(|time| time.duration_since(UNIX_EPOCH).ok()).map_or(0, |since| since.as_nanos());
+        Stamp { modified, len: meta.len() }
+    }
+}
+
+#[derive(Debug)]
+struct Cached {
+    stamp: Stamp,
+    text: String,
+    last_used: u64,
+}
+
+// Previews made by plugins, held in memory up to a budget. When it's
+// exceeded the least recently shown go first; they stay on disk, where
+// the oldest are pruned the same way.
+#[derive(Debug)]
+pub struct PreviewCache {
+    entries: HashMap<PathBuf, Cached>,
+    used: u64,
+    budget: u64,
+    clock: u64,
+}
+
+impl PreviewCache {
+    pub fn new(budget_mb: u64) -> PreviewCache {
+        PreviewCache { entries: HashMap::new(), used: 0, budget: budget_mb * 1024 * 1024, clock: 0 }
+    }
+
+    pub fn get(&mut self, path: &Path, stamp: Stamp) -> Option<String> {
+        self.clock += 1;
+        match self.entries.get_mut(path) {
+            Some(cached) if cached.stamp == stamp => {
+                cached.last_used = self.clock;
+                Some(cached.text.clone())
+            }
+            Some(_) => {
+                self.remove(path);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&mut self, path: PathBuf, stamp: Stamp, text: String) {
+        self.remove(&path);
+        self.clock += 1;
+        self.used += text.len() as u64;
+        self.entries.insert(path, Cached { stamp, text, last_used: self.clock });
+        self.evict();
+    }
+
+    pub fn set_budget(&mut self, budget_mb: u64) {
+        self.budget = budget_mb * 1024 * 1024;
+        self.evict();
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(cached) = self.entries.remove(path) {
+            self.used -= cached.text.len() as u64;
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.used > self.budget {
+            let Some(oldest) = self.entries.iter().min_by_key(|(_, cached)| cached.last_used).map(|(path, _)| path.clone()) else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Stored {
+    path: PathBuf,
+    stamp: Stamp,
+    text: String,
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    paths::cache_dir().map(|dir| dir.join("previews"))
+}
+
+fn stored_path(path: &Path) -> Option<PathBuf> {
+    let digest = Sha256::digest(path.as_os_str().as_encoded_bytes());
+    let name: String = digest.iter().take(16).map(|byte| format!("{:02x}", byte)).collect();
+    cache_dir().map(|dir| dir.join(format!("{}.json", name)))
+}
+
+// The preview kept on disk for `path`, if it was made from the file as it
+// is now
+pub fn load(path: &Path, stamp: Stamp) -> Option<String> {
+    let stored: Stored = serde_json::from_str(&read_to_string(stored_path(path)?).ok()?).ok()?;
+    (stored.path == path && stored.stamp == stamp).then_some(stored.text)
+}
+
+// Keeps a preview on disk, then prunes the least recently made ones
+// beyond `budget_mb`
+pub fn store(path: &Path, stamp: Stamp, text: &str, budget_mb: u64) -> std::io::Result<()> {
+    let (Some(dir), Some(file)) = (cache_dir(), stored_path(path)) else {
+        return Ok(());
+    };
+    create_dir_all(&dir)?;
+    let stored = Stored { path: path.to_path_buf(), stamp, text: text.to_string() };
+    let contents = serde_json::to_string(&stored).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    write(file, contents)?;
+
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = fs::read_dir(&dir)?
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            Some((meta.modified().unwrap_or(UNIX_EPOCH), meta.len(), entry.path()))
+        })
+        .collect();
+    files.sort();
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    for (_, len, file) in files {
+        if total <= budget_mb * 1024 * 1024 {
+            break;
+        }
+        fs::remove_file(file)?;
+        total -= len;
+    }
+    Ok(())
+}
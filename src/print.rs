@@ -0,0 +1,44 @@
+use std::path::Path;
+use std::process::Command;
+
+// Kinds the system spooler prints by itself, without the app that made them
+const PRINTABLE: &[&str] = &["pdf", "ps", "txt", "text", "log", "md", "csv", "jpg", "jpeg", "png", "gif", "tif", "tiff", "bmp"];
+
+pub fn can_print(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| PRINTABLE.contains(&ext.to_lowercase().as_str()))
+}
+
+// Sends `path` to the default printer, returning what the spooler said.
+// Waits for the spooler to take the job, so call it off the UI thread.
+#[cfg(unix)]
+pub fn print(path: &Path) -> Result<String, String> {
+    // lp comes with CUPS; lpr is the older BSD spooler
+    let output = Command::new("lp")
+        .arg(path)
+        .output()
+        .or_else(|_| Command::new("lpr").arg(path).output())
+        .map_err(|error| format!("lp/lpr: {}", error))?;
+    finish(output)
+}
+
+// The app registered for the file prints it, showing its dialog if it has one
+#[cfg(not(unix))]
+pub fn print(path: &Path) -> Result<String, String> {
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-Command", "& { Start-Process -FilePath $args[0] -Verb Print }"])
+        .arg(path)
+        .output()
+        .map_err(|error| format!("powershell: {}", error))?;
+    finish(output)
+}
+
+fn finish(output: std::process::Output) -> Result<String, String> {
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(if stderr.is_empty() { output.status.to_string() } else { stderr })
+    }
+}
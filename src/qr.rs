@@ -0,0 +1,456 @@
+use crate::Message;
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
+
+// Light modules kept around the code so scanners can find its edges
+const QUIET_ZONE: usize = 4;
+
+// Error correction codewords per block and number of blocks at level M,
+// for versions 1 to 10; index 0 is unused. Version 10 holds over 200
+// bytes, plenty for a URL.
+const ECC_PER_BLOCK: [usize; 11] = [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26];
+const BLOCKS: [usize; 11] = [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5];
+
+// A QR code in byte mode at error correction level M
+#[derive(Debug, Clone)]
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+}
+
+// The grid while it is being built, with the modules taken by fixed
+// patterns marked so data and masks leave them alone
+struct Grid {
+    size: usize,
+    dark: Vec<bool>,
+    fixed: Vec<bool>,
+}
+
+impl QrCode {
+    // The smallest code holding `text`, or None if it is too long
+    pub fn encode(text: &str) -> Option<QrCode> {
+        let bytes = text.as_bytes();
+        let version = (1..ECC_PER_BLOCK.len()).find(|&version| 4 + length_bits(version) + bytes.len() * 8 <= data_capacity(version) * 8)?;
+        let codewords = interleave(version, &data_codewords(version, bytes));
+
+        let mut grid = Grid::new(version);
+        grid.draw_codewords(&codewords);
+
+        // Each mask is tried and the one leaving the fewest confusing
+        // patterns is kept; masking twice undoes it
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                grid.apply_mask(mask);
+                grid.draw_format(mask);
+                let penalty = grid.penalty();
+                grid.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or(0);
+        grid.apply_mask(mask);
+        grid.draw_format(mask);
+
+        Some(QrCode { size: grid.size, modules: grid.dark })
+    }
+
+    fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+}
+
+// Codewords left for data once error correction takes its share
+fn data_capacity(version: usize) -> usize {
+    raw_modules(version) / 8 - ECC_PER_BLOCK[version] * BLOCKS[version]
+}
+
+// Modules left for codewords once the fixed patterns are drawn
+fn raw_modules(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        modules -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+    modules
+}
+
+// Bits giving the length of byte mode data
+fn length_bits(version: usize) -> usize {
+    if version < 10 { 8 } else { 16 }
+}
+
+fn push_bits(bits: &mut Vec<bool>, value: usize, count: usize) {
+    bits.extend((0..count).rev().map(|bit| (value >> bit) & 1 == 1));
+}
+
+// Mode, length and bytes, padded out to the version's capacity
+fn data_codewords(version: usize, bytes: &[u8]) -> Vec<u8> {
+    let capacity = data_capacity(version) * 8;
+    let mut bits = Vec::with_capacity(capacity);
+
+    push_bits(&mut bits, 0b0100, 4);
+    push_bits(&mut bits, bytes.len(), length_bits(version));
+    for &byte in bytes {
+        push_bits(&mut bits, byte as usize, 8);
+    }
+    let terminator = (capacity - bits.len()).min(4);
+    push_bits(&mut bits, 0, terminator);
+    let padding = (8 - bits.len() % 8) % 8;
+    push_bits(&mut bits, 0, padding);
+
+    let mut codewords: Vec<u8> = bits.chunks(8).map(|byte| byte.iter().fold(0, |value, &bit| value << 1 | u8::from(bit))).collect();
+    for pad in [0xEC, 0x11].into_iter().cycle() {
+        if codewords.len() >= capacity / 8 {
+            break;
+        }
+        codewords.push(pad);
+    }
+    codewords
+}
+
+// Splits the data into blocks, adds each block's error correction and
+// interleaves them in the order they are laid out
+fn interleave(version: usize, data: &[u8]) -> Vec<u8> {
+    let (blocks, ecc_len) = (BLOCKS[version], ECC_PER_BLOCK[version]);
+    let raw = raw_modules(version) / 8;
+    let short_blocks = blocks - raw % blocks;
+    let short_len = raw / blocks;
+    let divisor = reed_solomon_divisor(ecc_len);
+
+    let mut split = Vec::with_capacity(blocks);
+    let mut start = 0;
+    for index in 0..blocks {
+        let len = short_len - ecc_len + usize::from(index >= short_blocks);
+        let mut block = data[start..start + len].to_vec();
+        start += len;
+        let ecc = reed_solomon_remainder(&block, &divisor);
+        // Short blocks get a placeholder so all line up for interleaving
+        if index < short_blocks {
+            block.push(0);
+        }
+        block.extend(ecc);
+        split.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw);
+    for position in 0..split[0].len() {
+        for (index, block) in split.iter().enumerate() {
+            if position != short_len - ecc_len || index >= short_blocks {
+                result.push(block[position]);
+            }
+        }
+    }
+    result
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0; degree];
+    result[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for index in 0..degree {
+            result[index] = gf_multiply(result[index], root);
+            if index + 1 < degree {
+                result[index] ^= result[index + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (value, &coefficient) in result.iter_mut().zip(divisor) {
+            *value ^= gf_multiply(coefficient, factor);
+        }
+    }
+    result
+}
+
+// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut product: u32 = 0;
+    for bit in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x11D);
+        product ^= ((u32::from(y) >> bit) & 1) * u32::from(x);
+    }
+    product as u8
+}
+
+impl Grid {
+    // A grid with the finder, timing and alignment patterns and the version
+    // information drawn, and the format information reserved
+    fn new(version: usize) -> Grid {
+        let size = version * 4 + 17;
+        let mut grid = Grid { size, dark: vec![false; size * size], fixed: vec![false; size * size] };
+
+        for index in 0..size {
+            grid.set_fixed(6, index, index % 2 == 0);
+            grid.set_fixed(index, 6, index % 2 == 0);
+        }
+
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4..=4_isize {
+                for dx in -4..=4_isize {
+                    let (xx, yy) = (x as isize + dx, y as isize + dy);
+                    if (0..size as isize).contains(&xx) && (0..size as isize).contains(&yy) {
+                        let distance = dx.abs().max(dy.abs());
+                        grid.set_fixed(xx as usize, yy as usize, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+
+        let positions = alignment_positions(version, size);
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                // Not where the finder patterns are
+                if (i, j) == (0, 0) || (i, j) == (0, last) || (i, j) == (last, 0) {
+                    continue;
+                }
+                for dy in -2..=2_isize {
+                    for dx in -2..=2_isize {
+                        let distance = dx.abs().max(dy.abs());
+                        grid.set_fixed((x as isize + dx) as usize, (y as isize + dy) as usize, distance != 1);
+                    }
+                }
+            }
+        }
+
+        grid.draw_format(0);
+
+        if version >= 7 {
+            let mut remainder = version;
+            for _ in 0..12 {
+                remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+            }
+            let bits = version << 12 | remainder;
+            for index in 0..18 {
+                let dark = (bits >> index) & 1 == 1;
+                let (a, b) = (size - 11 + index % 3, index / 3);
+                grid.set_fixed(a, b, dark);
+                grid.set_fixed(b, a, dark);
+            }
+        }
+        grid
+    }
+
+    fn set_fixed(&mut self, x: usize, y: usize, dark: bool) {
+        self.dark[y * self.size + x] = dark;
+        self.fixed[y * self.size + x] = true;
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.dark[y * self.size + x]
+    }
+
+    // The level (M) and mask, protected by a BCH code, drawn twice
+    fn draw_format(&mut self, mask: usize) {
+        let data = mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let bit = |index: usize| (bits >> index) & 1 == 1;
+        let size = self.size;
+
+        for index in 0..=5 {
+            self.set_fixed(8, index, bit(index));
+        }
+        self.set_fixed(8, 7, bit(6));
+        self.set_fixed(8, 8, bit(7));
+        self.set_fixed(7, 8, bit(8));
+        for index in 9..15 {
+            self.set_fixed(14 - index, 8, bit(index));
+        }
+
+        for index in 0..8 {
+            self.set_fixed(size - 1 - index, 8, bit(index));
+        }
+        for index in 8..15 {
+            self.set_fixed(8, size - 15 + index, bit(index));
+        }
+        self.set_fixed(8, size - 8, true);
+    }
+
+    // Lays the codewords out in the zigzag of two-module columns, from the
+    // bottom right, skipping the vertical timing pattern
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut bit = 0;
+        let mut right = size as isize - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..size {
+                for offset in 0..2 {
+                    let x = right as usize - offset;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vertical } else { vertical };
+                    if !self.fixed[y * size + x] && bit < codewords.len() * 8 {
+                        self.dark[y * size + x] = (codewords[bit / 8] >> (7 - bit % 8)) & 1 == 1;
+                        bit += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: usize) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if invert && !self.fixed[y * self.size + x] {
+                    self.dark[y * self.size + x] ^= true;
+                }
+            }
+        }
+    }
+
+    // How hard the code would be to scan: long runs, 2×2 blocks, shapes
+    // like the finder patterns and an uneven share of dark modules
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+        let lines = (0..size).flat_map(|a| {
+            [
+                (0..size).map(|b| self.get(b, a)).collect::<Vec<_>>(),
+                (0..size).map(|b| self.get(a, b)).collect::<Vec<_>>(),
+            ]
+        });
+        for line in lines {
+            for run in line.chunk_by(|a, b| a == b).map(<[bool]>::len).filter(|&len| len >= 5) {
+                penalty += run - 2;
+            }
+            // Light modules beyond the edge count, as the quiet zone shows them
+            let padded: Vec<bool> = [false; 4].into_iter().chain(line).chain([false; 4]).collect();
+            for window in padded.windows(11) {
+                let core = [true, false, true, true, true, false, true];
+                if (window[..4].iter().all(|&dark| !dark) && window[4..] == core) || (window[..7] == core && window[7..].iter().all(|&dark| !dark)) {
+                    penalty += 40;
+                }
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.get(x, y);
+                if color == self.get(x + 1, y) && color == self.get(x, y + 1) && color == self.get(x + 1, y + 1) {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let total = size * size;
+        let dark = self.dark.iter().filter(|&&dark| dark).count();
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty + (deviation.div_ceil(total)).saturating_sub(1) * 10
+    }
+}
+
+// Centres of the alignment patterns along each axis
+fn alignment_positions(version: usize, size: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2;
+    let mut positions = vec![6];
+    let mut position = size - 7;
+    for _ in 0..count - 1 {
+        positions.insert(1, position);
+        position -= step;
+    }
+    positions
+}
+
+impl canvas::Program<Message> for QrCode {
+    type State = ();
+
+    fn draw(&self, _state: &(), renderer: &Renderer, _theme: &Theme, bounds: Rectangle, _cursor: mouse::Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        // Dark on light whatever the theme, as scanners expect
+        frame.fill_rectangle(Point::ORIGIN, bounds.size(), Color::WHITE);
+
+        let side = bounds.width.min(bounds.height);
+        let module = side / (self.size + 2 * QUIET_ZONE) as f32;
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.is_dark(x, y) {
+                    let position = Point::new((x + QUIET_ZONE) as f32 * module, (y + QUIET_ZONE) as f32 * module);
+                    frame.fill_rectangle(position, Size::new(module, module), Color::BLACK);
+                }
+            }
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_correction_matches_the_published_example() {
+        // HELLO WORLD at 1-M, from the worked example in the standard
+        let data = [32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17];
+        let ecc = reed_solomon_remainder(&data, &reed_solomon_divisor(10));
+        assert_eq!(ecc, [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+        // One block at version 1, so the data is laid out as it is
+        assert_eq!(interleave(1, &data), [&data[..], &ecc[..]].concat());
+    }
+
+    #[test]
+    fn bytes_are_framed_and_padded() {
+        assert_eq!(
+            data_codewords(1, b"hello"),
+            [0x40, 0x56, 0x86, 0x56, 0xC6, 0xC6, 0xF0, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC]
+        );
+    }
+
+    #[test]
+    fn format_and_version_bits_match_the_standard_tables() {
+        // Level M with each mask, most significant bit first
+        let expected = [0x5412, 0x5125, 0x5E7C, 0x5B4B, 0x45F9, 0x40CE, 0x4F97, 0x4AA0];
+        for (mask, expected) in expected.into_iter().enumerate() {
+            let mut grid = Grid::new(1);
+            grid.draw_format(mask);
+            let mut bits = (0..=5).map(|index| grid.get(8, index)).collect::<Vec<_>>();
+            bits.extend([grid.get(8, 7), grid.get(8, 8), grid.get(7, 8)]);
+            bits.extend((9..15).map(|index| grid.get(14 - index, 8)));
+            let read = bits.iter().enumerate().fold(0, |value, (index, &dark)| value | usize::from(dark) << index);
+            assert_eq!(read, expected, "mask {}", mask);
+        }
+
+        let grid = Grid::new(7);
+        let read = (0..18).fold(0, |value, index| value | usize::from(grid.get(grid.size - 11 + index % 3, index / 3)) << index);
+        assert_eq!(read, 0x07C94);
+    }
+
+    #[test]
+    fn picks_the_smallest_version() {
+        assert_eq!(QrCode::encode("hello").unwrap().size, 21);
+        assert_eq!(QrCode::encode(&"x".repeat(15)).unwrap().size, 25);
+        assert!(QrCode::encode(&"x".repeat(300)).is_none());
+    }
+}
@@ -0,0 +1,64 @@
+use crate::excludes::Excludes;
+use crate::loader::{self, Sink};
+use crate::translate::tr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// How far back the recently modified list reaches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Day,
+    Week,
+}
+
+impl Window {
+    pub const ALL: [Window; 2] = [Window::Day, Window::Week];
+
+    pub fn since(self) -> SystemTime {
+        let hours = match self {
+            Window::Day => 24,
+            Window::Week => 7 * 24,
+        };
+        SystemTime::now()
+            .checked_sub(Duration::from_secs(hours * 60 * 60))
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Window::Day => "Last 24 hours",
+            Window::Week => "Last 7 days",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+// Finds the files below `roots` modified within `window`; hidden and
+// excluded entries are skipped
+pub fn files(roots: &[PathBuf], window: Window, excludes: &Excludes, sink: &mut Sink) {
+    let since = window.since();
+    for root in roots {
+        if !scan(root, since, excludes, sink) {
+            return;
+        }
+    }
+}
+
+// Returns false once the walk should stop altogether
+fn scan(root: &Path, since: SystemTime, excludes: &Excludes, sink: &mut Sink) -> bool {
+    for entry in excludes.walk(root, false).build().flatten() {
+        if sink.is_cancelled() {
+            return false;
+        }
+
+        let recent = entry.file_type().is_some_and(|kind| kind.is_file())
+            && entry.metadata().ok().and_then(|meta| meta.modified().ok()).is_some_and(|time| time >= since);
+        if recent && !sink.push(loader::read_entry(entry.into_path())) {
+            return false;
+        }
+    }
+    true
+}
@@ -0,0 +1,708 @@
+use crate::boolean::Expr;
+use crate::checksum::{self, Algorithm};
+use crate::cloud;
+use crate::entry::{FileEntry, LineMatch};
+use crate::excludes::Excludes;
+use crate::external;
+use crate::loader::{self, Sink};
+use crate::ocr;
+use crate::permissions::PermissionFilter;
+use crate::tags::{self, Tags};
+use crate::translate::{fill, tr};
+use globset::{GlobBuilder, GlobMatcher};
+use ignore::{DirEntry, WalkState};
+use chrono::{Local, NaiveDate, TimeZone};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// Content search skips files larger than this
+pub const MAX_CONTENT_SIZE: u64 = 4 * 1024 * 1024;
+
+// At most this many matching lines are kept per file
+pub const MAX_SNIPPETS: usize = 5;
+
+// Snippets longer than this are cut off
+pub const MAX_SNIPPET_LEN: usize = 160;
+
+// How the query text is interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    // Substring, or a glob when the query has wildcards
+    Plain,
+    Regex,
+    Fuzzy,
+}
+
+// What a search looks at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Scope {
+    #[default]
+    Names,
+    Contents,
+    // Files whose MD5 or SHA-256 digest equals the query
+    Checksum,
+    // Entries whose mode or ownership fit terms like `setuid owner:root`
+    Permissions,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [Scope::Names, Scope::Contents, Scope::Checksum, Scope::Permissions];
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Scope::Names => "Names",
+            Scope::Contents => "Contents",
+            Scope::Checksum => "Checksum",
+            Scope::Permissions => "Permissions",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub matcher: Matcher,
+    pub scope: Scope,
+    // Only entries modified within this range match
+    pub modified: Option<DateRange>,
+    // Content searches also read text in images and scanned PDFs
+    pub ocr: bool,
+    // Name searches with `tag:name` terms look up each entry's tags in this
+    // copy of the database
+    pub tags: Option<tags::Snapshot>,
+}
+
+impl Query {
+    pub fn new(text: &str, syntax: Syntax, scope: Scope, known: &Tags) -> Result<Query, String> {
+        let matcher = match scope {
+            Scope::Names => Matcher::new(text, syntax)?,
+            Scope::Contents => Matcher::for_contents(text, syntax)?,
+            Scope::Checksum => Matcher::for_checksum(text)?,
+            Scope::Permissions => Matcher::Permissions {
+                text: text.trim().to_string(),
+                filter: PermissionFilter::parse(text)?,
+            },
+        };
+        let tags = matcher.uses_tags().then(|| known.snapshot());
+        Ok(Query {
+            matcher,
+            scope,
+            modified: None,
+            ocr: false,
+            tags,
+        })
+    }
+
+    pub fn accepts_modified(&self, time: SystemTime) -> bool {
+        self.modified.is_none_or(|range| range.contains(time))
+    }
+
+    // Reads the entry at `path`, `relative` below the search root, if its
+    // name matches. Tag terms need the tags reading it loads, so with those
+    // every entry is read before it is tested.
+    pub fn read_if_named(&self, path: PathBuf, relative: &Path) -> Option<FileEntry> {
+        match &self.tags {
+            Some(tags) => {
+                let entry = loader::read_entry(path);
+                self.matcher.is_tagged_match(relative, tags.of(&entry.path, &entry.tags)).then_some(entry)
+            }
+            None => self.matcher.is_match(relative).then(|| loader::read_entry(path)),
+        }
+    }
+}
+
+// Bounds on modification time; either end may be open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<SystemTime>,
+    pub until: Option<SystemTime>,
+}
+
+impl DateRange {
+    pub fn contains(&self, time: SystemTime) -> bool {
+        self.from.is_none_or(|from| time >= from) && self.until.is_none_or(|until| time < until)
+    }
+
+    // The range between two days typed as YYYY-MM-DD, both included; empty
+    // text leaves that end open. A day that can't be read is an error rather
+    // than an open end, so a typo never widens the search.
+    pub fn from_days(from: &str, until: &str) -> Result<Option<DateRange>, String> {
+        let (first, last) = (parse_day(from)?, parse_day(until)?);
+        if let (Some(first), Some(last)) = (first, last)
+            && last < first
+        {
+            return Err(fill(tr("The range ends on {} before it starts on {}"), &[&last, &first]));
+        }
+        let start = |day: NaiveDate| start_of_day(day).ok_or_else(|| fill(tr("'{}' is not a date like 2024-01-31"), &[&day]));
+        let from = first.map(start).transpose()?;
+        let until = last.map(|day| start(day.succ_opt().unwrap_or(NaiveDate::MAX))).transpose()?;
+        Ok((from.is_some() || until.is_some()).then_some(DateRange { from, until }))
+    }
+}
+
+fn parse_day(text: &str) -> Result<Option<NaiveDate>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| fill(tr("'{}' is not a date like 2024-01-31"), &[&text]))
+}
+
+// The first moment of `day` here; where the clocks skip midnight that is
+// the hour they skip to
+fn start_of_day(day: NaiveDate) -> Option<SystemTime> {
+    let start = (0..24).find_map(|hour| Local.from_local_datetime(&day.and_hms_opt(hour, 0, 0)?).earliest())?;
+    Some(start.into())
+}
+
+// Quick choices for the modification date filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DatePreset {
+    #[default]
+    AnyTime,
+    Today,
+    LastWeek,
+    LastMonth,
+    LastYear,
+    // Between two typed dates
+    Custom,
+}
+
+impl DatePreset {
+    pub const ALL: [DatePreset; 6] = [
+        DatePreset::AnyTime,
+        DatePreset::Today,
+        DatePreset::LastWeek,
+        DatePreset::LastMonth,
+        DatePreset::LastYear,
+        DatePreset::Custom,
+    ];
+
+    // The range a preset stands for; Custom is up to the caller
+    pub fn range(self) -> Option<DateRange> {
+        const DAY: u64 = 24 * 60 * 60;
+        let since = |days: u64| {
+            Some(DateRange {
+                from: SystemTime::now().checked_sub(Duration::from_secs(days * DAY)),
+                until: None,
+            })
+        };
+
+        match self {
+            DatePreset::AnyTime | DatePreset::Custom => None,
+            DatePreset::Today => Some(DateRange {
+                from: start_of_day(Local::now().date_naive()),
+                until: None,
+            }),
+            DatePreset::LastWeek => since(7),
+            DatePreset::LastMonth => since(30),
+            DatePreset::LastYear => since(365),
+        }
+    }
+}
+
+impl fmt::Display for DatePreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            DatePreset::AnyTime => "Any time",
+            DatePreset::Today => "Today",
+            DatePreset::LastWeek => "Last 7 days",
+            DatePreset::LastMonth => "Last 30 days",
+            DatePreset::LastYear => "Last year",
+            DatePreset::Custom => "Between dates…",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+// Decides which file names a search query matches
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    // Case-insensitive substring
+    Substring { text: String, lowered: String },
+    Regex(Regex),
+    // Shell-style wildcards; patterns containing '/' match the path
+    // relative to the search root, others just the file name
+    Glob { text: String, glob: GlobMatcher, match_path: bool },
+    // Every character in order, not necessarily adjacent; `lowered` has no
+    // whitespace
+    Fuzzy { text: String, lowered: String },
+    // Only used by checksum searches
+    Checksum { text: String, algorithm: Algorithm, digest: Vec<u8> },
+    // Only used by permission searches
+    Permissions { text: String, filter: PermissionFilter },
+    // Entries carrying the tag `name`, written `tag:name`; only in name
+    // searches
+    Tag { text: String, name: String },
+    // Several plain terms joined with AND, OR and NOT
+    Boolean { text: String, expr: Expr },
+}
+
+impl Matcher {
+    // Builds a matcher for `query`; invalid patterns are reported. Plain
+    // queries containing wildcards are treated as globs.
+    pub fn new(query: &str, syntax: Syntax) -> Result<Matcher, String> {
+        if syntax == Syntax::Plain && Expr::is_boolean(query) {
+            Ok(Matcher::Boolean {
+                text: query.to_string(),
+                expr: Expr::parse(query, |term| Matcher::new(term, Syntax::Plain))?,
+            })
+        } else if let Some(name) = query.trim().strip_prefix("tag:").filter(|_| syntax == Syntax::Plain) {
+            if name.is_empty() {
+                return Err(tr("Expected a tag name after tag:").to_string());
+            }
+            Ok(Matcher::Tag {
+                text: query.trim().to_string(),
+                name: name.to_string(),
+            })
+        } else if syntax == Syntax::Fuzzy {
+            Ok(Matcher::Fuzzy {
+                text: query.to_string(),
+                lowered: query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect(),
+            })
+        } else if syntax == Syntax::Regex {
+            RegexBuilder::new(query)
+                .case_insensitive(true)
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|e| fill(tr("Invalid regular expression: {}"), &[&e]))
+        } else if query.contains(['*', '?', '[']) {
+            GlobBuilder::new(query)
+                .case_insensitive(true)
+                .literal_separator(true)
+                .build()
+                .map(|glob| Matcher::Glob {
+                    text: query.to_string(),
+                    glob: glob.compile_matcher(),
+                    match_path: query.contains('/'),
+                })
+                .map_err(|e| fill(tr("Invalid glob pattern: {}"), &[&e]))
+        } else {
+            Ok(Matcher::Substring {
+                text: query.to_string(),
+                lowered: query.to_lowercase(),
+            })
+        }
+    }
+
+    // Like `new`, but wildcards are taken literally and fuzzy queries become
+    // substrings, since neither makes much sense inside file contents
+    pub fn for_contents(query: &str, syntax: Syntax) -> Result<Matcher, String> {
+        if syntax == Syntax::Regex {
+            Matcher::new(query, Syntax::Regex)
+        } else if syntax == Syntax::Plain && Expr::is_boolean(query) {
+            Ok(Matcher::Boolean {
+                text: query.to_string(),
+                expr: Expr::parse(query, |term| Matcher::for_contents(term, Syntax::Plain))?,
+            })
+        } else {
+            Ok(Matcher::Substring {
+                text: query.to_string(),
+                lowered: query.to_lowercase(),
+            })
+        }
+    }
+
+    pub fn for_checksum(query: &str) -> Result<Matcher, String> {
+        let (algorithm, digest) = checksum::parse_hex(query)
+            .ok_or_else(|| tr("Expected a 32-digit MD5 or 64-digit SHA-256 hex value").to_string())?;
+        Ok(Matcher::Checksum {
+            text: query.trim().to_string(),
+            algorithm,
+            digest,
+        })
+    }
+
+    // Tests a line of text for content search
+    pub fn is_text_match(&self, line: &str) -> bool {
+        match self {
+            Matcher::Substring { lowered, .. } => line.to_lowercase().contains(lowered),
+            Matcher::Regex(regex) => regex.is_match(line),
+            Matcher::Glob { text, .. } => line.contains(text.as_str()),
+            Matcher::Fuzzy { lowered, .. } => fuzzy_score(lowered, line).is_some(),
+            Matcher::Boolean { expr, .. } => expr.eval(&|term| term.is_text_match(line)),
+            Matcher::Checksum { .. } | Matcher::Permissions { .. } | Matcher::Tag { .. } => false,
+        }
+    }
+
+    // Tests an entry given its path relative to the search root
+    pub fn is_match(&self, relative: &Path) -> bool {
+        self.is_tagged_match(relative, &[])
+    }
+
+    // Like `is_match`, for an entry carrying `tags`
+    pub fn is_tagged_match(&self, relative: &Path, tags: &[String]) -> bool {
+        match self {
+            Matcher::Tag { name, .. } => return tags.iter().any(|tag| tag.eq_ignore_ascii_case(name)),
+            Matcher::Boolean { expr, .. } => return expr.eval(&|term| term.is_tagged_match(relative, tags)),
+            _ => {}
+        }
+
+        // Names that aren't UTF-8 are matched with the invalid bytes replaced
+        let Some(name) = relative.file_name().map(|n| n.to_string_lossy()) else {
+            return false;
+        };
+        let name = name.as_ref();
+
+        match self {
+            Matcher::Substring { lowered, .. } => name.to_lowercase().contains(lowered),
+            Matcher::Regex(regex) => regex.is_match(name),
+            Matcher::Glob { glob, match_path: true, .. } => glob.is_match(relative),
+            Matcher::Glob { glob, .. } => glob.is_match(name),
+            Matcher::Fuzzy { lowered, .. } => fuzzy_score(lowered, name).is_some(),
+            Matcher::Checksum { .. } | Matcher::Permissions { .. } | Matcher::Tag { .. } | Matcher::Boolean { .. } => false,
+        }
+    }
+
+    // Whether matching needs the tags of each entry
+    pub fn uses_tags(&self) -> bool {
+        match self {
+            Matcher::Tag { .. } => true,
+            Matcher::Boolean { expr, .. } => expr.terms().iter().any(|term| term.uses_tags()),
+            _ => false,
+        }
+    }
+
+    // How well an entry matches, higher is better; only fuzzy queries rank
+    pub fn score(&self, relative: &Path) -> Option<i64> {
+        let Matcher::Fuzzy { lowered, .. } = self else {
+            return None;
+        };
+        let name = relative.file_name()?.to_string_lossy();
+        fuzzy_score(lowered, &name)
+    }
+
+    // The query as the user typed it
+    pub fn pattern(&self) -> &str {
+        match self {
+            Matcher::Substring { text, .. }
+            | Matcher::Glob { text, .. }
+            | Matcher::Fuzzy { text, .. }
+            | Matcher::Checksum { text, .. }
+            | Matcher::Permissions { text, .. }
+            | Matcher::Tag { text, .. }
+            | Matcher::Boolean { text, .. } => text,
+            Matcher::Regex(regex) => regex.as_str(),
+        }
+    }
+
+    // Hidden entries are searched only when the query asks for them
+    pub fn wants_hidden(&self) -> bool {
+        match self {
+            Matcher::Substring { text, .. } | Matcher::Glob { text, .. } => {
+                text.starts_with('.') || text.contains("/.")
+            }
+            Matcher::Fuzzy { lowered, .. } => lowered.starts_with('.'),
+            Matcher::Boolean { expr, .. } => expr.positive_terms().iter().any(|term| term.wants_hidden()),
+            Matcher::Checksum { .. } | Matcher::Tag { .. } => false,
+            // Audits should not miss dotfiles
+            Matcher::Permissions { .. } => true,
+            Matcher::Regex(regex) => regex.as_str().starts_with("\\.") || regex.as_str().starts_with("^\\."),
+        }
+    }
+}
+
+// Narrows an existing result set: every plain term must appear in the
+// relative path and no `-term` may, ignoring case
+#[derive(Debug, Clone, Default)]
+pub struct Refinement {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl Refinement {
+    pub fn parse(text: &str) -> Refinement {
+        let mut refinement = Refinement::default();
+        for term in text.split_whitespace().map(str::to_lowercase) {
+            match term.strip_prefix('-') {
+                Some("") => {}
+                Some(excluded) => refinement.exclude.push(excluded.to_string()),
+                None => refinement.include.push(term),
+            }
+        }
+        refinement
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn accepts(&self, relative: &Path) -> bool {
+        let path = relative.to_string_lossy().to_lowercase();
+        self.include.iter().all(|term| path.contains(term.as_str()))
+            && !self.exclude.iter().any(|term| path.contains(term.as_str()))
+    }
+}
+
+// Byte range of the first case-insensitive occurrence of `needle`
+pub fn find_ignore_case(haystack: &str, needle: &str) -> Option<Range<usize>> {
+    if needle.is_empty() {
+        return None;
+    }
+
+    haystack.char_indices().find_map(|(start, _)| {
+        let mut rest = haystack[start..].char_indices();
+        for wanted in needle.chars() {
+            let (_, c) = rest.next()?;
+            if !c.to_lowercase().eq(wanted.to_lowercase()) {
+                return None;
+            }
+        }
+        let end = rest.next().map_or(haystack.len(), |(offset, _)| start + offset);
+        Some(start..end)
+    })
+}
+
+// Scores `candidate` against a lowercased fuzzy pattern, or None unless every
+// pattern character appears in order. Matches at the start of a word and runs
+// of adjacent matches are rewarded; gaps between matches cost a little.
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i64> {
+    let mut wanted = pattern.chars().peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+
+    for (position, c) in candidate.chars().enumerate() {
+        let Some(&next) = wanted.peek() else {
+            break;
+        };
+
+        if c.to_lowercase().next() == Some(next) {
+            score += 16;
+
+            let word_start = match previous {
+                None => true,
+                Some(before) => !before.is_alphanumeric() || (before.is_lowercase() && c.is_uppercase()),
+            };
+            if word_start {
+                score += 32;
+            }
+
+            match last_match {
+                Some(last) if last + 1 == position => score += 24,
+                Some(last) => score -= (position - last - 1).min(8) as i64,
+                None => score -= position.min(8) as i64,
+            }
+
+            last_match = Some(position);
+            wanted.next();
+        }
+
+        previous = Some(c);
+    }
+
+    wanted.peek().is_none().then_some(score)
+}
+
+// Finds the entries below `root` that match the query, skipping whatever
+// `excludes` leaves out. With `use_external` the walk is handed to rg/fd
+// when they are installed and can run the query.
+pub fn find(root: &Path, query: &Query, excludes: &Excludes, use_external: bool, sink: &mut Sink) {
+    // ripgrep can't read images nor fd tags, so those searches walk the
+    // tree here
+    if !(use_external && !query.ocr && query.tags.is_none() && external::search(root, query, excludes, sink)) {
+        walk(root, query, excludes, sink);
+    }
+}
+
+fn walk(root: &Path, query: &Query, excludes: &Excludes, sink: &mut Sink) {
+    let include_hidden = matches!(query.scope, Scope::Names | Scope::Permissions) && query.matcher.wants_hidden();
+
+    // Folders are read and files searched on several threads, each sending
+    // matches through its own sink; the list is sorted as they arrive, so
+    // the order they come in doesn't matter
+    excludes.walk(root, include_hidden).build_parallel().run(|| {
+        let mut sink = sink.fork();
+        Box::new(move |entry| match entry.map(|entry| visit(entry, root, query, &mut sink)) {
+            Ok(false) => WalkState::Quit,
+            _ => WalkState::Continue,
+        })
+    });
+}
+
+// Checks one entry of the walk, passing it on if it matches; false once
+// the search should stop
+fn visit(entry: DirEntry, root: &Path, query: &Query, sink: &mut Sink) -> bool {
+    let matcher = &query.matcher;
+
+    // Checked on every entry since reading file contents can take a
+    // while; the check itself is only an atomic load
+    if sink.is_cancelled() {
+        return false;
+    }
+
+    // The date is cheap to check, so it goes before reading contents
+    if query.modified.is_some() {
+        let modified = entry.metadata().ok().and_then(|meta| meta.modified().ok());
+        if !modified.is_some_and(|time| query.accepts_modified(time)) {
+            return true;
+        }
+    }
+
+    // Reading a placeholder's contents would download it
+    let readable = || entry.file_type().is_some_and(|kind| kind.is_file()) && entry.metadata().is_ok_and(|meta| !cloud::is_placeholder(&meta));
+
+    let found = match query.scope {
+        Scope::Names => match entry.path().strip_prefix(root) {
+            Ok(relative) => {
+                let relative = relative.to_path_buf();
+                query.read_if_named(entry.into_path(), &relative)
+            }
+            Err(_) => None,
+        },
+        Scope::Contents if readable() => {
+            let lines = if query.ocr && ocr::handles(entry.path()) {
+                ocr::text(entry.path()).map_or_else(Vec::new, |text| lines_matching(&text, matcher))
+            } else {
+                matching_lines(entry.path(), matcher)
+            };
+            (!lines.is_empty()).then(|| {
+                let mut found = loader::read_entry(entry.into_path());
+                found.line_matches = lines;
+                found
+            })
+        }
+        Scope::Checksum => match matcher {
+            Matcher::Checksum { algorithm, digest, .. } if readable() => {
+                checksum::hash_file(entry.path(), *algorithm, || sink.is_cancelled())
+                    .filter(|hash| hash == digest)
+                    .map(|_| loader::read_entry(entry.into_path()))
+            }
+            _ => None,
+        },
+        Scope::Permissions => match (matcher, entry.metadata()) {
+            (Matcher::Permissions { filter, .. }, Ok(meta)) if filter.accepts(&meta) => {
+                let mut found = loader::read_entry(entry.into_path());
+                found.detail = Some(filter.describe(&meta));
+                Some(found)
+            }
+            _ => None,
+        },
+        Scope::Contents => None,
+    };
+
+    match found {
+        Some(found) => sink.push(found),
+        None => true,
+    }
+}
+
+// Finds the first few matching lines of a text file; large and binary files
+// are skipped
+fn matching_lines(path: &Path, matcher: &Matcher) -> Vec<LineMatch> {
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+    if file.metadata().map_or(true, |meta| meta.len() > MAX_CONTENT_SIZE) {
+        return Vec::new();
+    }
+
+    let mut bytes = Vec::new();
+    if file.take(MAX_CONTENT_SIZE).read_to_end(&mut bytes).is_err() || bytes.contains(&0) {
+        return Vec::new();
+    }
+
+    lines_matching(&String::from_utf8_lossy(&bytes), matcher)
+}
+
+// The first few lines of `text` that match
+fn lines_matching(text: &str, matcher: &Matcher) -> Vec<LineMatch> {
+    // Boolean queries are about the whole file: `foo -bar` wants files that
+    // mention foo but never bar. Lines with any wanted term are shown.
+    let shown: Vec<&Matcher> = match matcher {
+        Matcher::Boolean { expr, .. } => {
+            if !expr.eval(&|term| text.lines().any(|line| term.is_text_match(line))) {
+                return Vec::new();
+            }
+            expr.positive_terms()
+        }
+        _ => vec![matcher],
+    };
+
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| shown.iter().any(|term| term.is_text_match(line)))
+        .take(MAX_SNIPPETS)
+        .map(|(index, line)| LineMatch {
+            line: index + 1,
+            text: line.trim().chars().take(MAX_SNIPPET_LEN).collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(query: &str, name: &str, tags: &[&str]) -> bool {
+        let query = Query::new(query, Syntax::Plain, Scope::Names, &Tags::default()).unwrap();
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+        query.matcher.is_tagged_match(Path::new(name), &tags)
+    }
+
+    #[test]
+    fn tags_are_terms_of_the_query() {
+        assert!(matches("tag:work", "a.txt", &["Work"]));
+        assert!(!matches("tag:work", "a.txt", &[]));
+        assert!(matches("tag:a OR tag:b", "a.txt", &["b"]));
+        assert!(!matches("tag:a tag:b", "a.txt", &["b"]));
+        assert!(matches("(tag:a)", "a.txt", &["a"]));
+        assert!(matches("report -tag:draft", "report.pdf", &["final"]));
+        assert!(!matches("report NOT tag:draft", "report.pdf", &["draft"]));
+    }
+
+    #[test]
+    fn only_tag_queries_read_tags() {
+        let known = Tags::default();
+        assert!(Query::new("a OR tag:b", Syntax::Plain, Scope::Names, &known).unwrap().tags.is_some());
+        assert!(Query::new("a OR b", Syntax::Plain, Scope::Names, &known).unwrap().tags.is_none());
+        assert!(Query::new("tag:", Syntax::Plain, Scope::Names, &known).is_err());
+    }
+
+    #[test]
+    fn fuzzy_matches_rank_prefixes_and_runs_first() {
+        assert!(fuzzy_score("mnrs", "main.rs").is_some());
+        let prefix = fuzzy_score("rep", "report.txt").unwrap();
+        let scattered = fuzzy_score("rep", "our_price.pdf").unwrap();
+        assert!(prefix > scattered, "{} <= {}", prefix, scattered);
+        // A run of adjacent letters beats the same letters spread out
+        assert!(fuzzy_score("main", "main.rs").unwrap() > fuzzy_score("main", "mxaxixn.rs").unwrap());
+        // Word starts count, after punctuation and in camel case
+        assert!(fuzzy_score("fb", "foo_bar").unwrap() > fuzzy_score("fb", "fobar").unwrap());
+        assert!(fuzzy_score("fb", "fooBar").unwrap() > fuzzy_score("fb", "foobar").unwrap());
+    }
+
+    #[test]
+    fn fuzzy_matching_ignores_case_and_needs_every_letter_in_order() {
+        assert_eq!(fuzzy_score("readme", "README.md"), fuzzy_score("readme", "readme.md"));
+        assert!(fuzzy_score("mr", "Makefile.RS").is_some());
+        assert_eq!(fuzzy_score("xyz", "main.rs"), None);
+        assert_eq!(fuzzy_score("sr", "rs"), None);
+        assert_eq!(fuzzy_score("mainn", "main.rs"), None);
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn typed_dates_bound_the_range_or_are_refused() {
+        assert_eq!(DateRange::from_days(" ", ""), Ok(None));
+        let range = DateRange::from_days("2024-01-31", "2024-02-01").unwrap().unwrap();
+        let day = |text: &str| start_of_day(NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap());
+        // Both days are included
+        assert_eq!((range.from, range.until), (day("2024-01-31"), day("2024-02-02")));
+        assert!(DateRange::from_days("", "2024-02-01").unwrap().unwrap().from.is_none());
+
+        for (from, until) in [("2024-13-01", ""), ("", "yesterday"), ("31.01.2024", ""), ("2024-02-01", "2024-01-31")] {
+            assert!(DateRange::from_days(from, until).is_err(), "{} to {} was accepted", from, until);
+        }
+    }
+}
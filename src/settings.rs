@@ -0,0 +1,462 @@
+use crate::commands::CustomCommand;
+use crate::entry::SortMode;
+use crate::i18n::{fill, tr, Language};
+use crate::icons::IconSet;
+use crate::keymap::Keymap;
+use crate::paths;
+use crate::search::Scope;
+use crate::toolbar::Toolbar;
+use chrono::{DateTime, Local, Locale};
+use iced::theme::Palette;
+use iced::{Color, Theme};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{create_dir_all, read_dir, read_to_string, write};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+pub use crate::sizes::{exact_bytes, SizeUnits};
+
+pub const MIN_ZOOM: f32 = 0.5;
+pub const MAX_ZOOM: f32 = 2.0;
+pub const ZOOM_STEP: f32 = 0.1;
+
+// Bounds of the interface scale, which applies to the whole window
+pub const MIN_UI_SCALE: f32 = 0.75;
+pub const MAX_UI_SCALE: f32 = 2.5;
+
+// Preferences persisted to the config file; missing keys fall back to defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    // Folder shown at startup instead of home
+    pub start_dir: Option<PathBuf>,
+    pub density: Density,
+    pub zoom: f32,
+    // Scales all text and controls; the zoom above only sizes the file list
+    pub ui_scale: f32,
+    pub folder_sizes: bool,
+    // Sort for folders without a remembered one
+    pub sort_mode: SortMode,
+    // Group folders ahead of files whatever the sort
+    pub dirs_first: bool,
+    // List dotfiles alongside everything else
+    pub show_hidden: bool,
+    pub icon_set: IconSet,
+    pub theme: ThemeChoice,
+    pub language: Language,
+    pub date_format: DateFormat,
+    pub size_units: SizeUnits,
+    // Overrides applied on top of the light or dark palette
+    pub colors: CustomColors,
+    // Size and place of the window when it was last closed
+    pub window: WindowGeometry,
+    pub saved_searches: Vec<SavedSearch>,
+    // Answer name searches from the background index
+    pub index_enabled: bool,
+    pub index_roots: Vec<PathBuf>,
+    // Leave out paths matched by .gitignore/.ignore files and
+    // `exclude_patterns` in searches, usage scans and the index
+    pub skip_ignored: bool,
+    pub exclude_patterns: Vec<String>,
+    // Names left out of every listing, like build leftovers and OS clutter
+    pub hide_patterns: Vec<String>,
+    // Hand searches to ripgrep/fd when they are installed
+    pub external_search: bool,
+    // Read text in images and scans during content searches, with tesseract
+    pub ocr_search: bool,
+    // Ask before a new file replaces an existing one
+    pub confirm_overwrite: bool,
+    // Ask before moving something to the trash
+    pub confirm_trash: bool,
+    // What Share… runs; empty for the desktop's mail client
+    pub share_command: String,
+    // Folders within this many levels of the root need their name typed
+    // before they are deleted or moved
+    pub protected_depth: usize,
+    // Megabytes of plugin previews kept in memory and on disk
+    pub preview_memory_mb: u64,
+    pub preview_disk_mb: u64,
+    // Shortcuts the user rebound
+    pub keymap: Keymap,
+    // Apps chosen per file extension, used instead of the system default
+    pub associations: BTreeMap<String, String>,
+    // Listed under Tools and run on the selected file
+    pub custom_commands: Vec<CustomCommand>,
+    // Which buttons sit on the toolbar rows, and in what order
+    pub toolbar: Toolbar,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            start_dir: None,
+            density: Density::Comfortable,
+            zoom: 1.0,
+            ui_scale: 1.0,
+            folder_sizes: false,
+            sort_mode: SortMode::NameAsc,
+            dirs_first: true,
+            show_hidden: false,
+            icon_set: IconSet::Emoji,
+            theme: ThemeChoice::System,
+            language: Language::from_env(),
+            date_format: DateFormat::Iso,
+            size_units: SizeUnits::Binary,
+            colors: CustomColors::default(),
+            window: WindowGeometry::default(),
+            saved_searches: Vec::new(),
+            index_enabled: false,
+            index_roots: dirs::home_dir().into_iter().collect(),
+            skip_ignored: true,
+            exclude_patterns: vec!["node_modules".to_string()],
+            hide_patterns: ["*.pyc", "__pycache__", "Thumbs.db", ".DS_Store"].map(String::from).to_vec(),
+            external_search: false,
+            ocr_search: false,
+            confirm_overwrite: true,
+            confirm_trash: true,
+            share_command: String::new(),
+            protected_depth: 2,
+            preview_memory_mb: 64,
+            preview_disk_mb: 256,
+            keymap: Keymap::default(),
+            associations: BTreeMap::new(),
+            custom_commands: Vec::new(),
+            toolbar: Toolbar::default(),
+        }
+    }
+}
+
+// A named search that can be re-run later from the same folder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub folder: PathBuf,
+    pub query: String,
+    pub use_regex: bool,
+    #[serde(default)]
+    pub fuzzy: bool,
+    #[serde(default)]
+    pub scope: Scope,
+}
+
+impl fmt::Display for SavedSearch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Density {
+    Compact,
+    Comfortable,
+    Spacious,
+}
+
+impl Density {
+    pub const ALL: [Density; 3] = [Density::Compact, Density::Comfortable, Density::Spacious];
+
+    // Padding applied around each row of the file list
+    pub fn row_padding(self) -> u16 {
+        match self {
+            Density::Compact => 4,
+            Density::Comfortable => 10,
+            Density::Spacious => 16,
+        }
+    }
+
+    // Vertical gap between rows of the file list
+    pub fn row_spacing(self) -> u16 {
+        match self {
+            Density::Compact => 0,
+            Density::Comfortable => 2,
+            Density::Spacious => 6,
+        }
+    }
+}
+
+impl fmt::Display for Density {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Density::Compact => "Compact",
+            Density::Comfortable => "Comfortable",
+            Density::Spacious => "Spacious",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+// How dates are written in the file list and the properties
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DateFormat {
+    // 2024-01-31 14:05
+    #[default]
+    Iso,
+    // As the system locale writes dates
+    Locale,
+    // "3 hours ago", falling back to the date for anything older than a month
+    Relative,
+}
+
+impl DateFormat {
+    pub const ALL: [DateFormat; 3] = [DateFormat::Iso, DateFormat::Locale, DateFormat::Relative];
+
+    // `precise` adds seconds, and the date itself to relative times
+    pub fn format(self, time: SystemTime, precise: bool) -> String {
+        let local: DateTime<Local> = time.into();
+        let iso = if precise { "%Y-%m-%d %H:%M:%S" } else { "%Y-%m-%d %H:%M" };
+        match self {
+            DateFormat::Iso => local.format(iso).to_string(),
+            DateFormat::Locale => local.format_localized("%x %X", system_locale()).to_string(),
+            DateFormat::Relative => match relative(time) {
+                Some(ago) if precise => format!("{} ({})", ago, local.format(iso)),
+                Some(ago) => ago,
+                None => local.format(iso).to_string(),
+            },
+        }
+    }
+}
+
+// Describes how long ago `time` was, or None if it is over a month ago or
+// in the future
+fn relative(time: SystemTime) -> Option<String> {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+
+    let ago = SystemTime::now().duration_since(time).ok()?.as_secs();
+    let text = match ago {
+        0..MINUTE => tr("just now").to_string(),
+        MINUTE..HOUR => fill(tr("{} min ago"), &[&(ago / MINUTE)]),
+        HOUR..7200 => tr("1 hour ago").to_string(),
+        7200..DAY => fill(tr("{} hours ago"), &[&(ago / HOUR)]),
+        DAY..172_800 => tr("yesterday").to_string(),
+        _ if ago < 30 * DAY => fill(tr("{} days ago"), &[&(ago / DAY)]),
+        _ => return None,
+    };
+    Some(text)
+}
+
+// The locale for dates from LC_ALL, LC_TIME or LANG, e.g. "de_DE.UTF-8"
+fn system_locale() -> Locale {
+    static LOCALE: OnceLock<Locale> = OnceLock::new();
+    *LOCALE.get_or_init(|| {
+        ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+            .and_then(|value| {
+                let name = value.split(['.', '@']).next().unwrap_or_default().to_string();
+                Locale::try_from(name.as_str()).ok()
+            })
+            .unwrap_or(Locale::POSIX)
+    })
+}
+
+impl fmt::Display for DateFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            DateFormat::Iso => "ISO (2024-01-31 14:05)",
+            DateFormat::Locale => "System locale",
+            DateFormat::Relative => "Relative (3 hours ago)",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThemeChoice {
+    // Light or dark, whichever the desktop uses
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemeChoice {
+    pub const ALL: [ThemeChoice; 3] = [ThemeChoice::System, ThemeChoice::Light, ThemeChoice::Dark];
+}
+
+impl fmt::Display for ThemeChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ThemeChoice::System => "Follow system",
+            ThemeChoice::Light => "Light",
+            ThemeChoice::Dark => "Dark",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowGeometry {
+    pub width: u32,
+    pub height: u32,
+    // Not every platform reports where the window is
+    pub position: Option<(i32, i32)>,
+}
+
+impl Default for WindowGeometry {
+    fn default() -> Self {
+        WindowGeometry {
+            width: 900,
+            height: 700,
+            position: None,
+        }
+    }
+}
+
+// Hex colors like "#3d7eff" from the `[colors]` table of the config file;
+// unset or unreadable ones keep the theme's own
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomColors {
+    // Buttons, the selected row and other highlights
+    pub accent: Option<String>,
+    pub background: Option<String>,
+    pub text: Option<String>,
+}
+
+impl CustomColors {
+    // The light or dark theme with these colors applied
+    pub fn apply(&self, dark: bool) -> Theme {
+        let base = if dark { Theme::Dark } else { Theme::Light };
+        let accent = self.accent.as_deref().and_then(parse_color);
+        let background = self.background.as_deref().and_then(parse_color);
+        let text = self.text.as_deref().and_then(parse_color);
+        if accent.is_none() && background.is_none() && text.is_none() {
+            return base;
+        }
+
+        let mut palette: Palette = base.palette();
+        if let Some(accent) = accent {
+            palette.primary = accent;
+        }
+        if let Some(background) = background {
+            palette.background = background;
+            // Keep text readable on a background of the other brightness
+            palette.text = if luminance(background) > 0.5 { Color::BLACK } else { Color::WHITE };
+        }
+        if let Some(text) = text {
+            palette.text = text;
+        }
+        Theme::custom(palette)
+    }
+}
+
+fn parse_color(hex: &str) -> Option<Color> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+    Some(Color::from_rgb8(channel(0..2)?, channel(2..4)?, channel(4..6)?))
+}
+
+fn luminance(color: Color) -> f32 {
+    0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b
+}
+
+// Asks the desktop whether it uses a dark theme; blocking, but quick
+pub fn system_prefers_dark() -> bool {
+    matches!(dark_light::detect(), Ok(dark_light::Mode::Dark))
+}
+
+// The profile whose preferences are loaded and saved; None is the default
+// one. Each named profile has its own file, so e.g. `--profile work` can
+// start in another folder with another theme.
+static PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+// A choice in the profile list, None being the default profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile(pub Option<String>);
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{}", tr("Default")),
+        }
+    }
+}
+
+pub fn set_profile(profile: Option<String>) {
+    *PROFILE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = profile;
+}
+
+pub fn profile() -> Option<String> {
+    PROFILE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+// Profile names become file names, so only plain ones are allowed
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' '))
+}
+
+fn profiles_dir() -> Option<PathBuf> {
+    paths::config_dir().map(|dir| dir.join("profiles"))
+}
+
+// Every profile that has been saved, the default one first
+pub fn profiles() -> Vec<Profile> {
+    let mut names: Vec<String> = profiles_dir()
+        .and_then(|dir| read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let is_toml = path.extension().is_some_and(|ext| ext == "toml");
+            is_toml.then(|| path.file_stem()?.to_str().map(String::from)).flatten()
+        })
+        .collect();
+    names.sort();
+    std::iter::once(Profile(None)).chain(names.into_iter().map(|name| Profile(Some(name)))).collect()
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    match profile() {
+        Some(name) => profiles_dir().map(|dir| dir.join(format!("{}.toml", name))),
+        None => paths::config_dir().map(|dir| dir.join("config.toml")),
+    }
+}
+
+// Loads preferences, using defaults if the file is missing or unreadable
+pub fn load() -> Preferences {
+    let preferences: Preferences = config_path()
+        .and_then(|path| read_to_string(path).ok())
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default();
+    // A hand-edited file may hold sizes nothing could be read at
+    Preferences {
+        zoom: clamp_zoom(preferences.zoom),
+        ui_scale: within(preferences.ui_scale, MIN_UI_SCALE, MAX_UI_SCALE),
+        ..preferences
+    }
+}
+
+// `zoom` kept to what the zoom keys reach
+pub fn clamp_zoom(zoom: f32) -> f32 {
+    within(zoom, MIN_ZOOM, MAX_ZOOM)
+}
+
+// `value` clamped to `min..=max`; what is no number at all is taken as 1
+fn within(value: f32, min: f32, max: f32) -> f32 {
+    if value.is_nan() { 1.0 } else { value.clamp(min, max) }
+}
+
+pub fn save(preferences: &Preferences) -> std::io::Result<()> {
+    let Some(path) = config_path() else {
+        return Ok(());
+    };
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let contents = toml::to_string_pretty(preferences)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    write(path, contents)
+}
@@ -0,0 +1,57 @@
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+// Hands `paths` to whatever sends them on: `handler` when one is set in
+// settings, with the files where it says `{}` or at the end, otherwise the
+// desktop's mail client with them attached
+pub fn share(paths: &[PathBuf], handler: &str) -> io::Result<()> {
+    let mut process = if handler.trim().is_empty() {
+        default_handler(paths)?
+    } else {
+        let mut words = handler.split_whitespace();
+        let mut process = Command::new(words.next().unwrap_or_default());
+        let mut placed = false;
+        for word in words {
+            if word == "{}" {
+                process.args(paths);
+                placed = true;
+            } else {
+                process.arg(word);
+            }
+        }
+        if !placed {
+            process.args(paths);
+        }
+        process
+    };
+
+    let mut child = process.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+    // Reap the app once it exits so it does not linger as a zombie
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+// xdg-email takes one file per --attach
+#[cfg(all(unix, not(target_os = "macos")))]
+fn default_handler(paths: &[PathBuf]) -> io::Result<Command> {
+    let mut process = Command::new("xdg-email");
+    for path in paths {
+        process.arg("--attach").arg(path);
+    }
+    Ok(process)
+}
+
+// Mail opens a new message with whatever files it is given
+#[cfg(target_os = "macos")]
+fn default_handler(paths: &[PathBuf]) -> io::Result<Command> {
+    let mut process = Command::new("open");
+    process.args(["-a", "Mail"]).args(paths);
+    Ok(process)
+}
+
+// The Windows share sheet is only reachable from packaged apps
+#[cfg(not(unix))]
+fn default_handler(_paths: &[PathBuf]) -> io::Result<Command> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "no share handler is set in Settings"))
+}
@@ -0,0 +1,285 @@
+use crate::translate::tr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Detached signatures, binary and armored, named after the file they sign
+const EXTENSIONS: &[&str] = &["sig", "asc"];
+
+// What gpg made of a signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Validity {
+    Good,
+    // The file was changed after it was signed, or the signature is broken
+    Bad,
+    // Good, but made by a key that has since expired or been revoked, or the
+    // signature itself has expired
+    Expired,
+    Revoked,
+    // The key that made it is not in the keyring, so it can't be checked
+    UnknownKey,
+    // gpg could not check it for another reason, given in its report
+    Unchecked,
+}
+
+impl Validity {
+    // How bad a result is; of several signatures the worst one counts
+    fn rank(&self) -> u8 {
+        match self {
+            Validity::Good => 0,
+            Validity::Expired => 1,
+            Validity::Revoked => 2,
+            Validity::UnknownKey => 3,
+            Validity::Unchecked => 4,
+            Validity::Bad => 5,
+        }
+    }
+}
+
+// How far the keyring vouches for the signer being who they claim
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trust {
+    Ultimate,
+    Full,
+    Marginal,
+    Undefined,
+    Never,
+}
+
+impl fmt::Display for Validity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Validity::Good => "✅ Good signature",
+            Validity::Bad => "❌ Bad signature: the file does not match it",
+            Validity::Expired => "⚠ Good signature from an expired key",
+            Validity::Revoked => "⚠ Good signature from a revoked key",
+            Validity::UnknownKey => "❔ Can't check: the signer's key is not in your keyring",
+            Validity::Unchecked => "❔ Can't check the signature; gpg's report says why",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+impl fmt::Display for Trust {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Trust::Ultimate => "Ultimate (your own key)",
+            Trust::Full => "Full",
+            Trust::Marginal => "Marginal",
+            Trust::Undefined => "Not certified; the signer may not be who the key claims",
+            Trust::Never => "Never",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Verification {
+    pub validity: Validity,
+    // The name and address on the key
+    pub signer: Option<String>,
+    pub key: Option<String>,
+    pub signed: Option<SystemTime>,
+    pub trust: Option<Trust>,
+    // What gpg said, for the details
+    pub report: String,
+}
+
+// The file and its signature for a selection that is either of them: a
+// signature beside a file, or a file with a signature beside it
+pub fn pair(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    if is_signature(path) {
+        let signed = path.with_extension("");
+        return signed.is_file().then(|| (signed, path.to_path_buf()));
+    }
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    EXTENSIONS
+        .iter()
+        .map(|ext| path.with_file_name(format!("{}.{}", name, ext)))
+        .find(|signature| signature.is_file())
+        .map(|signature| (path.to_path_buf(), signature))
+}
+
+fn is_signature(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+// Checks `signature` against `file` with gpg and the user's keyring. Waits
+// for gpg, so call it off the UI thread.
+pub fn verify(file: &Path, signature: &Path) -> Result<Verification, String> {
+    let output = Command::new("gpg")
+        .args(["--batch", "--status-fd", "1", "--verify"])
+        .arg(signature)
+        .arg(file)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|error| format!("gpg: {}", error))?;
+    let report = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    parse(&String::from_utf8_lossy(&output.stdout), report)
+}
+
+// Reads gpg's machine-readable status lines; see doc/DETAILS in GnuPG. A
+// file may carry several signatures, and then the worst of them is the
+// result, so a good one can't hide a bad one beside it.
+fn parse(status: &str, report: String) -> Result<Verification, String> {
+    let mut verification = Verification { validity: Validity::Bad, signer: None, key: None, signed: None, trust: None, report };
+    let mut seen = false;
+    // The lines being read are about a signature that is better than the
+    // result so far, and don't change it
+    let mut skipping = false;
+    for line in status.lines() {
+        let Some(line) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let (key, signer) = rest.split_once(' ').map_or((rest, None), |(key, signer)| (key, Some(signer.to_string())));
+        let validity = match keyword {
+            "GOODSIG" => Some(Validity::Good),
+            "BADSIG" => Some(Validity::Bad),
+            "EXPSIG" | "EXPKEYSIG" => Some(Validity::Expired),
+            "REVKEYSIG" => Some(Validity::Revoked),
+            "NO_PUBKEY" => Some(Validity::UnknownKey),
+            _ => None,
+        };
+        if let Some(validity) = validity {
+            skipping = seen && validity.rank() < verification.validity.rank();
+            if skipping {
+                continue;
+            }
+            seen = true;
+            verification.validity = validity;
+            // ERRSIG came first with the full fingerprint
+            if keyword != "NO_PUBKEY" {
+                verification.key = Some(key.to_string());
+                verification.signer = signer;
+                verification.signed = None;
+                verification.trust = None;
+            } else if verification.key.is_none() {
+                verification.key = Some(key.to_string());
+            }
+            continue;
+        }
+        match keyword {
+            // The signature could not be checked; code 9 means its key isn't
+            // in the keyring, anything else is left to gpg's own words
+            "ERRSIG" => {
+                let fields: Vec<&str> = rest.split(' ').collect();
+                let validity = if fields.get(5) == Some(&"9") { Validity::UnknownKey } else { Validity::Unchecked };
+                skipping = seen && validity.rank() < verification.validity.rank();
+                if skipping {
+                    continue;
+                }
+                seen = true;
+                verification.validity = validity;
+                verification.key = fields.get(6).filter(|key| **key != "-").or(fields.first()).map(|key| key.to_string());
+                verification.signer = None;
+                verification.signed = fields.get(4).and_then(|seconds| seconds.parse().ok()).map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds));
+                verification.trust = None;
+            }
+            _ if skipping => {}
+            // The full fingerprint and the time of signing
+            "VALIDSIG" => {
+                let mut fields = rest.split(' ');
+                verification.key = fields.next().map(str::to_string);
+                verification.signed = fields.nth(1).and_then(|seconds| seconds.parse().ok()).map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds));
+            }
+            "TRUST_ULTIMATE" => verification.trust = Some(Trust::Ultimate),
+            "TRUST_FULLY" => verification.trust = Some(Trust::Full),
+            "TRUST_MARGINAL" => verification.trust = Some(Trust::Marginal),
+            "TRUST_UNDEFINED" => verification.trust = Some(Trust::Undefined),
+            "TRUST_NEVER" => verification.trust = Some(Trust::Never),
+            _ => {}
+        }
+    }
+    if seen {
+        Ok(verification)
+    } else if verification.report.is_empty() {
+        Err(tr("gpg found no signature").to_string())
+    } else {
+        Err(verification.report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGERPRINT: &str = "D8692123C4065DEA5E0F3AB5249B39D24F25E3B6";
+
+    fn verified(status: &[&str]) -> Verification {
+        let status: String = status.iter().map(|line| format!("[GNUPG:] {}\n", line)).collect();
+        parse(&status, "gpg: report".to_string()).unwrap()
+    }
+
+    #[test]
+    fn reads_a_good_signature() {
+        let verification = verified(&[
+            "NEWSIG",
+            "GOODSIG 249B39D24F25E3B6 Alice <alice@example.org>",
+            &format!("VALIDSIG {} 2024-03-01 1709251200 0 4 0 22 8 00 {}", FINGERPRINT, FINGERPRINT),
+            "TRUST_FULLY 0 pgp",
+        ]);
+        assert_eq!(verification.validity, Validity::Good);
+        assert_eq!(verification.signer.as_deref(), Some("Alice <alice@example.org>"));
+        assert_eq!(verification.key.as_deref(), Some(FINGERPRINT));
+        assert_eq!(verification.signed, Some(UNIX_EPOCH + Duration::from_secs(1709251200)));
+        assert_eq!(verification.trust, Some(Trust::Full));
+    }
+
+    #[test]
+    fn a_bad_signature_is_not_hidden_by_a_good_one() {
+        let bad_first = verified(&[
+            "NEWSIG",
+            "BADSIG 1111111111111111 Mallory <mallory@example.org>",
+            "NEWSIG",
+            "GOODSIG 249B39D24F25E3B6 Alice <alice@example.org>",
+            &format!("VALIDSIG {} 2024-03-01 1709251200 0 4 0 22 8 00 {}", FINGERPRINT, FINGERPRINT),
+            "TRUST_ULTIMATE 0 pgp",
+        ]);
+        assert_eq!(bad_first.validity, Validity::Bad);
+        assert_eq!(bad_first.key.as_deref(), Some("1111111111111111"));
+        assert_eq!(bad_first.signer.as_deref(), Some("Mallory <mallory@example.org>"));
+        assert_eq!((bad_first.signed, bad_first.trust), (None, None));
+
+        let good_first = verified(&[
+            "NEWSIG",
+            "GOODSIG 249B39D24F25E3B6 Alice <alice@example.org>",
+            &format!("VALIDSIG {} 2024-03-01 1709251200 0 4 0 22 8 00 {}", FINGERPRINT, FINGERPRINT),
+            "TRUST_ULTIMATE 0 pgp",
+            "NEWSIG",
+            "BADSIG 1111111111111111 Mallory <mallory@example.org>",
+        ]);
+        assert_eq!(good_first.validity, Validity::Bad);
+        assert_eq!(good_first.key.as_deref(), Some("1111111111111111"));
+        assert_eq!((good_first.signed, good_first.trust), (None, None));
+    }
+
+    #[test]
+    fn signatures_that_cant_be_checked_are_kept() {
+        let unknown = verified(&[
+            "NEWSIG",
+            &format!("ERRSIG 249B39D24F25E3B6 22 8 00 1709251200 9 {}", FINGERPRINT),
+            "NO_PUBKEY 249B39D24F25E3B6",
+            "NEWSIG",
+            "GOODSIG 1111111111111111 Bob <bob@example.org>",
+        ]);
+        assert_eq!(unknown.validity, Validity::UnknownKey);
+        assert_eq!(unknown.key.as_deref(), Some(FINGERPRINT));
+        assert_eq!(unknown.signed, Some(UNIX_EPOCH + Duration::from_secs(1709251200)));
+
+        let unchecked = verified(&["NEWSIG", "ERRSIG 249B39D24F25E3B6 99 8 00 1709251200 4 -", "NEWSIG", "GOODSIG 1111111111111111 Bob <bob@example.org>"]);
+        assert_eq!(unchecked.validity, Validity::Unchecked);
+        assert_eq!(unchecked.key.as_deref(), Some("249B39D24F25E3B6"));
+    }
+
+    #[test]
+    fn expired_keys_and_missing_signatures() {
+        let expired = verified(&["NEWSIG", "EXPKEYSIG 249B39D24F25E3B6 Alice <alice@example.org>", "TRUST_UNDEFINED 0 pgp"]);
+        assert_eq!(expired.validity, Validity::Expired);
+        assert_eq!(expired.trust, Some(Trust::Undefined));
+
+        assert_eq!(parse("", "gpg: no valid OpenPGP data found.".to_string()).unwrap_err(), "gpg: no valid OpenPGP data found.");
+        assert!(parse("[GNUPG:] NODATA 1\n", String::new()).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+// Byte counts as people read them
+use crate::translate::{fill, tr};
+use humansize::{format_size, BINARY, DECIMAL};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Whether sizes count in powers of 1024 (KiB, MiB) or of 1000 (KB, MB)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SizeUnits {
+    #[default]
+    Binary,
+    Decimal,
+}
+
+impl SizeUnits {
+    pub const ALL: [SizeUnits; 2] = [SizeUnits::Binary, SizeUnits::Decimal];
+
+    pub fn format(self, bytes: u64) -> String {
+        match self {
+            SizeUnits::Binary => format_size(bytes, BINARY),
+            SizeUnits::Decimal => format_size(bytes, DECIMAL),
+        }
+    }
+}
+
+impl fmt::Display for SizeUnits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            SizeUnits::Binary => "Binary (KiB, MiB)",
+            SizeUnits::Decimal => "Decimal (KB, MB)",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+// The exact size, with digits grouped in threes by narrow spaces as SI
+// suggests, so it reads the same in every language
+pub fn exact_bytes(bytes: u64) -> String {
+    let digits = bytes.to_string();
+    let mut grouped = String::with_capacity(digits.len() * 2);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push('\u{202F}');
+        }
+        grouped.push(digit);
+    }
+    if bytes == 1 { tr("1 byte").to_string() } else { fill(tr("{} bytes"), &[&grouped]) }
+}
@@ -0,0 +1,81 @@
+use crate::loader::{self, Sink};
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, create_dir_all, read_to_string};
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Files and folders the user starred to get back to quickly, in the order
+// they were starred
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Starred {
+    paths: Vec<PathBuf>,
+}
+
+impl Starred {
+    // What was saved; a missing or damaged file means nothing is starred
+    pub fn load() -> Starred {
+        store_path()
+            .and_then(|path| read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = store_path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, contents)
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.iter().any(|starred| starred == path)
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    // Stars `path`, or takes its star off; true when it is starred now
+    pub fn toggle(&mut self, path: &Path) -> io::Result<bool> {
+        let starred = !self.contains(path);
+        if starred {
+            self.paths.push(path.to_path_buf());
+        } else {
+            self.paths.retain(|kept| kept != path);
+        }
+        self.save().map(|()| starred)
+    }
+
+    // Keeps the stars of what the app moved, and of everything below it
+    pub fn moved(&mut self, from: &Path, to: &Path) {
+        let mut changed = false;
+        for path in self.paths.iter_mut().filter(|path| path.starts_with(from)) {
+            if let Ok(relative) = path.strip_prefix(from) {
+                *path = to.join(relative);
+                changed = true;
+            }
+        }
+        if changed && let Err(error) = self.save() {
+            tracing::warn!("could not save starred files: {}", error);
+        }
+    }
+}
+
+fn store_path() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join("starred.json"))
+}
+
+// Reads the starred entries that still exist. Missing ones stay starred,
+// as they may be on a drive that isn't mounted right now.
+pub fn files(paths: Vec<PathBuf>, sink: &mut Sink) {
+    for path in paths {
+        if path.symlink_metadata().is_ok() && !sink.push(loader::read_entry(path)) {
+            return;
+        }
+    }
+}
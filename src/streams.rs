@@ -0,0 +1,157 @@
+// The library's workers as subscriptions: each runs on a thread of its own
+// and what it sends arrives as messages. Dropping a subscription closes its
+// channel, which tells the worker to stop.
+use crate::i18n::tr;
+use file_explorer::dirsize::{self, Measure};
+use file_explorer::excludes::Excludes;
+use file_explorer::index::{self, Index};
+use file_explorer::jobs::{self, Job, JobState};
+use file_explorer::loader::{self, Progress, Sink};
+use file_explorer::manifest::{self, Line};
+use file_explorer::outlet::Outlet;
+use file_explorer::recent::{self, Window};
+use file_explorer::search::{self, Query};
+use file_explorer::{largest, starred};
+use iced::futures::channel::mpsc;
+use iced::futures::StreamExt;
+use iced::subscription::{self, Subscription};
+use std::hash::Hash;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+
+// Carries what a worker sends to the subscription reading it
+#[derive(Clone)]
+struct Channel<T>(mpsc::UnboundedSender<T>);
+
+impl<T: Send> Outlet<T> for Channel<T> {
+    fn send(&self, item: T) -> bool {
+        self.0.unbounded_send(item).is_ok()
+    }
+
+    fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+}
+
+enum State<F, T> {
+    Starting(F),
+    Running(mpsc::UnboundedReceiver<T>),
+    Done,
+}
+
+// Runs `work` on a worker thread and turns each thing it sends into a
+// message with `message`; once it is done, `last` follows if there is one.
+// The subscription is keyed by `id` alone, so it keeps running until
+// replaced.
+fn worker<I, T, A, F, M>(id: I, work: F, message: M, last: Option<A>) -> Subscription<A>
+where
+    I: Hash + 'static,
+    T: Send + 'static,
+    A: Clone + Send + Sync + 'static,
+    F: FnOnce(Channel<T>) + Send + 'static,
+    M: Fn(T) -> A + Clone + Send + Sync + 'static,
+{
+    subscription::unfold(id, State::Starting(work), move |state| {
+        let (message, last) = (message.clone(), last.clone());
+        async move {
+            let mut receiver = match state {
+                State::Starting(work) => {
+                    let (sender, receiver) = mpsc::unbounded();
+                    thread::spawn(move || work(Channel(sender)));
+                    receiver
+                }
+                State::Running(receiver) => receiver,
+                State::Done => iced::futures::future::pending().await,
+            };
+            match (receiver.next().await, last) {
+                (Some(item), _) => (message(item), State::Running(receiver)),
+                (None, Some(last)) => (last, State::Done),
+                // Nothing more to say; park until the subscription is dropped
+                (None, None) => iced::futures::future::pending().await,
+            }
+        }
+    })
+}
+
+// Entries found by `work`, tagged with `generation` so stale batches can be
+// told apart, and `Progress::Finished` once it is done
+fn entries<I, F>(id: I, generation: u64, work: F) -> Subscription<(u64, Progress)>
+where
+    I: Hash + 'static,
+    F: FnOnce(&mut Sink) + Send + 'static,
+{
+    worker(
+        (id, generation),
+        move |channel| work(&mut Sink::new(channel)),
+        move |batch| match batch {
+            Ok(entries) => (generation, Progress::Batch(entries)),
+            Err(failure) => (generation, Progress::Failed(failure)),
+        },
+        Some((generation, Progress::Finished)),
+    )
+}
+
+// The entries of `dir`, dotfiles only with `show_hidden`
+pub fn folder(generation: u64, dir: PathBuf, show_hidden: bool, excludes: Excludes) -> Subscription<(u64, Progress)> {
+    entries("directory-loader", generation, move |sink| loader::read_directory(&dir, show_hidden, &excludes, sink))
+}
+
+pub fn search(generation: u64, root: PathBuf, query: Query, excludes: Excludes, use_external: bool) -> Subscription<(u64, Progress)> {
+    entries("recursive-search", generation, move |sink| search::find(&root, &query, &excludes, use_external, sink))
+}
+
+pub fn indexed_search(generation: u64, root: PathBuf, query: Query, index: Arc<Index>) -> Subscription<(u64, Progress)> {
+    entries("indexed-search", generation, move |sink| index::lookup(&index, &root, &query, sink))
+}
+
+pub fn largest(generation: u64, root: PathBuf, excludes: Excludes) -> Subscription<(u64, Progress)> {
+    entries("largest-files", generation, move |sink| largest::files(&root, &excludes, sink))
+}
+
+pub fn recent(generation: u64, roots: Vec<PathBuf>, window: Window, excludes: Excludes) -> Subscription<(u64, Progress)> {
+    entries("recent-files", generation, move |sink| recent::files(&roots, window, &excludes, sink))
+}
+
+pub fn starred(generation: u64, paths: Vec<PathBuf>) -> Subscription<(u64, Progress)> {
+    entries("starred-files", generation, move |sink| starred::files(paths, sink))
+}
+
+// Each folder's size or count, as soon as it is known
+pub fn measure(generation: u64, measure: Measure, folders: Vec<PathBuf>, excludes: Excludes) -> Subscription<(PathBuf, u64)> {
+    worker(
+        ("folder-measure", measure, generation),
+        move |channel| dirsize::measure(measure, folders, &excludes, &channel),
+        |measured| measured,
+        None,
+    )
+}
+
+pub fn verify(generation: u64, lines: Vec<Line>) -> Subscription<(u64, manifest::Event)> {
+    worker(
+        ("manifest-verify", generation),
+        move |channel| manifest::verify(&lines, &channel),
+        move |event| (generation, event),
+        None,
+    )
+}
+
+// The progress of `job`, tagged with its id
+pub fn job(job: &Job) -> Subscription<(u64, jobs::Event)> {
+    let id = job.id;
+    let job = job.clone();
+    worker(
+        ("file-operation", id),
+        move |channel: Channel<jobs::Event>| {
+            let ended = channel.clone();
+            if panic::catch_unwind(AssertUnwindSafe(|| jobs::run(&job, channel))).is_err() {
+                // The worker died without saying how it ended
+                let error = tr("The operation stopped unexpectedly").to_string().into();
+                ended.send(jobs::Event::Finished(JobState::Failed(error)));
+            }
+        },
+        move |event| (id, event),
+        None,
+    )
+}
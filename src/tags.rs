@@ -0,0 +1,204 @@
+use crate::paths;
+use crate::translate::tr;
+#[cfg(target_os = "linux")]
+use crate::xattr;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, create_dir_all, read_to_string};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[cfg(target_os = "linux")]
+const ATTRIBUTE: &str = "user.xdg.tags";
+
+// The colors a tag can have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TagColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Gray,
+}
+
+impl TagColor {
+    pub const ALL: [TagColor; 7] =
+        [TagColor::Red, TagColor::Orange, TagColor::Yellow, TagColor::Green, TagColor::Blue, TagColor::Purple, TagColor::Gray];
+}
+
+impl fmt::Display for TagColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            TagColor::Red => "Red",
+            TagColor::Orange => "Orange",
+            TagColor::Yellow => "Yellow",
+            TagColor::Green => "Green",
+            TagColor::Blue => "Blue",
+            TagColor::Purple => "Purple",
+            TagColor::Gray => "Gray",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    pub color: TagColor,
+}
+
+// The tags the user made and, for files whose own attributes can't hold
+// theirs, which tags they carry. Where attributes can be written the tags
+// go with the file, so they follow it when it is moved or copied elsewhere.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Tags {
+    pub defined: Vec<Tag>,
+    #[serde(default)]
+    assigned: HashMap<PathBuf, Vec<String>>,
+}
+
+impl Tags {
+    // What was saved; a missing or damaged file means no tags
+    pub fn load() -> Tags {
+        store_path()
+            .and_then(|path| read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = store_path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, contents)
+    }
+
+    // Tags found on files but never made here, by another app or on
+    // another machine, are gray
+    pub fn color(&self, name: &str) -> TagColor {
+        self.defined.iter().find(|tag| tag.name == name).map_or(TagColor::Gray, |tag| tag.color)
+    }
+
+    // The tags of `path`, given those in its attributes. The database has
+    // the last word, as it is only used where the attributes could not be
+    // changed.
+    pub fn of<'a>(&'a self, path: &Path, on_file: &'a [String]) -> &'a [String] {
+        self.assigned.get(path).map_or(on_file, Vec::as_slice)
+    }
+
+    // Makes a tag, or gives an existing one a new color
+    pub fn define(&mut self, name: &str, color: TagColor) -> io::Result<()> {
+        match self.defined.iter_mut().find(|tag| tag.name == name) {
+            Some(tag) => tag.color = color,
+            None => self.defined.push(Tag { name: name.to_string(), color }),
+        }
+        self.save()
+    }
+
+    // Forgets a tag and takes it off the files the database holds tags for.
+    // Files carrying it in their attributes keep it, shown in gray.
+    pub fn remove(&mut self, name: &str) -> io::Result<()> {
+        self.defined.retain(|tag| tag.name != name);
+        for names in self.assigned.values_mut() {
+            names.retain(|tagged| tagged != name);
+        }
+        self.save()
+    }
+
+    // Gives `path` exactly `names`, in its attributes when it can hold them
+    // and in the database otherwise
+    pub fn set(&mut self, path: &Path, names: Vec<String>) -> io::Result<()> {
+        match write(path, &names) {
+            Ok(()) => {
+                self.assigned.remove(path);
+            }
+            Err(error) => {
+                tracing::debug!(path = %path.display(), "tags kept in the database: {}", error);
+                self.assigned.insert(path.to_path_buf(), names);
+            }
+        }
+        self.save()
+    }
+
+    // Keeps the database tags of a file the app moved or renamed, and of
+    // everything below it
+    pub fn moved(&mut self, from: &Path, to: &Path) {
+        let moved: Vec<PathBuf> = self.assigned.keys().filter(|path| path.starts_with(from)).cloned().collect();
+        if moved.is_empty() {
+            return;
+        }
+        for path in moved {
+            if let (Some(names), Ok(relative)) = (self.assigned.remove(&path), path.strip_prefix(from)) {
+                self.assigned.insert(to.join(relative), names);
+            }
+        }
+        if let Err(error) = self.save() {
+            tracing::warn!("could not save tags: {}", error);
+        }
+    }
+
+    // The database as it is now, for a search to look tags up in
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot { assigned: Arc::new(self.assigned.clone()) }
+    }
+}
+
+// Read on every entry of a search, on the search's threads, so it holds its
+// own copy of the database
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    assigned: Arc<HashMap<PathBuf, Vec<String>>>,
+}
+
+impl Snapshot {
+    // Like `Tags::of`
+    pub fn of<'a>(&'a self, path: &Path, on_file: &'a [String]) -> &'a [String] {
+        self.assigned.get(path).map_or(on_file, Vec::as_slice)
+    }
+}
+
+fn store_path() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join("tags.json"))
+}
+
+// A tag name can't hold the comma that separates them in the attribute
+pub fn is_valid_name(name: &str) -> bool {
+    !name.trim().is_empty() && !name.contains(',')
+}
+
+// The tags in `path`'s attributes, under the freedesktop name other file
+// managers use, separated by commas
+#[cfg(target_os = "linux")]
+pub fn read(path: &Path) -> Vec<String> {
+    xattr::get(path, ATTRIBUTE)
+        .ok()
+        .flatten()
+        .and_then(|value| String::from_utf8(value).ok())
+        .map(|value| value.split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_path: &Path) -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "linux")]
+fn write(path: &Path, names: &[String]) -> io::Result<()> {
+    if names.is_empty() { xattr::remove(path, ATTRIBUTE) } else { xattr::set(path, ATTRIBUTE, names.join(",").as_bytes()) }
+}
+
+// Only Linux has the freedesktop attribute; elsewhere the database holds
+// every tag
+#[cfg(not(target_os = "linux"))]
+fn write(_path: &Path, _names: &[String]) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "tags are not kept in file attributes here"))
+}
@@ -0,0 +1,255 @@
+use crate::i18n::{fill, tr};
+use crate::{recent, search};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// A control that can be placed on the toolbar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tool {
+    Up,
+    Home,
+    Refresh,
+    Usage,
+    Empty,
+    Largest,
+    Recent,
+    New,
+    Delete,
+    Edit,
+    OpenWith,
+    Properties,
+    Settings,
+    CopyTo,
+    MoveTo,
+    Compress,
+    History,
+    Git,
+    Trash,
+    DeletePermanently,
+    Share,
+    ShareLan,
+    Print,
+    Encrypt,
+    Decrypt,
+    VerifySignature,
+    VerifyChecksums,
+    ConvertImages,
+    CopyPath,
+    CopyName,
+    CopyUri,
+    Tags,
+    Star,
+    Starred,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 34] = [
+        Tool::Up,
+        Tool::Home,
+        Tool::Refresh,
+        Tool::Usage,
+        Tool::Empty,
+        Tool::Largest,
+        Tool::Recent,
+        Tool::New,
+        Tool::Delete,
+        Tool::Edit,
+        Tool::OpenWith,
+        Tool::Properties,
+        Tool::Settings,
+        Tool::CopyTo,
+        Tool::MoveTo,
+        Tool::Compress,
+        Tool::History,
+        Tool::Git,
+        Tool::Trash,
+        Tool::DeletePermanently,
+        Tool::Share,
+        Tool::ShareLan,
+        Tool::Print,
+        Tool::Encrypt,
+        Tool::Decrypt,
+        Tool::VerifySignature,
+        Tool::VerifyChecksums,
+        Tool::ConvertImages,
+        Tool::CopyPath,
+        Tool::CopyName,
+        Tool::CopyUri,
+        Tool::Tags,
+        Tool::Star,
+        Tool::Starred,
+    ];
+}
+
+impl fmt::Display for Tool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Tool::Up => "⬆️ Up",
+            Tool::Home => "🏠 Home",
+            Tool::Refresh => "🔄 Refresh",
+            Tool::Usage => "📊 Usage",
+            Tool::Empty => "🧹 Empty",
+            Tool::Largest => "🐘 Largest",
+            Tool::Recent => "🕘 Recent",
+            Tool::New => "➕ New",
+            Tool::Delete => "🗑️ Delete",
+            Tool::Edit => "✏️ Edit",
+            Tool::OpenWith => "📂 Open With",
+            Tool::Properties => "ℹ️ Properties",
+            Tool::Settings => "⚙ Settings",
+            Tool::CopyTo => "📋 Copy to…",
+            Tool::MoveTo => "🚚 Move to…",
+            Tool::Compress => "🗜 Compress",
+            Tool::History => "📜 History",
+            Tool::Git => "⎇ Git",
+            Tool::Trash => "♻️ Trash",
+            Tool::DeletePermanently => "⛔ Delete permanently",
+            Tool::Share => "📤 Share…",
+            Tool::ShareLan => "📡 Share over LAN",
+            Tool::Print => "🖨 Print",
+            Tool::Encrypt => "🔒 Encrypt…",
+            Tool::Decrypt => "🔓 Decrypt…",
+            Tool::VerifySignature => "🔏 Verify signature",
+            Tool::VerifyChecksums => "✅ Verify checksums",
+            Tool::ConvertImages => "🖼 Convert images…",
+            Tool::CopyPath => "📎 Copy full path",
+            Tool::CopyName => "🔤 Copy name",
+            Tool::CopyUri => "🔗 Copy as file:// URI",
+            Tool::Tags => "🏷 Tags…",
+            Tool::Star => "☆ Star / unstar",
+            Tool::Starred => "⭐ Starred",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+// The tools the command palette offers for `query`: those whose label
+// fuzzily matches it, best first, or all of them in toolbar order while
+// nothing is typed
+pub fn matching(query: &str) -> Vec<Tool> {
+    let pattern = query.trim().to_lowercase();
+    let mut scored: Vec<(i64, Tool)> =
+        Tool::ALL.into_iter().filter_map(|tool| Some((search::fuzzy_score(&pattern, &tool.to_string())?, tool))).collect();
+    // Stable, so equal scores keep the toolbar's order
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, tool)| tool).collect()
+}
+
+// Where a tool is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Place {
+    Navigation,
+    Actions,
+    Overflow,
+}
+
+impl Place {
+    pub const ALL: [Place; 3] = [Place::Navigation, Place::Actions, Place::Overflow];
+}
+
+impl fmt::Display for Place {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Place::Navigation => "Navigation row",
+            Place::Actions => "Action row",
+            Place::Overflow => "More menu",
+        };
+        write!(f, "{}", tr(label))
+    }
+}
+
+// An entry of the More menu. Recent needs a time window, so it is offered
+// once per window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    Tool(Tool),
+    Recent(recent::Window),
+}
+
+impl fmt::Display for MenuItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MenuItem::Tool(tool) => write!(f, "{}", tool),
+            MenuItem::Recent(window) => write!(f, "{}", fill(tr("🕘 Recent: {}"), &[window])),
+        }
+    }
+}
+
+// The tools on each toolbar row, in order; tools on neither row are kept
+// in the More menu
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Toolbar {
+    pub navigation: Vec<Tool>,
+    pub actions: Vec<Tool>,
+}
+
+impl Default for Toolbar {
+    fn default() -> Self {
+        Toolbar {
+            navigation: vec![Tool::Up, Tool::Home, Tool::Refresh, Tool::Usage, Tool::Empty, Tool::Largest, Tool::Recent],
+            actions: vec![Tool::New, Tool::Delete, Tool::Edit, Tool::OpenWith, Tool::Properties, Tool::Settings],
+        }
+    }
+}
+
+impl Toolbar {
+    pub fn place(&self, tool: Tool) -> Place {
+        if self.navigation.contains(&tool) {
+            Place::Navigation
+        } else if self.actions.contains(&tool) {
+            Place::Actions
+        } else {
+            Place::Overflow
+        }
+    }
+
+    pub fn overflow(&self) -> Vec<MenuItem> {
+        Tool::ALL
+            .into_iter()
+            .filter(|&tool| self.place(tool) == Place::Overflow)
+            .flat_map(|tool| match tool {
+                Tool::Recent => recent::Window::ALL.map(MenuItem::Recent).to_vec(),
+                tool => vec![MenuItem::Tool(tool)],
+            })
+            .collect()
+    }
+
+    // Moves `tool` to the end of the row for `place`
+    pub fn put(&mut self, tool: Tool, place: Place) {
+        if self.place(tool) == place {
+            return;
+        }
+        self.navigation.retain(|&other| other != tool);
+        self.actions.retain(|&other| other != tool);
+        match place {
+            Place::Navigation => self.navigation.push(tool),
+            Place::Actions => self.actions.push(tool),
+            Place::Overflow => {}
+        }
+    }
+
+    // Moves `tool` one step left (negative) or right within its row
+    pub fn shift(&mut self, tool: Tool, offset: isize) {
+        for tools in [&mut self.navigation, &mut self.actions] {
+            if let Some(index) = tools.iter().position(|&other| other == tool) {
+                let target = index.saturating_add_signed(offset).min(tools.len() - 1);
+                tools.swap(index, target);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_palette_ranks_tools_by_what_was_typed() {
+        assert_eq!(matching(""), Tool::ALL.to_vec());
+        assert_eq!(matching("mv").first(), Some(&Tool::MoveTo));
+        assert_eq!(matching("copy path").first(), Some(&Tool::CopyPath));
+        assert_eq!(matching("  PROP ").first(), Some(&Tool::Properties));
+        assert!(matching("qqq").is_empty());
+    }
+}
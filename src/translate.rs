@@ -0,0 +1,68 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+// The library's messages are written in English. An app shows them in its
+// user's language by installing its translation, which is asked for each
+// message by its English text.
+static TRANSLATION: OnceLock<fn(&'static str) -> &'static str> = OnceLock::new();
+
+// Sets the translation once; later calls are ignored
+pub fn install(translation: fn(&'static str) -> &'static str) {
+    let _ = TRANSLATION.set(translation);
+}
+
+// Translates an English message with the installed translation, if any
+pub fn tr(text: &'static str) -> &'static str {
+    TRANSLATION.get().map_or(text, |translation| translation(text))
+}
+
+// Fills the placeholders of a translated template: `{}` takes the next
+// argument and `{1}` the one at that position, counted from 0, so a
+// translation can put them in another order than the English text. A
+// placeholder without its argument is left out.
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}').filter(|&end| after[..end].bytes().all(|byte| byte.is_ascii_digit())) else {
+            // A brace that opens no placeholder is kept as text
+            filled.push('{');
+            rest = after;
+            continue;
+        };
+        let index = if end == 0 {
+            next += 1;
+            next - 1
+        } else {
+            after[..end].parse().unwrap_or(usize::MAX)
+        };
+        if let Some(arg) = args.get(index) {
+            filled.push_str(&arg.to_string());
+        }
+        rest = &after[end + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_in_order_or_by_position() {
+        assert_eq!(fill("{} of {}", &[&1, &2]), "1 of 2");
+        assert_eq!(fill("{1} before {0}", &[&"a", &"b"]), "b before a");
+        assert_eq!(fill("{0}, {0}", &[&"again"]), "again, again");
+    }
+
+    #[test]
+    fn keeps_other_braces_and_drops_missing_arguments() {
+        assert_eq!(fill("{x} {}", &[&1]), "{x} 1");
+        assert_eq!(fill("{} and {} and {5}", &[&1]), "1 and  and ");
+        assert_eq!(fill("open {", &[]), "open {");
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::{Local, NaiveDateTime, TimeZone};
+use std::fs::{create_dir_all, read_dir, read_to_string, remove_dir_all, remove_file, rename, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+// Deletion dates as the trash spec writes them, in local time
+const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+// Something in the trash, with where it came from
+#[derive(Debug, Clone)]
+pub struct Item {
+    // The trashed file or folder itself, below the trash's files folder
+    pub file: PathBuf,
+    info: PathBuf,
+    pub original: PathBuf,
+    pub deleted: Option<SystemTime>,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+// The home trash of the freedesktop.org trash spec, which desktops on Linux
+// and the BSDs share; macOS and Windows keep theirs in ways this doesn't read
+#[cfg(all(unix, not(target_os = "macos")))]
+fn trash_dir() -> io::Result<PathBuf> {
+    dirs::data_dir()
+        .map(|dir| dir.join("Trash"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data folder for the trash"))
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn trash_dir() -> io::Result<PathBuf> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "the trash isn't supported on this system"))
+}
+
+pub fn is_supported() -> bool {
+    trash_dir().is_ok()
+}
+
+// Moves `path` into the trash, returning where it went. Only a rename is
+// done, so something on another drive than home can't be trashed.
+pub fn put(path: &Path) -> io::Result<PathBuf> {
+    let trash = trash_dir()?;
+    let (files, infos) = (trash.join("files"), trash.join("info"));
+    create_dir_all(&files)?;
+    create_dir_all(&infos)?;
+
+    let original = std::path::absolute(path)?;
+    let name = original.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "nothing to trash"))?;
+
+    // The info file is created first and exclusively, which claims the name
+    // for this item even when another program trashes at the same time
+    let mut counter = 1;
+    let (stem, info) = loop {
+        let stem = if counter == 1 {
+            name.to_os_string()
+        } else {
+            let mut numbered = name.to_os_string();
+            numbered.push(format!(".{}", counter));
+            numbered
+        };
+        let mut info_name = stem.clone();
+        info_name.push(".trashinfo");
+        let info = infos.join(info_name);
+        match OpenOptions::new().write(true).create_new(true).open(&info) {
+            Ok(mut file) => {
+                let deleted = Local::now().format(DATE_FORMAT);
+                write!(file, "[Trash Info]\nPath={}\nDeletionDate={}\n", encode(&original), deleted)?;
+                break (stem, info);
+            }
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => counter += 1,
+            Err(error) => return Err(error),
+        }
+    };
+
+    let target = files.join(stem);
+    if let Err(error) = rename(&original, &target) {
+        let _ = remove_file(info);
+        return Err(error);
+    }
+    Ok(target)
+}
+
+// Everything in the trash, most recently trashed first
+pub fn list() -> io::Result<Vec<Item>> {
+    let trash = trash_dir()?;
+    let infos = match read_dir(trash.join("info")) {
+        Ok(infos) => infos,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    let mut items: Vec<Item> = infos
+        .flatten()
+        .filter_map(|entry| {
+            let info = entry.path();
+            let stem = info.file_stem()?.to_os_string();
+            let contents = read_to_string(&info).ok()?;
+            let field = |key: &str| contents.lines().find_map(|line| line.strip_prefix(key)?.strip_prefix('='));
+
+            let file = trash.join("files").join(stem);
+            let meta = file.symlink_metadata().ok()?;
+            let size = if meta.is_dir() {
+                WalkDir::new(&file).into_iter().flatten().filter_map(|entry| entry.metadata().ok()).filter(|meta| meta.is_file()).map(|meta| meta.len()).sum()
+            } else {
+                meta.len()
+            };
+            Some(Item {
+                original: decode(field("Path")?),
+                deleted: field("DeletionDate")
+                    .and_then(|date| NaiveDateTime::parse_from_str(date, DATE_FORMAT).ok())
+                    .and_then(|date| Local.from_local_datetime(&date).earliest())
+                    .map(SystemTime::from),
+                is_dir: meta.is_dir(),
+                size,
+                file,
+                info,
+            })
+        })
+        .collect();
+    items.sort_by_key(|item| std::cmp::Reverse(item.deleted));
+    Ok(items)
+}
+
+// Puts `item` back where it was trashed from, unless something new is there
+pub fn restore(item: &Item) -> io::Result<()> {
+    if item.original.symlink_metadata().is_ok() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", item.original.display())));
+    }
+    if let Some(parent) = item.original.parent() {
+        create_dir_all(parent)?;
+    }
+    rename(&item.file, &item.original)?;
+    remove_file(&item.info)
+}
+
+// Deletes everything in the trash for good
+pub fn empty() -> io::Result<()> {
+    let trash = trash_dir()?;
+    for folder in ["files", "info"] {
+        let Ok(entries) = read_dir(trash.join(folder)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                remove_dir_all(path)?;
+            } else {
+                remove_file(path)?;
+            }
+        }
+    }
+    // A cache of folder sizes some file managers keep alongside
+    match remove_file(trash.join("directorysizes")) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+// Paths in info files are URL-escaped, except for the slashes
+fn encode(path: &Path) -> String {
+    path.as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .map(|&byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+// The path is the bytes it escapes, which need not be UTF-8
+fn decode(text: &str) -> PathBuf {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&first, tail)) = rest.split_first() {
+        if first == b'%'
+            && let Some(byte) = tail.get(..2).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+        {
+            bytes.push(byte);
+            rest = &tail[2..];
+        } else {
+            bytes.push(first);
+            rest = tail;
+        }
+    }
+    path_from(bytes)
+}
+
+#[cfg(unix)]
+fn path_from(bytes: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(std::ffi::OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_all_but_plain_characters() {
+        assert_eq!(encode(Path::new("/home/a b/100%.txt")), "/home/a%20b/100%25.txt");
+        assert_eq!(decode("/home/a%20b/100%25.txt"), Path::new("/home/a b/100%.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn round_trips_names_that_are_not_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"/tmp/caf\xe9 \xff.txt"));
+        assert_eq!(encode(path), "/tmp/caf%E9%20%FF.txt");
+        assert_eq!(decode(&encode(path)), path);
+    }
+}
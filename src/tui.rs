@@ -0,0 +1,519 @@
+// The terminal front end, started with `--tui`: the folder as a list to
+// move through with the keys, with the window's create, delete and
+// properties actions, on the same core library. Drawn and read through
+// crossterm, so it runs in the terminals of every system the window does.
+use crate::i18n::{self, fill, tr};
+use crate::settings::{self, Preferences};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use file_explorer::entry::{self, FileEntry, SortMode};
+use file_explorer::excludes::Excludes;
+use file_explorer::{associations, cloud, filetype, guard, history, loader, trash};
+use std::fs::{self, metadata, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// Rows besides the list: the folder at the top, the status line below
+const CHROME_ROWS: usize = 2;
+// Width of the size and modified columns
+const SIZE_WIDTH: usize = 10;
+const DATE_WIDTH: usize = 17;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Backspace,
+    Delete,
+    Escape,
+    Interrupt,
+    Char(char),
+}
+
+// What the keys do at the moment
+enum Mode {
+    Browse,
+    // Typing the name of a new file or folder
+    Create { folder: bool, name: String },
+    // Waiting for y to delete the selection; protected locations need their
+    // name typed instead
+    Delete { permanently: bool, protected: bool, typed: String },
+    // Labels and values shown over the list until a key is pressed
+    Properties(Vec<(String, String)>),
+}
+
+struct Tui {
+    home: PathBuf,
+    dir: PathBuf,
+    entries: Vec<FileEntry>,
+    selected: usize,
+    // The first entry on screen
+    top: usize,
+    show_hidden: bool,
+    preferences: Preferences,
+    excludes: Excludes,
+    mode: Mode,
+    message: Option<String>,
+    // Rows and columns of the terminal
+    size: (usize, usize),
+    quit: bool,
+}
+
+pub fn run(home: PathBuf) -> io::Result<()> {
+    let preferences = settings::load();
+    i18n::set_language(preferences.language);
+    let excludes = Excludes::new(preferences.skip_ignored, &preferences.exclude_patterns, &preferences.hide_patterns);
+    let dir = preferences.start_dir.clone().filter(|dir| dir.is_dir()).unwrap_or_else(|| home.clone());
+
+    let mut tui = Tui {
+        home,
+        dir: PathBuf::new(),
+        entries: Vec::new(),
+        selected: 0,
+        top: 0,
+        show_hidden: preferences.show_hidden,
+        preferences,
+        excludes,
+        mode: Mode::Browse,
+        message: None,
+        size: terminal_size(),
+        quit: false,
+    };
+    // Without a folder to start in, every action would land in the working
+    // directory instead
+    tui.list(dir.clone(), None)
+        .map_err(|error| io::Error::new(error.kind(), fill(tr("Could not read {}"), &[&format!("{}: {}", dir.display(), error)])))?;
+
+    let _terminal = Terminal::enter()?;
+    let mut output = io::stdout();
+    tui.draw(&mut output)?;
+    while !tui.quit {
+        match event::read()? {
+            // Windows reports releases too
+            Event::Key(event) if event.kind != KeyEventKind::Release => {
+                if let Some(key) = key(event) {
+                    tui.handle(key);
+                    tui.draw(&mut output)?;
+                }
+            }
+            Event::Resize(columns, rows) => {
+                tui.size = (usize::from(rows), usize::from(columns));
+                tui.draw(&mut output)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+impl Tui {
+    // Lists `dir`, selecting `select` when it is there; the shown folder
+    // stays as it was when `dir` can't be read
+    fn list(&mut self, dir: PathBuf, select: Option<&Path>) -> io::Result<()> {
+        let mut entries = loader::list_dir(&dir, self.show_hidden, &self.excludes)?;
+        let mode = match &self.preferences.sort_mode {
+            // Ranking needs a search, which this list never is
+            SortMode::Relevance => SortMode::NameAsc,
+            mode => mode.clone(),
+        };
+        entry::sort(&mut entries, &mode, self.preferences.dirs_first);
+        self.selected = select.and_then(|select| entries.iter().position(|entry| entry.path == select)).unwrap_or(0);
+        self.entries = entries;
+        self.dir = dir;
+        self.top = 0;
+        Ok(())
+    }
+
+    // Like `list`, telling on the status line when it fails
+    fn navigate(&mut self, dir: PathBuf, select: Option<&Path>) {
+        if let Err(error) = self.list(dir.clone(), select) {
+            self.message = Some(fill(tr("Could not read {}"), &[&format!("{}: {}", dir.display(), error)]));
+        }
+    }
+
+    fn reload(&mut self) {
+        let selected = self.selected_entry().map(|entry| entry.path.clone());
+        self.navigate(self.dir.clone(), selected.as_deref());
+    }
+
+    fn selected_entry(&self) -> Option<&FileEntry> {
+        self.entries.get(self.selected)
+    }
+
+    fn list_rows(&self) -> usize {
+        self.size.0.saturating_sub(CHROME_ROWS).max(1)
+    }
+
+    fn handle(&mut self, key: Key) {
+        match std::mem::replace(&mut self.mode, Mode::Browse) {
+            Mode::Browse => self.browse(key),
+            Mode::Create { folder, mut name } => match key {
+                Key::Enter => self.create(folder, name.trim()),
+                Key::Escape | Key::Interrupt => {}
+                Key::Backspace => {
+                    name.pop();
+                    self.mode = Mode::Create { folder, name };
+                }
+                Key::Char(typed) => {
+                    name.push(typed);
+                    self.mode = Mode::Create { folder, name };
+                }
+                _ => self.mode = Mode::Create { folder, name },
+            },
+            Mode::Delete { permanently, protected: true, mut typed } => match key {
+                Key::Enter if self.selected_entry().is_some_and(|entry| entry.display_name() == typed.trim()) => self.delete(permanently),
+                Key::Enter => self.message = Some(tr("The name did not match; nothing was deleted").to_string()),
+                Key::Escape | Key::Interrupt => {}
+                Key::Backspace => {
+                    typed.pop();
+                    self.mode = Mode::Delete { permanently, protected: true, typed };
+                }
+                Key::Char(character) => {
+                    typed.push(character);
+                    self.mode = Mode::Delete { permanently, protected: true, typed };
+                }
+                _ => self.mode = Mode::Delete { permanently, protected: true, typed },
+            },
+            Mode::Delete { permanently, .. } => {
+                if matches!(key, Key::Char('y') | Key::Char('Y')) {
+                    self.delete(permanently);
+                }
+            }
+            // Any key closes them
+            Mode::Properties(_) => {}
+        }
+    }
+
+    fn browse(&mut self, key: Key) {
+        self.message = None;
+        let last = self.entries.len().saturating_sub(1);
+        let page = self.list_rows();
+        match key {
+            Key::Up | Key::Char('k') => self.selected = self.selected.saturating_sub(1),
+            Key::Down | Key::Char('j') => self.selected = (self.selected + 1).min(last),
+            Key::PageUp => self.selected = self.selected.saturating_sub(page),
+            Key::PageDown => self.selected = (self.selected + page).min(last),
+            Key::Home | Key::Char('g') => self.selected = 0,
+            Key::End | Key::Char('G') => self.selected = last,
+            Key::Enter | Key::Right | Key::Char('l') => self.open(),
+            Key::Backspace | Key::Left | Key::Char('h') => {
+                if let Some(parent) = self.dir.parent().map(Path::to_path_buf) {
+                    let left = self.dir.clone();
+                    self.navigate(parent, Some(&left));
+                }
+            }
+            Key::Char('~') => self.navigate(self.home.clone(), None),
+            Key::Char('r') => self.reload(),
+            Key::Char('.') => {
+                self.show_hidden = !self.show_hidden;
+                self.reload();
+            }
+            Key::Char('n') => self.mode = Mode::Create { folder: false, name: String::new() },
+            Key::Char('N') => self.mode = Mode::Create { folder: true, name: String::new() },
+            Key::Char('d') | Key::Delete => self.ask_delete(!trash::is_supported()),
+            Key::Char('D') => self.ask_delete(true),
+            Key::Char('i') => {
+                if let Some(entry) = self.selected_entry() {
+                    self.mode = Mode::Properties(self.properties(&entry.path));
+                }
+            }
+            Key::Char('q') | Key::Escape | Key::Interrupt => self.quit = true,
+            _ => {}
+        }
+    }
+
+    // Goes into a folder, or opens a file with its application
+    fn open(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let path = entry.path.clone();
+        if entry.is_dir {
+            self.navigate(path, None);
+            return;
+        }
+        tracing::info!(path = %path.display(), "open");
+        if let Err(error) = associations::open(&path, &self.preferences.associations) {
+            self.message = Some(format!("{}: {}", fill(tr("Could not open {}"), &[&path.display()]), error));
+        }
+    }
+
+    fn create(&mut self, folder: bool, name: &str) {
+        if name.is_empty() {
+            return;
+        }
+        if let Some(problem) = entry::name_problem(name) {
+            self.message = Some(problem.to_string());
+            return;
+        }
+        let path = self.dir.join(name);
+        tracing::info!(path = %path.display(), folder, "create");
+        // Nothing is written over here, as there is no dialog to ask first
+        let created = if folder { fs::create_dir(&path) } else { File::create_new(&path).map(|_| ()) };
+        remember(history::Action::Create, path.clone(), None, None, &created);
+        match created {
+            Ok(()) => self.navigate(self.dir.clone(), Some(&path)),
+            Err(error) => self.message = Some(format!("{}: {}", fill(tr("Could not create {}"), &[&path.display()]), error)),
+        }
+    }
+
+    fn ask_delete(&mut self, permanently: bool) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let protected = guard::is_protected(&entry.path, &self.home, self.preferences.protected_depth);
+        self.mode = Mode::Delete { permanently, protected, typed: String::new() };
+    }
+
+    // Moves the selection to the trash, or deletes it for good
+    fn delete(&mut self, permanently: bool) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let (path, size) = (entry.path.clone(), (!entry.is_dir).then_some(entry.size));
+        tracing::info!(path = %path.display(), permanently, "delete");
+        if permanently {
+            let removed = if entry.is_dir { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+            remember(history::Action::Delete, path.clone(), None, size, &removed);
+            if let Err(error) = removed {
+                self.message = Some(format!("{}: {}", fill(tr("Could not delete {}"), &[&path.display()]), error));
+            }
+        } else {
+            let trashed = trash::put(&path);
+            remember(history::Action::Trash, path.clone(), trashed.as_ref().ok().cloned(), size, &trashed);
+            if let Err(error) = trashed {
+                self.message = Some(format!("{}: {}", fill(tr("Could not move {} to the trash"), &[&path.display()]), error));
+            }
+        }
+        let selected = self.selected;
+        self.reload();
+        self.selected = selected.min(self.entries.len().saturating_sub(1));
+    }
+
+    // What the Properties dialog's General tab shows
+    fn properties(&self, path: &Path) -> Vec<(String, String)> {
+        let mut rows = vec![(tr("Path:").to_string(), path.display().to_string())];
+        let meta = match metadata(path) {
+            Ok(meta) => meta,
+            Err(error) => {
+                rows.push((tr("Error:").to_string(), error.to_string()));
+                return rows;
+            }
+        };
+        let date_format = self.preferences.date_format;
+        let file_type = if meta.is_dir() {
+            tr("Directory").to_string()
+        } else if cloud::is_placeholder(&meta) {
+            // Sniffing a placeholder would download it
+            filetype::describe_by_name(path)
+        } else {
+            filetype::detect(path).0
+        };
+        let size = meta.len();
+        rows.push((tr("Type:").to_string(), file_type));
+        rows.push((tr("Size:").to_string(), format!("{} ({})", self.preferences.size_units.format(size), settings::exact_bytes(size))));
+        if let Ok(modified) = meta.modified() {
+            rows.push((tr("Modified:").to_string(), date_format.format(modified, true)));
+        }
+        let created = meta.created().map_or_else(|_| tr("Unknown").to_string(), |created| date_format.format(created, true));
+        rows.push((tr("Created:").to_string(), created));
+        rows.push((tr("Permissions:").to_string(), permissions(&meta)));
+        rows
+    }
+
+    fn draw(&mut self, output: &mut impl Write) -> io::Result<()> {
+        let (rows, columns) = self.size;
+        let list_rows = self.list_rows();
+        // Keep the selection on screen
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + list_rows {
+            self.top = self.selected + 1 - list_rows;
+        }
+
+        queue!(output, Clear(ClearType::All), at(0, 0))?;
+        reversed(output, &pad(&self.dir.display().to_string(), columns))?;
+
+        let name_width = columns.saturating_sub(SIZE_WIDTH + DATE_WIDTH + 4);
+        for (row, index) in (self.top..self.top + list_rows).enumerate() {
+            queue!(output, at(0, row + 1))?;
+            if let Some(entry) = self.entries.get(index) {
+                let name = if entry.is_dir { format!("{}/", entry.display_name()) } else { entry.display_name().into_owned() };
+                let size = if entry.is_dir { String::new() } else { self.preferences.size_units.format(entry.size) };
+                let modified = self.preferences.date_format.format(entry.modified, false);
+                let line = format!(
+                    " {} {:>size_width$} {:>date_width$} ",
+                    pad(&name, name_width),
+                    cut(&size, SIZE_WIDTH),
+                    cut(&modified, DATE_WIDTH),
+                    size_width = SIZE_WIDTH,
+                    date_width = DATE_WIDTH
+                );
+                if index == self.selected {
+                    reversed(output, &pad(&line, columns))?;
+                } else {
+                    queue!(output, Print(cut(&line, columns)))?;
+                }
+            } else if index == 0 {
+                queue!(output, Print(cut(&format!(" {}", tr("This folder is empty.")), columns)))?;
+            }
+        }
+
+        if let Mode::Properties(properties) = &self.mode {
+            self.draw_properties(output, properties)?;
+        }
+
+        queue!(output, at(0, rows.saturating_sub(1)))?;
+        reversed(output, &pad(&self.status(), columns))?;
+        output.flush()
+    }
+
+    // A box in the middle of the list, one row per property
+    fn draw_properties(&self, output: &mut impl Write, properties: &[(String, String)]) -> io::Result<()> {
+        let (rows, columns) = self.size;
+        let width = columns.saturating_sub(8).clamp(20, 100);
+        let label_width = properties.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+        let left = (columns.saturating_sub(width)) / 2;
+        let top = (rows.saturating_sub(properties.len() + 2)) / 2;
+        let title = tr("Properties");
+        let lines = std::iter::once(format!("─ {} ", title))
+            .chain(properties.iter().map(|(label, value)| format!(" {} {}", pad(label, label_width), value)))
+            .chain(std::iter::once(format!(" {}", tr("Press any key to close"))));
+        for (offset, line) in lines.enumerate() {
+            queue!(output, at(left, top + offset))?;
+            reversed(output, &pad(&line, width))?;
+        }
+        Ok(())
+    }
+
+    fn status(&self) -> String {
+        let name = self.selected_entry().map(|entry| entry.display_name().into_owned()).unwrap_or_default();
+        match &self.mode {
+            Mode::Create { folder: false, name } => format!("{} {}▏", tr("New file name:"), name),
+            Mode::Create { folder: true, name } => format!("{} {}▏", tr("New folder name:"), name),
+            Mode::Delete { protected: true, typed, .. } => {
+                format!("{} {}▏", fill(tr("'{}' is a protected location; type its name to delete it:"), &[&name]), typed)
+            }
+            Mode::Delete { permanently: true, .. } => fill(tr("Delete '{}' for good? (y/n)"), &[&name]),
+            Mode::Delete { permanently: false, .. } => fill(tr("Move '{}' to the trash? (y/n)"), &[&name]),
+            Mode::Properties(_) => String::new(),
+            Mode::Browse => {
+                let count = fill(tr("{} items"), &[&self.entries.len()]);
+                let message = self.message.clone().unwrap_or_else(|| {
+                    tr("↑↓ move · ⏎ open · ⌫ up · n new file · N new folder · d trash · D delete · i properties · . hidden · q quit")
+                        .to_string()
+                });
+                format!("{} · {}", count, message)
+            }
+        }
+    }
+}
+
+fn remember<T>(action: history::Action, path: PathBuf, target: Option<PathBuf>, size: Option<u64>, result: &io::Result<T>) {
+    let record = history::Record::new(action, path, target, size, history::Outcome::of(result));
+    if let Err(error) = history::append(&record) {
+        tracing::warn!("could not write the history: {}", error);
+    }
+}
+
+#[cfg(unix)]
+fn permissions(meta: &fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    format!("{:o}", meta.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn permissions(meta: &fs::Metadata) -> String {
+    if meta.permissions().readonly() { tr("Read-only").to_string() } else { tr("Read-write").to_string() }
+}
+
+// `text` cut or padded with spaces to exactly `width` characters
+fn pad(text: &str, width: usize) -> String {
+    format!("{:<width$}", cut(text, width), width = width)
+}
+
+// `text` cut to at most `width` characters, ending in … when cut
+fn cut(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+// The cursor moved to `column` and `row`, counted from 0 at the top left;
+// both come from the terminal's size, so they fit its numbers
+fn at(column: usize, row: usize) -> MoveTo {
+    MoveTo(column.try_into().unwrap_or(u16::MAX), row.try_into().unwrap_or(u16::MAX))
+}
+
+// `text` in reverse video, as the bars and the selection are shown
+fn reversed(output: &mut impl Write, text: &str) -> io::Result<()> {
+    queue!(output, SetAttribute(Attribute::Reverse), Print(text), SetAttribute(Attribute::Reset))
+}
+
+// Raw mode and the alternate screen while it lives; the terminal is put
+// back as it was when it is dropped, also when the program panics
+struct Terminal;
+
+impl Terminal {
+    fn enter() -> io::Result<Terminal> {
+        terminal::enable_raw_mode()?;
+        // Made first, so raw mode is left again when the rest fails
+        let terminal = Terminal;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(terminal)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Rows and columns, or the classic 24 by 80 when the terminal can't tell
+fn terminal_size() -> (usize, usize) {
+    terminal::size()
+        .ok()
+        .filter(|&(columns, rows)| rows > 0 && columns > 0)
+        .map_or((24, 80), |(columns, rows)| (usize::from(rows), usize::from(columns)))
+}
+
+// The key a key press stands for, if the list has a use for it
+fn key(event: KeyEvent) -> Option<Key> {
+    let control = event.modifiers.contains(KeyModifiers::CONTROL);
+    let key = match event.code {
+        // Raw mode leaves Ctrl+C to us
+        KeyCode::Char('c') if control => Key::Interrupt,
+        // AltGr comes as Ctrl+Alt, and types characters like any other key
+        KeyCode::Char(_) if control && !event.modifiers.contains(KeyModifiers::ALT) => return None,
+        KeyCode::Char(character) => Key::Char(character),
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::PageDown => Key::PageDown,
+        KeyCode::Home => Key::Home,
+        KeyCode::End => Key::End,
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Delete => Key::Delete,
+        KeyCode::Esc => Key::Escape,
+        _ => return None,
+    };
+    Some(key)
+}
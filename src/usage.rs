@@ -0,0 +1,322 @@
+use crate::excludes::Excludes;
+use crate::icons::{IconKind, IconSet};
+use crate::settings::SizeUnits;
+use crate::Message;
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry, Path as CanvasPath, Stroke, Text};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
+use ignore::WalkState;
+use std::collections::HashMap;
+use std::fs::{symlink_metadata, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+// Rectangles smaller than this (in either dimension) get no label
+const MIN_LABEL_WIDTH: f32 = 60.0;
+const MIN_LABEL_HEIGHT: f32 = 20.0;
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub path: PathBuf,
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    // Finds the node for `path` somewhere below (or at) this node
+    pub fn find(&self, path: &Path) -> Option<&Node> {
+        if self.path == path {
+            return Some(self);
+        }
+
+        self.children
+            .iter()
+            .find(|child| path.starts_with(&child.path))
+            .and_then(|child| child.find(path))
+    }
+
+    // Removes the node for `path`, returning its size so ancestors can be updated
+    pub fn remove(&mut self, path: &Path) -> Option<u64> {
+        if let Some(index) = self.children.iter().position(|child| child.path == path) {
+            let removed = self.children.remove(index).size;
+            self.size = self.size.saturating_sub(removed);
+            return Some(removed);
+        }
+
+        let child = self.children.iter_mut().find(|child| path.starts_with(&child.path))?;
+        let removed = child.remove(path)?;
+        self.size = self.size.saturating_sub(removed);
+        Some(removed)
+    }
+}
+
+// Measures everything below `path` that `excludes` keeps, without following
+// symlinks. Folders are read on several threads, so entries arrive in no
+// particular order and the tree is put together once all are in.
+pub fn scan(path: &Path, excludes: &Excludes) -> Node {
+    let mut root = leaf(path, symlink_metadata(path).ok().as_ref());
+    if !root.is_dir {
+        return root;
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    excludes.walk(path, true).build_parallel().run(|| {
+        let sender = sender.clone();
+        Box::new(move |entry| {
+            if let Ok(entry) = entry {
+                let _ = sender.send(leaf(entry.path(), entry.metadata().ok().as_ref()));
+            }
+            WalkState::Continue
+        })
+    });
+    drop(sender);
+
+    // Folders are completed deepest first, so every folder below one is
+    // whole by the time it goes into it
+    let mut folders = Vec::new();
+    let mut children: HashMap<PathBuf, Vec<Node>> = HashMap::new();
+    for node in receiver {
+        if node.is_dir {
+            folders.push(node);
+        } else if let Some(parent) = node.path.parent() {
+            children.entry(parent.to_path_buf()).or_default().push(node);
+        }
+    }
+    folders.sort_by_key(|folder| std::cmp::Reverse(folder.path.components().count()));
+
+    for mut folder in folders {
+        folder.children = children.remove(&folder.path).unwrap_or_default();
+        total_up(&mut folder);
+        if let Some(parent) = folder.path.parent() {
+            children.entry(parent.to_path_buf()).or_default().push(folder);
+        }
+    }
+
+    root.children = children.remove(path).unwrap_or_default();
+    total_up(&mut root);
+    root
+}
+
+// A node for `path` with no children yet
+fn leaf(path: &Path, meta: Option<&Metadata>) -> Node {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    let is_dir = meta.is_some_and(|meta| meta.is_dir());
+
+    Node {
+        path: path.to_path_buf(),
+        name,
+        size: if is_dir { 0 } else { meta.map_or(0, |meta| meta.len()) },
+        is_dir,
+        children: Vec::new(),
+    }
+}
+
+fn total_up(folder: &mut Node) {
+    folder.children.sort_by_key(|child| std::cmp::Reverse(child.size));
+    folder.size = folder.children.iter().map(|child| child.size).sum();
+}
+
+// Lays out `sizes` (sorted largest first) inside `bounds` using the
+// squarified treemap algorithm, which keeps rectangles close to square
+pub fn squarify(sizes: &[u64], bounds: Rectangle) -> Vec<Rectangle> {
+    let total: u64 = sizes.iter().sum();
+    if total == 0 || bounds.width <= 0.0 || bounds.height <= 0.0 {
+        return vec![Rectangle::new(bounds.position(), Size::ZERO); sizes.len()];
+    }
+
+    let scale = (bounds.width * bounds.height) as f64 / total as f64;
+    let areas: Vec<f64> = sizes.iter().map(|size| *size as f64 * scale).collect();
+
+    let mut rects = Vec::with_capacity(areas.len());
+    let mut remaining = bounds;
+    let mut start = 0;
+
+    while start < areas.len() {
+        let side = remaining.width.min(remaining.height) as f64;
+
+        // Grow the current row while it keeps improving the worst aspect ratio
+        let mut end = start + 1;
+        let mut best = worst_ratio(&areas[start..end], side);
+        while end < areas.len() {
+            let ratio = worst_ratio(&areas[start..=end], side);
+            if ratio > best {
+                break;
+            }
+            best = ratio;
+            end += 1;
+        }
+
+        let row_area: f64 = areas[start..end].iter().sum();
+
+        if remaining.width >= remaining.height {
+            // Vertical strip along the left edge
+            let thickness = if remaining.height > 0.0 { row_area / remaining.height as f64 } else { 0.0 };
+            let mut y = remaining.y;
+            for area in &areas[start..end] {
+                let height = if thickness > 0.0 { (area / thickness) as f32 } else { 0.0 };
+                rects.push(Rectangle::new(Point::new(remaining.x, y), Size::new(thickness as f32, height)));
+                y += height;
+            }
+            remaining.x += thickness as f32;
+            remaining.width -= thickness as f32;
+        } else {
+            // Horizontal strip along the top edge
+            let thickness = if remaining.width > 0.0 { row_area / remaining.width as f64 } else { 0.0 };
+            let mut x = remaining.x;
+            for area in &areas[start..end] {
+                let width = if thickness > 0.0 { (area / thickness) as f32 } else { 0.0 };
+                rects.push(Rectangle::new(Point::new(x, remaining.y), Size::new(width, thickness as f32)));
+                x += width;
+            }
+            remaining.y += thickness as f32;
+            remaining.height -= thickness as f32;
+        }
+
+        start = end;
+    }
+
+    rects
+}
+
+fn worst_ratio(row: &[f64], side: f64) -> f64 {
+    let sum: f64 = row.iter().sum();
+    if sum <= 0.0 || side <= 0.0 {
+        return f64::MAX;
+    }
+
+    let (min, max) = row
+        .iter()
+        .fold((f64::MAX, 0.0_f64), |(min, max), area| (min.min(*area), max.max(*area)));
+    let side_squared = side * side;
+    let sum_squared = sum * sum;
+
+    (side_squared * max / sum_squared).max(sum_squared / (side_squared * min))
+}
+
+// Canvas program drawing one level of the tree: left click descends into a
+// folder, right click asks to delete the entry under the cursor
+pub struct Treemap<'a> {
+    pub node: &'a Node,
+    pub icons: IconSet,
+    pub units: SizeUnits,
+}
+
+impl Treemap<'_> {
+    fn layout(&self, bounds: Rectangle) -> Vec<(Rectangle, &Node)> {
+        let children: Vec<&Node> = self.node.children.iter().filter(|child| child.size > 0).collect();
+        let sizes: Vec<u64> = children.iter().map(|child| child.size).collect();
+        let area = Rectangle::new(Point::ORIGIN, bounds.size());
+
+        squarify(&sizes, area).into_iter().zip(children).collect()
+    }
+}
+
+impl canvas::Program<Message> for Treemap<'_> {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut (),
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let Some(position) = cursor.position_in(bounds) else {
+            return (canvas::event::Status::Ignored, None);
+        };
+
+        let canvas::Event::Mouse(mouse::Event::ButtonPressed(button)) = event else {
+            return (canvas::event::Status::Ignored, None);
+        };
+
+        let Some((_, node)) = self.layout(bounds).into_iter().find(|(rect, _)| rect.contains(position)) else {
+            return (canvas::event::Status::Ignored, None);
+        };
+
+        let message = match button {
+            mouse::Button::Left if node.is_dir => Some(Message::UsageOpen(node.path.clone())),
+            mouse::Button::Right => Some(Message::UsageDelete(node.path.clone())),
+            _ => None,
+        };
+
+        (canvas::event::Status::Captured, message)
+    }
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let hovered = cursor.position_in(bounds);
+
+        for (index, (rect, node)) in self.layout(bounds).into_iter().enumerate() {
+            let is_hovered = hovered.is_some_and(|point| rect.contains(point));
+            let color = tile_color(index, node.is_dir, is_hovered);
+
+            frame.fill_rectangle(rect.position(), rect.size(), color);
+            frame.stroke(
+                &CanvasPath::rectangle(rect.position(), rect.size()),
+                Stroke::default().with_color(Color::WHITE).with_width(1.0),
+            );
+
+            if rect.width >= MIN_LABEL_WIDTH && rect.height >= MIN_LABEL_HEIGHT {
+                let icon = if node.is_dir { format!("{} ", self.icons.glyph(IconKind::Folder)) } else { String::new() };
+                frame.with_clip(rect, |frame| {
+                    frame.fill_text(Text {
+                        content: format!("{}{}\n{}", icon, node.name, self.units.format(node.size)),
+                        position: Point::new(4.0, 4.0),
+                        color: Color::WHITE,
+                        size: 13.0,
+                        ..Text::default()
+                    });
+                });
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(&self, _state: &(), bounds: Rectangle, cursor: mouse::Cursor) -> mouse::Interaction {
+        if cursor.is_over(bounds) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+fn tile_color(index: usize, is_dir: bool, hovered: bool) -> Color {
+    const FOLDER_COLORS: [Color; 4] = [
+        Color::from_rgb(0.20, 0.40, 0.70),
+        Color::from_rgb(0.25, 0.50, 0.60),
+        Color::from_rgb(0.30, 0.35, 0.65),
+        Color::from_rgb(0.20, 0.45, 0.55),
+    ];
+    const FILE_COLORS: [Color; 4] = [
+        Color::from_rgb(0.55, 0.40, 0.25),
+        Color::from_rgb(0.50, 0.45, 0.30),
+        Color::from_rgb(0.60, 0.35, 0.30),
+        Color::from_rgb(0.45, 0.40, 0.35),
+    ];
+
+    let base = if is_dir {
+        FOLDER_COLORS[index % FOLDER_COLORS.len()]
+    } else {
+        FILE_COLORS[index % FILE_COLORS.len()]
+    };
+
+    if hovered {
+        Color::from_rgb((base.r + 0.15).min(1.0), (base.g + 0.15).min(1.0), (base.b + 0.15).min(1.0))
+    } else {
+        base
+    }
+}
@@ -0,0 +1,58 @@
+use crate::entry::SortMode;
+use crate::paths;
+use crate::settings::Density;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+// How a folder was last shown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FolderView {
+    pub sort_mode: SortMode,
+    pub density: Density,
+    pub show_hidden: bool,
+}
+
+// View settings remembered per folder, for folders where the user changed
+// one; other folders use the defaults from the preferences
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FolderViews {
+    folders: HashMap<PathBuf, FolderView>,
+}
+
+impl FolderViews {
+    pub fn get(&self, dir: &Path) -> Option<&FolderView> {
+        self.folders.get(dir)
+    }
+
+    pub fn remember(&mut self, dir: &Path, view: FolderView) -> std::io::Result<()> {
+        self.folders.insert(dir.to_path_buf(), view);
+        save(self)
+    }
+}
+
+fn store_path() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join("folders.json"))
+}
+
+// Loads the remembered views; an unreadable store starts over empty
+pub fn load() -> FolderViews {
+    store_path()
+        .and_then(|path| read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save(views: &FolderViews) -> std::io::Result<()> {
+    let Some(path) = store_path() else {
+        return Ok(());
+    };
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let contents = serde_json::to_string(views).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    write(path, contents)
+}
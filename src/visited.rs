@@ -0,0 +1,46 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+// What tells two folders apart regardless of the path they were reached by
+#[cfg(unix)]
+type Identity = (u64, u64);
+#[cfg(not(unix))]
+type Identity = std::path::PathBuf;
+
+// The folders a recursive walk has gone into. Walks don't follow symlinks,
+// but a bind mount of a parent or a junction still leads back to a folder
+// already seen; remembering folders by identity rather than path means
+// such a loop is entered once instead of until paths grow too long.
+#[derive(Debug, Default)]
+pub struct Visited {
+    seen: HashSet<Identity>,
+}
+
+impl Visited {
+    // True the first time the folder at `path` is entered, false when the
+    // walk has looped back to it. Folders whose identity can't be read are
+    // always entered.
+    pub fn enter(&mut self, path: &Path) -> bool {
+        match identity(path) {
+            Some(identity) => self.seen.insert(identity),
+            None => true,
+        }
+    }
+
+    // For walkdir's filter_entry: leaves out folders the walk has been in
+    pub fn filter() -> impl FnMut(&walkdir::DirEntry) -> bool {
+        let mut visited = Visited::default();
+        move |entry| !entry.file_type().is_dir() || visited.enter(entry.path())
+    }
+}
+
+#[cfg(unix)]
+fn identity(path: &Path) -> Option<Identity> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|meta| (meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn identity(path: &Path) -> Option<Identity> {
+    std::fs::canonicalize(path).ok()
+}
@@ -0,0 +1,244 @@
+// Extended attributes through the C library's calls, which std doesn't
+// offer. macOS takes a position and options besides, and names the missing
+// attribute error differently; other systems report them as unsupported.
+// Names are kept as the bytes they are, so those that aren't UTF-8 can
+// still be read and removed.
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::path::Path;
+
+// Values longer than this are not read; attributes are meant to be small
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+const MAX_VALUE: usize = 64 * 1024;
+
+// Whether attributes can be read and written here at all
+pub const SUPPORTED: bool = cfg!(any(target_os = "linux", target_os = "macos"));
+
+// Attributes the user may add and remove. On Linux the other namespaces
+// belong to the system; macOS has none, but its own start with com.apple.
+pub fn is_user(name: &OsStr) -> bool {
+    let name = name.as_encoded_bytes();
+    if cfg!(target_os = "linux") { name.starts_with(b"user.") } else { !name.starts_with(b"com.apple.") }
+}
+
+// The name an attribute the user typed is stored under
+pub fn user_name(name: &str) -> String {
+    let name = name.trim();
+    if cfg!(target_os = "linux") && !name.starts_with("user.") { format!("user.{}", name) } else { name.to_string() }
+}
+
+// Every attribute of `path` the user may read, with its value, by name
+pub fn list(path: &Path) -> io::Result<Vec<(OsString, Vec<u8>)>> {
+    let names = sys::list(path)?;
+    let mut attributes: Vec<(OsString, Vec<u8>)> = names
+        .split(|&byte| byte == 0)
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| {
+            let name = sys::name(name);
+            // One may go between listing and reading
+            get(path, &name).ok().flatten().map(|value| (name, value))
+        })
+        .collect();
+    attributes.sort();
+    Ok(attributes)
+}
+
+// None when `path` has no attribute `name`
+pub fn get(path: &Path, name: impl AsRef<OsStr>) -> io::Result<Option<Vec<u8>>> {
+    match sys::get(path, name.as_ref()) {
+        Ok(value) => Ok(Some(value)),
+        Err(error) if error.raw_os_error() == Some(sys::NO_ATTRIBUTE) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn set(path: &Path, name: impl AsRef<OsStr>, value: &[u8]) -> io::Result<()> {
+    sys::set(path, name.as_ref(), value)
+}
+
+// Removing an attribute that isn't there is fine
+pub fn remove(path: &Path, name: impl AsRef<OsStr>) -> io::Result<()> {
+    match sys::remove(path, name.as_ref()) {
+        Err(error) if error.raw_os_error() == Some(sys::NO_ATTRIBUTE) => Ok(()),
+        removed => removed,
+    }
+}
+
+// A value as text when it is some, or else as hex. Values written from C
+// often end in NUL, which is not shown.
+pub fn display(value: &[u8]) -> String {
+    match std::str::from_utf8(value) {
+        Ok(text) if !text.trim_end_matches('\0').chars().any(|c| c.is_control() && c != '\n' && c != '\t') => text.trim_end_matches('\0').to_string(),
+        _ => format!("0x{}", value.iter().map(|byte| format!("{:02x}", byte)).collect::<String>()),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn c_string(text: impl AsRef<OsStr>) -> io::Result<std::ffi::CString> {
+    use std::os::unix::ffi::OsStrExt;
+    std::ffi::CString::new(text.as_ref().as_bytes()).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))
+}
+
+// Asks for the size first, then reads; `call` is given the buffer and its
+// length and returns what the C call did
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn read_sized(mut call: impl FnMut(*mut u8, usize) -> isize) -> io::Result<Vec<u8>> {
+    let size = call(std::ptr::null_mut(), 0);
+    let size = usize::try_from(size).map_err(|_| io::Error::last_os_error())?;
+    if size > MAX_VALUE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "the attribute is too large to show"));
+    }
+    // A little room in case it grew since
+    let mut buffer = vec![0u8; size + 256];
+    let read = call(buffer.as_mut_ptr(), buffer.len());
+    let read = usize::try_from(read).map_err(|_| io::Error::last_os_error())?;
+    buffer.truncate(read);
+    Ok(buffer)
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use super::{c_string, read_sized};
+    use std::ffi::{OsStr, OsString};
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    pub const NO_ATTRIBUTE: i32 = libc::ENODATA;
+
+    pub fn name(bytes: &[u8]) -> OsString {
+        OsStr::from_bytes(bytes).to_os_string()
+    }
+
+    pub fn list(path: &Path) -> io::Result<Vec<u8>> {
+        let path = c_string(path)?;
+        // SAFETY: the path ends in NUL and the buffer is as long as given
+        read_sized(|buffer, size| unsafe { libc::listxattr(path.as_ptr(), buffer.cast(), size) })
+    }
+
+    pub fn get(path: &Path, name: &OsStr) -> io::Result<Vec<u8>> {
+        let (path, name) = (c_string(path)?, c_string(name)?);
+        // SAFETY: both strings end in NUL and the buffer is as long as given
+        read_sized(|buffer, size| unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), buffer.cast(), size) })
+    }
+
+    pub fn set(path: &Path, name: &OsStr, value: &[u8]) -> io::Result<()> {
+        let (path, name) = (c_string(path)?, c_string(name)?);
+        // SAFETY: both strings end in NUL and `value` is as long as given
+        let result = unsafe { libc::setxattr(path.as_ptr(), name.as_ptr(), value.as_ptr().cast(), value.len(), 0) };
+        if result == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+    }
+
+    pub fn remove(path: &Path, name: &OsStr) -> io::Result<()> {
+        let (path, name) = (c_string(path)?, c_string(name)?);
+        // SAFETY: both strings end in NUL
+        let result = unsafe { libc::removexattr(path.as_ptr(), name.as_ptr()) };
+        if result == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod sys {
+    use super::{c_string, read_sized};
+    use std::ffi::{OsStr, OsString};
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    pub const NO_ATTRIBUTE: i32 = libc::ENOATTR;
+
+    pub fn name(bytes: &[u8]) -> OsString {
+        OsStr::from_bytes(bytes).to_os_string()
+    }
+
+    pub fn list(path: &Path) -> io::Result<Vec<u8>> {
+        let path = c_string(path)?;
+        // SAFETY: the path ends in NUL and the buffer is as long as given
+        read_sized(|buffer, size| unsafe { libc::listxattr(path.as_ptr(), buffer.cast(), size, 0) })
+    }
+
+    pub fn get(path: &Path, name: &OsStr) -> io::Result<Vec<u8>> {
+        let (path, name) = (c_string(path)?, c_string(name)?);
+        // SAFETY: both strings end in NUL and the buffer is as long as given
+        read_sized(|buffer, size| unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), buffer.cast(), size, 0, 0) })
+    }
+
+    pub fn set(path: &Path, name: &OsStr, value: &[u8]) -> io::Result<()> {
+        let (path, name) = (c_string(path)?, c_string(name)?);
+        // SAFETY: both strings end in NUL and `value` is as long as given
+        let result = unsafe { libc::setxattr(path.as_ptr(), name.as_ptr(), value.as_ptr().cast(), value.len(), 0, 0) };
+        if result == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+    }
+
+    pub fn remove(path: &Path, name: &OsStr) -> io::Result<()> {
+        let (path, name) = (c_string(path)?, c_string(name)?);
+        // SAFETY: both strings end in NUL
+        let result = unsafe { libc::removexattr(path.as_ptr(), name.as_ptr(), 0) };
+        if result == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod sys {
+    use std::ffi::{OsStr, OsString};
+    use std::io;
+    use std::path::Path;
+
+    pub const NO_ATTRIBUTE: i32 = -1;
+
+    pub fn name(bytes: &[u8]) -> OsString {
+        String::from_utf8_lossy(bytes).into_owned().into()
+    }
+
+    fn unsupported<T>() -> io::Result<T> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "extended attributes are not supported here"))
+    }
+
+    pub fn list(_path: &Path) -> io::Result<Vec<u8>> {
+        unsupported()
+    }
+
+    pub fn get(_path: &Path, _name: &OsStr) -> io::Result<Vec<u8>> {
+        unsupported()
+    }
+
+    pub fn set(_path: &Path, _name: &OsStr, _value: &[u8]) -> io::Result<()> {
+        unsupported()
+    }
+
+    pub fn remove(_path: &Path, _name: &OsStr) -> io::Result<()> {
+        unsupported()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_show_as_text_or_hex() {
+        assert_eq!(display(b"blue,work\0"), "blue,work");
+        assert_eq!(display(&[0xff, 0x00, 0x10]), "0xff0010");
+        assert_eq!(display(b"a\x07b"), "0x610762");
+    }
+
+    // Skipped where the file system takes no user attributes, as tmpfs
+    // didn't before Linux 6.6
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn names_that_are_not_utf8_round_trip() {
+        use std::os::unix::ffi::OsStrExt;
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let name = OsStr::from_bytes(b"user.caf\xe9");
+        if let Err(error) = set(file.path(), name, b"1") {
+            assert_eq!(error.raw_os_error(), Some(libc::ENOTSUP), "{}", error);
+            return;
+        }
+        let listed = list(file.path()).unwrap();
+        assert_eq!(listed, [(name.to_os_string(), b"1".to_vec())]);
+        assert!(is_user(&listed[0].0));
+        remove(file.path(), &listed[0].0).unwrap();
+        assert_eq!(get(file.path(), name).unwrap(), None);
+        remove(file.path(), name).unwrap();
+    }
+}